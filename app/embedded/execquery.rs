@@ -9,7 +9,7 @@ use simpledb::rdbc::{
     resultsetadapter::ResultSetAdapter,
     resultsetmetadataadapter::DataType,
     resultsetmetadataadapter::ResultSetMetaDataAdapter,
-    statementadapter::StatementAdapter,
+    statementadapter::{StatementAdapter, StatementError},
 };
 
 fn print_record(results: &mut EmbeddedResultSet, meta: &EmbeddedMetaData) -> Result<()> {
@@ -18,6 +18,10 @@ fn print_record(results: &mut EmbeddedResultSet, meta: &EmbeddedMetaData) -> Res
         let w = meta
             .get_column_display_size(i)
             .expect("get column display size");
+        if results.is_null(fldname)? {
+            print!("{:width$} ", "NULL", width = w);
+            continue;
+        }
         match meta.get_column_type(i).expect("get column type") {
             DataType::Int16 => {
                 print!("{:width$} ", results.get_i16(fldname)?, width = w);
@@ -78,7 +82,10 @@ pub fn exec_query<'a>(stmt: &'a mut EmbeddedStatement<'a>) {
     let qry = stmt.sql().to_string();
     let start = Instant::now();
     match stmt.execute_query() {
-        Err(_) => println!("invalid query: {}", qry),
+        Err(e) => match e.downcast_ref::<StatementError>() {
+            Some(StatementError::SyntaxError(err)) => println!("{}\n{}", err, err.caret(&qry)),
+            _ => println!("invalid query: {}", qry),
+        },
         Ok(result) => {
             let cnt = print_result_set(result).expect("print result set");
             let end = start.elapsed();
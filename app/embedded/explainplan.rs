@@ -9,7 +9,14 @@ fn format_operation(op: Operation) -> String {
             idxname: _,
             idxfldname,
             joinfld,
+            outer: false,
         } => format!("INDEX JOIN SCAN BY {} = {}", idxfldname, joinfld),
+        Operation::IndexJoinScan {
+            idxname: _,
+            idxfldname,
+            joinfld,
+            outer: true,
+        } => format!("INDEX LEFT OUTER JOIN SCAN BY {} = {}", idxfldname, joinfld),
         Operation::IndexSelectScan {
             idxname: _,
             idxfldname,
@@ -20,15 +27,61 @@ fn format_operation(op: Operation) -> String {
             aggfns: _,
         } => format!("GROUP BY",),
         Operation::Materialize => format!("MATERIALIZE"),
-        Operation::MergeJoinScan { fldname1, fldname2 } => {
-            format!("MERGE JOIN SCAN BY {} = {}", fldname1, fldname2)
-        }
+        Operation::MergeJoinScan {
+            fldname1,
+            fldname2,
+            outer: false,
+        } => format!("MERGE JOIN SCAN BY {} = {}", fldname1, fldname2),
+        Operation::MergeJoinScan {
+            fldname1,
+            fldname2,
+            outer: true,
+        } => format!("MERGE LEFT OUTER JOIN SCAN BY {} = {}", fldname1, fldname2),
         Operation::SortScan { compflds } => format!("SORT SCAN BY ({})", compflds.iter().join(",")),
         Operation::MultibufferProductScan => format!("MULTIBUFFER PRODUCT SCAN"),
         Operation::ProductScan => format!("PRODUCT SCAN"),
         Operation::ProjectScan => format!("PROJECT SCAN"),
-        Operation::SelectScan { pred: _ } => format!("SELECT SCAN"),
+        Operation::SelectScan { pred } => format!("SELECT SCAN WHERE {}", pred),
         Operation::TableScan { tblname: _ } => format!("TABLE SCAN"),
+        Operation::DistinctScan => format!("DISTINCT SCAN"),
+        Operation::LimitScan { limit, offset } => {
+            format!("LIMIT SCAN {} OFFSET {}", limit, offset)
+        }
+        Operation::OuterJoinScan { pred } => format!("LEFT OUTER JOIN SCAN ON {}", pred),
+        Operation::OuterRecordScan { fields } => {
+            format!("OUTER RECORD SCAN ({})", fields.iter().join(","))
+        }
+        Operation::UnionScan => format!("UNION ALL SCAN"),
+        Operation::SetOpScan { op } => format!("{} SCAN", op.to_uppercase()),
+        Operation::IndexRangeScan {
+            idxname: _,
+            idxfldname,
+            loval,
+            hival,
+            inclusive: false,
+        } => format!(
+            "INDEX RANGE SCAN BY {} >= {} AND {} < {}",
+            idxfldname, loval, idxfldname, hival
+        ),
+        Operation::IndexRangeScan {
+            idxname: _,
+            idxfldname,
+            loval,
+            hival,
+            inclusive: true,
+        } => format!(
+            "INDEX RANGE SCAN BY {} >= {} AND {} <= {}",
+            idxfldname, loval, idxfldname, hival
+        ),
+        Operation::IndexInScan {
+            idxname: _,
+            idxfldname,
+            vals,
+        } => format!(
+            "INDEX SELECT SCAN BY {} IN ({})",
+            idxfldname,
+            vals.iter().join(",")
+        ),
     }
 }
 
@@ -38,6 +91,7 @@ fn format_name(op: Operation) -> String {
             idxname,
             idxfldname: _,
             joinfld: _,
+            outer: _,
         } => format!("{}", idxname),
         Operation::IndexSelectScan {
             idxname,
@@ -52,6 +106,7 @@ fn format_name(op: Operation) -> String {
         Operation::MergeJoinScan {
             fldname1: _,
             fldname2: _,
+            outer: _,
         } => format!(""),
         Operation::SortScan { compflds: _ } => format!(""),
         Operation::MultibufferProductScan => format!(""),
@@ -59,6 +114,27 @@ fn format_name(op: Operation) -> String {
         Operation::ProjectScan => format!(""),
         Operation::SelectScan { pred: _ } => format!(""),
         Operation::TableScan { tblname } => format!("{}", tblname),
+        Operation::DistinctScan => format!(""),
+        Operation::LimitScan {
+            limit: _,
+            offset: _,
+        } => format!(""),
+        Operation::OuterJoinScan { pred: _ } => format!(""),
+        Operation::OuterRecordScan { fields: _ } => format!(""),
+        Operation::UnionScan => format!(""),
+        Operation::SetOpScan { op: _ } => format!(""),
+        Operation::IndexRangeScan {
+            idxname,
+            idxfldname: _,
+            loval: _,
+            hival: _,
+            inclusive: _,
+        } => format!("{}", idxname),
+        Operation::IndexInScan {
+            idxname,
+            idxfldname: _,
+            vals: _,
+        } => format!("{}", idxname),
     }
 }
 
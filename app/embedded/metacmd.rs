@@ -3,6 +3,7 @@ use std::process;
 
 use simpledb::rdbc::{
     connectionadapter::ConnectionAdapter, embedded::connection::EmbeddedConnection,
+    statementadapter::StatementError,
 };
 
 use crate::{
@@ -81,10 +82,18 @@ SELECT viewname, viewdef FROM viewcat;"#
             if !words.is_empty() {
                 let cmd = words[0].trim().to_ascii_lowercase();
                 if &cmd == "select" {
-                    if let Ok(plan_repr) = stmt.explain_plan() {
-                        print_explain_plan(plan_repr.repr());
-                        println!();
-                        return;
+                    match stmt.explain_plan() {
+                        Ok(plan_repr) => {
+                            print_explain_plan(plan_repr.repr());
+                            println!();
+                            return;
+                        }
+                        Err(e) => {
+                            if let Some(StatementError::SyntaxError(err)) = e.downcast_ref() {
+                                println!("{}\n{}", err, err.caret(sql));
+                                return;
+                            }
+                        }
                     }
                 }
             }
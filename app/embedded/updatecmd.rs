@@ -1,14 +1,21 @@
 use std::time::Instant;
 
 use log::info;
-use simpledb::rdbc::{embedded::statement::EmbeddedStatement, statementadapter::StatementAdapter};
+use simpledb::rdbc::{
+    embedded::statement::EmbeddedStatement,
+    statementadapter::{StatementAdapter, StatementError},
+};
 
 pub fn exec_update_cmd<'a>(stmt: &'a mut EmbeddedStatement<'a>) {
     let qry = stmt.sql().to_string();
     let start = Instant::now();
     let res = stmt.execute_update();
     match res {
-        Err(_) => println!("invalid command: {}", qry),
+        Err(e) => match e.downcast_ref::<StatementError>() {
+            Some(violation @ StatementError::ConstraintViolation(_)) => println!("{}", violation),
+            Some(StatementError::SyntaxError(err)) => println!("{}\n{}", err, err.caret(&qry)),
+            _ => println!("invalid command: {}", qry),
+        },
         Ok(affected) => {
             let end = start.elapsed();
             println!(
@@ -38,6 +38,9 @@ fn print_record(row: HashMap<&str, resultset::Value>, meta: &NetworkResultSetMet
             resultset::Value::Date(v) => {
                 print!("{:width$} ", v.clone(), width = w);
             }
+            resultset::Value::Null => {
+                print!("{:width$} ", "NULL", width = w);
+            }
         }
     }
     println!();
@@ -107,10 +110,10 @@ pub async fn exec_query(stmt: &mut NetworkStatement) {
                     end.subsec_nanos() / 1_000_000
                 );
             }
-            Err(e) => {
-                warn!("failed to exec query: {}", e);
-                return;
-            }
+            Err(e) => match stmt.syntax_error().await {
+                Ok(Some(err)) => println!("{}\n{}", err, err.caret(stmt.sql())),
+                _ => warn!("failed to exec query: {}", e),
+            },
         },
     }
 }
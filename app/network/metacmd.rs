@@ -89,6 +89,10 @@ SELECT viewname, viewdef FROM viewcat;"#
                         println!();
                         return;
                     }
+                    if let Ok(Some(err)) = stmt.syntax_error().await {
+                        println!("{}\n{}", err, err.caret(sql));
+                        return;
+                    }
                 }
             }
             println!("expect query(not command).");
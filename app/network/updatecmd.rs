@@ -1,13 +1,26 @@
 use std::time::Instant;
 
 use log::info;
-use simpledb::rdbc::{network::statement::NetworkStatement, statementadapter::StatementAdapter};
+use simpledb::rdbc::{
+    network::statement::NetworkStatement,
+    statementadapter::{StatementAdapter, StatementError},
+};
 
 pub async fn exec_update_cmd(stmt: &mut NetworkStatement) {
     let start = Instant::now();
     let res = stmt.execute_update().unwrap();
     match res.affected().await {
-        Err(_) => println!("invalid command"),
+        Err(e) => match e.downcast_ref::<StatementError>() {
+            Some(violation @ StatementError::ConstraintViolation(_)) => {
+                println!("{}", violation);
+                return;
+            }
+            Some(StatementError::SyntaxError(err)) => {
+                println!("{}\n{}", err, err.caret(stmt.sql()));
+                return;
+            }
+            _ => println!("invalid command"),
+        },
         Ok(affected) => {
             let end = start.elapsed();
             println!(
@@ -1,8 +1,8 @@
-CREATE TABLE student (sid integer, sname varchar(10), grad_year smallint, major_id integer, birth date, sex bool);
-CREATE TABLE dept (did integer, dname varchar(10));
+CREATE TABLE dept (did integer PRIMARY KEY, dname varchar(10));
+CREATE TABLE student (sid integer PRIMARY KEY, sname varchar(10), grad_year smallint, major_id integer REFERENCES dept (did), birth date, sex bool);
 CREATE TABLE course (cid integer, title varchar(16), dept_id integer);
 CREATE TABLE section (sect_id integer, course_id integer, prof varchar(10), year_offered integer);
-CREATE TABLE enroll (eid integer, student_id integer, section_id integer, grade varchar(2));
+CREATE TABLE enroll (eid integer, student_id integer REFERENCES student (sid) ON DELETE CASCADE, section_id integer, grade varchar(2));
 CREATE INDEX idx_grad_year ON student (grad_year);
 CREATE INDEX idx_major_id ON student (major_id);
 CREATE INDEX idx_sex ON student (sex);
@@ -12,6 +12,10 @@ INSERT INTO sex (sex_name, value) VALUES ('male', true);
 INSERT INTO sex (sex_name, value) VALUES ('female', false);
 
 
+INSERT INTO dept (did, dname) VALUES (10, 'compsci');
+INSERT INTO dept (did, dname) VALUES (20, 'math');
+INSERT INTO dept (did, dname) VALUES (30, 'drama');
+
 INSERT INTO student (sid, sname, grad_year, major_id, birth, sex) VALUES (1, 'joe', 2021, 10, '2002-06-22', true);
 INSERT INTO student (sid, sname, grad_year, major_id, birth, sex) VALUES (2, 'amy', 2020, 20, '2001-09-13', false);
 INSERT INTO student (sid, sname, grad_year, major_id, birth, sex) VALUES (3, 'max', 2022, 10, '2000-11-09', true);
@@ -22,10 +26,6 @@ INSERT INTO student (sid, sname, grad_year, major_id, birth, sex) VALUES (7, 'ar
 INSERT INTO student (sid, sname, grad_year, major_id, birth, sex) VALUES (8, 'pat', 2019, 20, '2000-07-23', false);
 INSERT INTO student (sid, sname, grad_year, major_id, birth, sex) VALUES (9, 'lee', 2021, 10, '2002-12-29', true);
 
-INSERT INTO dept (did, dname) VALUES (10, 'compsci');
-INSERT INTO dept (did, dname) VALUES (20, 'math');
-INSERT INTO dept (did, dname) VALUES (30, 'drama');
-
 INSERT INTO course (cid, title, dept_id) VALUES (12, 'db systems', 10);
 INSERT INTO course (cid, title, dept_id) VALUES (22, 'compilers', 10);
 INSERT INTO course (cid, title, dept_id) VALUES (32, 'calculus', 20);
@@ -152,6 +152,18 @@ impl FileMgr {
         // ceiling
         Ok((meta.len() as i32 + self.blocksize - 1) / self.blocksize)
     }
+    // my own extends
+    // closes the file and removes it from the directory.
+    // a block of it read after this, reads a new empty file.
+    pub fn delete(&mut self, filename: &str) -> Result<()> {
+        self.open_files.remove(filename);
+        let path = Path::new(&self.db_directory).join(filename);
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+
+        Ok(())
+    }
     pub fn is_new(&self) -> bool {
         self.is_new
     }
@@ -11,6 +11,7 @@ pub mod query;
 #[derive(Debug)]
 pub enum IndexError {
     NoTableScan,
+    RangeNotSupported,
 }
 
 impl std::error::Error for IndexError {}
@@ -20,12 +21,18 @@ impl fmt::Display for IndexError {
             IndexError::NoTableScan => {
                 write!(f, "no table scan")
             }
+            IndexError::RangeNotSupported => {
+                write!(f, "range search not supported")
+            }
         }
     }
 }
 
 pub trait Index {
     fn before_first(&mut self, searchkey: Constant) -> Result<()>;
+    // positions before the first record whose dataval is in [lokey, hikey),
+    // or in [lokey, hikey] if the upper bound is inclusive.
+    fn before_range(&mut self, lokey: Constant, hikey: Constant, inclusive: bool) -> Result<()>;
     fn next(&mut self) -> bool;
     fn get_data_rid(&mut self) -> Result<RID>;
     fn insert(&mut self, dataval: Constant, datarid: RID) -> Result<()>;
@@ -104,6 +104,8 @@ impl BTPage {
             let blk = self.tx.lock().unwrap().append(&currentblk.file_name())?;
             self.tx.lock().unwrap().pin(&blk)?;
             self.format(&blk, flag)?;
+            // the caller pins the new block again to use it
+            self.tx.lock().unwrap().unpin(&blk)?;
             return Ok(blk);
         }
 
@@ -126,6 +128,10 @@ impl BTPage {
         Ok(())
     }
     pub fn make_default_record(&self, blk: &BlockId, pos: usize) -> Result<()> {
+        for offset in self.layout.null_bitmap_offsets() {
+            let mut tx = self.tx.lock().unwrap();
+            tx.set_i32(blk, (pos + offset) as i32, 0, false)?;
+        }
         for fldname in self.layout.schema().fields() {
             let offset = self.layout.offset(fldname);
             let mut tx = self.tx.lock().unwrap();
@@ -234,6 +240,9 @@ impl BTPage {
         Err(From::from(BTPageError::NoCurrentBlockError))
     }
     fn get_val(&self, slot: i32, fldname: &str) -> Result<Constant> {
+        if self.is_null(slot, fldname)? {
+            return Ok(Constant::Null);
+        }
         let fldtype = self.layout.schema().field_type(fldname);
         match fldtype {
             FieldType::SMALLINT => Ok(Constant::new_i16(self.get_i16(slot, fldname)?)),
@@ -289,6 +298,10 @@ impl BTPage {
         Err(From::from(BTPageError::NoCurrentBlockError))
     }
     fn set_val(&mut self, slot: i32, fldname: &str, val: Constant) -> Result<()> {
+        self.set_null_flag(slot, fldname, val.is_null())?;
+        if val.is_null() {
+            return Ok(());
+        }
         let fldtype = self.layout.schema().field_type(fldname);
         match fldtype {
             FieldType::SMALLINT => self.set_i16(slot, fldname, val.as_i16()?),
@@ -298,6 +311,36 @@ impl BTPage {
             FieldType::DATE => self.set_date(slot, fldname, val.as_date()?),
         }
     }
+    // my own extends
+    // a null key is kept in the null bitmap of the record, like a record page.
+    fn is_null(&self, slot: i32, fldname: &str) -> Result<bool> {
+        if let Some(currentblk) = self.currentblk.as_ref() {
+            let (offset, bit) = self.layout.null_flag(fldname);
+            let pos = self.slotpos(slot) + offset as i32;
+            let flags = self.tx.lock().unwrap().get_i32(currentblk, pos)?;
+            return Ok(flags & bit != 0);
+        }
+
+        Err(From::from(BTPageError::NoCurrentBlockError))
+    }
+    fn set_null_flag(&mut self, slot: i32, fldname: &str, isnull: bool) -> Result<()> {
+        if let Some(currentblk) = self.currentblk.as_ref() {
+            let (offset, bit) = self.layout.null_flag(fldname);
+            let pos = self.slotpos(slot) + offset as i32;
+            let mut tx = self.tx.lock().unwrap();
+            let flags = tx.get_i32(currentblk, pos)?;
+            let newflags = match isnull {
+                true => flags | bit,
+                false => flags & !bit,
+            };
+            if newflags == flags {
+                return Ok(());
+            }
+            return tx.set_i32(currentblk, pos, newflags, true);
+        }
+
+        Err(From::from(BTPageError::NoCurrentBlockError))
+    }
     fn set_num_recs(&mut self, n: i32) -> Result<()> {
         if let Some(currentblk) = self.currentblk.as_ref() {
             let mut tx = self.tx.lock().unwrap();
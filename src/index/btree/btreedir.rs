@@ -40,6 +40,41 @@ impl BTreeDir {
 
         Ok(childblk.number())
     }
+    // the leaf blocks in key order, which may hold the keys in [lokey, hikey),
+    // or in [lokey, hikey] if inclusive.
+    pub fn search_range(
+        &mut self,
+        lokey: &Constant,
+        hikey: &Constant,
+        inclusive: bool,
+    ) -> Result<Vec<i32>> {
+        let level = self.contents.get_flag()?;
+        let numrecs = self.contents.get_num_recs()?;
+        let mut result = vec![];
+        let mut slot = self.find_child_slot(lokey)?;
+        while slot < numrecs {
+            // each entry holds the least key of the child
+            if !result.is_empty() {
+                let val = self.contents.get_data_val(slot)?;
+                if val > *hikey || (val == *hikey && !inclusive) {
+                    break;
+                }
+            }
+            let blknum = self.contents.get_child_num(slot)?;
+            if level == 0 {
+                result.push(blknum);
+            } else {
+                let childblk = BlockId::new(&self.filename, blknum);
+                let mut child =
+                    BTreeDir::new(Arc::clone(&self.tx), childblk, Arc::clone(&self.layout))?;
+                result.extend(child.search_range(lokey, hikey, inclusive)?);
+                child.close()?;
+            }
+            slot += 1;
+        }
+
+        Ok(result)
+    }
     pub fn make_new_root(&mut self, e: DirEntry) -> Result<()> {
         let firstval = self.contents.get_data_val(0)?;
         let level = self.contents.get_flag()?;
@@ -76,11 +111,19 @@ impl BTreeDir {
         Some(DirEntry::new(splitval, newblk.number()))
     }
     pub fn find_child_block(&self, searchkey: &Constant) -> Result<BlockId> {
+        let slot = self.find_child_slot(searchkey)?;
+        let blknum = self.contents.get_child_num(slot)?;
+        Ok(BlockId::new(&self.filename, blknum))
+    }
+    fn find_child_slot(&self, searchkey: &Constant) -> Result<i32> {
         let mut slot = self.contents.find_slot_before(searchkey)?;
-        if self.contents.get_data_val(slot + 1)? == *searchkey {
+        if slot + 1 < self.contents.get_num_recs()?
+            && self.contents.get_data_val(slot + 1)? == *searchkey
+        {
             slot += 1;
         }
-        let blknum = self.contents.get_child_num(slot)?;
-        Ok(BlockId::new(&self.filename, blknum))
+        // the keys of the page may be all greater than the search key,
+        // when the page isn't the leftmost one of its level.
+        Ok(slot.max(0))
     }
 }
@@ -69,6 +69,21 @@ impl BTreeLeaf {
             self.try_overflow()
         }
     }
+    // moves to the next record whose key is less than hikey,
+    // or not greater than hikey if inclusive, for the range search from the search key.
+    pub fn next_before(&mut self, hikey: &Constant, inclusive: bool) -> bool {
+        self.currentslot += 1;
+        if self.currentslot < self.contents.get_num_recs().unwrap()
+            && below(
+                &self.contents.get_data_val(self.currentslot).unwrap(),
+                hikey,
+                inclusive,
+            )
+        {
+            return true;
+        }
+        self.try_range_overflow(hikey, inclusive)
+    }
     pub fn get_data_rid(&self) -> Result<RID> {
         self.contents.get_data_rid(self.currentslot)
     }
@@ -151,4 +166,27 @@ impl BTreeLeaf {
         self.currentslot = 0;
         return true;
     }
+    // the overflow blocks hold the records of the first key,
+    // which are not followed by the records of the greater keys in this block.
+    fn try_range_overflow(&mut self, hikey: &Constant, inclusive: bool) -> bool {
+        if self.contents.get_num_recs().unwrap() == 0 {
+            return false;
+        }
+        let firstkey = self.contents.get_data_val(0).unwrap();
+        let flag = self.contents.get_flag().unwrap();
+        if firstkey < self.searchkey || !below(&firstkey, hikey, inclusive) || flag < 0 {
+            return false;
+        }
+        self.contents.close().unwrap();
+        let nextblk = BlockId::new(&self.filename, flag);
+        self.contents =
+            BTPage::new(Arc::clone(&self.tx), nextblk, Arc::clone(&self.layout)).unwrap();
+        self.currentslot = 0;
+        return true;
+    }
+}
+
+// whether the key is below the upper bound of the range.
+fn below(key: &Constant, hikey: &Constant, inclusive: bool) -> bool {
+    key < hikey || (inclusive && key == hikey)
 }
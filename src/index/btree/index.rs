@@ -1,5 +1,4 @@
 use anyhow::Result;
-use chrono::NaiveDate;
 use std::sync::{Arc, Mutex};
 
 use super::{btreedir::BTreeDir, btreeleaf::BTreeLeaf};
@@ -7,11 +6,7 @@ use crate::{
     file::block_id::BlockId,
     index::{btree::btpage::BTPage, Index},
     query::constant::Constant,
-    record::{
-        layout::Layout,
-        rid::RID,
-        schema::{FieldType, Schema},
-    },
+    record::{layout::Layout, rid::RID, schema::Schema},
     tx::transaction::Transaction,
 };
 
@@ -23,6 +18,9 @@ pub struct BTreeIndex {
     leaftbl: String,
     leaf: Option<BTreeLeaf>,
     rootblk: BlockId,
+    // the bounds of the range search, and the leaf blocks left to be searched
+    range: Option<(Constant, Constant, bool)>,
+    leafblks: Vec<i32>,
 }
 
 impl BTreeIndex {
@@ -37,6 +35,7 @@ impl BTreeIndex {
             let blk = tx.lock().unwrap().append(&leaftbl)?;
             let mut node = BTPage::new(Arc::clone(&tx), blk.clone(), Arc::clone(&leaf_layout))?;
             node.format(&blk, -1)?;
+            node.close()?;
         }
 
         // deal with the directory
@@ -52,14 +51,8 @@ impl BTreeIndex {
             let mut node = BTPage::new(Arc::clone(&tx), rootblk.clone(), Arc::clone(&dir_layout))?;
             node.format(&rootblk, 0)?;
             // insert initial directory entry
-            let fldtype = dirsch.field_type("dataval");
-            let minval = match fldtype {
-                FieldType::SMALLINT => Constant::new_i16(i16::MIN),
-                FieldType::INTEGER => Constant::new_i32(i32::MIN),
-                FieldType::VARCHAR => Constant::new_string("".to_string()),
-                FieldType::BOOL => Constant::new_bool(false),
-                FieldType::DATE => Constant::new_date(NaiveDate::from_ymd_opt(0, 1, 1).unwrap()), // NOTE: default 0000-01-01
-            };
+            // NOTE: null is the least value of any type
+            let minval = Constant::new_null();
             node.insert_dir(0, minval, 0)?;
             node.close()?;
         }
@@ -71,11 +64,23 @@ impl BTreeIndex {
             leaftbl,
             leaf: None,
             rootblk,
+            range: None,
+            leafblks: vec![],
         })
     }
     pub fn search_cost(numblocks: i32, rpb: i32) -> i32 {
         1 + ((numblocks as f32).ln() / (rpb as f32).ln()) as i32
     }
+    fn open_leaf(&mut self, blknum: i32, searchkey: Constant) {
+        let leafblk = BlockId::new(&self.leaftbl, blknum);
+        self.leaf = BTreeLeaf::new(
+            Arc::clone(&self.tx),
+            leafblk,
+            Arc::clone(&self.leaf_layout),
+            searchkey,
+        )
+        .ok();
+    }
 }
 
 impl Index for BTreeIndex {
@@ -88,19 +93,47 @@ impl Index for BTreeIndex {
         )?;
         let blknum = root.search(&searchkey)?;
         root.close()?;
-        let leafblk = BlockId::new(&self.leaftbl, blknum);
-        self.leaf = BTreeLeaf::new(
+        self.range = None;
+        self.leafblks.clear();
+        self.open_leaf(blknum, searchkey);
+
+        Ok(())
+    }
+    fn before_range(&mut self, lokey: Constant, hikey: Constant, inclusive: bool) -> Result<()> {
+        self.close()?;
+        let mut root = BTreeDir::new(
             Arc::clone(&self.tx),
-            leafblk,
-            Arc::clone(&self.leaf_layout),
-            searchkey,
-        )
-        .ok();
+            self.rootblk.clone(),
+            Arc::clone(&self.dir_layout),
+        )?;
+        let mut blknums = root.search_range(&lokey, &hikey, inclusive)?;
+        root.close()?;
+        // the blocks are popped from the back
+        blknums.reverse();
+        let blknum = blknums.pop().unwrap();
+        self.leafblks = blknums;
+        self.open_leaf(blknum, lokey.clone());
+        self.range = Some((lokey, hikey, inclusive));
 
         Ok(())
     }
     fn next(&mut self) -> bool {
-        self.leaf.as_mut().unwrap().next()
+        let (lokey, hikey, inclusive) = match &self.range {
+            Some(range) => range.clone(),
+            None => return self.leaf.as_mut().unwrap().next(),
+        };
+        loop {
+            if self.leaf.as_mut().unwrap().next_before(&hikey, inclusive) {
+                return true;
+            }
+            match self.leafblks.pop() {
+                Some(blknum) => {
+                    self.leaf.as_mut().unwrap().close().unwrap();
+                    self.open_leaf(blknum, lokey.clone());
+                }
+                None => return false,
+            }
+        }
     }
     fn get_data_rid(&mut self) -> Result<RID> {
         self.leaf.as_mut().unwrap().get_data_rid()
@@ -138,3 +171,76 @@ impl Index for BTreeIndex {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use super::*;
+    use crate::server::simpledb::SimpleDB;
+
+    #[test]
+    fn range_test() -> Result<()> {
+        if Path::new("_test/btreerange").exists() {
+            fs::remove_dir_all("_test/btreerange")?;
+        }
+
+        let simpledb = SimpleDB::new_with("_test/btreerange", 400, 8);
+        let tx = Arc::new(Mutex::new(simpledb.new_tx()?));
+        let mut sch = Schema::new();
+        sch.add_i32_field("block");
+        sch.add_i32_field("id");
+        sch.add_string_field("dataval", 10);
+        let layout = Arc::new(Layout::new(Arc::new(sch)));
+        let mut idx = BTreeIndex::new(Arc::clone(&tx), "range_idx", layout)?;
+
+        // enough keys to split the leaves and the directory,
+        // and enough duplicates to make an overflow block
+        let mut keys = vec![];
+        for i in 0..300 {
+            keys.push(format!("k{:03}", i));
+        }
+        for _ in 0..50 {
+            keys.push("k150".to_string());
+        }
+        for (i, key) in keys.iter().enumerate() {
+            idx.insert(Constant::String(key.clone()), RID::new(0, i as i32))?;
+        }
+
+        let cases = vec![
+            ("k1", "k2", false, 150),
+            ("k15", "k16", false, 60),
+            ("k299", "k3", false, 1),
+            ("k149", "k150", false, 1),
+            // the upper bound and its duplicates are included
+            ("k149", "k150", true, 52),
+            ("k298", "k299", true, 2),
+        ];
+        for (lo, hi, inclusive, expected) in cases {
+            idx.before_range(
+                Constant::String(lo.to_string()),
+                Constant::String(hi.to_string()),
+                inclusive,
+            )?;
+            let mut ids = vec![];
+            while idx.next() {
+                ids.push(idx.get_data_rid()?.slot() as usize);
+            }
+            assert_eq!(ids.len(), expected);
+            assert!(ids.iter().all(|i| keys[*i].as_str() >= lo
+                && (keys[*i].as_str() < hi || inclusive && keys[*i].as_str() == hi)));
+        }
+        // the equality search is not affected by the previous range search
+        idx.before_first(Constant::String("k150".to_string()))?;
+        let mut count = 0;
+        while idx.next() {
+            count += 1;
+        }
+        assert_eq!(count, 51);
+        idx.close()?;
+        tx.lock().unwrap().commit()?;
+        assert_eq!(tx.lock().unwrap().available_buffs(), 8);
+
+        Ok(())
+    }
+}
@@ -50,6 +50,10 @@ impl Index for HashIndex {
 
         Ok(())
     }
+    fn before_range(&mut self, _lokey: Constant, _hikey: Constant, _inclusive: bool) -> Result<()> {
+        // the hashed values are not ordered
+        Err(From::from(IndexError::RangeNotSupported))
+    }
     fn next(&mut self) -> bool {
         if let Some(ts) = self.ts.as_mut() {
             while ts.next() {
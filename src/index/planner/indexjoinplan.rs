@@ -1,6 +1,9 @@
 use anyhow::Result;
 use core::fmt;
-use std::sync::{Arc, Mutex};
+use std::{
+    cmp::max,
+    sync::{Arc, Mutex},
+};
 
 use crate::{
     index::query::indexjoinscan::IndexJoinScan,
@@ -33,6 +36,7 @@ pub struct IndexJoinPlan {
     ii: IndexInfo,
     joinfield: String,
     sch: Arc<Schema>,
+    outer: bool,
 }
 
 impl IndexJoinPlan {
@@ -47,8 +51,16 @@ impl IndexJoinPlan {
             ii,
             joinfield: joinfield.to_string(),
             sch: Arc::new(sch),
+            outer: false,
         }
     }
+    // my own extends
+    // every record of p1 is output, even if it matches no record of p2.
+    pub fn new_outer(p1: Arc<dyn Plan>, p2: Arc<dyn Plan>, ii: IndexInfo, joinfield: &str) -> Self {
+        let mut plan = Self::new(p1, p2, ii, joinfield);
+        plan.outer = true;
+        plan
+    }
 }
 
 impl Plan for IndexJoinPlan {
@@ -56,12 +68,12 @@ impl Plan for IndexJoinPlan {
         let s = self.p1.open()?;
         // throws an exception if p2 is not a table plan
         if let Ok(ts) = self.p2.open()?.lock().unwrap().as_table_scan() {
-            let scan = IndexJoinScan::new(
-                s,
-                self.ii.open(),
-                &self.joinfield,
-                Arc::new(Mutex::new(ts.clone())),
-            )?;
+            let rhs = Arc::new(Mutex::new(ts.clone()));
+            let scan = if self.outer {
+                IndexJoinScan::new_outer(s, self.ii.open(), &self.joinfield, rhs, self.p2.schema())?
+            } else {
+                IndexJoinScan::new(s, self.ii.open(), &self.joinfield, rhs)?
+            };
             return Ok(Arc::new(Mutex::new(scan)));
         }
 
@@ -73,7 +85,11 @@ impl Plan for IndexJoinPlan {
             + self.records_output()
     }
     fn records_output(&self) -> i32 {
-        self.p1.records_output() * self.ii.records_output()
+        let joined = self.p1.records_output() * self.ii.records_output();
+        if self.outer {
+            return max(self.p1.records_output(), joined);
+        }
+        joined
     }
     fn distinct_values(&self, fldname: &str) -> i32 {
         if self.p1.schema().has_field(fldname) {
@@ -93,6 +109,7 @@ impl Plan for IndexJoinPlan {
             idxname: self.ii.index_name().to_string(),
             idxfldname: self.ii.field_name().to_string(),
             joinfld: self.joinfield.clone(),
+            outer: self.outer,
             r: self.blocks_accessed(),
             w: self.records_output(),
         })
@@ -106,6 +123,7 @@ pub struct IndexJoinPlanRepr {
     idxname: String,
     idxfldname: String,
     joinfld: String,
+    outer: bool,
     r: i32,
     w: i32,
 }
@@ -116,6 +134,7 @@ impl PlanRepr for IndexJoinPlanRepr {
             idxname: self.idxname.clone(),
             idxfldname: self.idxfldname.clone(),
             joinfld: self.joinfld.clone(),
+            outer: self.outer,
         }
     }
     fn reads(&self) -> i32 {
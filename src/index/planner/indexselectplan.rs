@@ -3,10 +3,10 @@ use core::fmt;
 use std::sync::{Arc, Mutex};
 
 use crate::{
-    index::query::indexselectscan::IndexSelectScan,
+    index::query::indexselectscan::{IndexSearch, IndexSelectScan},
     metadata::indexmanager::IndexInfo,
     plan::plan::Plan,
-    query::{constant::Constant, scan::Scan},
+    query::{constant::Constant, scan::Scan, term::range_reduction_factor},
     record::schema::Schema,
     repr::planrepr::{Operation, PlanRepr},
 };
@@ -29,12 +29,36 @@ impl fmt::Display for IndexSelectPlanError {
 pub struct IndexSelectPlan {
     p: Arc<dyn Plan>,
     ii: IndexInfo,
-    val: Constant,
+    search: IndexSearch,
 }
 
 impl IndexSelectPlan {
     pub fn new(p: Arc<dyn Plan>, ii: IndexInfo, val: Constant) -> Self {
-        Self { p, ii, val }
+        Self::new_in(p, ii, vec![val])
+    }
+    // my own extends
+    // the records whose indexed values equal any of the values.
+    pub fn new_in(p: Arc<dyn Plan>, ii: IndexInfo, vals: Vec<Constant>) -> Self {
+        Self {
+            p,
+            ii,
+            search: IndexSearch::Eq(vals),
+        }
+    }
+    // the records whose indexed values are in [loval, hival),
+    // or in [loval, hival] if inclusive.
+    pub fn new_range(
+        p: Arc<dyn Plan>,
+        ii: IndexInfo,
+        loval: Constant,
+        hival: Constant,
+        inclusive: bool,
+    ) -> Self {
+        Self {
+            p,
+            ii,
+            search: IndexSearch::Range(loval, hival, inclusive),
+        }
     }
 }
 
@@ -45,9 +69,21 @@ impl Plan for IndexSelectPlan {
             // NOTE: need to convert val to the correct type.
             let fldname = self.ii.field_name();
             let fldtype = self.ii.table_schema().field_type(fldname);
-            let val = self.val.as_field_type(fldtype)?;
-
-            let scan = IndexSelectScan::new(Arc::new(Mutex::new(ts.clone())), self.ii.open(), val)?;
+            let search = match &self.search {
+                IndexSearch::Eq(vals) => IndexSearch::Eq(
+                    vals.iter()
+                        .map(|val| val.as_field_type(fldtype))
+                        .collect::<Result<_>>()?,
+                ),
+                IndexSearch::Range(loval, hival, inclusive) => IndexSearch::Range(
+                    loval.as_field_type(fldtype)?,
+                    hival.as_field_type(fldtype)?,
+                    *inclusive,
+                ),
+            };
+
+            let ts = Arc::new(Mutex::new(ts.clone()));
+            let scan = IndexSelectScan::new_with(ts, self.ii.open(), search)?;
             return Ok(Arc::new(Mutex::new(scan)));
         }
 
@@ -57,10 +93,27 @@ impl Plan for IndexSelectPlan {
         self.ii.blocks_accessed() + self.records_output()
     }
     fn records_output(&self) -> i32 {
-        self.ii.records_output()
+        match &self.search {
+            IndexSearch::Eq(vals) if vals.len() == 1 => self.ii.records_output(),
+            IndexSearch::Eq(vals) => {
+                (self.ii.records_output() * vals.len() as i32).min(self.p.records_output())
+            }
+            IndexSearch::Range(loval, hival, _) => {
+                let dv = self.p.distinct_values(self.ii.field_name());
+                self.p.records_output() / range_reduction_factor(dv, loval, hival)
+            }
+        }
     }
     fn distinct_values(&self, fldname: &str) -> i32 {
-        self.ii.distinct_values(fldname)
+        match &self.search {
+            IndexSearch::Eq(vals) if fldname == self.ii.field_name() => vals.len() as i32,
+            IndexSearch::Eq(_) => self.ii.distinct_values(fldname),
+            IndexSearch::Range(_, _, _) => self
+                .p
+                .distinct_values(fldname)
+                .min(self.records_output())
+                .max(1),
+        }
     }
     fn schema(&self) -> Arc<Schema> {
         self.p.schema()
@@ -71,7 +124,7 @@ impl Plan for IndexSelectPlan {
             p: self.p.repr(),
             idxname: self.ii.index_name().to_string(),
             idxfldname: self.ii.field_name().to_string(),
-            val: self.val.clone(),
+            search: self.search.clone(),
             r: self.blocks_accessed(),
             w: self.records_output(),
         })
@@ -83,17 +136,31 @@ pub struct IndexSelectPlanRepr {
     p: Arc<dyn PlanRepr>,
     idxname: String,
     idxfldname: String,
-    val: Constant,
+    search: IndexSearch,
     r: i32,
     w: i32,
 }
 
 impl PlanRepr for IndexSelectPlanRepr {
     fn operation(&self) -> Operation {
-        Operation::IndexSelectScan {
-            idxname: self.idxname.clone(),
-            idxfldname: self.idxfldname.clone(),
-            val: self.val.clone(),
+        match &self.search {
+            IndexSearch::Eq(vals) if vals.len() == 1 => Operation::IndexSelectScan {
+                idxname: self.idxname.clone(),
+                idxfldname: self.idxfldname.clone(),
+                val: vals[0].clone(),
+            },
+            IndexSearch::Eq(vals) => Operation::IndexInScan {
+                idxname: self.idxname.clone(),
+                idxfldname: self.idxfldname.clone(),
+                vals: vals.clone(),
+            },
+            IndexSearch::Range(loval, hival, inclusive) => Operation::IndexRangeScan {
+                idxname: self.idxname.clone(),
+                idxfldname: self.idxfldname.clone(),
+                loval: loval.clone(),
+                hival: hival.clone(),
+                inclusive: *inclusive,
+            },
         }
     }
     fn reads(&self) -> i32 {
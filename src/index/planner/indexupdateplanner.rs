@@ -1,18 +1,34 @@
 use anyhow::Result;
 use core::fmt;
 use log::debug;
-use std::sync::{Arc, Mutex};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
 
 use crate::{
     metadata::manager::MetadataMgr,
     parser::{
-        createindexdata::CreateIndexData, createtabledata::CreateTableData,
-        createviewdata::CreateViewData, deletedata::DeleteData, insertdata::InsertData,
+        altertabledata::{AlterAction, AlterTableData},
+        createindexdata::CreateIndexData,
+        createtabledata::CreateTableData,
+        createviewdata::CreateViewData,
+        deletedata::DeleteData,
+        dropdata::DropData,
+        insertdata::InsertData,
         modifydata::ModifyData,
     },
     plan::{
-        plan::Plan, selectplan::SelectPlan, tableplan::TablePlan, updateplanner::UpdatePlanner,
+        plan::Plan,
+        queryplanner::expand_wildcards,
+        selectplan::SelectPlan,
+        tableplan::TablePlan,
+        updateplanner::{
+            check_constraints, check_key, check_reference, check_unreferenced, delete_references,
+            fill_defaults, record_values, UpdatePlanner,
+        },
     },
+    query::constant::Constant,
     tx::transaction::Transaction,
 };
 
@@ -45,34 +61,64 @@ impl IndexUpdatePlanner {
 
 impl UpdatePlanner for IndexUpdatePlanner {
     fn execute_insert(&self, data: InsertData, tx: Arc<Mutex<Transaction>>) -> Result<i32> {
+        let constraints = self
+            .mdm
+            .lock()
+            .unwrap()
+            .get_field_constraints(data.table_name(), Arc::clone(&tx))?;
+        let data = fill_defaults(data, &constraints)?;
         let tblname = data.table_name();
         let p = TablePlan::new(tblname, Arc::clone(&tx), Arc::clone(&self.mdm))?;
-        // first, insert the record
+        let sch = p.schema();
         if let Ok(s) = p.open()?.lock().unwrap().to_update_scan() {
-            s.insert()?;
-            let rid = s.get_rid()?;
-            // then modify each field, inserting index records
             let mut md = self.mdm.lock().unwrap();
             let indexes = md.get_index_info(tblname, Arc::clone(&tx))?;
-            let mut valiter = data.vals().iter();
-            for fldname in data.fields() {
-                let val = valiter.next().unwrap();
-                debug!("Modify field {} to val {:?}", fldname, &val);
-                // NOTE: UpdateScan can convert val to the correct type.
-                s.set_val(fldname, val.clone())?;
-                if let Some(ii) = indexes.get(fldname) {
-                    // NOTE: convert the type here, because Index doesn't convert val.
-                    let fldtype = ii.table_schema().field_type(fldname);
-                    let val = val.as_field_type(fldtype)?;
-
-                    let idx = ii.open();
-                    idx.lock().unwrap().insert(val, rid)?;
-                    idx.lock().unwrap().close()?;
+            let keys = md.get_keys(tblname, Arc::clone(&tx))?;
+            let fks = md.get_foreign_keys(tblname, Arc::clone(&tx))?;
+            for vals in data.rows() {
+                // the constraints and the keys are checked before the record is inserted
+                let record = record_values(&sch, HashMap::new(), data.fields().iter().zip(vals))?;
+                check_constraints(tblname, &constraints, record)?;
+                for key in keys.iter() {
+                    let val = data
+                        .fields()
+                        .iter()
+                        .position(|fldname| fldname == key.field_name())
+                        .map_or(Constant::Null, |i| vals[i].clone());
+                    check_key(tblname, key, &indexes, &val, None)?;
+                }
+                for fk in fks.iter() {
+                    let val = data
+                        .fields()
+                        .iter()
+                        .position(|fldname| fldname == fk.field_name())
+                        .map_or(Constant::Null, |i| vals[i].clone());
+                    check_reference(&mut md, fk, &val, Arc::clone(&tx))?;
+                }
+                // first, insert the record
+                s.insert()?;
+                let rid = s.get_rid()?;
+                // then modify each field, inserting index records
+                let mut valiter = vals.iter();
+                for fldname in data.fields() {
+                    let val = valiter.next().unwrap();
+                    debug!("Modify field {} to val {:?}", fldname, &val);
+                    // NOTE: UpdateScan can convert val to the correct type.
+                    s.set_val(fldname, val.clone())?;
+                    if let Some(ii) = indexes.get(fldname) {
+                        // NOTE: convert the type here, because Index doesn't convert val.
+                        let fldtype = ii.table_schema().field_type(fldname);
+                        let val = val.as_field_type(fldtype)?;
+
+                        let idx = ii.open();
+                        idx.lock().unwrap().insert(val, rid)?;
+                        idx.lock().unwrap().close()?;
+                    }
                 }
             }
             s.close()?;
 
-            return Ok(1);
+            return Ok(data.rows().len() as i32);
         }
 
         Err(From::from(IndexUpdatePlannerError::DowncastError))
@@ -83,10 +129,17 @@ impl UpdatePlanner for IndexUpdatePlanner {
         let p = SelectPlan::new(Arc::new(tp), data.pred().clone());
         let mut md = self.mdm.lock().unwrap();
         let indexes = md.get_index_info(tblname, Arc::clone(&tx))?;
+        let refs = md.get_referencing_keys(tblname, Arc::clone(&tx))?;
 
         if let Ok(s) = p.open()?.lock().unwrap().to_update_scan() {
             let mut count = 0;
             while s.next() {
+                // the referenced keys are kept for the referencing records
+                let mut refvals = HashMap::new();
+                for fk in refs.iter() {
+                    let fldname = fk.ref_field_name();
+                    refvals.insert(fldname.to_string(), s.get_val(fldname)?);
+                }
                 // first, delete the record's RID from every index
                 let rid = s.get_rid()?;
                 for fldname in indexes.keys() {
@@ -104,6 +157,8 @@ impl UpdatePlanner for IndexUpdatePlanner {
                 }
                 // then delete the record
                 s.delete()?;
+                // finally, apply the actions to the records which reference it
+                delete_references(&mut md, tblname, &refvals, Arc::clone(&tx))?;
                 count += 1;
             }
             s.close()?;
@@ -115,37 +170,77 @@ impl UpdatePlanner for IndexUpdatePlanner {
     }
     fn execute_modify(&self, data: ModifyData, tx: Arc<Mutex<Transaction>>) -> Result<i32> {
         let tblname = data.table_name();
-        let fldname = data.target_field();
         let tp = TablePlan::new(tblname, Arc::clone(&tx), Arc::clone(&self.mdm))?;
+        let sch = tp.schema();
         let p = SelectPlan::new(Arc::new(tp), data.pred().clone());
         let mut md = self.mdm.lock().unwrap();
         let indexes = md.get_index_info(tblname, Arc::clone(&tx))?;
-        let ii = indexes.get(fldname);
-        let idx = ii.map(|ii| ii.open());
+        let keys = md.get_keys(tblname, Arc::clone(&tx))?;
+        let constraints = md.get_field_constraints(tblname, Arc::clone(&tx))?;
+        let fks = md.get_foreign_keys(tblname, Arc::clone(&tx))?;
+        let refs = md.get_referencing_keys(tblname, Arc::clone(&tx))?;
+        // the index of each target field, if it exists
+        let idxs: Vec<_> = data
+            .assignments()
+            .iter()
+            .map(|(fldname, _)| indexes.get(fldname).map(|ii| (ii, ii.open())))
+            .collect();
 
         if let Ok(s) = p.open()?.lock().unwrap().to_update_scan() {
             let mut count = 0;
             while s.next() {
-                // first, update the record
-                let scan = s.to_scan()?;
-                let newval = data.new_value().evaluate(scan)?;
-                let oldval = s.get_val(fldname)?;
-                // NOTE: UpdateScan can convert val to the correct type.
-                s.set_val(data.target_field(), newval.clone())?;
-                // then update the appropriate index, if it exists
-                if let Some(idx) = idx.as_ref() {
-                    // NOTE: convert the type here, because Index doesn't convert val.
-                    let fldtype = ii.unwrap().table_schema().field_type(fldname);
-                    let oldval = oldval.as_field_type(fldtype)?;
-                    let newval = newval.as_field_type(fldtype)?;
-
-                    let rid = s.get_rid()?;
-                    idx.lock().unwrap().delete(oldval, rid)?;
-                    idx.lock().unwrap().insert(newval, rid)?;
+                // all values are evaluated on the record before the update
+                let mut newvals = vec![];
+                for (_, expr) in data.assignments() {
+                    newvals.push(expr.evaluate(s.to_scan()?)?);
+                }
+                let rid = s.get_rid()?;
+                // the constraints and the keys are checked before the record is updated
+                if !constraints.is_empty() {
+                    let mut base = HashMap::new();
+                    for fldname in sch.fields() {
+                        base.insert(fldname.clone(), s.get_val(fldname)?);
+                    }
+                    let fldnames = data.assignments().iter().map(|(fldname, _)| fldname);
+                    let record = record_values(&sch, base, fldnames.zip(newvals.iter()))?;
+                    check_constraints(tblname, &constraints, record)?;
+                }
+                for ((fldname, _), newval) in data.assignments().iter().zip(newvals.iter()) {
+                    if let Some(key) = keys.iter().find(|key| key.field_name() == fldname) {
+                        check_key(tblname, key, &indexes, newval, Some(rid))?;
+                    }
+                    if let Some(fk) = fks.iter().find(|fk| fk.field_name() == fldname) {
+                        check_reference(&mut md, fk, newval, Arc::clone(&tx))?;
+                    }
+                    // a referenced key can't be changed
+                    for fk in refs.iter().filter(|fk| fk.ref_field_name() == fldname) {
+                        let oldval = s.get_val(fldname)?;
+                        if oldval != *newval {
+                            check_unreferenced(&mut md, fk, &oldval, Arc::clone(&tx))?;
+                        }
+                    }
+                }
+                for (((fldname, _), newval), idx) in
+                    data.assignments().iter().zip(newvals).zip(idxs.iter())
+                {
+                    // first, update the record
+                    let oldval = s.get_val(fldname)?;
+                    // NOTE: UpdateScan can convert val to the correct type.
+                    s.set_val(fldname, newval.clone())?;
+                    // then update the appropriate index, if it exists
+                    if let Some((ii, idx)) = idx {
+                        // NOTE: convert the type here, because Index doesn't convert val.
+                        let fldtype = ii.table_schema().field_type(fldname);
+                        let oldval = oldval.as_field_type(fldtype)?;
+                        let newval = newval.as_field_type(fldtype)?;
+
+                        idx.lock().unwrap().delete(oldval, rid)?;
+                        idx.lock().unwrap().insert(newval, rid)?;
+                    }
                 }
                 count += 1;
             }
-            if let Some(idx) = idx.as_ref() {
+            for (_, idx) in idxs.iter().flatten() {
                 idx.lock().unwrap().close()?;
             }
             s.close()?;
@@ -161,14 +256,38 @@ impl UpdatePlanner for IndexUpdatePlanner {
         tx: Arc<Mutex<Transaction>>,
     ) -> Result<i32> {
         let md = self.mdm.lock().unwrap();
-        md.create_table(data.table_name(), Arc::new(data.new_schema().clone()), tx)?;
+        md.create_table(
+            data.table_name(),
+            Arc::new(data.new_schema().clone()),
+            Arc::clone(&tx),
+        )?;
+        for key in data.keys() {
+            md.create_key(
+                data.table_name(),
+                key.field_name(),
+                key.is_primary(),
+                Arc::clone(&tx),
+            )?;
+        }
+        for fc in data.constraints() {
+            md.create_field_constraint(data.table_name(), fc, Arc::clone(&tx))?;
+        }
+        for fk in data.foreign_keys() {
+            md.create_foreign_key(data.table_name(), fk, Arc::clone(&tx))?;
+        }
         Ok(0)
     }
     fn execute_create_view(
         &self,
-        data: CreateViewData,
+        mut data: CreateViewData,
         tx: Arc<Mutex<Transaction>>,
     ) -> Result<i32> {
+        // the view keeps the fields at the creation, even if the tables are altered.
+        expand_wildcards(
+            data.query_data_mut(),
+            Arc::clone(&self.mdm),
+            Arc::clone(&tx),
+        )?;
         let md = self.mdm.lock().unwrap();
         md.create_view(data.view_name(), &data.view_def(), tx)?;
         Ok(0)
@@ -182,4 +301,45 @@ impl UpdatePlanner for IndexUpdatePlanner {
         md.create_index(data.index_name(), data.table_name(), data.field_name(), tx)?;
         Ok(0)
     }
+    fn execute_drop_table(&self, data: DropData, tx: Arc<Mutex<Transaction>>) -> Result<i32> {
+        let mut md = self.mdm.lock().unwrap();
+        if data.if_exists() && !md.has_table(data.name(), Arc::clone(&tx))? {
+            return Ok(0);
+        }
+        md.drop_table(data.name(), tx)?;
+        Ok(0)
+    }
+    fn execute_drop_view(&self, data: DropData, tx: Arc<Mutex<Transaction>>) -> Result<i32> {
+        let md = self.mdm.lock().unwrap();
+        if data.if_exists() && !md.has_view(data.name(), Arc::clone(&tx))? {
+            return Ok(0);
+        }
+        md.drop_view(data.name(), tx)?;
+        Ok(0)
+    }
+    fn execute_drop_index(&self, data: DropData, tx: Arc<Mutex<Transaction>>) -> Result<i32> {
+        let md = self.mdm.lock().unwrap();
+        if data.if_exists() && !md.has_index(data.name(), Arc::clone(&tx))? {
+            return Ok(0);
+        }
+        md.drop_index(data.name(), tx)?;
+        Ok(0)
+    }
+    fn execute_alter_table(
+        &self,
+        data: AlterTableData,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<i32> {
+        let mut md = self.mdm.lock().unwrap();
+        let tblname = data.table_name();
+        match data.action() {
+            AlterAction::AddColumn(fldname, fi) => md.add_field(tblname, fldname, *fi, tx)?,
+            AlterAction::DropColumn(fldname) => md.drop_field(tblname, fldname, tx)?,
+            AlterAction::RenameColumn(fldname, newname) => {
+                md.rename_field(tblname, fldname, newname, tx)?
+            }
+            AlterAction::RenameTable(newname) => md.rename_table(tblname, newname, tx)?,
+        }
+        Ok(0)
+    }
 }
@@ -7,7 +7,7 @@ use crate::{
     index::Index,
     materialize::sortscan::SortScan,
     query::{constant::Constant, scan::Scan, updatescan::UpdateScan},
-    record::tablescan::TableScan,
+    record::{schema::Schema, tablescan::TableScan},
 };
 
 #[derive(Debug)]
@@ -31,6 +31,14 @@ pub struct IndexJoinScan {
     idx: Arc<Mutex<dyn Index>>,
     joinfield: String,
     rhs: Arc<Mutex<TableScan>>,
+    // the schema of rhs, with which an unmatched lhs record is padded,
+    // if this is a left outer join.
+    padding: Option<Arc<Schema>>,
+    hasmore1: bool,
+    matched: bool,
+    padded: bool,
+    // the join value of the lhs record is null, which matches no rhs record
+    nullkey: bool,
 }
 
 impl IndexJoinScan {
@@ -45,37 +53,82 @@ impl IndexJoinScan {
             idx,
             joinfield: joinfld.to_string(),
             rhs,
+            padding: None,
+            hasmore1: false,
+            matched: false,
+            padded: false,
+            nullkey: false,
         };
         scan.before_first()?;
 
         Ok(scan)
     }
-    fn reset_index(&self) -> Result<()> {
+    // my own extends
+    pub fn new_outer(
+        lhs: Arc<Mutex<dyn Scan>>,
+        idx: Arc<Mutex<dyn Index>>,
+        joinfld: &str,
+        rhs: Arc<Mutex<TableScan>>,
+        sch2: Arc<Schema>,
+    ) -> Result<Self> {
+        let mut scan = Self::new(lhs, idx, joinfld, rhs)?;
+        scan.padding = Some(sch2);
+
+        Ok(scan)
+    }
+    fn reset_index(&mut self) -> Result<()> {
         let searchkey = self.lhs.lock().unwrap().get_val(&self.joinfield)?;
+        self.nullkey = searchkey.is_null();
         self.idx.lock().unwrap().before_first(searchkey)
     }
+    fn padding(&self, fldname: &str) -> Option<Constant> {
+        match &self.padding {
+            Some(sch) if self.padded && sch.has_field(fldname) => Some(Constant::Null),
+            _ => None,
+        }
+    }
 }
 
 impl Scan for IndexJoinScan {
     fn before_first(&mut self) -> Result<()> {
         self.lhs.lock().unwrap().before_first()?;
-        self.lhs.lock().unwrap().next();
-        self.reset_index()
+        self.hasmore1 = self.lhs.lock().unwrap().next();
+        self.matched = false;
+        self.padded = false;
+        if self.hasmore1 {
+            self.reset_index()?;
+        }
+
+        Ok(())
     }
     fn next(&mut self) -> bool {
+        if !self.hasmore1 {
+            return false;
+        }
         loop {
-            if self.idx.lock().unwrap().next() {
+            if !self.padded && !self.nullkey && self.idx.lock().unwrap().next() {
                 let rid = self.idx.lock().unwrap().get_data_rid().unwrap();
                 self.rhs.lock().unwrap().move_to_rid(rid).unwrap();
+                self.matched = true;
                 return true;
             }
-            if !self.lhs.lock().unwrap().next() {
+            if self.padding.is_some() && !self.matched && !self.padded {
+                self.padded = true;
+                return true;
+            }
+            self.hasmore1 = self.lhs.lock().unwrap().next();
+            if !self.hasmore1 {
                 return false;
             }
+            self.matched = false;
+            self.padded = false;
             self.reset_index().unwrap();
         }
     }
     fn get_i16(&mut self, fldname: &str) -> Result<i16> {
+        if let Some(val) = self.padding(fldname) {
+            return val.as_i16();
+        }
         if self.rhs.lock().unwrap().has_field(fldname) {
             self.rhs.lock().unwrap().get_i16(fldname)
         } else {
@@ -83,6 +136,9 @@ impl Scan for IndexJoinScan {
         }
     }
     fn get_i32(&mut self, fldname: &str) -> Result<i32> {
+        if let Some(val) = self.padding(fldname) {
+            return val.as_i32();
+        }
         if self.rhs.lock().unwrap().has_field(fldname) {
             self.rhs.lock().unwrap().get_i32(fldname)
         } else {
@@ -90,6 +146,9 @@ impl Scan for IndexJoinScan {
         }
     }
     fn get_string(&mut self, fldname: &str) -> Result<String> {
+        if let Some(val) = self.padding(fldname) {
+            return val.as_string().map(|s| s.to_string());
+        }
         if self.rhs.lock().unwrap().has_field(fldname) {
             self.rhs.lock().unwrap().get_string(fldname)
         } else {
@@ -97,6 +156,9 @@ impl Scan for IndexJoinScan {
         }
     }
     fn get_bool(&mut self, fldname: &str) -> Result<bool> {
+        if let Some(val) = self.padding(fldname) {
+            return val.as_bool();
+        }
         if self.rhs.lock().unwrap().has_field(fldname) {
             self.rhs.lock().unwrap().get_bool(fldname)
         } else {
@@ -104,6 +166,9 @@ impl Scan for IndexJoinScan {
         }
     }
     fn get_date(&mut self, fldname: &str) -> Result<NaiveDate> {
+        if let Some(val) = self.padding(fldname) {
+            return val.as_date();
+        }
         if self.rhs.lock().unwrap().has_field(fldname) {
             self.rhs.lock().unwrap().get_date(fldname)
         } else {
@@ -111,6 +176,9 @@ impl Scan for IndexJoinScan {
         }
     }
     fn get_val(&mut self, fldname: &str) -> Result<Constant> {
+        if let Some(val) = self.padding(fldname) {
+            return Ok(val);
+        }
         if self.rhs.lock().unwrap().has_field(fldname) {
             self.rhs.lock().unwrap().get_val(fldname)
         } else {
@@ -26,10 +26,23 @@ impl fmt::Display for IndexSelectScanError {
     }
 }
 
+// my own extends
+// the lookups of the index by the scan.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum IndexSearch {
+    // the records whose indexed value equals any of the values,
+    // which are looked up one after another.
+    Eq(Vec<Constant>),
+    // the records whose indexed value is in [lo, hi), or [lo, hi] if inclusive.
+    Range(Constant, Constant, bool),
+}
+
 pub struct IndexSelectScan {
     ts: Arc<Mutex<TableScan>>,
     idx: Arc<Mutex<dyn Index>>,
-    val: Constant,
+    search: IndexSearch,
+    // the position of the value being looked up, for IndexSearch::Eq
+    current: usize,
 }
 
 impl IndexSelectScan {
@@ -38,7 +51,20 @@ impl IndexSelectScan {
         idx: Arc<Mutex<dyn Index>>,
         val: Constant,
     ) -> Result<Self> {
-        let mut scan = Self { ts, idx, val };
+        Self::new_with(ts, idx, IndexSearch::Eq(vec![val]))
+    }
+    // my own extends
+    pub fn new_with(
+        ts: Arc<Mutex<TableScan>>,
+        idx: Arc<Mutex<dyn Index>>,
+        search: IndexSearch,
+    ) -> Result<Self> {
+        let mut scan = Self {
+            ts,
+            idx,
+            search,
+            current: 0,
+        };
         scan.before_first()?;
 
         Ok(scan)
@@ -47,16 +73,34 @@ impl IndexSelectScan {
 
 impl Scan for IndexSelectScan {
     fn before_first(&mut self) -> Result<()> {
-        self.idx.lock().unwrap().before_first(self.val.clone())
+        self.current = 0;
+        match &self.search {
+            IndexSearch::Eq(vals) => self.idx.lock().unwrap().before_first(vals[0].clone()),
+            IndexSearch::Range(lo, hi, inclusive) => {
+                self.idx
+                    .lock()
+                    .unwrap()
+                    .before_range(lo.clone(), hi.clone(), *inclusive)
+            }
+        }
     }
     fn next(&mut self) -> bool {
-        let ok = self.idx.lock().unwrap().next();
-        if ok {
-            let rid = self.idx.lock().unwrap().get_data_rid().unwrap();
-            self.ts.lock().unwrap().move_to_rid(rid).unwrap();
+        loop {
+            if self.idx.lock().unwrap().next() {
+                let rid = self.idx.lock().unwrap().get_data_rid().unwrap();
+                self.ts.lock().unwrap().move_to_rid(rid).unwrap();
+                return true;
+            }
+            // looks up the next value
+            match &self.search {
+                IndexSearch::Eq(vals) if self.current + 1 < vals.len() => {
+                    self.current += 1;
+                    let val = vals[self.current].clone();
+                    self.idx.lock().unwrap().before_first(val).unwrap();
+                }
+                _ => return false,
+            }
         }
-
-        ok
     }
     fn get_i16(&mut self, fldname: &str) -> Result<i16> {
         self.ts.lock().unwrap().get_i16(fldname)
@@ -1,4 +1,6 @@
 pub mod aggregationfn;
+pub mod distinctplan;
+pub mod distinctscan;
 pub mod groupbyplan;
 pub mod groupbyscan;
 pub mod groupvalue;
@@ -6,6 +8,8 @@ pub mod materializeplan;
 pub mod mergejoinplan;
 pub mod mergejoinscan;
 pub mod recordcomparator;
+pub mod setopplan;
+pub mod setopscan;
 pub mod sortplan;
 pub mod sortscan;
 pub mod temptable;
@@ -1,12 +1,106 @@
+use anyhow::Result;
+use core::fmt;
 use std::sync::{Arc, Mutex};
 
-use crate::query::{constant::Constant, scan::Scan};
+use crate::{
+    query::{constant::Constant, scan::Scan},
+    record::schema::{FieldInfo, FieldType, Schema},
+};
 
+pub mod avgfn;
+pub mod countfn;
 pub mod maxfn;
+pub mod minfn;
+pub mod sumfn;
+
+use self::{avgfn::AvgFn, countfn::CountFn, maxfn::MaxFn, minfn::MinFn, sumfn::SumFn};
+
+#[derive(Debug)]
+pub enum AggregationFnError {
+    Overflow(String),
+    FieldNotFound(String),
+    TypeMismatch(String),
+}
+
+impl std::error::Error for AggregationFnError {}
+impl fmt::Display for AggregationFnError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AggregationFnError::Overflow(fldname) => {
+                write!(f, "overflow: {}", fldname)
+            }
+            AggregationFnError::FieldNotFound(fldname) => {
+                write!(f, "field not found: {}", fldname)
+            }
+            AggregationFnError::TypeMismatch(fldname) => {
+                write!(f, "type mismatch: {}", fldname)
+            }
+        }
+    }
+}
 
 pub trait AggregationFn {
     fn process_first(&self, scan: Arc<Mutex<dyn Scan>>);
     fn process_next(&self, scan: Arc<Mutex<dyn Scan>>);
+    // the name must not contain a dot, which would read as a qualified name
     fn field_name(&self) -> String;
-    fn value(&self) -> Constant;
+    fn value(&self) -> Result<Constant>;
+    // my own extends
+    fn reset(&self);
+    fn field_info(&self, sch: Arc<Schema>) -> Result<FieldInfo>;
+}
+
+// An aggregate function call in SQL, like COUNT(*) or MAX(GradYear).
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Aggregate {
+    CountAll,
+    Count(String),
+    Sum(String),
+    Avg(String),
+    Min(String),
+    Max(String),
+}
+
+impl fmt::Display for Aggregate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Aggregate::CountAll => write!(f, "count(*)"),
+            Aggregate::Count(fldname) => write!(f, "count({})", fldname),
+            Aggregate::Sum(fldname) => write!(f, "sum({})", fldname),
+            Aggregate::Avg(fldname) => write!(f, "avg({})", fldname),
+            Aggregate::Min(fldname) => write!(f, "min({})", fldname),
+            Aggregate::Max(fldname) => write!(f, "max({})", fldname),
+        }
+    }
+}
+
+impl Aggregate {
+    pub fn to_aggregation_fn(&self) -> Arc<dyn AggregationFn> {
+        match self {
+            Aggregate::CountAll => Arc::new(CountFn::new_all()),
+            Aggregate::Count(fldname) => Arc::new(CountFn::new(fldname)),
+            Aggregate::Sum(fldname) => Arc::new(SumFn::new(fldname)),
+            Aggregate::Avg(fldname) => Arc::new(AvgFn::new(fldname)),
+            Aggregate::Min(fldname) => Arc::new(MinFn::new(fldname)),
+            Aggregate::Max(fldname) => Arc::new(MaxFn::new(fldname)),
+        }
+    }
+    // the name of the field, which GroupByScan outputs for this aggregate.
+    pub fn field_name(&self) -> String {
+        self.to_aggregation_fn().field_name()
+    }
+}
+
+fn numeric_field_info(fldname: &str, sch: Arc<Schema>) -> Result<FieldInfo> {
+    if !sch.has_field(fldname) {
+        return Err(From::from(AggregationFnError::FieldNotFound(
+            fldname.to_string(),
+        )));
+    }
+    match sch.field_type(fldname) {
+        FieldType::SMALLINT | FieldType::INTEGER => Ok(FieldInfo::new(FieldType::INTEGER, 0)),
+        _ => Err(From::from(AggregationFnError::TypeMismatch(
+            fldname.to_string(),
+        ))),
+    }
 }
@@ -0,0 +1,63 @@
+use anyhow::Result;
+use std::{
+    cell::RefCell,
+    sync::{Arc, Mutex},
+};
+
+use super::{numeric_field_info, AggregationFn};
+use crate::{
+    query::{constant::Constant, scan::Scan},
+    record::schema::{FieldInfo, Schema},
+};
+
+// NOTE: there is no decimal type, so the average is truncated to an integer.
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub struct AvgFn {
+    fldname: String,
+    sum: RefCell<Option<i64>>,
+    count: RefCell<i64>,
+}
+
+impl AvgFn {
+    pub fn new(fldname: &str) -> Self {
+        Self {
+            fldname: fldname.to_string(),
+            sum: RefCell::new(None),
+            count: RefCell::new(0),
+        }
+    }
+}
+
+impl AggregationFn for AvgFn {
+    fn process_first(&self, scan: Arc<Mutex<dyn Scan>>) {
+        self.reset();
+        self.process_next(scan);
+    }
+    // the nulls are ignored, and not counted either
+    fn process_next(&self, scan: Arc<Mutex<dyn Scan>>) {
+        let newval = scan.lock().unwrap().get_val(&self.fldname);
+        if let Ok(v) = newval.and_then(|v| v.as_i32()) {
+            let sum = self.sum.borrow().unwrap_or(0);
+            *self.sum.borrow_mut() = Some(sum + v as i64);
+            *self.count.borrow_mut() += 1;
+        }
+    }
+    fn field_name(&self) -> String {
+        format!("avgof{}", self.fldname.replace('.', "_"))
+    }
+    fn value(&self) -> Result<Constant> {
+        match *self.sum.borrow() {
+            // the average of i32 values always fits in i32
+            Some(sum) => Ok(Constant::I32((sum / *self.count.borrow()) as i32)),
+            // there are no values other than null
+            None => Ok(Constant::Null),
+        }
+    }
+    fn reset(&self) {
+        *self.sum.borrow_mut() = None;
+        *self.count.borrow_mut() = 0;
+    }
+    fn field_info(&self, sch: Arc<Schema>) -> Result<FieldInfo> {
+        numeric_field_info(&self.fldname, sch)
+    }
+}
@@ -0,0 +1,71 @@
+use anyhow::Result;
+use std::{
+    cell::RefCell,
+    sync::{Arc, Mutex},
+};
+
+use super::{AggregationFn, AggregationFnError};
+use crate::{
+    query::{constant::Constant, scan::Scan},
+    record::schema::{FieldInfo, FieldType, Schema},
+};
+
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub struct CountFn {
+    // None means COUNT(*)
+    fldname: Option<String>,
+    count: RefCell<i32>,
+}
+
+impl CountFn {
+    pub fn new(fldname: &str) -> Self {
+        Self {
+            fldname: Some(fldname.to_string()),
+            count: RefCell::new(0),
+        }
+    }
+    pub fn new_all() -> Self {
+        Self {
+            fldname: None,
+            count: RefCell::new(0),
+        }
+    }
+}
+
+impl AggregationFn for CountFn {
+    fn process_first(&self, scan: Arc<Mutex<dyn Scan>>) {
+        *self.count.borrow_mut() = 0;
+        self.process_next(scan);
+    }
+    // COUNT(field) doesn't count the nulls
+    fn process_next(&self, scan: Arc<Mutex<dyn Scan>>) {
+        if let Some(fldname) = self.fldname.as_ref() {
+            if scan.lock().unwrap().is_null(fldname).unwrap_or(true) {
+                return;
+            }
+        }
+        *self.count.borrow_mut() += 1;
+    }
+    fn field_name(&self) -> String {
+        match self.fldname.as_ref() {
+            Some(fldname) => format!("countof{}", fldname.replace('.', "_")),
+            None => "countofall".to_string(),
+        }
+    }
+    fn value(&self) -> Result<Constant> {
+        Ok(Constant::I32(*self.count.borrow()))
+    }
+    fn reset(&self) {
+        *self.count.borrow_mut() = 0;
+    }
+    fn field_info(&self, sch: Arc<Schema>) -> Result<FieldInfo> {
+        if let Some(fldname) = self.fldname.as_ref() {
+            if !sch.has_field(fldname) {
+                return Err(From::from(AggregationFnError::FieldNotFound(
+                    fldname.to_string(),
+                )));
+            }
+        }
+        Ok(FieldInfo::new(FieldType::INTEGER, 0))
+    }
+}
@@ -1,10 +1,14 @@
+use anyhow::Result;
 use std::{
     cell::RefCell,
     sync::{Arc, Mutex},
 };
 
-use super::AggregationFn;
-use crate::query::{constant::Constant, scan::Scan};
+use super::{AggregationFn, AggregationFnError};
+use crate::{
+    query::{constant::Constant, scan::Scan},
+    record::schema::{FieldInfo, Schema},
+};
 
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
 pub struct MaxFn {
@@ -23,18 +27,42 @@ impl MaxFn {
 
 impl AggregationFn for MaxFn {
     fn process_first(&self, scan: Arc<Mutex<dyn Scan>>) {
-        *self.val.borrow_mut() = scan.lock().unwrap().get_val(&self.fldname).ok()
+        self.reset();
+        self.process_next(scan);
     }
+    // the nulls are ignored
     fn process_next(&self, scan: Arc<Mutex<dyn Scan>>) {
-        let newval = scan.lock().unwrap().get_val(&self.fldname).ok();
-        if newval > *self.val.borrow() {
-            *self.val.borrow_mut() = newval;
+        let newval = match scan.lock().unwrap().get_val(&self.fldname) {
+            Ok(v) if !v.is_null() => v,
+            _ => return,
+        };
+        let replace = match self.val.borrow().as_ref() {
+            Some(val) => newval > *val,
+            None => true,
+        };
+        if replace {
+            *self.val.borrow_mut() = Some(newval);
         }
     }
     fn field_name(&self) -> String {
-        format!("maxof{}", self.fldname)
+        format!("maxof{}", self.fldname.replace('.', "_"))
+    }
+    fn value(&self) -> Result<Constant> {
+        // there are no values other than null
+        Ok((*self.val.borrow()).clone().unwrap_or(Constant::Null))
     }
-    fn value(&self) -> Constant {
-        (*self.val.borrow()).as_ref().unwrap().clone()
+    fn reset(&self) {
+        *self.val.borrow_mut() = None;
+    }
+    fn field_info(&self, sch: Arc<Schema>) -> Result<FieldInfo> {
+        if !sch.has_field(&self.fldname) {
+            return Err(From::from(AggregationFnError::FieldNotFound(
+                self.fldname.clone(),
+            )));
+        }
+        Ok(FieldInfo::new(
+            sch.field_type(&self.fldname),
+            sch.length(&self.fldname),
+        ))
     }
 }
@@ -0,0 +1,68 @@
+use anyhow::Result;
+use std::{
+    cell::RefCell,
+    sync::{Arc, Mutex},
+};
+
+use super::{AggregationFn, AggregationFnError};
+use crate::{
+    query::{constant::Constant, scan::Scan},
+    record::schema::{FieldInfo, Schema},
+};
+
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub struct MinFn {
+    fldname: String,
+    val: RefCell<Option<Constant>>,
+}
+
+impl MinFn {
+    pub fn new(fldname: &str) -> Self {
+        Self {
+            fldname: fldname.to_string(),
+            val: RefCell::new(None),
+        }
+    }
+}
+
+impl AggregationFn for MinFn {
+    fn process_first(&self, scan: Arc<Mutex<dyn Scan>>) {
+        self.reset();
+        self.process_next(scan);
+    }
+    // the nulls are ignored
+    fn process_next(&self, scan: Arc<Mutex<dyn Scan>>) {
+        let newval = match scan.lock().unwrap().get_val(&self.fldname) {
+            Ok(v) if !v.is_null() => v,
+            _ => return,
+        };
+        let replace = match self.val.borrow().as_ref() {
+            Some(val) => newval < *val,
+            None => true,
+        };
+        if replace {
+            *self.val.borrow_mut() = Some(newval);
+        }
+    }
+    fn field_name(&self) -> String {
+        format!("minof{}", self.fldname.replace('.', "_"))
+    }
+    fn value(&self) -> Result<Constant> {
+        // there are no values other than null
+        Ok((*self.val.borrow()).clone().unwrap_or(Constant::Null))
+    }
+    fn reset(&self) {
+        *self.val.borrow_mut() = None;
+    }
+    fn field_info(&self, sch: Arc<Schema>) -> Result<FieldInfo> {
+        if !sch.has_field(&self.fldname) {
+            return Err(From::from(AggregationFnError::FieldNotFound(
+                self.fldname.clone(),
+            )));
+        }
+        Ok(FieldInfo::new(
+            sch.field_type(&self.fldname),
+            sch.length(&self.fldname),
+        ))
+    }
+}
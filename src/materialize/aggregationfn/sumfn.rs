@@ -0,0 +1,61 @@
+use anyhow::Result;
+use std::{
+    cell::RefCell,
+    sync::{Arc, Mutex},
+};
+
+use super::{numeric_field_info, AggregationFn, AggregationFnError};
+use crate::{
+    query::{constant::Constant, scan::Scan},
+    record::schema::{FieldInfo, Schema},
+};
+
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub struct SumFn {
+    fldname: String,
+    // accumulate in i64, and check the overflow only once at the end.
+    // None until a non-null value is found.
+    sum: RefCell<Option<i64>>,
+}
+
+impl SumFn {
+    pub fn new(fldname: &str) -> Self {
+        Self {
+            fldname: fldname.to_string(),
+            sum: RefCell::new(None),
+        }
+    }
+}
+
+impl AggregationFn for SumFn {
+    fn process_first(&self, scan: Arc<Mutex<dyn Scan>>) {
+        self.reset();
+        self.process_next(scan);
+    }
+    // the nulls are ignored
+    fn process_next(&self, scan: Arc<Mutex<dyn Scan>>) {
+        let newval = scan.lock().unwrap().get_val(&self.fldname);
+        if let Ok(v) = newval.and_then(|v| v.as_i32()) {
+            let sum = self.sum.borrow().unwrap_or(0);
+            *self.sum.borrow_mut() = Some(sum + v as i64);
+        }
+    }
+    fn field_name(&self) -> String {
+        format!("sumof{}", self.fldname.replace('.', "_"))
+    }
+    fn value(&self) -> Result<Constant> {
+        match *self.sum.borrow() {
+            Some(sum) => i32::try_from(sum)
+                .map(|v| Constant::I32(v))
+                .map_err(|_| From::from(AggregationFnError::Overflow(self.field_name()))),
+            // there are no values other than null
+            None => Ok(Constant::Null),
+        }
+    }
+    fn reset(&self) {
+        *self.sum.borrow_mut() = None;
+    }
+    fn field_info(&self, sch: Arc<Schema>) -> Result<FieldInfo> {
+        numeric_field_info(&self.fldname, sch)
+    }
+}
@@ -0,0 +1,155 @@
+use anyhow::Result;
+use std::sync::{Arc, Mutex};
+
+use super::{distinctscan::DistinctScan, sortplan::SortPlan};
+use crate::{
+    plan::plan::Plan,
+    query::scan::Scan,
+    record::schema::Schema,
+    repr::planrepr::{Operation, PlanRepr},
+    tx::transaction::Transaction,
+};
+
+// Removes duplicate records by sorting on all fields,
+// so that the duplicates become adjacent.
+#[derive(Clone)]
+pub struct DistinctPlan {
+    p: Arc<dyn Plan>,
+    sch: Arc<Schema>,
+}
+
+impl DistinctPlan {
+    pub fn new(
+        next_table_num: Arc<Mutex<i32>>,
+        tx: Arc<Mutex<Transaction>>,
+        p: Arc<dyn Plan>,
+    ) -> Self {
+        let sch = p.schema();
+        let plan = SortPlan::new(next_table_num, p, sch.fields().clone(), tx);
+
+        Self {
+            p: Arc::new(plan),
+            sch,
+        }
+    }
+}
+
+impl Plan for DistinctPlan {
+    fn open(&self) -> Result<Arc<Mutex<dyn Scan>>> {
+        let s = self.p.open()?;
+        let scan = DistinctScan::new(s, self.sch.fields().clone());
+
+        Ok(Arc::new(Mutex::new(scan)))
+    }
+    fn blocks_accessed(&self) -> i32 {
+        self.p.blocks_accessed()
+    }
+    fn records_output(&self) -> i32 {
+        let mut numrecs: i32 = 1;
+        for fldname in self.sch.fields() {
+            numrecs = numrecs.saturating_mul(self.p.distinct_values(fldname));
+        }
+
+        numrecs.min(self.p.records_output())
+    }
+    fn distinct_values(&self, fldname: &str) -> i32 {
+        self.p.distinct_values(fldname)
+    }
+    fn schema(&self) -> Arc<Schema> {
+        Arc::clone(&self.sch)
+    }
+
+    fn repr(&self) -> Arc<dyn PlanRepr> {
+        Arc::new(DistinctPlanRepr {
+            p: self.p.repr(),
+            r: self.blocks_accessed(),
+            w: self.records_output(),
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct DistinctPlanRepr {
+    p: Arc<dyn PlanRepr>,
+    r: i32,
+    w: i32,
+}
+
+impl PlanRepr for DistinctPlanRepr {
+    fn operation(&self) -> Operation {
+        Operation::DistinctScan
+    }
+    fn reads(&self) -> i32 {
+        self.r
+    }
+    fn writes(&self) -> i32 {
+        self.w
+    }
+    fn sub_plan_reprs(&self) -> Vec<Arc<dyn PlanRepr>> {
+        vec![Arc::clone(&self.p)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use super::*;
+    use crate::{
+        metadata::manager::MetadataMgr,
+        plan::{projectplan::ProjectPlan, tableplan::TablePlan},
+        query::tests,
+        server::simpledb::SimpleDB,
+    };
+
+    #[test]
+    fn unit_test() -> Result<()> {
+        if Path::new("_test/distinctplan").exists() {
+            fs::remove_dir_all("_test/distinctplan")?;
+        }
+
+        let simpledb = SimpleDB::new_with("_test/distinctplan", 400, 8);
+
+        let tx = Arc::new(Mutex::new(simpledb.new_tx()?));
+        let next_table_num = Arc::new(Mutex::new(0));
+        let mut mdm = MetadataMgr::new(true, Arc::clone(&tx))?;
+        tests::init_sampledb(&mut mdm, Arc::clone(&tx))?;
+        let mdm = Arc::new(Mutex::new(mdm));
+
+        let srcplan = Arc::new(TablePlan::new(
+            "STUDENT",
+            Arc::clone(&tx),
+            Arc::clone(&mdm),
+        )?);
+        let projected = Arc::new(ProjectPlan::new(
+            srcplan,
+            vec!["MajorId".to_string(), "GradYear".to_string()],
+        ));
+        let plan = DistinctPlan::new(Arc::clone(&next_table_num), Arc::clone(&tx), projected);
+
+        let scan = plan.open()?;
+        scan.lock().unwrap().before_first()?;
+        let mut rows = vec![];
+        let mut iter = scan.lock().unwrap();
+        while iter.next() {
+            rows.push((iter.get_i32("MajorId")?, iter.get_i32("GradYear")?));
+        }
+        iter.close()?;
+        assert_eq!(
+            rows,
+            vec![
+                (10, 2021),
+                (10, 2022),
+                (20, 2019),
+                (20, 2020),
+                (20, 2022),
+                (30, 2020),
+                (30, 2021)
+            ]
+        );
+        tx.lock().unwrap().commit()?;
+        assert_eq!(tx.lock().unwrap().available_buffs(), 8);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use core::fmt;
+use std::sync::{Arc, Mutex};
+
+use super::{groupvalue::GroupValue, sortscan::SortScan};
+use crate::{
+    query::{constant::Constant, scan::Scan, updatescan::UpdateScan},
+    record::tablescan::TableScan,
+};
+
+#[derive(Debug)]
+pub enum DistinctScanError {
+    DowncastError,
+}
+
+impl std::error::Error for DistinctScanError {}
+impl fmt::Display for DistinctScanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DistinctScanError::DowncastError => {
+                write!(f, "downcast error")
+            }
+        }
+    }
+}
+
+// The underlying scan must be sorted on all fields,
+// so that the duplicates are adjacent.
+#[derive(Clone)]
+pub struct DistinctScan {
+    s: Arc<Mutex<dyn Scan>>,
+    fields: Vec<String>,
+    prevval: Option<GroupValue>,
+}
+
+impl DistinctScan {
+    pub fn new(s: Arc<Mutex<dyn Scan>>, fields: Vec<String>) -> Self {
+        Self {
+            s,
+            fields,
+            prevval: None,
+        }
+    }
+}
+
+impl Scan for DistinctScan {
+    fn before_first(&mut self) -> Result<()> {
+        self.prevval = None;
+        self.s.lock().unwrap().before_first()
+    }
+    fn next(&mut self) -> bool {
+        while self.s.lock().unwrap().next() {
+            let val = Some(GroupValue::new(Arc::clone(&self.s), self.fields.clone()));
+            if self.prevval != val {
+                self.prevval = val;
+                return true;
+            }
+        }
+
+        false
+    }
+    fn get_i16(&mut self, fldname: &str) -> Result<i16> {
+        self.s.lock().unwrap().get_i16(fldname)
+    }
+    fn get_i32(&mut self, fldname: &str) -> Result<i32> {
+        self.s.lock().unwrap().get_i32(fldname)
+    }
+    fn get_string(&mut self, fldname: &str) -> Result<String> {
+        self.s.lock().unwrap().get_string(fldname)
+    }
+    fn get_bool(&mut self, fldname: &str) -> Result<bool> {
+        self.s.lock().unwrap().get_bool(fldname)
+    }
+    fn get_date(&mut self, fldname: &str) -> Result<NaiveDate> {
+        self.s.lock().unwrap().get_date(fldname)
+    }
+    fn get_val(&mut self, fldname: &str) -> Result<Constant> {
+        self.s.lock().unwrap().get_val(fldname)
+    }
+    fn has_field(&self, fldname: &str) -> bool {
+        self.fields.contains(&fldname.to_string())
+    }
+    fn close(&mut self) -> Result<()> {
+        self.s.lock().unwrap().close()
+    }
+    fn to_update_scan(&mut self) -> Result<&mut dyn UpdateScan> {
+        Err(From::from(DistinctScanError::DowncastError))
+    }
+    fn as_table_scan(&mut self) -> Result<&mut TableScan> {
+        Err(From::from(DistinctScanError::DowncastError))
+    }
+    fn as_sort_scan(&mut self) -> Result<&mut SortScan> {
+        Err(From::from(DistinctScanError::DowncastError))
+    }
+}
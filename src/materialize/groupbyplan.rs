@@ -4,7 +4,7 @@ use std::sync::{Arc, Mutex};
 use super::{aggregationfn::AggregationFn, groupbyscan::GroupByScan, sortplan::SortPlan};
 use crate::{
     plan::plan::Plan,
-    query::{constant::Constant, scan::Scan},
+    query::scan::Scan,
     record::schema::Schema,
     repr::planrepr::{Operation, PlanRepr},
     tx::transaction::Transaction,
@@ -25,7 +25,15 @@ impl GroupByPlan {
         p: Arc<dyn Plan>,
         groupfields: Vec<String>,
         aggfns: Vec<Arc<dyn AggregationFn>>,
-    ) -> Self {
+    ) -> Result<Self> {
+        // an unqualified group field is named as the field it refers to
+        let groupfields: Vec<String> = groupfields
+            .into_iter()
+            .map(|fldname| match p.schema().resolve(&fldname) {
+                Some(f) => f.clone(),
+                None => fldname,
+            })
+            .collect();
         let plan = SortPlan::new(
             Arc::clone(&next_table_num),
             p,
@@ -38,15 +46,16 @@ impl GroupByPlan {
             sch.add(fldname, plan.schema());
         }
         for aggfn in aggfns.iter() {
-            sch.add_i32_field(&aggfn.field_name());
+            let info = aggfn.field_info(plan.schema())?;
+            sch.add_field(&aggfn.field_name(), info.fld_type, info.length);
         }
 
-        Self {
+        Ok(Self {
             p: Arc::new(plan),
             groupfields,
             aggfns,
             sch: Arc::new(sch),
-        }
+        })
     }
     // my own extends
     pub fn aggfns(&self) -> Vec<Arc<dyn AggregationFn>> {
@@ -87,11 +96,7 @@ impl Plan for GroupByPlan {
         Arc::new(GroupByPlanRepr {
             p: self.p.repr(),
             fields: self.groupfields.clone(),
-            aggfns: self
-                .aggfns()
-                .iter()
-                .map(|f| (f.field_name(), f.value()))
-                .collect(),
+            aggfns: self.aggfns().iter().map(|f| f.field_name()).collect(),
             r: self.blocks_accessed(),
             w: self.records_output(),
         })
@@ -102,7 +107,7 @@ impl Plan for GroupByPlan {
 pub struct GroupByPlanRepr {
     p: Arc<dyn PlanRepr>,
     fields: Vec<String>,
-    aggfns: Vec<(String, Constant)>,
+    aggfns: Vec<String>,
     r: i32,
     w: i32,
 }
@@ -169,7 +174,7 @@ mod tests {
             srcplan,
             vec!["MajorId".to_string()],
             vec![Arc::new(MaxFn::new("GradYear"))],
-        );
+        )?;
 
         let scan = plan.open()?;
         scan.lock().unwrap().before_first()?;
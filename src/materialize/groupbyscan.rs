@@ -6,7 +6,7 @@ use std::sync::{Arc, Mutex};
 use super::{aggregationfn::AggregationFn, groupvalue::GroupValue, sortscan::SortScan};
 use crate::{
     query::{constant::Constant, scan::Scan, updatescan::UpdateScan},
-    record::tablescan::TableScan,
+    record::{schema::resolve_field, tablescan::TableScan},
 };
 
 #[derive(Debug)]
@@ -36,6 +36,8 @@ pub struct GroupByScan {
     aggfns: Vec<Arc<dyn AggregationFn>>,
     groupval: Option<GroupValue>,
     moregroups: bool,
+    // without group fields, an empty input still makes one group
+    emptygroup: bool,
 }
 
 impl GroupByScan {
@@ -50,6 +52,7 @@ impl GroupByScan {
             aggfns,
             groupval: None,
             moregroups: false,
+            emptygroup: false,
         };
         scan.before_first().unwrap();
 
@@ -61,10 +64,18 @@ impl Scan for GroupByScan {
     fn before_first(&mut self) -> Result<()> {
         self.s.lock().unwrap().before_first()?;
         self.moregroups = self.s.lock().unwrap().next();
+        self.emptygroup = !self.moregroups && self.groupfields.is_empty();
 
         Ok(())
     }
     fn next(&mut self) -> bool {
+        if self.emptygroup {
+            self.emptygroup = false;
+            for aggfn in self.aggfns.iter() {
+                aggfn.reset();
+            }
+            return true;
+        }
         if !self.moregroups {
             return false;
         }
@@ -113,15 +124,14 @@ impl Scan for GroupByScan {
         self.get_val(fldname)?.as_date()
     }
     fn get_val(&mut self, fldname: &str) -> Result<Constant> {
-        if self.groupfields.contains(&fldname.to_string()) {
-            if let Some(val) = self.groupval.as_ref().unwrap().get_val(fldname) {
+        if let Some(groupfield) = resolve_field(&self.groupfields, fldname) {
+            if let Some(val) = self.groupval.as_ref().unwrap().get_val(groupfield) {
                 return Ok(val.clone());
             }
         }
         for aggfn in self.aggfns.iter() {
             if aggfn.field_name() == fldname {
-                let val = aggfn.value();
-                return Ok(val);
+                return aggfn.value();
             }
         }
 
@@ -130,7 +140,7 @@ impl Scan for GroupByScan {
         )))
     }
     fn has_field(&self, fldname: &str) -> bool {
-        if self.groupfields.contains(&fldname.to_string()) {
+        if resolve_field(&self.groupfields, fldname).is_some() {
             return true;
         }
         for aggfn in self.aggfns.iter() {
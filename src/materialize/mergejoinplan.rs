@@ -38,6 +38,7 @@ pub struct MergeJoinPlan {
     fldname1: String,
     fldname2: String,
     sch: Arc<Schema>,
+    outer: bool,
 }
 
 impl MergeJoinPlan {
@@ -65,8 +66,23 @@ impl MergeJoinPlan {
             fldname1: fldname1.to_string(),
             fldname2: fldname2.to_string(),
             sch: Arc::new(sch),
+            outer: false,
         }
     }
+    // my own extends
+    // every record of p1 is output, even if it matches no record of p2.
+    pub fn new_outer(
+        next_table_num: Arc<Mutex<i32>>,
+        tx: Arc<Mutex<Transaction>>,
+        p1: Arc<dyn Plan>,
+        p2: Arc<dyn Plan>,
+        fldname1: &str,
+        fldname2: &str,
+    ) -> Self {
+        let mut plan = Self::new(next_table_num, tx, p1, p2, fldname1, fldname2);
+        plan.outer = true;
+        plan
+    }
 }
 
 impl Plan for MergeJoinPlan {
@@ -74,7 +90,12 @@ impl Plan for MergeJoinPlan {
         let s1 = self.p1.open()?;
         if let Ok(s2) = self.p2.open() {
             let s2 = Arc::new(Mutex::new(s2.lock().unwrap().as_sort_scan()?.to_owned()));
-            let scan = MergeJoinScan::new(s1, s2, &self.fldname1, &self.fldname2);
+            let scan = if self.outer {
+                let sch2 = self.p2.schema();
+                MergeJoinScan::new_outer(s1, s2, &self.fldname1, &self.fldname2, sch2)
+            } else {
+                MergeJoinScan::new(s1, s2, &self.fldname1, &self.fldname2)
+            };
 
             return Ok(Arc::new(Mutex::new(scan)));
         }
@@ -90,7 +111,11 @@ impl Plan for MergeJoinPlan {
             self.p2.distinct_values(&self.fldname2),
         );
 
-        (self.p1.records_output() * self.p2.records_output()) / maxvals
+        let joined = (self.p1.records_output() * self.p2.records_output()) / maxvals;
+        if self.outer {
+            return max(self.p1.records_output(), joined);
+        }
+        joined
     }
     fn distinct_values(&self, fldname: &str) -> i32 {
         if self.p1.schema().has_field(fldname) {
@@ -109,6 +134,7 @@ impl Plan for MergeJoinPlan {
             p2: self.p2.repr(),
             fldname1: self.fldname1.clone(),
             fldname2: self.fldname2.clone(),
+            outer: self.outer,
             r: self.blocks_accessed(),
             w: self.records_output(),
         })
@@ -121,6 +147,7 @@ pub struct MergeJoinPlanRepr {
     p2: Arc<dyn PlanRepr>,
     fldname1: String,
     fldname2: String,
+    outer: bool,
     r: i32,
     w: i32,
 }
@@ -130,6 +157,7 @@ impl PlanRepr for MergeJoinPlanRepr {
         Operation::MergeJoinScan {
             fldname1: self.fldname1.clone(),
             fldname2: self.fldname2.clone(),
+            outer: self.outer,
         }
     }
     fn reads(&self) -> i32 {
@@ -149,7 +177,9 @@ mod tests {
 
     use super::*;
     use crate::{
-        metadata::manager::MetadataMgr, plan::tableplan::TablePlan, query::tests,
+        metadata::manager::MetadataMgr,
+        plan::tableplan::TablePlan,
+        query::{constant::Constant, tests},
         server::simpledb::SimpleDB,
     };
 
@@ -184,7 +214,14 @@ mod tests {
         let p2 = Arc::new(TablePlan::new("DEPT", Arc::clone(&tx), Arc::clone(&mdm))?);
         assert_eq!(tx.lock().unwrap().available_buffs(), 8);
 
-        let plan = MergeJoinPlan::new(next_table_num, Arc::clone(&tx), p1, p2, "MajorId", "DId");
+        let plan = MergeJoinPlan::new(
+            Arc::clone(&next_table_num),
+            Arc::clone(&tx),
+            p1,
+            p2,
+            "MajorId",
+            "DId",
+        );
 
         let scan = plan.open()?;
         scan.lock().unwrap().before_first()?;
@@ -199,6 +236,38 @@ mod tests {
         tx.lock().unwrap().commit()?;
         assert_eq!(tx.lock().unwrap().available_buffs(), 8);
 
+        // the left outer join keeps the courses without any section
+        let tx = Arc::new(Mutex::new(simpledb.new_tx()?));
+        let p1 = Arc::new(TablePlan::new("COURSE", Arc::clone(&tx), Arc::clone(&mdm))?);
+        let p2 = Arc::new(TablePlan::new(
+            "SECTION",
+            Arc::clone(&tx),
+            Arc::clone(&mdm),
+        )?);
+        let plan =
+            MergeJoinPlan::new_outer(next_table_num, Arc::clone(&tx), p1, p2, "CId", "CourseId");
+        let scan = plan.open()?;
+        let mut iter = scan.lock().unwrap();
+        let mut rows = vec![];
+        while iter.next() {
+            rows.push((iter.get_i32("CId")?, iter.get_val("Prof")?));
+        }
+        iter.close()?;
+        assert_eq!(
+            rows,
+            vec![
+                (12, Constant::new_string("turing".to_string())),
+                (12, Constant::new_string("turing".to_string())),
+                (22, Constant::Null),
+                (32, Constant::new_string("newton".to_string())),
+                (32, Constant::new_string("einstein".to_string())),
+                (42, Constant::Null),
+                (52, Constant::Null),
+                (62, Constant::new_string("brando".to_string())),
+            ]
+        );
+        tx.lock().unwrap().commit()?;
+
         Ok(())
     }
 }
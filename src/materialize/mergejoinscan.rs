@@ -4,7 +4,10 @@ use core::fmt;
 use std::sync::{Arc, Mutex};
 
 use super::sortscan::SortScan;
-use crate::query::{constant::Constant, scan::Scan};
+use crate::{
+    query::{constant::Constant, scan::Scan},
+    record::schema::Schema,
+};
 
 #[derive(Debug)]
 pub enum MergeJoinScanError {
@@ -29,6 +32,11 @@ pub struct MergeJoinScan {
     fldname1: String,
     fldname2: String,
     joinval: Option<Constant>,
+    // the schema of s2, with which an unmatched s1 record is padded,
+    // if this is a left outer join.
+    padding: Option<Arc<Schema>>,
+    padded: bool,
+    hasmore2: bool,
 }
 
 impl MergeJoinScan {
@@ -44,24 +52,61 @@ impl MergeJoinScan {
             fldname1: fldname1.to_string(),
             fldname2: fldname2.to_string(),
             joinval: None,
+            padding: None,
+            padded: false,
+            hasmore2: false,
         };
         scan.before_first().unwrap();
 
         scan
     }
+    // my own extends
+    pub fn new_outer(
+        s1: Arc<Mutex<dyn Scan>>,
+        s2: Arc<Mutex<SortScan>>,
+        fldname1: &str,
+        fldname2: &str,
+        sch2: Arc<Schema>,
+    ) -> Self {
+        let mut scan = Self::new(s1, s2, fldname1, fldname2);
+        scan.padding = Some(sch2);
+
+        scan
+    }
+    fn padding(&self, fldname: &str) -> Option<Constant> {
+        match &self.padding {
+            Some(sch)
+                if self.padded
+                    && !self.s1.lock().unwrap().has_field(fldname)
+                    && sch.has_field(fldname) =>
+            {
+                Some(Constant::Null)
+            }
+            _ => None,
+        }
+    }
 }
 
 impl Scan for MergeJoinScan {
     fn before_first(&mut self) -> Result<()> {
         self.s1.lock().unwrap().before_first()?;
         self.s2.lock().unwrap().before_first()?;
+        self.joinval = None;
+        self.padded = false;
 
         Ok(())
     }
     fn next(&mut self) -> bool {
-        let mut hasmore2 = self.s2.lock().unwrap().next();
-        if hasmore2 && self.s2.lock().unwrap().get_val(&self.fldname2).ok() == self.joinval {
-            return true;
+        let mut hasmore2;
+        if self.padded {
+            // s2 has stayed on the record after the padded s1 record
+            self.padded = false;
+            hasmore2 = self.hasmore2;
+        } else {
+            hasmore2 = self.s2.lock().unwrap().next();
+            if hasmore2 && self.s2.lock().unwrap().get_val(&self.fldname2).ok() == self.joinval {
+                return true;
+            }
         }
 
         let mut hasmore1 = self.s1.lock().unwrap().next();
@@ -73,7 +118,13 @@ impl Scan for MergeJoinScan {
         while hasmore1 && hasmore2 {
             let v1 = self.s1.lock().unwrap().get_val(&self.fldname1).unwrap();
             let v2 = self.s2.lock().unwrap().get_val(&self.fldname2).unwrap();
-            if v1 < v2 {
+            // a null join value matches nothing, even another null
+            if v1 < v2 || v1.is_null() {
+                if self.padding.is_some() {
+                    self.padded = true;
+                    self.hasmore2 = hasmore2;
+                    return true;
+                }
                 hasmore1 = self.s1.lock().unwrap().next();
             } else if v1 > v2 {
                 hasmore2 = self.s2.lock().unwrap().next();
@@ -83,10 +134,19 @@ impl Scan for MergeJoinScan {
                 return true;
             }
         }
+        // the rest of s1 matches no record of s2
+        if hasmore1 && self.padding.is_some() {
+            self.padded = true;
+            self.hasmore2 = false;
+            return true;
+        }
 
         false
     }
     fn get_i16(&mut self, fldname: &str) -> Result<i16> {
+        if let Some(val) = self.padding(fldname) {
+            return val.as_i16();
+        }
         if self.s1.lock().unwrap().has_field(fldname) {
             self.s1.lock().unwrap().get_i16(fldname)
         } else {
@@ -94,6 +154,9 @@ impl Scan for MergeJoinScan {
         }
     }
     fn get_i32(&mut self, fldname: &str) -> Result<i32> {
+        if let Some(val) = self.padding(fldname) {
+            return val.as_i32();
+        }
         if self.s1.lock().unwrap().has_field(fldname) {
             self.s1.lock().unwrap().get_i32(fldname)
         } else {
@@ -101,6 +164,9 @@ impl Scan for MergeJoinScan {
         }
     }
     fn get_string(&mut self, fldname: &str) -> Result<String> {
+        if let Some(val) = self.padding(fldname) {
+            return val.as_string().map(|s| s.to_string());
+        }
         if self.s1.lock().unwrap().has_field(fldname) {
             self.s1.lock().unwrap().get_string(fldname)
         } else {
@@ -108,6 +174,9 @@ impl Scan for MergeJoinScan {
         }
     }
     fn get_bool(&mut self, fldname: &str) -> Result<bool> {
+        if let Some(val) = self.padding(fldname) {
+            return val.as_bool();
+        }
         if self.s1.lock().unwrap().has_field(fldname) {
             self.s1.lock().unwrap().get_bool(fldname)
         } else {
@@ -115,6 +184,9 @@ impl Scan for MergeJoinScan {
         }
     }
     fn get_date(&mut self, fldname: &str) -> Result<NaiveDate> {
+        if let Some(val) = self.padding(fldname) {
+            return val.as_date();
+        }
         if self.s1.lock().unwrap().has_field(fldname) {
             self.s1.lock().unwrap().get_date(fldname)
         } else {
@@ -122,6 +194,9 @@ impl Scan for MergeJoinScan {
         }
     }
     fn get_val(&mut self, fldname: &str) -> Result<Constant> {
+        if let Some(val) = self.padding(fldname) {
+            return Ok(val);
+        }
         if self.s1.lock().unwrap().has_field(fldname) {
             self.s1.lock().unwrap().get_val(fldname)
         } else {
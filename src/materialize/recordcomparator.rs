@@ -1,24 +1,47 @@
+use core::fmt;
 use std::{
     cmp::Ordering,
     sync::{Arc, Mutex},
 };
 
-use crate::query::scan::Scan;
+use super::groupvalue::GroupValue;
+use crate::query::{constant::Constant, scan::Scan};
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+impl fmt::Display for SortOrder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SortOrder::Asc => write!(f, "asc"),
+            SortOrder::Desc => write!(f, "desc"),
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct RecordComparator {
     fields: Vec<String>,
+    orders: Vec<SortOrder>,
 }
 
 impl RecordComparator {
     pub fn new(fields: Vec<String>) -> Self {
-        Self { fields }
+        let orders = vec![SortOrder::Asc; fields.len()];
+        Self { fields, orders }
+    }
+    pub fn new_with(keys: Vec<(String, SortOrder)>) -> Self {
+        let (fields, orders) = keys.into_iter().unzip();
+        Self { fields, orders }
     }
     pub fn compare(&self, s1: Arc<Mutex<dyn Scan>>, s2: Arc<Mutex<dyn Scan>>) -> Ordering {
-        for fldname in self.fields.iter() {
+        for (fldname, order) in self.fields.iter().zip(self.orders.iter()) {
             let val1 = s1.lock().unwrap().get_val(fldname).unwrap();
             let val2 = s2.lock().unwrap().get_val(fldname).unwrap();
-            let result = val1.cmp(&val2);
+            let result = compare_in_order(&val1, &val2, *order);
             if result.is_ne() {
                 return result;
             }
@@ -30,4 +53,27 @@ impl RecordComparator {
     pub fn fields(&self) -> Vec<String> {
         self.fields.clone()
     }
+    pub fn orders(&self) -> Vec<SortOrder> {
+        self.orders.clone()
+    }
+    // compares the records, which are held in memory.
+    pub fn compare_values(&self, v1: &GroupValue, v2: &GroupValue) -> Ordering {
+        for (fldname, order) in self.fields.iter().zip(self.orders.iter()) {
+            let val1 = v1.get_val(fldname).unwrap();
+            let val2 = v2.get_val(fldname).unwrap();
+            let result = compare_in_order(val1, val2, *order);
+            if result.is_ne() {
+                return result;
+            }
+        }
+
+        Ordering::Equal
+    }
+}
+
+fn compare_in_order(val1: &Constant, val2: &Constant, order: SortOrder) -> Ordering {
+    match order {
+        SortOrder::Asc => val1.cmp(val2),
+        SortOrder::Desc => val1.cmp(val2).reverse(),
+    }
 }
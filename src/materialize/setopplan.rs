@@ -0,0 +1,203 @@
+use anyhow::Result;
+use std::sync::{Arc, Mutex};
+
+use super::{setopscan::SetOpScan, sortplan::SortPlan};
+use crate::{
+    parser::queryexpr::SetOp,
+    plan::{plan::Plan, unionplan::union_schema},
+    query::scan::Scan,
+    record::schema::Schema,
+    repr::planrepr::{Operation, PlanRepr},
+    tx::transaction::Transaction,
+};
+
+// Combines the records of two plans by sorting both of them on all fields,
+// and merging them like a merge join. The duplicates are removed.
+// The schemas of them must be union compatible.
+#[derive(Clone)]
+pub struct SetOpPlan {
+    op: SetOp,
+    p1: Arc<dyn Plan>,
+    p2: Arc<dyn Plan>,
+    sch: Arc<Schema>,
+}
+
+impl SetOpPlan {
+    pub fn new(
+        next_table_num: Arc<Mutex<i32>>,
+        tx: Arc<Mutex<Transaction>>,
+        op: SetOp,
+        p1: Arc<dyn Plan>,
+        p2: Arc<dyn Plan>,
+    ) -> Self {
+        let sch = Arc::new(union_schema(&p1.schema(), &p2.schema()));
+        let fields1 = p1.schema().fields().clone();
+        let fields2 = p2.schema().fields().clone();
+        let p1 = SortPlan::new(Arc::clone(&next_table_num), p1, fields1, Arc::clone(&tx));
+        let p2 = SortPlan::new(next_table_num, p2, fields2, tx);
+
+        Self {
+            op,
+            p1: Arc::new(p1),
+            p2: Arc::new(p2),
+            sch,
+        }
+    }
+}
+
+impl Plan for SetOpPlan {
+    fn open(&self) -> Result<Arc<Mutex<dyn Scan>>> {
+        let s1 = self.p1.open()?;
+        let s2 = self.p2.open()?;
+        let scan = SetOpScan::new(
+            self.op,
+            s1,
+            s2,
+            self.p1.schema().fields().clone(),
+            self.p2.schema().fields().clone(),
+        );
+
+        Ok(Arc::new(Mutex::new(scan)))
+    }
+    fn blocks_accessed(&self) -> i32 {
+        self.p1.blocks_accessed() + self.p2.blocks_accessed()
+    }
+    fn records_output(&self) -> i32 {
+        match self.op {
+            SetOp::Union | SetOp::UnionAll => self.p1.records_output() + self.p2.records_output(),
+            SetOp::Intersect => self.p1.records_output().min(self.p2.records_output()),
+            SetOp::Except => self.p1.records_output(),
+        }
+    }
+    fn distinct_values(&self, fldname: &str) -> i32 {
+        let pos = match self.sch.resolve(fldname) {
+            Some(f) => self.sch.fields().iter().position(|x| x == f).unwrap(),
+            None => return 1,
+        };
+        let dv1 = self.p1.distinct_values(&self.p1.schema().fields()[pos]);
+        let dv2 = self.p2.distinct_values(&self.p2.schema().fields()[pos]);
+        match self.op {
+            SetOp::Union | SetOp::UnionAll => dv1 + dv2,
+            SetOp::Intersect => dv1.min(dv2),
+            SetOp::Except => dv1,
+        }
+    }
+    fn schema(&self) -> Arc<Schema> {
+        Arc::clone(&self.sch)
+    }
+
+    fn repr(&self) -> Arc<dyn PlanRepr> {
+        Arc::new(SetOpPlanRepr {
+            op: self.op,
+            p1: self.p1.repr(),
+            p2: self.p2.repr(),
+            r: self.blocks_accessed(),
+            w: self.records_output(),
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct SetOpPlanRepr {
+    op: SetOp,
+    p1: Arc<dyn PlanRepr>,
+    p2: Arc<dyn PlanRepr>,
+    r: i32,
+    w: i32,
+}
+
+impl PlanRepr for SetOpPlanRepr {
+    fn operation(&self) -> Operation {
+        Operation::SetOpScan {
+            op: self.op.to_string(),
+        }
+    }
+    fn reads(&self) -> i32 {
+        self.r
+    }
+    fn writes(&self) -> i32 {
+        self.w
+    }
+    fn sub_plan_reprs(&self) -> Vec<Arc<dyn PlanRepr>> {
+        vec![Arc::clone(&self.p1), Arc::clone(&self.p2)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use super::*;
+    use crate::{
+        metadata::manager::MetadataMgr,
+        plan::{projectplan::ProjectPlan, tableplan::TablePlan},
+        query::tests,
+        server::simpledb::SimpleDB,
+    };
+
+    #[test]
+    fn unit_test() -> Result<()> {
+        if Path::new("_test/setopplan").exists() {
+            fs::remove_dir_all("_test/setopplan")?;
+        }
+
+        let simpledb = SimpleDB::new_with("_test/setopplan", 400, 8);
+
+        let tx = Arc::new(Mutex::new(simpledb.new_tx()?));
+        let next_table_num = Arc::new(Mutex::new(0));
+        let mut mdm = MetadataMgr::new(true, Arc::clone(&tx))?;
+        tests::init_sampledb(&mut mdm, Arc::clone(&tx))?;
+        let mdm = Arc::new(Mutex::new(mdm));
+
+        let project = |tblname: &str, fldname: &str| -> Result<Arc<dyn Plan>> {
+            let srcplan = Arc::new(TablePlan::new(tblname, Arc::clone(&tx), Arc::clone(&mdm))?);
+            Ok(Arc::new(ProjectPlan::new(
+                srcplan,
+                vec![fldname.to_string()],
+            )))
+        };
+        let cases = vec![
+            (
+                SetOp::Union,
+                ("STUDENT", "MajorId"),
+                ("COURSE", "DeptId"),
+                vec![10, 20, 30],
+            ),
+            (
+                SetOp::Intersect,
+                ("STUDENT", "SId"),
+                ("ENROLL", "StudentId"),
+                vec![1, 2, 4, 6],
+            ),
+            (
+                SetOp::Except,
+                ("ENROLL", "StudentId"),
+                ("STUDENT", "SId"),
+                vec![],
+            ),
+        ];
+        for (op, (tbl1, fld1), (tbl2, fld2), expected) in cases {
+            let plan = SetOpPlan::new(
+                Arc::clone(&next_table_num),
+                Arc::clone(&tx),
+                op,
+                project(tbl1, fld1)?,
+                project(tbl2, fld2)?,
+            );
+            assert_eq!(plan.schema().fields(), &vec![fld1.to_string()]);
+
+            let scan = plan.open()?;
+            let mut rows = vec![];
+            let mut iter = scan.lock().unwrap();
+            while iter.next() {
+                rows.push(iter.get_i32(fld1)?);
+            }
+            iter.close()?;
+            assert_eq!(rows, expected);
+        }
+        tx.lock().unwrap().commit()?;
+        assert_eq!(tx.lock().unwrap().available_buffs(), 8);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,217 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use core::fmt;
+use std::{
+    cmp::Ordering,
+    sync::{Arc, Mutex},
+};
+
+use super::sortscan::SortScan;
+use crate::{
+    parser::queryexpr::SetOp,
+    query::{constant::Constant, scan::Scan, updatescan::UpdateScan},
+    record::{schema::resolve_field, tablescan::TableScan},
+};
+
+#[derive(Debug)]
+pub enum SetOpScanError {
+    DowncastError,
+    NoCurrentRecord,
+    FieldNotFound(String),
+}
+
+impl std::error::Error for SetOpScanError {}
+impl fmt::Display for SetOpScanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SetOpScanError::DowncastError => {
+                write!(f, "downcast error")
+            }
+            SetOpScanError::NoCurrentRecord => {
+                write!(f, "no current record")
+            }
+            SetOpScanError::FieldNotFound(fldname) => {
+                write!(f, "field not found: {}", fldname)
+            }
+        }
+    }
+}
+
+// The underlying scans must be sorted on all fields.
+// The records of them are compared by the position of the fields,
+// and each distinct record is output once.
+#[derive(Clone)]
+pub struct SetOpScan {
+    op: SetOp,
+    s1: Arc<Mutex<dyn Scan>>,
+    s2: Arc<Mutex<dyn Scan>>,
+    fields1: Vec<String>,
+    fields2: Vec<String>,
+    hasmore1: bool,
+    hasmore2: bool,
+    // the values of the current record, in the order of fields1
+    current: Option<Vec<Constant>>,
+}
+
+impl SetOpScan {
+    pub fn new(
+        op: SetOp,
+        s1: Arc<Mutex<dyn Scan>>,
+        s2: Arc<Mutex<dyn Scan>>,
+        fields1: Vec<String>,
+        fields2: Vec<String>,
+    ) -> Self {
+        let mut scan = Self {
+            op,
+            s1,
+            s2,
+            fields1,
+            fields2,
+            hasmore1: false,
+            hasmore2: false,
+            current: None,
+        };
+        scan.before_first().unwrap();
+
+        scan
+    }
+    fn record1(&self) -> Option<Vec<Constant>> {
+        if !self.hasmore1 {
+            return None;
+        }
+        let mut s1 = self.s1.lock().unwrap();
+        Some(
+            self.fields1
+                .iter()
+                .map(|f| s1.get_val(f).unwrap())
+                .collect(),
+        )
+    }
+    fn record2(&self) -> Option<Vec<Constant>> {
+        if !self.hasmore2 {
+            return None;
+        }
+        let mut s2 = self.s2.lock().unwrap();
+        Some(
+            self.fields2
+                .iter()
+                .map(|f| s2.get_val(f).unwrap())
+                .collect(),
+        )
+    }
+    fn next1(&mut self) {
+        self.hasmore1 = self.s1.lock().unwrap().next();
+    }
+    fn next2(&mut self) {
+        self.hasmore2 = self.s2.lock().unwrap().next();
+    }
+}
+
+impl Scan for SetOpScan {
+    fn before_first(&mut self) -> Result<()> {
+        self.s1.lock().unwrap().before_first()?;
+        self.s2.lock().unwrap().before_first()?;
+        self.next1();
+        self.next2();
+        self.current = None;
+
+        Ok(())
+    }
+    fn next(&mut self) -> bool {
+        loop {
+            let found = match (self.op, self.record1(), self.record2()) {
+                (_, None, None) => return false,
+                (SetOp::Union | SetOp::UnionAll, Some(r1), r2) => {
+                    if r2.as_ref().map_or(true, |r2| r1 <= *r2) {
+                        self.next1();
+                        r1
+                    } else {
+                        self.next2();
+                        r2.unwrap()
+                    }
+                }
+                (SetOp::Union | SetOp::UnionAll, None, Some(r2)) => {
+                    self.next2();
+                    r2
+                }
+                (SetOp::Intersect, Some(r1), Some(r2)) => match r1.cmp(&r2) {
+                    Ordering::Less => {
+                        self.next1();
+                        continue;
+                    }
+                    Ordering::Greater => {
+                        self.next2();
+                        continue;
+                    }
+                    Ordering::Equal => {
+                        self.next1();
+                        r1
+                    }
+                },
+                (SetOp::Intersect, _, _) => return false,
+                (SetOp::Except, Some(r1), r2) => {
+                    match r2.map_or(Ordering::Less, |r2| r1.cmp(&r2)) {
+                        Ordering::Less => {
+                            self.next1();
+                            r1
+                        }
+                        Ordering::Greater => {
+                            self.next2();
+                            continue;
+                        }
+                        // keeps r2, so that the duplicates of r1 are excluded too
+                        Ordering::Equal => {
+                            self.next1();
+                            continue;
+                        }
+                    }
+                }
+                (SetOp::Except, None, _) => return false,
+            };
+            if self.current.as_ref() != Some(&found) {
+                self.current = Some(found);
+                return true;
+            }
+        }
+    }
+    fn get_i16(&mut self, fldname: &str) -> Result<i16> {
+        self.get_val(fldname)?.as_i16()
+    }
+    fn get_i32(&mut self, fldname: &str) -> Result<i32> {
+        self.get_val(fldname)?.as_i32()
+    }
+    fn get_string(&mut self, fldname: &str) -> Result<String> {
+        self.get_val(fldname)?.as_string().map(|s| s.to_string())
+    }
+    fn get_bool(&mut self, fldname: &str) -> Result<bool> {
+        self.get_val(fldname)?.as_bool()
+    }
+    fn get_date(&mut self, fldname: &str) -> Result<NaiveDate> {
+        self.get_val(fldname)?.as_date()
+    }
+    fn get_val(&mut self, fldname: &str) -> Result<Constant> {
+        let pos = resolve_field(&self.fields1, fldname)
+            .and_then(|f| self.fields1.iter().position(|x| x == f))
+            .ok_or_else(|| SetOpScanError::FieldNotFound(fldname.to_string()))?;
+        match &self.current {
+            Some(vals) => Ok(vals[pos].clone()),
+            None => Err(From::from(SetOpScanError::NoCurrentRecord)),
+        }
+    }
+    fn has_field(&self, fldname: &str) -> bool {
+        resolve_field(&self.fields1, fldname).is_some()
+    }
+    fn close(&mut self) -> Result<()> {
+        self.s1.lock().unwrap().close()?;
+        self.s2.lock().unwrap().close()
+    }
+    fn to_update_scan(&mut self) -> Result<&mut dyn UpdateScan> {
+        Err(From::from(SetOpScanError::DowncastError))
+    }
+    fn as_table_scan(&mut self) -> Result<&mut TableScan> {
+        Err(From::from(SetOpScanError::DowncastError))
+    }
+    fn as_sort_scan(&mut self) -> Result<&mut SortScan> {
+        Err(From::from(SetOpScanError::DowncastError))
+    }
+}
@@ -1,14 +1,21 @@
 use anyhow::Result;
-use std::sync::{Arc, Mutex};
+use core::cmp::Ordering;
+use std::{
+    collections::BinaryHeap,
+    sync::{Arc, Mutex},
+};
 
 use super::{
-    materializeplan::MaterializePlan, recordcomparator::RecordComparator, sortscan::SortScan,
+    groupvalue::GroupValue,
+    materializeplan::MaterializePlan,
+    recordcomparator::{RecordComparator, SortOrder},
+    sortscan::SortScan,
     temptable::TempTable,
 };
 use crate::{
     plan::plan::Plan,
     query::{scan::Scan, updatescan::UpdateScan},
-    record::schema::Schema,
+    record::{layout::Layout, schema::Schema},
     repr::planrepr::{Operation, PlanRepr},
     tx::transaction::Transaction,
 };
@@ -22,6 +29,8 @@ pub struct SortPlan {
     tx: Arc<Mutex<Transaction>>,
     sch: Arc<Schema>,
     comp: RecordComparator,
+    // only the first records are needed
+    limit: Option<usize>,
 }
 
 impl SortPlan {
@@ -40,14 +49,38 @@ impl SortPlan {
             tx,
             sch,
             comp,
+            limit: None,
+        }
+    }
+    pub fn new_with(
+        next_table_num: Arc<Mutex<i32>>,
+        p: Arc<dyn Plan>,
+        sortkeys: Vec<(String, SortOrder)>,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Self {
+        let sch = p.schema();
+        // an unqualified sort field is named as the field it refers to
+        let sortkeys = sortkeys
+            .into_iter()
+            .map(|(fldname, order)| match sch.resolve(&fldname) {
+                Some(f) => (f.clone(), order),
+                None => (fldname, order),
+            })
+            .collect();
+        let comp = RecordComparator::new_with(sortkeys);
+
+        Self {
+            next_table_num,
+            p,
+            tx,
+            sch,
+            comp,
+            limit: None,
         }
     }
     fn split_into_runs(&self, src: Arc<Mutex<dyn Scan>>) -> Vec<TempTable> {
         let mut temps = vec![];
         src.lock().unwrap().before_first().unwrap();
-        if !src.lock().unwrap().next() {
-            return temps;
-        }
         let mut currenttemp = TempTable::new(
             Arc::clone(&self.next_table_num),
             Arc::clone(&self.tx),
@@ -55,6 +88,11 @@ impl SortPlan {
         );
         let mut currentscan = currenttemp.open().unwrap();
         temps.push(currenttemp);
+        if !src.lock().unwrap().next() {
+            // an empty run, so that SortScan always has at least one run.
+            currentscan.lock().unwrap().close().unwrap();
+            return temps;
+        }
         while self.copy(Arc::clone(&src), Arc::clone(&currentscan)) {
             let curscan = currentscan.lock().unwrap().to_scan().unwrap();
             if self.comp.compare(Arc::clone(&src), curscan).is_lt() {
@@ -86,6 +124,56 @@ impl SortPlan {
 
         result
     }
+    // keeps only the first records, so that the sort is done in memory.
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = Some(limit);
+    }
+    // the first records are sorted in memory only if they fit in the available buffers.
+    fn fits_in_buffers(&self, limit: usize) -> bool {
+        let tx = self.tx.lock().unwrap();
+        let slotsize = Layout::new(Arc::clone(&self.sch)).slot_size();
+        let rpb = (tx.block_size() as usize / slotsize).max(1);
+
+        limit.div_ceil(rpb) <= tx.available_buffs()
+    }
+    fn top_n(&self, src: Arc<Mutex<dyn Scan>>, limit: usize) -> Result<TempTable> {
+        // the last of the first records is on the top of the heap
+        let mut records: BinaryHeap<TopRecord> = BinaryHeap::new();
+        let mut seq = 0;
+        src.lock().unwrap().before_first()?;
+        while src.lock().unwrap().next() {
+            let rec = TopRecord {
+                rec: GroupValue::new(Arc::clone(&src), self.sch.fields().clone()),
+                seq,
+                comp: &self.comp,
+            };
+            seq += 1;
+            if records.len() < limit {
+                records.push(rec);
+            } else if let Some(mut last) = records.peek_mut() {
+                if rec < *last {
+                    *last = rec;
+                }
+            }
+        }
+
+        let mut temp = TempTable::new(
+            Arc::clone(&self.next_table_num),
+            Arc::clone(&self.tx),
+            Arc::clone(&self.sch),
+        );
+        let dest = temp.open()?;
+        for TopRecord { rec, .. } in records.into_sorted_vec() {
+            dest.lock().unwrap().insert()?;
+            for fldname in self.sch.fields() {
+                let val = rec.get_val(fldname).unwrap().clone();
+                dest.lock().unwrap().set_val(fldname, val)?;
+            }
+        }
+        dest.lock().unwrap().close()?;
+
+        Ok(temp)
+    }
     fn merge_two_runs(&self, mut p1: TempTable, mut p2: TempTable) -> TempTable {
         let src1 = p1.open().unwrap().lock().unwrap().to_scan().unwrap();
         let src2 = p2.open().unwrap().lock().unwrap().to_scan().unwrap();
@@ -139,6 +227,15 @@ impl SortPlan {
 impl Plan for SortPlan {
     fn open(&self) -> Result<Arc<Mutex<dyn Scan>>> {
         let src = self.p.open()?;
+        if let Some(limit) = self.limit.filter(|n| self.fits_in_buffers(*n)) {
+            // a single run, without splitting into runs and merging them
+            let run = self.top_n(Arc::clone(&src), limit)?;
+            src.lock().unwrap().close()?;
+            return Ok(Arc::new(Mutex::new(SortScan::new(
+                vec![run],
+                self.comp.clone(),
+            ))));
+        }
         let mut runs = self.split_into_runs(Arc::clone(&src));
         src.lock().unwrap().close()?;
         while runs.len() > 2 {
@@ -158,7 +255,13 @@ impl Plan for SortPlan {
         mp.blocks_accessed()
     }
     fn records_output(&self) -> i32 {
-        self.p.records_output()
+        match self.limit {
+            Some(limit) => self
+                .p
+                .records_output()
+                .min(i32::try_from(limit).unwrap_or(i32::MAX)),
+            None => self.p.records_output(),
+        }
     }
     fn distinct_values(&self, fldname: &str) -> i32 {
         self.p.distinct_values(fldname)
@@ -168,15 +271,52 @@ impl Plan for SortPlan {
     }
 
     fn repr(&self) -> Arc<dyn PlanRepr> {
+        let compflds = self
+            .comp
+            .fields()
+            .into_iter()
+            .zip(self.comp.orders())
+            .map(|(fldname, order)| match order {
+                SortOrder::Asc => fldname,
+                SortOrder::Desc => format!("{} {}", fldname, order),
+            })
+            .collect();
         Arc::new(SortPlanRepr {
             p: self.p.repr(),
-            compflds: self.comp.fields(),
+            compflds,
             r: self.blocks_accessed(),
             w: self.records_output(),
         })
     }
 }
 
+// a record of top_n, ordered by the sort fields and then by the order it was read,
+// to keep the sort stable.
+struct TopRecord<'a> {
+    rec: GroupValue,
+    seq: usize,
+    comp: &'a RecordComparator,
+}
+
+impl Ord for TopRecord<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.comp
+            .compare_values(&self.rec, &other.rec)
+            .then(self.seq.cmp(&other.seq))
+    }
+}
+impl PartialOrd for TopRecord<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl PartialEq for TopRecord<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+impl Eq for TopRecord<'_> {}
+
 #[derive(Clone)]
 pub struct SortPlanRepr {
     p: Arc<dyn PlanRepr>,
@@ -241,8 +381,8 @@ mod tests {
         assert_eq!(tx.lock().unwrap().available_buffs(), 8);
 
         let plan = SortPlan::new(
-            next_table_num,
-            srcplan,
+            Arc::clone(&next_table_num),
+            Arc::clone(&srcplan) as Arc<dyn Plan>,
             vec!["GradYear".to_string(), "SName".to_string()],
             Arc::clone(&tx),
         );
@@ -256,6 +396,42 @@ mod tests {
             let major_id = iter.get_i32("MajorId")?;
             println!("{:<10}{:>8}{:>8}", name, major_id, year);
         }
+        drop(iter);
+
+        // top-N
+        let mut plan = SortPlan::new_with(
+            next_table_num,
+            srcplan,
+            vec![
+                ("GradYear".to_string(), SortOrder::Desc),
+                ("SName".to_string(), SortOrder::Asc),
+            ],
+            Arc::clone(&tx),
+        );
+        plan.set_limit(4);
+        assert_eq!(plan.records_output(), 4);
+        let scan = plan.open()?;
+        let mut names = vec![];
+        let mut iter = scan.lock().unwrap();
+        while iter.next() {
+            names.push(iter.get_string("SName")?);
+        }
+        assert_eq!(names, vec!["max", "sue", "art", "joe"]);
+        iter.close()?;
+        drop(iter);
+
+        // too many records for the buffers, so all of them are sorted in the runs
+        plan.set_limit(usize::MAX);
+        let scan = plan.open()?;
+        let mut names = vec![];
+        let mut iter = scan.lock().unwrap();
+        while iter.next() {
+            names.push(iter.get_string("SName")?);
+        }
+        assert_eq!(names.len(), 9);
+        assert_eq!(names[..4], ["max", "sue", "art", "joe"]);
+        iter.close()?;
+        drop(iter);
         tx.lock().unwrap().commit()?;
         assert_eq!(tx.lock().unwrap().available_buffs(), 8);
 
@@ -1,3 +1,4 @@
+pub mod constraintmanager;
 pub mod indexmanager;
 pub mod manager;
 pub mod statmanager;
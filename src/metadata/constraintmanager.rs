@@ -0,0 +1,459 @@
+use anyhow::Result;
+use core::fmt;
+use num_traits::FromPrimitive;
+use std::sync::{Arc, Mutex};
+
+use super::tablemanager::{TableMgr, MAX_NAME};
+use crate::{
+    parser::{
+        createtabledata::{FieldConstraint, ForeignKeyDef, RefAction},
+        parser::{check_predicate, default_value},
+        syntaxerror::parse_sql,
+    },
+    query::{constant::Constant, scan::Scan, updatescan::UpdateScan},
+    record::{layout::Layout, schema::Schema, tablescan::TableScan},
+    tx::transaction::Transaction,
+};
+
+// max chars of the default value and the check of a field
+pub const MAX_CONSDEF: usize = 100;
+
+// the catalogs of the constraints, which have the fields tablename and fieldname.
+const CATALOGS: [&str; 3] = ["keycat", "conscat", "fkcat"];
+
+#[derive(Debug)]
+pub enum ConstraintMgrError {
+    DefinitionTooLong(String),
+}
+
+impl std::error::Error for ConstraintMgrError {}
+impl fmt::Display for ConstraintMgrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConstraintMgrError::DefinitionTooLong(fldname) => {
+                write!(f, "constraint definition too long: {}", fldname)
+            }
+        }
+    }
+}
+
+// my own extends
+// the violations of the constraints, which are detected by the update planners.
+#[derive(Debug)]
+pub enum ConstraintError {
+    DuplicateKey(String, String, Constant),
+    NullKey(String, String),
+    NullField(String, String),
+    CheckFailed(String, String, String),
+    MissingReference(String, String, Constant),
+    ReferencedKey(String, String, Constant, String),
+    MissingKeyIndex(String, String),
+}
+
+impl std::error::Error for ConstraintError {}
+impl fmt::Display for ConstraintError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConstraintError::DuplicateKey(tblname, fldname, val) => {
+                write!(f, "duplicate key: {}.{} = {}", tblname, fldname, val)
+            }
+            ConstraintError::NullKey(tblname, fldname) => {
+                write!(f, "primary key can't be null: {}.{}", tblname, fldname)
+            }
+            ConstraintError::NullField(tblname, fldname) => {
+                write!(f, "field can't be null: {}.{}", tblname, fldname)
+            }
+            ConstraintError::CheckFailed(tblname, fldname, pred) => {
+                write!(f, "check failed: {}.{} ({})", tblname, fldname, pred)
+            }
+            ConstraintError::MissingReference(tblname, fldname, val) => {
+                write!(
+                    f,
+                    "referenced key not found: {}.{} = {}",
+                    tblname, fldname, val
+                )
+            }
+            ConstraintError::ReferencedKey(tblname, fldname, val, reftblname) => {
+                write!(
+                    f,
+                    "key is referenced by {}: {}.{} = {}",
+                    reftblname, tblname, fldname, val
+                )
+            }
+            ConstraintError::MissingKeyIndex(tblname, fldname) => {
+                write!(f, "index of the key not found: {}.{}", tblname, fldname)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ConstraintMgr {
+    key_layout: Arc<Layout>,
+    cons_layout: Arc<Layout>,
+    fk_layout: Arc<Layout>,
+}
+
+impl ConstraintMgr {
+    pub fn new(isnew: bool, tblmgr: TableMgr, tx: Arc<Mutex<Transaction>>) -> Result<Self> {
+        if isnew {
+            let mut sch = Schema::new();
+            sch.add_string_field("tablename", MAX_NAME);
+            sch.add_string_field("fieldname", MAX_NAME);
+            sch.add_string_field("indexname", MAX_NAME);
+            sch.add_bool_field("primarykey");
+            tblmgr.create_table("keycat", Arc::new(sch), Arc::clone(&tx))?;
+
+            // the default value and the check are null, if the field doesn't have them.
+            let mut sch = Schema::new();
+            sch.add_string_field("tablename", MAX_NAME);
+            sch.add_string_field("fieldname", MAX_NAME);
+            sch.add_bool_field("notnull");
+            sch.add_string_field("defaultval", MAX_CONSDEF);
+            sch.add_string_field("checkpred", MAX_CONSDEF);
+            tblmgr.create_table("conscat", Arc::new(sch), Arc::clone(&tx))?;
+
+            let mut sch = Schema::new();
+            sch.add_string_field("tablename", MAX_NAME);
+            sch.add_string_field("fieldname", MAX_NAME);
+            sch.add_string_field("reftable", MAX_NAME);
+            sch.add_string_field("reffield", MAX_NAME);
+            sch.add_i32_field("ondelete");
+            tblmgr.create_table("fkcat", Arc::new(sch), Arc::clone(&tx))?;
+        }
+        let key_layout = tblmgr.get_layout("keycat", Arc::clone(&tx))?;
+        let cons_layout = tblmgr.get_layout("conscat", Arc::clone(&tx))?;
+        let fk_layout = tblmgr.get_layout("fkcat", tx)?;
+
+        Ok(Self {
+            key_layout,
+            cons_layout,
+            fk_layout,
+        })
+    }
+    // the index of the key must be created by the caller.
+    pub fn create_key(
+        &self,
+        tblname: &str,
+        fldname: &str,
+        idxname: &str,
+        primary: bool,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<()> {
+        let mut ts = TableScan::new(tx, "keycat", Arc::clone(&self.key_layout))?;
+        ts.insert()?;
+        ts.set_string("tablename", tblname.to_string())?;
+        ts.set_string("fieldname", fldname.to_string())?;
+        ts.set_string("indexname", idxname.to_string())?;
+        ts.set_bool("primarykey", primary)?;
+        ts.close()?;
+
+        Ok(())
+    }
+    pub fn get_keys(&self, tblname: &str, tx: Arc<Mutex<Transaction>>) -> Result<Vec<KeyInfo>> {
+        let mut result = vec![];
+        let mut ts = TableScan::new(tx, "keycat", Arc::clone(&self.key_layout))?;
+        while ts.next() {
+            if ts.get_string("tablename")? == tblname {
+                result.push(KeyInfo::new(
+                    ts.get_string("fieldname")?,
+                    ts.get_string("indexname")?,
+                    ts.get_bool("primarykey")?,
+                ));
+            }
+        }
+        ts.close()?;
+
+        Ok(result)
+    }
+    // whether the index is the index of a key.
+    pub fn is_key_index(&self, idxname: &str, tx: Arc<Mutex<Transaction>>) -> Result<bool> {
+        let mut found = false;
+        let mut ts = TableScan::new(tx, "keycat", Arc::clone(&self.key_layout))?;
+        while ts.next() {
+            if ts.get_string("indexname")? == idxname {
+                found = true;
+                break;
+            }
+        }
+        ts.close()?;
+
+        Ok(found)
+    }
+    // the default value and the check are kept as text, and parsed when they are read.
+    pub fn create_field_constraint(
+        &self,
+        tblname: &str,
+        fc: &FieldConstraint,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<()> {
+        let fldname = fc.field_name();
+        let default = fc.default().map(|expr| expr.to_string());
+        let check = fc.check().map(|pred| pred.to_string());
+        for def in default.iter().chain(check.iter()) {
+            if def.len() > MAX_CONSDEF {
+                return Err(From::from(ConstraintMgrError::DefinitionTooLong(
+                    fldname.to_string(),
+                )));
+            }
+        }
+
+        let mut ts = TableScan::new(tx, "conscat", Arc::clone(&self.cons_layout))?;
+        ts.insert()?;
+        ts.set_string("tablename", tblname.to_string())?;
+        ts.set_string("fieldname", fldname.to_string())?;
+        ts.set_bool("notnull", fc.is_not_null())?;
+        ts.set_val(
+            "defaultval",
+            default.map_or(Constant::Null, Constant::String),
+        )?;
+        ts.set_val("checkpred", check.map_or(Constant::Null, Constant::String))?;
+        ts.close()?;
+
+        Ok(())
+    }
+    pub fn get_field_constraints(
+        &self,
+        tblname: &str,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<Vec<FieldConstraint>> {
+        let mut result = vec![];
+        let mut ts = TableScan::new(tx, "conscat", Arc::clone(&self.cons_layout))?;
+        while ts.next() {
+            if ts.get_string("tablename")? == tblname {
+                let default = match ts.get_val("defaultval")? {
+                    Constant::String(def) => Some(parse_sql(default_value(), def.as_str())?),
+                    _ => None,
+                };
+                let check = match ts.get_val("checkpred")? {
+                    Constant::String(def) => Some(parse_sql(check_predicate(), def.as_str())?),
+                    _ => None,
+                };
+                result.push(FieldConstraint::new(
+                    ts.get_string("fieldname")?,
+                    ts.get_bool("notnull")?,
+                    default,
+                    check,
+                ));
+            }
+        }
+        ts.close()?;
+
+        Ok(result)
+    }
+    pub fn create_foreign_key(
+        &self,
+        tblname: &str,
+        fk: &ForeignKeyDef,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<()> {
+        let mut ts = TableScan::new(tx, "fkcat", Arc::clone(&self.fk_layout))?;
+        ts.insert()?;
+        ts.set_string("tablename", tblname.to_string())?;
+        ts.set_string("fieldname", fk.field_name().to_string())?;
+        ts.set_string("reftable", fk.ref_table_name().to_string())?;
+        ts.set_string("reffield", fk.ref_field_name().to_string())?;
+        ts.set_i32("ondelete", fk.on_delete() as i32)?;
+        ts.close()?;
+
+        Ok(())
+    }
+    // the foreign keys of the table.
+    pub fn get_foreign_keys(
+        &self,
+        tblname: &str,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<Vec<ForeignKeyInfo>> {
+        self.foreign_keys_where(tx, |ts| Ok(ts.get_string("tablename")? == tblname))
+    }
+    // the foreign keys of any table, which reference the table.
+    pub fn get_referencing_keys(
+        &self,
+        reftblname: &str,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<Vec<ForeignKeyInfo>> {
+        self.foreign_keys_where(tx, |ts| Ok(ts.get_string("reftable")? == reftblname))
+    }
+    // the indexes of the keys are dropped by the caller.
+    pub fn drop_table_constraints(&self, tblname: &str, tx: Arc<Mutex<Transaction>>) -> Result<()> {
+        self.update_where(tx, tblname, None, |ts| ts.delete())
+    }
+    pub fn drop_field_constraints(
+        &self,
+        tblname: &str,
+        fldname: &str,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<()> {
+        self.update_where(tx, tblname, Some(fldname), |ts| ts.delete())
+    }
+    pub fn rename_table(
+        &self,
+        tblname: &str,
+        newname: &str,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<()> {
+        self.update_where(Arc::clone(&tx), tblname, None, |ts| {
+            ts.set_string("tablename", newname.to_string())
+        })?;
+        self.update_refs_where(tx, tblname, None, |ts| {
+            ts.set_string("reftable", newname.to_string())
+        })
+    }
+    pub fn rename_field(
+        &self,
+        tblname: &str,
+        fldname: &str,
+        newname: &str,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<()> {
+        self.update_where(Arc::clone(&tx), tblname, Some(fldname), |ts| {
+            ts.set_string("fieldname", newname.to_string())
+        })?;
+        self.update_refs_where(tx, tblname, Some(fldname), |ts| {
+            ts.set_string("reffield", newname.to_string())
+        })
+    }
+    // updates the records of all catalogs on the table (and the field).
+    fn update_where<F>(
+        &self,
+        tx: Arc<Mutex<Transaction>>,
+        tblname: &str,
+        fldname: Option<&str>,
+        update: F,
+    ) -> Result<()>
+    where
+        F: Fn(&mut TableScan) -> Result<()>,
+    {
+        let layouts = [&self.key_layout, &self.cons_layout, &self.fk_layout];
+        for (catalog, layout) in CATALOGS.iter().zip(layouts) {
+            let mut ts = TableScan::new(Arc::clone(&tx), catalog, Arc::clone(layout))?;
+            while ts.next() {
+                if ts.get_string("tablename")? == tblname
+                    && fldname.map_or(Ok(true), |f| ts.get_string("fieldname").map(|v| v == f))?
+                {
+                    update(&mut ts)?;
+                }
+            }
+            ts.close()?;
+        }
+
+        Ok(())
+    }
+    // updates the records of fkcat, which reference the table (and the field).
+    fn update_refs_where<F>(
+        &self,
+        tx: Arc<Mutex<Transaction>>,
+        reftblname: &str,
+        reffldname: Option<&str>,
+        update: F,
+    ) -> Result<()>
+    where
+        F: Fn(&mut TableScan) -> Result<()>,
+    {
+        let mut ts = TableScan::new(tx, "fkcat", Arc::clone(&self.fk_layout))?;
+        while ts.next() {
+            if ts.get_string("reftable")? == reftblname
+                && reffldname.map_or(Ok(true), |f| ts.get_string("reffield").map(|v| v == f))?
+            {
+                update(&mut ts)?;
+            }
+        }
+        ts.close()
+    }
+    fn foreign_keys_where<F>(
+        &self,
+        tx: Arc<Mutex<Transaction>>,
+        matches: F,
+    ) -> Result<Vec<ForeignKeyInfo>>
+    where
+        F: Fn(&mut TableScan) -> Result<bool>,
+    {
+        let mut result = vec![];
+        let mut ts = TableScan::new(tx, "fkcat", Arc::clone(&self.fk_layout))?;
+        while ts.next() {
+            if matches(&mut ts)? {
+                let on_delete = FromPrimitive::from_i32(ts.get_i32("ondelete")?).unwrap();
+                result.push(ForeignKeyInfo::new(
+                    ts.get_string("tablename")?,
+                    ts.get_string("fieldname")?,
+                    ts.get_string("reftable")?,
+                    ts.get_string("reffield")?,
+                    on_delete,
+                ));
+            }
+        }
+        ts.close()?;
+
+        Ok(result)
+    }
+}
+
+// a primary key or a unique key on a field, and the index which enforces it.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct KeyInfo {
+    fldname: String,
+    idxname: String,
+    primary: bool,
+}
+
+impl KeyInfo {
+    pub fn new(fldname: String, idxname: String, primary: bool) -> Self {
+        Self {
+            fldname,
+            idxname,
+            primary,
+        }
+    }
+    pub fn field_name(&self) -> &str {
+        &self.fldname
+    }
+    pub fn index_name(&self) -> &str {
+        &self.idxname
+    }
+    pub fn is_primary(&self) -> bool {
+        self.primary
+    }
+}
+
+// a field of a table, which references a key of a table.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ForeignKeyInfo {
+    tblname: String,
+    fldname: String,
+    reftblname: String,
+    reffldname: String,
+    on_delete: RefAction,
+}
+
+impl ForeignKeyInfo {
+    pub fn new(
+        tblname: String,
+        fldname: String,
+        reftblname: String,
+        reffldname: String,
+        on_delete: RefAction,
+    ) -> Self {
+        Self {
+            tblname,
+            fldname,
+            reftblname,
+            reffldname,
+            on_delete,
+        }
+    }
+    pub fn table_name(&self) -> &str {
+        &self.tblname
+    }
+    pub fn field_name(&self) -> &str {
+        &self.fldname
+    }
+    pub fn ref_table_name(&self) -> &str {
+        &self.reftblname
+    }
+    pub fn ref_field_name(&self) -> &str {
+        &self.reffldname
+    }
+    pub fn on_delete(&self) -> RefAction {
+        self.on_delete
+    }
+}
@@ -90,6 +90,116 @@ impl IndexMgr {
 
         Ok(result)
     }
+    // my own extends
+    // whether the field has an index, e.g. the index of its key.
+    pub fn has_field_index(
+        &self,
+        tblname: &str,
+        fldname: &str,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<bool> {
+        let mut found = false;
+        let mut ts = TableScan::new(tx, "idxcat", Arc::clone(&self.layout))?;
+        while ts.next() {
+            if ts.get_string("tablename")? == tblname && ts.get_string("fieldname")? == fldname {
+                found = true;
+                break;
+            }
+        }
+        ts.close()?;
+
+        Ok(found)
+    }
+    // my own extends
+    // the table name of the index, if it exists.
+    pub fn index_table(
+        &self,
+        idxname: &str,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<Option<String>> {
+        let mut result = None;
+        let mut ts = TableScan::new(tx, "idxcat", Arc::clone(&self.layout))?;
+        while ts.next() {
+            if ts.get_string("indexname")? == idxname {
+                result = Some(ts.get_string("tablename")?);
+                break;
+            }
+        }
+        ts.close()?;
+
+        Ok(result)
+    }
+    // my own extends
+    pub fn drop_index(&self, idxname: &str, tx: Arc<Mutex<Transaction>>) -> Result<()> {
+        self.drop_indexes_where(tx, |ts| Ok(ts.get_string("indexname")? == idxname))
+    }
+    // my own extends
+    // drops all indexes on the table.
+    pub fn drop_table_indexes(&mut self, tblname: &str, tx: Arc<Mutex<Transaction>>) -> Result<()> {
+        self.drop_indexes_where(tx, |ts| Ok(ts.get_string("tablename")? == tblname))
+    }
+    // my own extends
+    // forgets the statistics of the table, which are cached for the indexes.
+    pub fn drop_table_stats(&mut self, tblname: &str) {
+        self.statmgr.remove_stat_info(tblname);
+    }
+    // my own extends
+    // moves the indexes of the table to the new table name.
+    pub fn rename_table(
+        &self,
+        tblname: &str,
+        newname: &str,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<()> {
+        let mut ts = TableScan::new(tx, "idxcat", Arc::clone(&self.layout))?;
+        while ts.next() {
+            if ts.get_string("tablename")? == tblname {
+                ts.set_string("tablename", newname.to_string())?;
+            }
+        }
+        ts.close()
+    }
+    // my own extends
+    // the entries of the indexes are kept, because the values of the field are kept.
+    pub fn rename_field(
+        &self,
+        tblname: &str,
+        fldname: &str,
+        newname: &str,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<()> {
+        let mut ts = TableScan::new(tx, "idxcat", Arc::clone(&self.layout))?;
+        while ts.next() {
+            if ts.get_string("tablename")? == tblname && ts.get_string("fieldname")? == fldname {
+                ts.set_string("fieldname", newname.to_string())?;
+            }
+        }
+        ts.close()
+    }
+    // the records of idxcat are deleted by the transaction,
+    // and the files of the indexes are removed when it commits.
+    fn drop_indexes_where<F>(&self, tx: Arc<Mutex<Transaction>>, matches: F) -> Result<()>
+    where
+        F: Fn(&mut TableScan) -> Result<bool>,
+    {
+        let mut idxnames = vec![];
+        let mut ts = TableScan::new(Arc::clone(&tx), "idxcat", Arc::clone(&self.layout))?;
+        while ts.next() {
+            if matches(&mut ts)? {
+                idxnames.push(ts.get_string("indexname")?);
+                ts.delete()?;
+            }
+        }
+        ts.close()?;
+
+        let mut tx = tx.lock().unwrap();
+        for idxname in idxnames {
+            tx.delete_file(&format!("{}leaf", idxname))?;
+            tx.delete_file(&format!("{}dir", idxname))?;
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone)]
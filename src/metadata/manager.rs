@@ -1,42 +1,162 @@
 use anyhow::Result;
+use core::fmt;
 use std::{
     collections::HashMap,
     sync::{Arc, Mutex},
 };
 
 use super::{
+    constraintmanager::{ConstraintMgr, ForeignKeyInfo, KeyInfo},
     indexmanager::{IndexInfo, IndexMgr},
     statmanager::{StatInfo, StatMgr},
-    tablemanager::TableMgr,
+    tablemanager::{TableMgr, MAX_NAME},
     viewmanager::ViewMgr,
 };
 use crate::{
-    record::{layout::Layout, schema::Schema},
+    file::block_id::BlockId,
+    materialize::temptable::TempTable,
+    parser::createtabledata::{FieldConstraint, ForeignKeyDef},
+    query::{expression::Expression, scan::Scan, updatescan::UpdateScan},
+    record::{
+        layout::Layout,
+        recordpage::RecordPage,
+        schema::{FieldInfo, Schema},
+        tablescan::TableScan,
+    },
     tx::transaction::Transaction,
 };
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug)]
+pub enum MetadataMgrError {
+    TableNotFound(String),
+    ViewNotFound(String),
+    IndexNotFound(String),
+    CatalogTable(String),
+    FieldNotFound(String),
+    DuplicateField(String),
+    DuplicateTable(String),
+    LastField(String),
+    KeyExists(String),
+    PrimaryKeyExists(String),
+    KeyIndex(String),
+    IndexedField(String),
+    CheckedField(String),
+    NotKey(String, String),
+    ReferenceTypeMismatch(String),
+    ReferencedTable(String),
+    ReferencedField(String),
+    FormatMismatch(i32),
+}
+
+impl std::error::Error for MetadataMgrError {}
+impl fmt::Display for MetadataMgrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MetadataMgrError::TableNotFound(tblname) => {
+                write!(f, "table not found: {}", tblname)
+            }
+            MetadataMgrError::ViewNotFound(viewname) => {
+                write!(f, "view not found: {}", viewname)
+            }
+            MetadataMgrError::IndexNotFound(idxname) => {
+                write!(f, "index not found: {}", idxname)
+            }
+            MetadataMgrError::CatalogTable(tblname) => {
+                write!(f, "catalog table can't be changed: {}", tblname)
+            }
+            MetadataMgrError::FieldNotFound(fldname) => {
+                write!(f, "field not found: {}", fldname)
+            }
+            MetadataMgrError::DuplicateField(fldname) => {
+                write!(f, "field already exists: {}", fldname)
+            }
+            MetadataMgrError::DuplicateTable(tblname) => {
+                write!(f, "table or view already exists: {}", tblname)
+            }
+            MetadataMgrError::LastField(fldname) => {
+                write!(f, "the only field of a table can't be dropped: {}", fldname)
+            }
+            MetadataMgrError::KeyExists(fldname) => {
+                write!(f, "key already exists: {}", fldname)
+            }
+            MetadataMgrError::PrimaryKeyExists(tblname) => {
+                write!(f, "primary key already exists: {}", tblname)
+            }
+            MetadataMgrError::KeyIndex(idxname) => {
+                write!(f, "index of a key can't be dropped: {}", idxname)
+            }
+            MetadataMgrError::IndexedField(fldname) => {
+                write!(f, "field already has an index: {}", fldname)
+            }
+            MetadataMgrError::CheckedField(fldname) => {
+                write!(f, "field is used by a check: {}", fldname)
+            }
+            MetadataMgrError::NotKey(tblname, fldname) => {
+                write!(f, "referenced field is not a key: {}.{}", tblname, fldname)
+            }
+            MetadataMgrError::ReferenceTypeMismatch(fldname) => {
+                write!(f, "type doesn't match the referenced field: {}", fldname)
+            }
+            MetadataMgrError::ReferencedTable(tblname) => {
+                write!(f, "table is referenced by a foreign key: {}", tblname)
+            }
+            MetadataMgrError::ReferencedField(fldname) => {
+                write!(f, "field is referenced by a foreign key: {}", fldname)
+            }
+            MetadataMgrError::FormatMismatch(version) => {
+                write!(
+                    f,
+                    "database format {} can't be opened by format {}, recreate the database",
+                    version, FORMAT_VERSION
+                )
+            }
+        }
+    }
+}
+
+// the file which holds the format of the records, e.g. of the catalog tables.
+// the format 1 had no null bitmap in the slots, and was written without this file.
+const FORMAT_FILE: &str = "simpledb.format";
+const FORMAT_VERSION: i32 = 2;
+
+// the tables which hold the metadata
+const CATALOG_TABLES: [&str; 7] = [
+    "tblcat", "fldcat", "viewcat", "idxcat", "keycat", "conscat", "fkcat",
+];
+
+#[derive(Debug, Clone)]
 pub struct MetadataMgr {
     tblmgr: TableMgr,
     viewmgr: ViewMgr,
     statmgr: StatMgr,
     idxmgr: IndexMgr,
+    conmgr: ConstraintMgr,
+    // numbers the temp tables, shared with the query planner
+    next_table_num: Arc<Mutex<i32>>,
 }
 
 impl MetadataMgr {
     pub fn new(isnew: bool, tx: Arc<Mutex<Transaction>>) -> Result<Self> {
+        check_format(isnew, Arc::clone(&tx))?;
         let tblmgr = TableMgr::new(isnew, Arc::clone(&tx))?;
         let viewmgr = ViewMgr::new(isnew, tblmgr.clone(), Arc::clone(&tx))?;
         let statmgr = StatMgr::new(tblmgr.clone(), Arc::clone(&tx))?;
         let idxmgr = IndexMgr::new(isnew, tblmgr.clone(), statmgr.clone(), Arc::clone(&tx))?;
+        let conmgr = ConstraintMgr::new(isnew, tblmgr.clone(), Arc::clone(&tx))?;
 
         Ok(Self {
             tblmgr,
             viewmgr,
             statmgr,
             idxmgr,
+            conmgr,
+            next_table_num: Arc::new(Mutex::new(0)),
         })
     }
+    // my own extends
+    pub fn next_table_num(&self) -> Arc<Mutex<i32>> {
+        Arc::clone(&self.next_table_num)
+    }
     pub fn create_table(
         &self,
         tblname: &str,
@@ -66,6 +186,16 @@ impl MetadataMgr {
         fldname: &str,
         tx: Arc<Mutex<Transaction>>,
     ) -> Result<()> {
+        // the planners find one index for a field, so another index of it would not be
+        // maintained, e.g. the index of a key and an index created on the same field.
+        if self
+            .idxmgr
+            .has_field_index(tblname, fldname, Arc::clone(&tx))?
+        {
+            return Err(From::from(MetadataMgrError::IndexedField(
+                fldname.to_string(),
+            )));
+        }
         self.idxmgr.create_index(idxname, tblname, fldname, tx)
     }
     pub fn get_index_info(
@@ -83,6 +213,507 @@ impl MetadataMgr {
     ) -> Result<StatInfo> {
         self.statmgr.get_stat_info(tblname, layout, tx)
     }
+    // my own extends
+    pub fn has_table(&self, tblname: &str, tx: Arc<Mutex<Transaction>>) -> Result<bool> {
+        self.tblmgr.has_table(tblname, tx)
+    }
+    pub fn has_view(&self, viewname: &str, tx: Arc<Mutex<Transaction>>) -> Result<bool> {
+        Ok(!self.viewmgr.get_view_def(viewname, tx)?.is_empty())
+    }
+    pub fn has_index(&self, idxname: &str, tx: Arc<Mutex<Transaction>>) -> Result<bool> {
+        Ok(self.idxmgr.index_table(idxname, tx)?.is_some())
+    }
+    // drops the table with the indexes on it.
+    // the views on it are kept, and fail when they are used.
+    pub fn drop_table(&mut self, tblname: &str, tx: Arc<Mutex<Transaction>>) -> Result<()> {
+        self.check_user_table(tblname, Arc::clone(&tx))?;
+        // a table can reference itself
+        let refs = self.get_referencing_keys(tblname, Arc::clone(&tx))?;
+        if refs.iter().any(|fk| fk.table_name() != tblname) {
+            return Err(From::from(MetadataMgrError::ReferencedTable(
+                tblname.to_string(),
+            )));
+        }
+        self.idxmgr.drop_table_indexes(tblname, Arc::clone(&tx))?;
+        self.conmgr
+            .drop_table_constraints(tblname, Arc::clone(&tx))?;
+        self.tblmgr.drop_table(tblname, tx)?;
+        self.remove_stat_info(tblname);
+
+        Ok(())
+    }
+    pub fn drop_view(&self, viewname: &str, tx: Arc<Mutex<Transaction>>) -> Result<()> {
+        if !self.has_view(viewname, Arc::clone(&tx))? {
+            return Err(From::from(MetadataMgrError::ViewNotFound(
+                viewname.to_string(),
+            )));
+        }
+        self.viewmgr.drop_view(viewname, tx)
+    }
+    pub fn drop_index(&self, idxname: &str, tx: Arc<Mutex<Transaction>>) -> Result<()> {
+        if !self.has_index(idxname, Arc::clone(&tx))? {
+            return Err(From::from(MetadataMgrError::IndexNotFound(
+                idxname.to_string(),
+            )));
+        }
+        if self.conmgr.is_key_index(idxname, Arc::clone(&tx))? {
+            return Err(From::from(MetadataMgrError::KeyIndex(idxname.to_string())));
+        }
+        self.idxmgr.drop_index(idxname, tx)
+    }
+    // creates the key with the index, which enforces it.
+    // a table has one primary key at most, and a field has one key at most.
+    pub fn create_key(
+        &self,
+        tblname: &str,
+        fldname: &str,
+        primary: bool,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<()> {
+        let layout = self.tblmgr.get_layout(tblname, Arc::clone(&tx))?;
+        if !layout.schema().has_field(fldname) {
+            return Err(From::from(MetadataMgrError::FieldNotFound(
+                fldname.to_string(),
+            )));
+        }
+        for key in self.get_keys(tblname, Arc::clone(&tx))? {
+            if primary && key.is_primary() {
+                return Err(From::from(MetadataMgrError::PrimaryKeyExists(
+                    tblname.to_string(),
+                )));
+            }
+            if key.field_name() == fldname {
+                return Err(From::from(MetadataMgrError::KeyExists(fldname.to_string())));
+            }
+        }
+
+        let idxname = self.key_index_name(tblname, fldname, primary, Arc::clone(&tx))?;
+        self.idxmgr
+            .create_index(&idxname, tblname, fldname, Arc::clone(&tx))?;
+        self.conmgr
+            .create_key(tblname, fldname, &idxname, primary, tx)
+    }
+    pub fn get_keys(&self, tblname: &str, tx: Arc<Mutex<Transaction>>) -> Result<Vec<KeyInfo>> {
+        self.conmgr.get_keys(tblname, tx)
+    }
+    // the check may use the other fields of the table.
+    pub fn create_field_constraint(
+        &self,
+        tblname: &str,
+        fc: &FieldConstraint,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<()> {
+        let sch = self.tblmgr.get_layout(tblname, Arc::clone(&tx))?.schema();
+        let mut fldnames = vec![fc.field_name().to_string()];
+        if let Some(pred) = fc.check() {
+            fldnames.extend(pred.field_names());
+        }
+        if let Some(fldname) = fldnames.iter().find(|f| !sch.has_field(f)) {
+            return Err(From::from(MetadataMgrError::FieldNotFound(
+                fldname.to_string(),
+            )));
+        }
+        // a default value of a wrong type is found here, rather than at an insert.
+        if let Some(Expression::Val(val)) = fc.default() {
+            val.as_field_type(sch.field_type(fc.field_name()))?;
+        }
+
+        self.conmgr.create_field_constraint(tblname, fc, tx)
+    }
+    pub fn get_field_constraints(
+        &self,
+        tblname: &str,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<Vec<FieldConstraint>> {
+        self.conmgr.get_field_constraints(tblname, tx)
+    }
+    // the referenced field must be a primary key or a unique key,
+    // so the referenced record is found by the index of the key.
+    pub fn create_foreign_key(
+        &self,
+        tblname: &str,
+        fk: &ForeignKeyDef,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<()> {
+        let sch = self.tblmgr.get_layout(tblname, Arc::clone(&tx))?.schema();
+        if !sch.has_field(fk.field_name()) {
+            return Err(From::from(MetadataMgrError::FieldNotFound(
+                fk.field_name().to_string(),
+            )));
+        }
+        let reftblname = fk.ref_table_name();
+        let reffldname = fk.ref_field_name();
+        if !self.has_table(reftblname, Arc::clone(&tx))? {
+            return Err(From::from(MetadataMgrError::TableNotFound(
+                reftblname.to_string(),
+            )));
+        }
+        let refkeys = self.get_keys(reftblname, Arc::clone(&tx))?;
+        if !refkeys.iter().any(|key| key.field_name() == reffldname) {
+            return Err(From::from(MetadataMgrError::NotKey(
+                reftblname.to_string(),
+                reffldname.to_string(),
+            )));
+        }
+        let refsch = self
+            .tblmgr
+            .get_layout(reftblname, Arc::clone(&tx))?
+            .schema();
+        if sch.field_type(fk.field_name()) != refsch.field_type(reffldname) {
+            return Err(From::from(MetadataMgrError::ReferenceTypeMismatch(
+                fk.field_name().to_string(),
+            )));
+        }
+
+        self.conmgr.create_foreign_key(tblname, fk, tx)
+    }
+    pub fn get_foreign_keys(
+        &self,
+        tblname: &str,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<Vec<ForeignKeyInfo>> {
+        self.conmgr.get_foreign_keys(tblname, tx)
+    }
+    pub fn get_referencing_keys(
+        &self,
+        reftblname: &str,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<Vec<ForeignKeyInfo>> {
+        self.conmgr.get_referencing_keys(reftblname, tx)
+    }
+    // the new field is null in the existing records.
+    pub fn add_field(
+        &mut self,
+        tblname: &str,
+        fldname: &str,
+        fi: FieldInfo,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<()> {
+        self.check_user_table(tblname, Arc::clone(&tx))?;
+        let layout = self.tblmgr.get_layout(tblname, Arc::clone(&tx))?;
+        if layout.schema().has_field(fldname) {
+            return Err(From::from(MetadataMgrError::DuplicateField(
+                fldname.to_string(),
+            )));
+        }
+        let mut sch = (*layout.schema()).clone();
+        sch.add_field(fldname, fi.fld_type, fi.length);
+
+        self.rewrite_table(tblname, tblname, layout, Arc::new(sch), tx)
+    }
+    // the indexes on the field are dropped, and the views which may use it are invalidated.
+    pub fn drop_field(
+        &mut self,
+        tblname: &str,
+        fldname: &str,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<()> {
+        self.check_user_table(tblname, Arc::clone(&tx))?;
+        let layout = self.tblmgr.get_layout(tblname, Arc::clone(&tx))?;
+        let oldsch = layout.schema();
+        if !oldsch.has_field(fldname) {
+            return Err(From::from(MetadataMgrError::FieldNotFound(
+                fldname.to_string(),
+            )));
+        }
+        if oldsch.fields().len() == 1 {
+            return Err(From::from(MetadataMgrError::LastField(fldname.to_string())));
+        }
+        self.check_unchecked_field(tblname, fldname, true, Arc::clone(&tx))?;
+        let refs = self.get_referencing_keys(tblname, Arc::clone(&tx))?;
+        if refs.iter().any(|fk| fk.ref_field_name() == fldname) {
+            return Err(From::from(MetadataMgrError::ReferencedField(
+                fldname.to_string(),
+            )));
+        }
+        let mut sch = Schema::new();
+        for f in oldsch.fields().iter().filter(|f| *f != fldname) {
+            sch.add_field(f, oldsch.field_type(f), oldsch.length(f));
+        }
+
+        let indexes = self.get_index_info(tblname, Arc::clone(&tx))?;
+        if let Some(ii) = indexes.get(fldname) {
+            self.idxmgr.drop_index(ii.index_name(), Arc::clone(&tx))?;
+        }
+        self.conmgr
+            .drop_field_constraints(tblname, fldname, Arc::clone(&tx))?;
+        self.invalidate_views(tblname, Some(fldname), Arc::clone(&tx))?;
+        self.rewrite_table(tblname, tblname, layout, Arc::new(sch), tx)
+    }
+    // the records are kept as they are, because the layout doesn't change.
+    pub fn rename_field(
+        &mut self,
+        tblname: &str,
+        fldname: &str,
+        newname: &str,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<()> {
+        self.check_user_table(tblname, Arc::clone(&tx))?;
+        let oldsch = self.tblmgr.get_layout(tblname, Arc::clone(&tx))?.schema();
+        if !oldsch.has_field(fldname) {
+            return Err(From::from(MetadataMgrError::FieldNotFound(
+                fldname.to_string(),
+            )));
+        }
+        if oldsch.has_field(newname) {
+            return Err(From::from(MetadataMgrError::DuplicateField(
+                newname.to_string(),
+            )));
+        }
+        self.check_unchecked_field(tblname, fldname, false, Arc::clone(&tx))?;
+        let mut sch = Schema::new();
+        for f in oldsch.fields() {
+            let name = if f == fldname { newname } else { f };
+            sch.add_field(name, oldsch.field_type(f), oldsch.length(f));
+        }
+
+        self.tblmgr
+            .alter_table(tblname, tblname, Arc::new(sch), Arc::clone(&tx))?;
+        self.idxmgr
+            .rename_field(tblname, fldname, newname, Arc::clone(&tx))?;
+        self.conmgr
+            .rename_field(tblname, fldname, newname, Arc::clone(&tx))?;
+        self.invalidate_views(tblname, Some(fldname), tx)
+    }
+    // the records are moved to the file of the new name.
+    pub fn rename_table(
+        &mut self,
+        tblname: &str,
+        newname: &str,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<()> {
+        self.check_user_table(tblname, Arc::clone(&tx))?;
+        if CATALOG_TABLES.contains(&newname)
+            || self.has_table(newname, Arc::clone(&tx))?
+            || self.has_view(newname, Arc::clone(&tx))?
+        {
+            return Err(From::from(MetadataMgrError::DuplicateTable(
+                newname.to_string(),
+            )));
+        }
+        let layout = self.tblmgr.get_layout(tblname, Arc::clone(&tx))?;
+
+        self.invalidate_views(tblname, None, Arc::clone(&tx))?;
+        self.rewrite_table(tblname, newname, Arc::clone(&layout), layout.schema(), tx)
+    }
+    // the index is named after the key like "STUDENT_pkey" or "STUDENT_Email_key",
+    // and the name is cut and numbered to fit in the catalog.
+    fn key_index_name(
+        &self,
+        tblname: &str,
+        fldname: &str,
+        primary: bool,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<String> {
+        let base = match primary {
+            true => format!("{}_pkey", tblname),
+            false => format!("{}_{}_key", tblname, fldname),
+        };
+        let mut idxname: String = base.chars().take(MAX_NAME).collect();
+        let mut n = 1;
+        while self.has_index(&idxname, Arc::clone(&tx))? {
+            let suffix = n.to_string();
+            idxname = base.chars().take(MAX_NAME - suffix.len()).collect();
+            idxname.push_str(&suffix);
+            n += 1;
+        }
+
+        Ok(idxname)
+    }
+    // the checks are kept as text, so a field used by them can't be dropped or renamed.
+    // the own checks of the field are dropped with it.
+    fn check_unchecked_field(
+        &self,
+        tblname: &str,
+        fldname: &str,
+        dropped: bool,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<()> {
+        for fc in self.get_field_constraints(tblname, tx)? {
+            if dropped && fc.field_name() == fldname {
+                continue;
+            }
+            if let Some(pred) = fc.check() {
+                if pred.field_names().iter().any(|f| f == fldname) {
+                    return Err(From::from(MetadataMgrError::CheckedField(
+                        fldname.to_string(),
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+    fn check_user_table(&self, tblname: &str, tx: Arc<Mutex<Transaction>>) -> Result<()> {
+        if CATALOG_TABLES.contains(&tblname) {
+            return Err(From::from(MetadataMgrError::CatalogTable(
+                tblname.to_string(),
+            )));
+        }
+        if !self.tblmgr.has_table(tblname, tx)? {
+            return Err(From::from(MetadataMgrError::TableNotFound(
+                tblname.to_string(),
+            )));
+        }
+
+        Ok(())
+    }
+    // rewrites the records of the table for the new schema, whose fields are
+    // taken from the old records by name, or are null if they are new.
+    // the records are streamed through a temp table, instead of being held in memory.
+    // every write is logged, so that a rollback restores the old records.
+    // the records may move, so the entries of the indexes are rebuilt.
+    fn rewrite_table(
+        &mut self,
+        tblname: &str,
+        newname: &str,
+        oldlayout: Arc<Layout>,
+        sch: Arc<Schema>,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<()> {
+        let oldsch = oldlayout.schema();
+        let indexes = self.get_index_info(tblname, Arc::clone(&tx))?;
+        let mut temp = TempTable::new(self.next_table_num(), Arc::clone(&tx), Arc::clone(&sch));
+
+        // copies the records to the temp table, and deletes their index entries
+        let tempscan = temp.open()?;
+        let mut tempscan = tempscan.lock().unwrap();
+        let mut ts = TableScan::new(Arc::clone(&tx), tblname, Arc::clone(&oldlayout))?;
+        let idxs = indexes
+            .iter()
+            .map(|(fldname, ii)| (fldname, ii.open()))
+            .collect::<Vec<_>>();
+        while ts.next() {
+            tempscan.insert()?;
+            for fldname in sch.fields() {
+                if oldsch.has_field(fldname) {
+                    tempscan.set_val(fldname, ts.get_val(fldname)?)?;
+                }
+            }
+            let rid = ts.get_rid()?;
+            for (fldname, idx) in idxs.iter() {
+                idx.lock().unwrap().delete(ts.get_val(fldname)?, rid)?;
+            }
+        }
+        for (_, idx) in idxs.iter() {
+            idx.lock().unwrap().close()?;
+        }
+        ts.close()?;
+
+        // the blocks of the file are reused with the new layout
+        let newlayout = Arc::new(Layout::new(Arc::clone(&sch)));
+        let filename = format!("{}.tbl", newname);
+        let size = tx.lock().unwrap().size(&filename)?;
+        for blknum in 0..size {
+            let blk = BlockId::new(&filename, blknum);
+            let mut rp = RecordPage::new(Arc::clone(&tx), blk.clone(), Arc::clone(&newlayout))?;
+            rp.erase()?;
+            // the zero words aren't valid dates, so the slots get the default values
+            rp.format()?;
+            tx.lock().unwrap().unpin(&blk)?;
+        }
+        if tblname != newname {
+            tx.lock()
+                .unwrap()
+                .delete_file(&format!("{}.tbl", tblname))?;
+            self.idxmgr
+                .rename_table(tblname, newname, Arc::clone(&tx))?;
+            self.conmgr
+                .rename_table(tblname, newname, Arc::clone(&tx))?;
+        }
+        self.tblmgr
+            .alter_table(tblname, newname, Arc::clone(&sch), Arc::clone(&tx))?;
+
+        // copies the records back, and inserts their index entries
+        let mut ts = TableScan::new(Arc::clone(&tx), newname, newlayout)?;
+        let idxs = indexes
+            .iter()
+            .map(|(fldname, ii)| (fldname, ii.open()))
+            .collect::<Vec<_>>();
+        tempscan.before_first()?;
+        while tempscan.next() {
+            ts.insert()?;
+            for fldname in sch.fields() {
+                ts.set_val(fldname, tempscan.get_val(fldname)?)?;
+            }
+            let rid = ts.get_rid()?;
+            for (fldname, idx) in idxs.iter() {
+                idx.lock()
+                    .unwrap()
+                    .insert(tempscan.get_val(fldname)?, rid)?;
+            }
+        }
+        for (_, idx) in idxs.iter() {
+            idx.lock().unwrap().close()?;
+        }
+        ts.close()?;
+        tempscan.close()?;
+        tx.lock()
+            .unwrap()
+            .delete_file(&format!("{}.tbl", temp.table_name()))?;
+        self.remove_stat_info(tblname);
+
+        Ok(())
+    }
+    // forgets the statistics of the table, cached both here and by the indexes.
+    fn remove_stat_info(&mut self, tblname: &str) {
+        self.statmgr.remove_stat_info(tblname);
+        self.idxmgr.drop_table_stats(tblname);
+    }
+    // drops the views, whose definitions mention the table (and the field).
+    // the definitions are searched by words, so a view may be dropped needlessly,
+    // but never kept with a stale reference. the views on the dropped views are dropped too.
+    fn invalidate_views(
+        &self,
+        tblname: &str,
+        fldname: Option<&str>,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<()> {
+        let mut names = vec![tblname.to_string()];
+        let mut fldname = fldname;
+        while !names.is_empty() {
+            let mut dropped = vec![];
+            for (viewname, viewdef) in self.viewmgr.view_defs(Arc::clone(&tx))? {
+                let words: Vec<&str> = viewdef
+                    .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+                    .collect();
+                let mentions = |name: &str| words.contains(&name);
+                if names.iter().any(|n| mentions(n)) && fldname.map_or(true, mentions) {
+                    self.viewmgr.drop_view(&viewname, Arc::clone(&tx))?;
+                    dropped.push(viewname);
+                }
+            }
+            names = dropped;
+            fldname = None;
+        }
+
+        Ok(())
+    }
+}
+
+// a new database is marked with the format, and an existing one must have the same format,
+// because the catalog tables would be misread in another format.
+fn check_format(isnew: bool, tx: Arc<Mutex<Transaction>>) -> Result<()> {
+    let mut tx = tx.lock().unwrap();
+    if isnew {
+        let blk = tx.append(FORMAT_FILE)?;
+        tx.pin(&blk)?;
+        tx.set_i32(&blk, 0, FORMAT_VERSION, false)?;
+        tx.unpin(&blk)?;
+        return Ok(());
+    }
+    let mut version = 1;
+    if tx.size(FORMAT_FILE)? > 0 {
+        let blk = BlockId::new(FORMAT_FILE, 0);
+        tx.pin(&blk)?;
+        version = tx.get_i32(&blk, 0)?;
+        tx.unpin(&blk)?;
+    }
+    if version != FORMAT_VERSION {
+        return Err(From::from(MetadataMgrError::FormatMismatch(version)));
+    }
+
+    Ok(())
 }
 
 #[cfg(test)]
@@ -91,9 +722,8 @@ mod tests {
     use std::{fs, path::Path};
 
     use super::*;
-    use crate::query::updatescan::UpdateScan;
+    use crate::query::constant::Constant;
     use crate::record::schema::FieldType;
-    use crate::record::tablescan::TableScan;
     use crate::server::simpledb::SimpleDB;
 
     #[test]
@@ -173,4 +803,253 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn drop_test() -> Result<()> {
+        if Path::new("_test/metadatamgr/drop").exists() {
+            fs::remove_dir_all("_test/metadatamgr/drop")?;
+        }
+
+        let simpledb = SimpleDB::new_with("_test/metadatamgr/drop", 400, 8);
+        let dbdir = Path::new("_test/metadatamgr/drop");
+
+        let tx = Arc::new(Mutex::new(simpledb.new_tx()?));
+        let mut mdm = MetadataMgr::new(true, Arc::clone(&tx))?;
+        let mut sch = Schema::new();
+        sch.add_i32_field("A");
+        mdm.create_table("T", Arc::new(sch), Arc::clone(&tx))?;
+        mdm.create_index("idxA", "T", "A", Arc::clone(&tx))?;
+        mdm.create_view("viewT", "select A from T", Arc::clone(&tx))?;
+        let layout = mdm.get_layout("T", Arc::clone(&tx))?;
+        let mut ts = TableScan::new(Arc::clone(&tx), "T", Arc::clone(&layout))?;
+        for i in 0..3 {
+            ts.insert()?;
+            ts.set_i32("A", i)?;
+        }
+        ts.close()?;
+        // creates the files of the index
+        let idxmap = mdm.get_index_info("T", Arc::clone(&tx))?;
+        idxmap.get("A").unwrap().open().lock().unwrap().close()?;
+        tx.lock().unwrap().commit()?;
+
+        // the drop is undone by the rollback
+        let tx = Arc::new(Mutex::new(simpledb.new_tx()?));
+        mdm.drop_table("T", Arc::clone(&tx))?;
+        assert!(!mdm.has_table("T", Arc::clone(&tx))?);
+        assert!(!mdm.has_index("idxA", Arc::clone(&tx))?);
+        assert!(mdm
+            .get_layout("T", Arc::clone(&tx))?
+            .schema()
+            .fields()
+            .is_empty());
+        tx.lock().unwrap().rollback()?;
+        assert!(dbdir.join("T.tbl").exists());
+        assert!(dbdir.join("idxAleaf").exists());
+
+        let tx = Arc::new(Mutex::new(simpledb.new_tx()?));
+        assert!(mdm.has_table("T", Arc::clone(&tx))?);
+        assert!(mdm.has_index("idxA", Arc::clone(&tx))?);
+        let mut ts = TableScan::new(Arc::clone(&tx), "T", Arc::clone(&layout))?;
+        let mut count = 0;
+        while ts.next() {
+            count += 1;
+        }
+        ts.close()?;
+        assert_eq!(count, 3);
+
+        // the files are removed when the drop is committed
+        mdm.drop_table("T", Arc::clone(&tx))?;
+        assert!(dbdir.join("T.tbl").exists());
+        // the file can't be extended after the drop
+        assert!(tx.lock().unwrap().append("T.tbl").is_err());
+        tx.lock().unwrap().commit()?;
+        assert!(!dbdir.join("T.tbl").exists());
+        assert!(!dbdir.join("idxAleaf").exists());
+        assert!(!dbdir.join("idxAdir").exists());
+
+        let tx = Arc::new(Mutex::new(simpledb.new_tx()?));
+        assert!(mdm.get_index_info("T", Arc::clone(&tx))?.is_empty());
+        assert!(mdm.drop_table("T", Arc::clone(&tx)).is_err());
+        assert!(mdm.drop_table("tblcat", Arc::clone(&tx)).is_err());
+        assert!(mdm.drop_index("idxA", Arc::clone(&tx)).is_err());
+        // the view is kept
+        assert!(mdm.has_view("viewT", Arc::clone(&tx))?);
+        mdm.drop_view("viewT", Arc::clone(&tx))?;
+        assert!(!mdm.has_view("viewT", Arc::clone(&tx))?);
+        assert!(mdm.drop_view("viewT", Arc::clone(&tx)).is_err());
+
+        // a table of the same name starts empty
+        let mut sch = Schema::new();
+        sch.add_i32_field("A");
+        mdm.create_table("T", Arc::new(sch), Arc::clone(&tx))?;
+        let mut ts = TableScan::new(Arc::clone(&tx), "T", Arc::clone(&layout))?;
+        assert!(!ts.next());
+        ts.close()?;
+        tx.lock().unwrap().commit()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn alter_test() -> Result<()> {
+        if Path::new("_test/metadatamgr/alter").exists() {
+            fs::remove_dir_all("_test/metadatamgr/alter")?;
+        }
+
+        let simpledb = SimpleDB::new_with("_test/metadatamgr/alter", 400, 8);
+        let dbdir = Path::new("_test/metadatamgr/alter");
+
+        let tx = Arc::new(Mutex::new(simpledb.new_tx()?));
+        let mut mdm = MetadataMgr::new(true, Arc::clone(&tx))?;
+        let mut sch = Schema::new();
+        sch.add_i32_field("A");
+        sch.add_string_field("B", 5);
+        mdm.create_table("T", Arc::new(sch), Arc::clone(&tx))?;
+        mdm.create_index("idxA", "T", "A", Arc::clone(&tx))?;
+        mdm.create_view("viewA", "select A from T", Arc::clone(&tx))?;
+        mdm.create_view("viewB", "select B from T", Arc::clone(&tx))?;
+        mdm.create_view("viewBB", "select B from viewB", Arc::clone(&tx))?;
+        let layout = mdm.get_layout("T", Arc::clone(&tx))?;
+        let idxmap = mdm.get_index_info("T", Arc::clone(&tx))?;
+        let idx = idxmap.get("A").unwrap().open();
+        let mut ts = TableScan::new(Arc::clone(&tx), "T", Arc::clone(&layout))?;
+        for i in 0..30 {
+            ts.insert()?;
+            ts.set_i32("A", i)?;
+            ts.set_string("B", format!("b{}", i))?;
+            idx.lock()
+                .unwrap()
+                .insert(Constant::I32(i), ts.get_rid()?)?;
+        }
+        // the holes move the records, when they are rewritten
+        ts.before_first()?;
+        while ts.next() {
+            let a = ts.get_i32("A")?;
+            if a % 3 == 0 {
+                idx.lock()
+                    .unwrap()
+                    .delete(Constant::I32(a), ts.get_rid()?)?;
+                ts.delete()?;
+            }
+        }
+        ts.close()?;
+        idx.lock().unwrap().close()?;
+        tx.lock().unwrap().commit()?;
+
+        // the values of the field, and the value found by the index of A
+        let read = |mdm: &mut MetadataMgr,
+                    tblname: &str,
+                    idxfld: &str,
+                    fldname: &str,
+                    tx: Arc<Mutex<Transaction>>|
+         -> Result<(Vec<Constant>, Constant)> {
+            let layout = mdm.get_layout(tblname, Arc::clone(&tx))?;
+            let mut ts = TableScan::new(Arc::clone(&tx), tblname, layout)?;
+            let mut vals = vec![];
+            while ts.next() {
+                vals.push(ts.get_val(fldname)?);
+            }
+            let idxmap = mdm.get_index_info(tblname, Arc::clone(&tx))?;
+            let idx = idxmap.get(idxfld).unwrap().open();
+            let mut idx = idx.lock().unwrap();
+            idx.before_first(Constant::I32(7))?;
+            assert!(idx.next());
+            ts.move_to_rid(idx.get_data_rid()?)?;
+            let found = ts.get_val(fldname)?;
+            idx.close()?;
+            ts.close()?;
+            Ok((vals, found))
+        };
+        let values = |f: &dyn Fn(i32) -> Constant| (0..30).filter(|i| i % 3 != 0).map(f).collect();
+        let b_values: Vec<Constant> = values(&|i| Constant::String(format!("b{}", i)));
+
+        // the rewrite is undone by the rollback
+        let tx = Arc::new(Mutex::new(simpledb.new_tx()?));
+        mdm.add_field(
+            "T",
+            "C",
+            FieldInfo::new(FieldType::INTEGER, 0),
+            Arc::clone(&tx),
+        )?;
+        let layout = mdm.get_layout("T", Arc::clone(&tx))?;
+        assert_eq!(layout.schema().fields(), &vec!["A", "B", "C"]);
+        let (vals, found) = read(&mut mdm, "T", "A", "C", Arc::clone(&tx))?;
+        assert_eq!(vals, vec![Constant::Null; 20]);
+        assert_eq!(found, Constant::Null);
+        let (vals, found) = read(&mut mdm, "T", "A", "B", Arc::clone(&tx))?;
+        assert_eq!(vals, b_values);
+        assert_eq!(found, Constant::String("b7".to_string()));
+        tx.lock().unwrap().rollback()?;
+
+        let tx = Arc::new(Mutex::new(simpledb.new_tx()?));
+        let layout = mdm.get_layout("T", Arc::clone(&tx))?;
+        assert_eq!(layout.schema().fields(), &vec!["A", "B"]);
+        let (vals, found) = read(&mut mdm, "T", "A", "B", Arc::clone(&tx))?;
+        assert_eq!(vals, b_values);
+        assert_eq!(found, Constant::String("b7".to_string()));
+
+        // the views on B are invalidated
+        mdm.drop_field("T", "B", Arc::clone(&tx))?;
+        assert_eq!(
+            mdm.get_layout("T", Arc::clone(&tx))?.schema().fields(),
+            &vec!["A"]
+        );
+        let (vals, found) = read(&mut mdm, "T", "A", "A", Arc::clone(&tx))?;
+        assert_eq!(vals, values(&|i| Constant::I32(i)));
+        assert_eq!(found, Constant::I32(7));
+        assert!(mdm.has_view("viewA", Arc::clone(&tx))?);
+        assert!(!mdm.has_view("viewB", Arc::clone(&tx))?);
+        assert!(!mdm.has_view("viewBB", Arc::clone(&tx))?);
+        assert!(mdm.drop_field("T", "A", Arc::clone(&tx)).is_err());
+
+        // the index follows the field
+        mdm.rename_field("T", "A", "Z", Arc::clone(&tx))?;
+        let (vals, found) = read(&mut mdm, "T", "Z", "Z", Arc::clone(&tx))?;
+        assert_eq!(vals, values(&|i| Constant::I32(i)));
+        assert_eq!(found, Constant::I32(7));
+        assert!(!mdm.has_view("viewA", Arc::clone(&tx))?);
+
+        // the index follows the table
+        mdm.rename_table("T", "U", Arc::clone(&tx))?;
+        assert!(!mdm.has_table("T", Arc::clone(&tx))?);
+        let (vals, found) = read(&mut mdm, "U", "Z", "Z", Arc::clone(&tx))?;
+        assert_eq!(vals, values(&|i| Constant::I32(i)));
+        assert_eq!(found, Constant::I32(7));
+        tx.lock().unwrap().commit()?;
+        assert!(!dbdir.join("T.tbl").exists());
+
+        let tx = Arc::new(Mutex::new(simpledb.new_tx()?));
+        let int = FieldInfo::new(FieldType::INTEGER, 0);
+        assert!(mdm.add_field("U", "Z", int, Arc::clone(&tx)).is_err());
+        assert!(mdm.add_field("T", "Y", int, Arc::clone(&tx)).is_err());
+        assert!(mdm.add_field("tblcat", "Y", int, Arc::clone(&tx)).is_err());
+        assert!(mdm.rename_field("U", "A", "Y", Arc::clone(&tx)).is_err());
+        assert!(mdm.rename_table("U", "fldcat", Arc::clone(&tx)).is_err());
+        tx.lock().unwrap().commit()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn format_test() -> Result<()> {
+        if Path::new("_test/formattest").exists() {
+            fs::remove_dir_all("_test/formattest")?;
+        }
+
+        // a new database is marked with the format, and can be opened again
+        let simpledb = SimpleDB::new("_test/formattest")?;
+        drop(simpledb);
+        let simpledb = SimpleDB::new("_test/formattest")?;
+        drop(simpledb);
+
+        // a database without the mark is in the format before the null bitmap
+        fs::remove_file("_test/formattest/simpledb.format")?;
+        let err = SimpleDB::new("_test/formattest").err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "database format 1 can't be opened by format 2, recreate the database"
+        );
+
+        Ok(())
+    }
 }
@@ -51,6 +51,11 @@ impl StatMgr {
             return Ok(si);
         }
     }
+    // my own extends
+    // forgets the statistics of the dropped table.
+    pub fn remove_stat_info(&mut self, tblname: &str) {
+        self.tablestats.remove(tblname);
+    }
     // synchronized
     pub fn refresh_statistics(&mut self, tx: Arc<Mutex<Transaction>>) -> Result<()> {
         self.tablestats = HashMap::new();
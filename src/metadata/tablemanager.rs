@@ -102,6 +102,58 @@ impl TableMgr {
         let layout = Arc::new(Layout::new_with(Arc::new(sch), offsets, size as usize));
         Ok(layout)
     }
+    // my own extends
+    pub fn has_table(&self, tblname: &str, tx: Arc<Mutex<Transaction>>) -> Result<bool> {
+        let mut found = false;
+        let mut tcat = TableScan::new(tx, "tblcat", Arc::clone(&self.tcat_layout))?;
+        while tcat.next() {
+            if tcat.get_string("tblname")? == tblname {
+                found = true;
+                break;
+            }
+        }
+        tcat.close()?;
+
+        Ok(found)
+    }
+    // my own extends
+    // the records of the catalog are deleted by the transaction,
+    // and the file of the table is removed when it commits.
+    pub fn drop_table(&self, tblname: &str, tx: Arc<Mutex<Transaction>>) -> Result<()> {
+        self.delete_catalog(tblname, Arc::clone(&tx))?;
+
+        tx.lock().unwrap().delete_file(&format!("{}.tbl", tblname))
+    }
+    // my own extends
+    // replaces the records of the catalog, which describe the table.
+    // the records of the table must be rewritten for the new layout by the caller.
+    pub fn alter_table(
+        &self,
+        tblname: &str,
+        newname: &str,
+        sch: Arc<Schema>,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<()> {
+        self.delete_catalog(tblname, Arc::clone(&tx))?;
+        self.create_table(newname, sch, tx)
+    }
+    fn delete_catalog(&self, tblname: &str, tx: Arc<Mutex<Transaction>>) -> Result<()> {
+        let mut tcat = TableScan::new(Arc::clone(&tx), "tblcat", Arc::clone(&self.tcat_layout))?;
+        while tcat.next() {
+            if tcat.get_string("tblname")? == tblname {
+                tcat.delete()?;
+            }
+        }
+        tcat.close()?;
+
+        let mut fcat = TableScan::new(tx, "fldcat", Arc::clone(&self.fcat_layout))?;
+        while fcat.next() {
+            if fcat.get_string("tblname")? == tblname {
+                fcat.delete()?;
+            }
+        }
+        fcat.close()
+    }
 }
 
 #[cfg(test)]
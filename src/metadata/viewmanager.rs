@@ -1,4 +1,5 @@
 use anyhow::Result;
+use core::fmt;
 use std::sync::{Arc, Mutex};
 
 use super::tablemanager::{TableMgr, MAX_NAME};
@@ -10,6 +11,22 @@ use crate::{
 
 pub const MAX_VIEWDEF: usize = 100; // max view def chars
 
+#[derive(Debug)]
+pub enum ViewMgrError {
+    DefinitionTooLong(String),
+}
+
+impl std::error::Error for ViewMgrError {}
+impl fmt::Display for ViewMgrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ViewMgrError::DefinitionTooLong(vname) => {
+                write!(f, "view definition too long: {}", vname)
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct ViewMgr {
     tbl_mgr: TableMgr,
@@ -29,6 +46,12 @@ impl ViewMgr {
         Ok(mgr)
     }
     pub fn create_view(&self, vname: &str, vdef: &str, tx: Arc<Mutex<Transaction>>) -> Result<()> {
+        // a longer definition would overrun the slot, e.g. after the wildcards are expanded.
+        if vdef.len() > MAX_VIEWDEF {
+            return Err(From::from(ViewMgrError::DefinitionTooLong(
+                vname.to_string(),
+            )));
+        }
         let layout = self.tbl_mgr.get_layout("viewcat", Arc::clone(&tx))?;
         let mut ts = TableScan::new(tx, "viewcat", layout)?;
         ts.insert()?;
@@ -53,6 +76,32 @@ impl ViewMgr {
 
         Ok(result)
     }
+    // my own extends
+    // the names and the definitions of all views
+    pub fn view_defs(&self, tx: Arc<Mutex<Transaction>>) -> Result<Vec<(String, String)>> {
+        let mut result = vec![];
+
+        let layout = self.tbl_mgr.get_layout("viewcat", Arc::clone(&tx))?;
+        let mut ts = TableScan::new(tx, "viewcat", layout)?;
+        while ts.next() {
+            result.push((ts.get_string("viewname")?, ts.get_string("viewdef")?));
+        }
+        ts.close()?;
+
+        Ok(result)
+    }
+    pub fn drop_view(&self, vname: &str, tx: Arc<Mutex<Transaction>>) -> Result<()> {
+        let layout = self.tbl_mgr.get_layout("viewcat", Arc::clone(&tx))?;
+        let mut ts = TableScan::new(tx, "viewcat", layout)?;
+        while ts.next() {
+            if ts.get_string("viewname")? == vname {
+                ts.delete()?;
+            }
+        }
+        ts.close()?;
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -123,6 +123,9 @@ impl Scan for ChunkScan {
             .get_date(self.currentslot, fldname)
     }
     fn get_val(&mut self, fldname: &str) -> Result<Constant> {
+        if self.is_null(fldname)? {
+            return Ok(Constant::Null);
+        }
         match self.layout.schema().field_type(fldname) {
             FieldType::SMALLINT => Ok(Constant::new_i16(self.get_i16(fldname)?)),
             FieldType::INTEGER => Ok(Constant::new_i32(self.get_i32(fldname)?)),
@@ -131,6 +134,9 @@ impl Scan for ChunkScan {
             FieldType::DATE => Ok(Constant::new_date(self.get_date(fldname)?)),
         }
     }
+    fn is_null(&mut self, fldname: &str) -> Result<bool> {
+        self.rp.as_mut().unwrap().is_null(self.currentslot, fldname)
+    }
     fn has_field(&self, fldname: &str) -> bool {
         self.layout.schema().has_field(fldname)
     }
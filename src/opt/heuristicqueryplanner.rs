@@ -1,19 +1,45 @@
 use anyhow::Result;
-use combine::Parser;
 use core::fmt;
 use std::sync::{Arc, Mutex};
 
 use super::tableplanner::TablePlanner;
 use crate::{
+    materialize::{
+        distinctplan::DistinctPlan, groupbyplan::GroupByPlan, setopplan::SetOpPlan,
+        sortplan::SortPlan,
+    },
     metadata::manager::MetadataMgr,
-    parser::{parser::query, querydata::QueryData},
-    plan::{plan::Plan, planner::Planner, projectplan::ProjectPlan, queryplanner::QueryPlanner},
+    parser::{
+        parser::query,
+        querydata::{JoinKind, QueryData},
+        queryexpr::SetOp,
+        syntaxerror::parse_sql,
+    },
+    plan::{
+        limitplan::LimitPlan,
+        outerrecordplan::OuterRecordPlan,
+        plan::Plan,
+        planner::Planner,
+        projectplan::ProjectPlan,
+        queryplanner::{check_ambiguous_fields, expand_wildcards, QueryPlanner},
+        selectplan::SelectPlan,
+        unionplan::UnionPlan,
+    },
+    query::{
+        predicate::Predicate,
+        subquery::{OuterRecord, Subquery},
+    },
+    record::schema::Schema,
     tx::transaction::Transaction,
 };
 
 #[derive(Debug)]
 pub enum HeuristicQueryPlannerError {
     NoPlan,
+    InvalidOrderBy,
+    UnsupportedView(String),
+    SubqueryInJoin,
+    NotUnionCompatible,
 }
 
 impl std::error::Error for HeuristicQueryPlannerError {}
@@ -23,6 +49,21 @@ impl fmt::Display for HeuristicQueryPlannerError {
             HeuristicQueryPlannerError::NoPlan => {
                 write!(f, "no plan")
             }
+            HeuristicQueryPlannerError::InvalidOrderBy => {
+                write!(f, "order by fields must be in the select list of distinct")
+            }
+            HeuristicQueryPlannerError::UnsupportedView(viewname) => {
+                write!(f, "view {} can't be merged into the query", viewname)
+            }
+            HeuristicQueryPlannerError::SubqueryInJoin => {
+                write!(f, "subquery can't be used in a join condition")
+            }
+            HeuristicQueryPlannerError::NotUnionCompatible => {
+                write!(
+                    f,
+                    "queries of a set operation must have the same field types"
+                )
+            }
         }
     }
 }
@@ -104,36 +145,60 @@ impl HeuristicQueryPlanner {
         self.tableplanners.remove(besttp as usize);
         bestplan.ok_or_else(|| From::from(HeuristicQueryPlannerError::NoPlan))
     }
+    fn create_sort_plan(
+        &self,
+        p: Arc<dyn Plan>,
+        data: &QueryData,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Arc<dyn Plan> {
+        let mut plan = SortPlan::new_with(
+            Arc::clone(&self.next_table_num),
+            p,
+            data.order_by().clone(),
+            tx,
+        );
+        if let Some(limit) = data.limit().and_then(|n| n.checked_add(data.offset())) {
+            // only the first records are needed, so sort them in memory if they fit
+            plan.set_limit(limit);
+        }
+        Arc::new(plan)
+    }
     pub fn set_planner(&mut self, _p: Planner) {
         // for use in planning views, which
         // for simplicity this code doesn't do.
     }
 
     // view support
+    // plans the first n tables of the query, which are joined by the predicate.
     fn create_tableplanners_rec(
         &mut self,
         data: &QueryData,
+        n: usize,
+        pred: &Predicate,
         tx: Arc<Mutex<Transaction>>,
     ) -> Result<()> {
-        for tblname in data.tables().iter() {
-            let mut viewdef = self
-                .mdm
-                .lock()
-                .unwrap()
-                .get_view_def(tblname, Arc::clone(&tx))?;
-            if !viewdef.is_empty() {
-                let mut parser = query();
-                // NOTE: query parser expect terminater.
-                viewdef = format!("{};", viewdef);
-                let (viewdata, _) = parser.parse(viewdef.as_str())?;
-                self.create_tableplanners_rec(&viewdata, Arc::clone(&tx))?;
+        for (tblname, qualifier) in data.tables().iter().zip(data.qualifiers()).take(n) {
+            if let Some(viewdata) = self.view_data(tblname, Arc::clone(&tx))? {
+                // the subqueries would be planned on the records of the view
+                if viewdata.has_outer_join() || !viewdata.pred().subqueries().is_empty() {
+                    let viewname = tblname.clone();
+                    return Err(From::from(HeuristicQueryPlannerError::UnsupportedView(
+                        viewname,
+                    )));
+                }
+                let n = viewdata.tables().len();
+                // the tables of the view are selected by the query too
+                let mut viewpred = viewdata.pred().clone();
+                viewpred.conjoin_with(&mut pred.clone());
+                self.create_tableplanners_rec(&viewdata, n, &viewpred, Arc::clone(&tx))?;
                 continue;
             }
 
             let tp = TablePlanner::new(
                 Arc::clone(&self.next_table_num),
                 tblname,
-                data.pred().clone(),
+                &qualifier,
+                pred.clone(),
                 Arc::clone(&tx),
                 Arc::clone(&self.mdm),
             );
@@ -142,19 +207,75 @@ impl HeuristicQueryPlanner {
 
         Ok(())
     }
-}
-
-impl QueryPlanner for HeuristicQueryPlanner {
-    fn create_plan(
+    fn view_data(&self, tblname: &str, tx: Arc<Mutex<Transaction>>) -> Result<Option<QueryData>> {
+        let mut viewdef = self.mdm.lock().unwrap().get_view_def(tblname, tx)?;
+        if viewdef.is_empty() {
+            return Ok(None);
+        }
+        // NOTE: query parser expect terminater.
+        viewdef = format!("{};", viewdef);
+        let viewdata = parse_sql(query(), viewdef.as_str())?;
+        Ok(Some(viewdata))
+    }
+    fn create_plan_with(
         &mut self,
         data: QueryData,
+        outer: Option<(Arc<Schema>, OuterRecord)>,
         tx: Arc<Mutex<Transaction>>,
     ) -> Result<Arc<dyn Plan>> {
+        let mut data = data;
+        expand_wildcards(&mut data, Arc::clone(&self.mdm), Arc::clone(&tx))?;
+        check_ambiguous_fields(&data, Arc::clone(&self.mdm), Arc::clone(&tx))?;
+
+        if data
+            .joins()
+            .iter()
+            .any(|(_, on)| !on.subqueries().is_empty())
+        {
+            return Err(From::from(HeuristicQueryPlannerError::SubqueryInJoin));
+        }
+
         // Step 1, Create a TablePlanner object for each mentioned table
-        self.create_tableplanners_rec(&data, Arc::clone(&tx))?;
+        // up to the first outer join, with the inner join predicates of them.
+        // The factors with subqueries are selected after all the joins.
+        let outerjoin = data
+            .joins()
+            .iter()
+            .position(|(kind, _)| matches!(kind, JoinKind::Left | JoinKind::Right));
+        let n = outerjoin.unwrap_or(data.tables().len());
+        let (subfactors, factors): (Vec<_>, Vec<_>) = data
+            .pred()
+            .factors()
+            .iter()
+            .cloned()
+            .partition(|fct| !fct.subqueries().is_empty());
+        let mut pred = Predicate::new_empty();
+        if outerjoin.is_none() {
+            pred.init_with_factors(factors);
+        }
+        for (_, on) in data.joins().iter().take(n) {
+            pred.conjoin_with(&mut on.clone());
+        }
+        self.create_tableplanners_rec(&data, n, &pred, Arc::clone(&tx))?;
 
-        // Step 2, Choose the lowest-size plan to begin the join order
-        let mut currentplan = self.get_lowest_select_plan()?;
+        // Step 2, Choose the lowest-size plan to begin the join order,
+        // or the outer record which a correlated subquery refers to.
+        let mut inner = Schema::new();
+        for tp in self.tableplanners.iter() {
+            inner.add_all(tp.schema());
+        }
+        let outerplan =
+            outer.and_then(|(env, outer)| OuterRecordPlan::new(&data, Arc::new(inner), env, outer));
+        let mut currentplan: Arc<dyn Plan> = match outerplan {
+            Some(outerplan) => {
+                let outerplan = Arc::new(outerplan);
+                match pred.select_sub_pred(outerplan.schema()) {
+                    Some(selectpred) => Arc::new(SelectPlan::new(outerplan, selectpred)),
+                    None => outerplan,
+                }
+            }
+            None => self.get_lowest_select_plan()?,
+        };
 
         // Step 3, Repeatedly add a plan to the join order
         while !self.tableplanners.is_empty() {
@@ -166,8 +287,170 @@ impl QueryPlanner for HeuristicQueryPlanner {
             }
         }
 
-        // Step 4, Project on the field names and return
-        let plan = ProjectPlan::new(currentplan, data.fields().clone());
-        Ok(Arc::new(plan))
+        // Step 3.1, Add the rest of the tables in order, from the first outer join,
+        // and then select on the where clause.
+        let mut toppred = Predicate::new_empty();
+        if let Some(start) = outerjoin {
+            currentplan = self.join_in_order(currentplan, &data, start, Arc::clone(&tx))?;
+            toppred = data.pred().clone();
+        } else {
+            toppred.init_with_factors(subfactors);
+        }
+        if !toppred.is_empty() {
+            let env = currentplan.schema();
+            self.bind_subqueries(toppred.subqueries_mut(), env, Arc::clone(&tx))?;
+            currentplan = Arc::new(SelectPlan::new(currentplan, toppred));
+        }
+
+        // Step 4, Group the records and compute the aggregates,
+        // and add a selection plan for the having clause
+        if data.is_grouped() {
+            let aggfns = data
+                .aggregates()
+                .iter()
+                .map(|agg| agg.to_aggregation_fn())
+                .collect();
+            currentplan = Arc::new(GroupByPlan::new(
+                Arc::clone(&self.next_table_num),
+                Arc::clone(&tx),
+                currentplan,
+                data.group_by().clone(),
+                aggfns,
+            )?);
+            if !data.having().is_empty() {
+                let mut having = data.having().clone();
+                let env = currentplan.schema();
+                self.bind_subqueries(having.subqueries_mut(), env, Arc::clone(&tx))?;
+                currentplan = Arc::new(SelectPlan::new(currentplan, having));
+            }
+        }
+
+        // Step 5, Project on the field names, and remove the duplicates
+        let mut projection = data.projection();
+        let subqueries = projection
+            .iter_mut()
+            .flat_map(|(_, expr)| expr.subqueries_mut())
+            .collect();
+        self.bind_subqueries(subqueries, currentplan.schema(), Arc::clone(&tx))?;
+        let mut projected: Arc<dyn Plan> = Arc::new(ProjectPlan::new_with(
+            Arc::clone(&currentplan),
+            projection.clone(),
+        )?);
+        if data.is_distinct() {
+            projected = Arc::new(DistinctPlan::new(
+                Arc::clone(&self.next_table_num),
+                Arc::clone(&tx),
+                projected,
+            ));
+        }
+
+        // Step 6, Sort on the order by fields,
+        // below the projection if it sorts by fields which aren't projected.
+        let mut result = projected;
+        if !data.order_by().is_empty() {
+            let sch = result.schema();
+            if data
+                .order_by()
+                .iter()
+                .all(|(fldname, _)| sch.has_field(fldname))
+            {
+                result = self.create_sort_plan(result, &data, Arc::clone(&tx));
+            } else if data.is_distinct() {
+                // the order of the duplicates, which are removed, would be undefined
+                return Err(From::from(HeuristicQueryPlannerError::InvalidOrderBy));
+            } else {
+                currentplan = self.create_sort_plan(currentplan, &data, Arc::clone(&tx));
+                result = Arc::new(ProjectPlan::new_with(currentplan, projection)?);
+            }
+        }
+
+        // Step 7, Limit the number of records and return
+        if let Some(limit) = data.limit() {
+            result = Arc::new(LimitPlan::new(result, limit, data.offset()));
+        }
+
+        Ok(result)
+    }
+    // joins the tables after the first outer join in the written order,
+    // since an outer join can't be reordered with the other joins.
+    fn join_in_order(
+        &mut self,
+        mut currentplan: Arc<dyn Plan>,
+        data: &QueryData,
+        start: usize,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<Arc<dyn Plan>> {
+        let tables = data
+            .tables()
+            .iter()
+            .zip(data.qualifiers())
+            .zip(data.joins());
+        for ((tblname, qualifier), (kind, on)) in tables.skip(start) {
+            if self.view_data(tblname, Arc::clone(&tx))?.is_some() {
+                let viewname = tblname.clone();
+                return Err(From::from(HeuristicQueryPlannerError::UnsupportedView(
+                    viewname,
+                )));
+            }
+            let tp = TablePlanner::new(
+                Arc::clone(&self.next_table_num),
+                tblname,
+                &qualifier,
+                on.clone(),
+                Arc::clone(&tx),
+                Arc::clone(&self.mdm),
+            );
+            currentplan = match kind {
+                JoinKind::Cross => tp.make_product_plan(currentplan).unwrap(),
+                JoinKind::Inner => {
+                    // the terms of the on clause on the joined tables only
+                    if let Some(selectpred) = on.select_sub_pred(currentplan.schema()) {
+                        currentplan = Arc::new(SelectPlan::new(currentplan, selectpred));
+                    }
+                    match tp.make_join_plan(Arc::clone(&currentplan)) {
+                        Some(p) => p,
+                        None => tp.make_product_plan(currentplan).unwrap(),
+                    }
+                }
+                JoinKind::Left => tp.make_left_join_plan(currentplan),
+                JoinKind::Right => tp.make_right_join_plan(currentplan),
+            };
+        }
+
+        Ok(currentplan)
+    }
+}
+
+impl QueryPlanner for HeuristicQueryPlanner {
+    fn create_plan(
+        &mut self,
+        data: QueryData,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<Arc<dyn Plan>> {
+        self.create_plan_with(data, None, tx)
+    }
+    fn create_subquery_plan(
+        &mut self,
+        sq: &Subquery,
+        env: Arc<Schema>,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<Arc<dyn Plan>> {
+        self.create_plan_with(sq.data().clone(), Some((env, sq.outer())), tx)
+    }
+    fn create_set_op_plan(
+        &mut self,
+        op: SetOp,
+        p1: Arc<dyn Plan>,
+        p2: Arc<dyn Plan>,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<Arc<dyn Plan>> {
+        if !p1.schema().is_union_compatible(&p2.schema()) {
+            return Err(From::from(HeuristicQueryPlannerError::NotUnionCompatible));
+        }
+        if op == SetOp::UnionAll {
+            return Ok(Arc::new(UnionPlan::new(p1, p2)));
+        }
+        let next_table_num = Arc::clone(&self.next_table_num);
+        Ok(Arc::new(SetOpPlan::new(next_table_num, tx, op, p1, p2)))
     }
 }
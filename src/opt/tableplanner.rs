@@ -5,11 +5,14 @@ use std::{
 
 use crate::{
     index::planner::{indexjoinplan::IndexJoinPlan, indexselectplan::IndexSelectPlan},
+    materialize::mergejoinplan::MergeJoinPlan,
     metadata::{indexmanager::IndexInfo, manager::MetadataMgr},
     multibuffer::multibufferproductplan::MultibufferProductPlan,
-    plan::{plan::Plan, selectplan::SelectPlan, tableplan::TablePlan},
-    query::predicate::Predicate,
-    record::schema::Schema,
+    plan::{
+        outerjoinplan::OuterJoinPlan, plan::Plan, selectplan::SelectPlan, tableplan::TablePlan,
+    },
+    query::{constant::Constant, factor::Factor, predicate::Predicate, term::CompOp},
+    record::schema::{qualify_name, FieldType, Schema},
     tx::transaction::Transaction,
 };
 
@@ -21,6 +24,7 @@ pub struct TablePlanner {
     myplan: Arc<TablePlan>,
     mypred: Predicate,
     myschema: Arc<Schema>,
+    qualifier: String,
     indexes: HashMap<String, IndexInfo>,
     tx: Arc<Mutex<Transaction>>,
 }
@@ -29,11 +33,14 @@ impl TablePlanner {
     pub fn new(
         next_table_num: Arc<Mutex<i32>>,
         tblname: &str,
+        qualifier: &str,
         mypred: Predicate,
         tx: Arc<Mutex<Transaction>>,
         mdm: Arc<Mutex<MetadataMgr>>,
     ) -> Self {
-        let myplan = Arc::new(TablePlan::new(tblname, Arc::clone(&tx), Arc::clone(&mdm)).unwrap());
+        let myplan = Arc::new(
+            TablePlan::new_with(tblname, qualifier, Arc::clone(&tx), Arc::clone(&mdm)).unwrap(),
+        );
         let myschema = myplan.schema();
         let mut mdm = mdm.lock().unwrap();
         let indexes = mdm.get_index_info(tblname, Arc::clone(&tx)).unwrap();
@@ -43,10 +50,14 @@ impl TablePlanner {
             myplan,
             mypred,
             myschema,
+            qualifier: qualifier.to_string(),
             indexes,
             tx,
         }
     }
+    pub fn schema(&self) -> Arc<Schema> {
+        Arc::clone(&self.myschema)
+    }
     pub fn make_select_plan(&self) -> Option<Arc<dyn Plan>> {
         let p = match self.make_index_select() {
             Some(p) => p,
@@ -83,15 +94,134 @@ impl TablePlanner {
 
         None
     }
+    // my own extends
+    // the left outer join of current and my table on my predicate.
+    pub fn make_left_join_plan(&self, current: Arc<dyn Plan>) -> Arc<dyn Plan> {
+        let currsch = current.schema();
+        // the terms of my table select the records before the join
+        let joinpred = self.sub_pred_not_applying_to(Arc::clone(&self.myschema));
+        if self
+            .mypred
+            .select_sub_pred(Arc::clone(&self.myschema))
+            .is_none()
+        {
+            if let Some(p) = self.make_index_outer_join(Arc::clone(&current), &joinpred) {
+                return p;
+            }
+        }
+        let myplan = self.make_select_plan().unwrap();
+        if let Some((fldname1, fldname2)) = equated_fields(&joinpred, &currsch, &self.myschema) {
+            return Arc::new(MergeJoinPlan::new_outer(
+                Arc::clone(&self.next_table_num),
+                Arc::clone(&self.tx),
+                current,
+                myplan,
+                &fldname1,
+                &fldname2,
+            ));
+        }
+
+        Arc::new(OuterJoinPlan::new(current, myplan, joinpred))
+    }
+    // the right outer join of current and my table on my predicate,
+    // so every record of my table is output.
+    pub fn make_right_join_plan(&self, current: Arc<dyn Plan>) -> Arc<dyn Plan> {
+        let currsch = current.schema();
+        // the terms of current select the records before the join
+        let mut current = current;
+        if let Some(selectpred) = self.mypred.select_sub_pred(Arc::clone(&currsch)) {
+            current = Arc::new(SelectPlan::new(current, selectpred));
+        }
+        let joinpred = self.sub_pred_not_applying_to(Arc::clone(&currsch));
+        let myplan: Arc<dyn Plan> = Arc::clone(&self.myplan) as Arc<dyn Plan>;
+        if let Some((fldname1, fldname2)) = equated_fields(&joinpred, &self.myschema, &currsch) {
+            return Arc::new(MergeJoinPlan::new_outer(
+                Arc::clone(&self.next_table_num),
+                Arc::clone(&self.tx),
+                myplan,
+                current,
+                &fldname1,
+                &fldname2,
+            ));
+        }
+
+        Arc::new(OuterJoinPlan::new(myplan, current, joinpred))
+    }
+    fn make_index_outer_join(
+        &self,
+        current: Arc<dyn Plan>,
+        joinpred: &Predicate,
+    ) -> Option<Arc<dyn Plan>> {
+        // the index join can't check any other terms
+        if joinpred.factors().len() != 1 {
+            return None;
+        }
+        let currsch = current.schema();
+        for fldname in self.indexes.keys() {
+            if let Some(outerfield) = self.equates_with_field(fldname) {
+                if let Some(outerfield) = currsch.resolve(outerfield) {
+                    let ii = self.indexes.get(fldname).unwrap().clone();
+                    let myplan = Arc::clone(&self.myplan);
+                    let plan = IndexJoinPlan::new_outer(current, myplan, ii, outerfield);
+                    return Some(Arc::new(plan));
+                }
+            }
+        }
+
+        None
+    }
+    fn sub_pred_not_applying_to(&self, sch: Arc<Schema>) -> Predicate {
+        let factors = self
+            .mypred
+            .factors()
+            .iter()
+            .filter(|fct| !fct.applies_to(Arc::clone(&sch)))
+            .cloned()
+            .collect();
+        let mut pred = Predicate::new_empty();
+        pred.init_with_factors(factors);
+        pred
+    }
     fn make_index_select(&self) -> Option<Arc<dyn Plan>> {
         for fldname in self.indexes.keys() {
-            if let Some(val) = self.mypred.equates_with_constant(fldname) {
+            if let Some(val) = self.equates_with_constant(fldname) {
                 let ii = self.indexes.get(fldname).unwrap();
                 let myplan = Arc::clone(&self.myplan);
                 let plan = IndexSelectPlan::new(myplan, ii.clone(), val.clone());
                 return Some(Arc::new(plan));
             }
         }
+        // the index can look up each value of the IN list, or the range of BETWEEN.
+        // the term itself is still checked by the select plan.
+        for fldname in self.indexes.keys() {
+            if let Some(vals) = self.in_values(fldname) {
+                let ii = self.indexes.get(fldname).unwrap();
+                let myplan = Arc::clone(&self.myplan);
+                let plan = IndexSelectPlan::new_in(myplan, ii.clone(), vals);
+                return Some(Arc::new(plan));
+            }
+        }
+        for fldname in self.indexes.keys() {
+            if let Some((loval, hival)) = self.between_range(fldname) {
+                let ii = self.indexes.get(fldname).unwrap();
+                let myplan = Arc::clone(&self.myplan);
+                let plan = IndexSelectPlan::new_range(myplan, ii.clone(), loval, hival, true);
+                return Some(Arc::new(plan));
+            }
+        }
+        // the index on a string field can look up the literal prefix of LIKE.
+        // the term itself is still checked by the select plan.
+        for fldname in self.indexes.keys() {
+            if self.myschema.field_type(fldname) != FieldType::VARCHAR {
+                continue;
+            }
+            if let Some((loval, hival)) = self.like_range(fldname) {
+                let ii = self.indexes.get(fldname).unwrap();
+                let myplan = Arc::clone(&self.myplan);
+                let plan = IndexSelectPlan::new_range(myplan, ii.clone(), loval, hival, false);
+                return Some(Arc::new(plan));
+            }
+        }
 
         None
     }
@@ -101,7 +231,7 @@ impl TablePlanner {
         currsch: Arc<Schema>,
     ) -> Option<Arc<dyn Plan>> {
         for fldname in self.indexes.keys() {
-            if let Some(outerfield) = self.mypred.equates_with_field(fldname) {
+            if let Some(outerfield) = self.equates_with_field(fldname) {
                 if currsch.has_field(outerfield) {
                     let ii = self.indexes.get(fldname).unwrap().clone();
                     let myplan = Arc::clone(&self.myplan);
@@ -130,6 +260,37 @@ impl TablePlanner {
 
         Some(p)
     }
+    // the predicate may refer to the indexed field by a qualified name or not.
+    fn equates_with_constant(&self, fldname: &str) -> Option<&Constant> {
+        self.mypred.equates_with_constant(fldname).or_else(|| {
+            self.mypred
+                .equates_with_constant(&qualify_name(&self.qualifier, fldname))
+        })
+    }
+    fn like_range(&self, fldname: &str) -> Option<(Constant, Constant)> {
+        self.mypred.like_range(fldname).or_else(|| {
+            self.mypred
+                .like_range(&qualify_name(&self.qualifier, fldname))
+        })
+    }
+    fn in_values(&self, fldname: &str) -> Option<Vec<Constant>> {
+        self.mypred.in_values(fldname).or_else(|| {
+            self.mypred
+                .in_values(&qualify_name(&self.qualifier, fldname))
+        })
+    }
+    fn between_range(&self, fldname: &str) -> Option<(Constant, Constant)> {
+        self.mypred.between_range(fldname).or_else(|| {
+            self.mypred
+                .between_range(&qualify_name(&self.qualifier, fldname))
+        })
+    }
+    fn equates_with_field(&self, fldname: &str) -> Option<&str> {
+        self.mypred.equates_with_field(fldname).or_else(|| {
+            self.mypred
+                .equates_with_field(&qualify_name(&self.qualifier, fldname))
+        })
+    }
     fn add_join_pred(&self, p: Arc<dyn Plan>, currsch: Arc<Schema>) -> Option<Arc<dyn Plan>> {
         if let Some(joinpred) = self
             .mypred
@@ -141,3 +302,26 @@ impl TablePlanner {
         Some(p)
     }
 }
+
+// the fields of sch1 and sch2, if the predicate is the single term "f1 = f2".
+fn equated_fields(pred: &Predicate, sch1: &Schema, sch2: &Schema) -> Option<(String, String)> {
+    if pred.factors().len() != 1 {
+        return None;
+    }
+    let Factor::Term(t) = &pred.factors()[0] else {
+        return None;
+    };
+    if t.op() != CompOp::Eq {
+        return None;
+    }
+    let (Ok(lhs), Ok(rhs)) = (t.lhs().as_field_name(), t.rhs().as_field_name()) else {
+        return None;
+    };
+    match (sch1.resolve(lhs), sch2.resolve(rhs)) {
+        (Some(f1), Some(f2)) => Some((f1.clone(), f2.clone())),
+        _ => match (sch1.resolve(rhs), sch2.resolve(lhs)) {
+            (Some(f1), Some(f2)) => Some((f1.clone(), f2.clone())),
+            _ => None,
+        },
+    }
+}
@@ -1,11 +1,15 @@
+pub mod altertabledata;
 pub mod createindexdata;
 pub mod createtabledata;
 pub mod createviewdata;
 pub mod ddl;
 pub mod deletedata;
 pub mod dml;
+pub mod dropdata;
 pub mod insertdata;
 pub mod modifydata;
 pub mod parser;
 pub mod querydata;
+pub mod queryexpr;
 pub mod sql;
+pub mod syntaxerror;
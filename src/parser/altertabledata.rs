@@ -0,0 +1,28 @@
+use crate::record::schema::FieldInfo;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum AlterAction {
+    AddColumn(String, FieldInfo),
+    DropColumn(String),
+    // the old name and the new name
+    RenameColumn(String, String),
+    RenameTable(String),
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AlterTableData {
+    tblname: String,
+    action: AlterAction,
+}
+
+impl AlterTableData {
+    pub fn new(tblname: String, action: AlterAction) -> Self {
+        Self { tblname, action }
+    }
+    pub fn table_name(&self) -> &str {
+        &self.tblname
+    }
+    pub fn action(&self) -> &AlterAction {
+        &self.action
+    }
+}
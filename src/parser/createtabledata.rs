@@ -1,14 +1,155 @@
-use crate::record::schema::Schema;
+use core::fmt;
+use num_derive::FromPrimitive;
+
+use crate::{
+    query::{expression::Expression, predicate::Predicate},
+    record::schema::Schema,
+};
+
+// my own extends
+// a key of the table, which is enforced through an index on the field.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum KeyDef {
+    PrimaryKey(String),
+    Unique(String),
+}
+
+impl KeyDef {
+    pub fn field_name(&self) -> &str {
+        match self {
+            KeyDef::PrimaryKey(fldname) | KeyDef::Unique(fldname) => fldname,
+        }
+    }
+    pub fn is_primary(&self) -> bool {
+        matches!(self, KeyDef::PrimaryKey(_))
+    }
+}
+
+// my own extends
+// NOT NULL, DEFAULT and CHECK of a field, which are checked before a record is written.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FieldConstraint {
+    fldname: String,
+    not_null: bool,
+    default: Option<Expression>,
+    check: Option<Predicate>,
+}
+
+impl FieldConstraint {
+    pub fn new(
+        fldname: String,
+        not_null: bool,
+        default: Option<Expression>,
+        check: Option<Predicate>,
+    ) -> Self {
+        Self {
+            fldname,
+            not_null,
+            default,
+            check,
+        }
+    }
+    pub fn field_name(&self) -> &str {
+        &self.fldname
+    }
+    pub fn is_not_null(&self) -> bool {
+        self.not_null
+    }
+    pub fn default(&self) -> Option<&Expression> {
+        self.default.as_ref()
+    }
+    pub fn check(&self) -> Option<&Predicate> {
+        self.check.as_ref()
+    }
+    pub fn is_empty(&self) -> bool {
+        !self.not_null && self.default.is_none() && self.check.is_none()
+    }
+}
+
+// my own extends
+// the action on the referencing records, when the referenced record is deleted.
+#[derive(FromPrimitive, Debug, Copy, Clone, Eq, PartialEq)]
+pub enum RefAction {
+    Restrict = 1,
+    Cascade = 2,
+    SetNull = 3,
+}
+
+impl fmt::Display for RefAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RefAction::Restrict => write!(f, "RESTRICT"),
+            RefAction::Cascade => write!(f, "CASCADE"),
+            RefAction::SetNull => write!(f, "SET NULL"),
+        }
+    }
+}
+
+// my own extends
+// a field which references a key of a table.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ForeignKeyDef {
+    fldname: String,
+    reftblname: String,
+    reffldname: String,
+    on_delete: RefAction,
+}
+
+impl ForeignKeyDef {
+    pub fn new(
+        fldname: String,
+        reftblname: String,
+        reffldname: String,
+        on_delete: RefAction,
+    ) -> Self {
+        Self {
+            fldname,
+            reftblname,
+            reffldname,
+            on_delete,
+        }
+    }
+    pub fn field_name(&self) -> &str {
+        &self.fldname
+    }
+    pub fn ref_table_name(&self) -> &str {
+        &self.reftblname
+    }
+    pub fn ref_field_name(&self) -> &str {
+        &self.reffldname
+    }
+    pub fn on_delete(&self) -> RefAction {
+        self.on_delete
+    }
+}
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct CreateTableData {
     tblname: String,
     sch: Schema,
+    keys: Vec<KeyDef>,
+    constraints: Vec<FieldConstraint>,
+    foreign_keys: Vec<ForeignKeyDef>,
 }
 
 impl CreateTableData {
     pub fn new(tblname: String, sch: Schema) -> Self {
-        Self { tblname, sch }
+        Self::new_with(tblname, sch, vec![], vec![], vec![])
+    }
+    pub fn new_with(
+        tblname: String,
+        sch: Schema,
+        keys: Vec<KeyDef>,
+        constraints: Vec<FieldConstraint>,
+        foreign_keys: Vec<ForeignKeyDef>,
+    ) -> Self {
+        Self {
+            tblname,
+            sch,
+            keys,
+            constraints,
+            foreign_keys,
+        }
     }
     pub fn table_name(&self) -> &str {
         &self.tblname
@@ -16,4 +157,14 @@ impl CreateTableData {
     pub fn new_schema(&self) -> &Schema {
         &self.sch
     }
+    // my own extends
+    pub fn keys(&self) -> &Vec<KeyDef> {
+        &self.keys
+    }
+    pub fn constraints(&self) -> &Vec<FieldConstraint> {
+        &self.constraints
+    }
+    pub fn foreign_keys(&self) -> &Vec<ForeignKeyDef> {
+        &self.foreign_keys
+    }
 }
@@ -13,6 +13,9 @@ impl CreateViewData {
     pub fn view_name(&self) -> &str {
         &self.viewname
     }
+    pub fn query_data_mut(&mut self) -> &mut QueryData {
+        &mut self.qrydata
+    }
     pub fn view_def(&self) -> String {
         self.qrydata.to_string()
     }
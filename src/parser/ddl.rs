@@ -1,6 +1,6 @@
 use super::{
-    createindexdata::CreateIndexData, createtabledata::CreateTableData,
-    createviewdata::CreateViewData,
+    altertabledata::AlterTableData, createindexdata::CreateIndexData,
+    createtabledata::CreateTableData, createviewdata::CreateViewData, dropdata::DropData,
 };
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -8,4 +8,8 @@ pub enum DDL {
     Table(CreateTableData),
     View(CreateViewData),
     Index(CreateIndexData),
+    DropTable(DropData),
+    DropView(DropData),
+    DropIndex(DropData),
+    AlterTable(AlterTableData),
 }
@@ -1,18 +1,35 @@
+use super::queryexpr::QueryExpr;
 use crate::query::constant::Constant;
 
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
 pub struct InsertData {
     tblname: String,
     flds: Vec<String>,
-    vals: Vec<Constant>,
+    // a record for each tuple of the values clause
+    rows: Vec<Vec<Constant>>,
+    // the query, whose records are inserted instead of the values
+    query: Option<QueryExpr>,
 }
 
 impl InsertData {
     pub fn new(tblname: String, flds: Vec<String>, vals: Vec<Constant>) -> Self {
+        Self::new_rows(tblname, flds, vec![vals])
+    }
+    // my own extends
+    pub fn new_rows(tblname: String, flds: Vec<String>, rows: Vec<Vec<Constant>>) -> Self {
         Self {
             tblname,
             flds,
-            vals,
+            rows,
+            query: None,
+        }
+    }
+    pub fn new_query(tblname: String, flds: Vec<String>, query: QueryExpr) -> Self {
+        Self {
+            tblname,
+            flds,
+            rows: vec![],
+            query: Some(query),
         }
     }
     pub fn table_name(&self) -> &str {
@@ -21,7 +38,10 @@ impl InsertData {
     pub fn fields(&self) -> &Vec<String> {
         &self.flds
     }
-    pub fn vals(&self) -> &Vec<Constant> {
-        &self.vals
+    pub fn rows(&self) -> &Vec<Vec<Constant>> {
+        &self.rows
+    }
+    pub fn query(&self) -> Option<&QueryExpr> {
+        self.query.as_ref()
     }
 }
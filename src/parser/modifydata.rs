@@ -3,28 +3,33 @@ use crate::query::{expression::Expression, predicate::Predicate};
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
 pub struct ModifyData {
     tblname: String,
-    fldname: String,
-    newval: Expression,
+    // the new value of each target field,
+    // which is evaluated on the record before the update.
+    assignments: Vec<(String, Expression)>,
     pred: Predicate,
 }
 
 impl ModifyData {
     pub fn new(tblname: String, fldname: String, newval: Expression, pred: Predicate) -> Self {
+        Self::new_with(tblname, vec![(fldname, newval)], pred)
+    }
+    // my own extends
+    pub fn new_with(
+        tblname: String,
+        assignments: Vec<(String, Expression)>,
+        pred: Predicate,
+    ) -> Self {
         Self {
             tblname,
-            fldname,
-            newval,
+            assignments,
             pred,
         }
     }
     pub fn table_name(&self) -> &str {
         &self.tblname
     }
-    pub fn target_field(&self) -> &str {
-        &self.fldname
-    }
-    pub fn new_value(&self) -> &Expression {
-        &self.newval
+    pub fn assignments(&self) -> &Vec<(String, Expression)> {
+        &self.assignments
     }
     pub fn pred(&self) -> &Predicate {
         &self.pred
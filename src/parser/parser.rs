@@ -1,19 +1,38 @@
 use combine::{
     any, attempt,
-    error::ParseError,
+    error::{ParseError, StreamError},
+    parser,
     parser::char::{alpha_num, char, digit, letter, spaces, string, string_cmp},
-    stream::Stream,
-    {between, chainl1, many, many1, optional, satisfy, sep_by, sep_by1, Parser},
+    stream::{Stream, StreamErrorFor},
+    {between, chainl1, many, many1, not_followed_by, optional, satisfy, sep_by, sep_by1, Parser},
 };
 use std::usize;
 
 use super::{
-    createindexdata::CreateIndexData, createtabledata::CreateTableData,
-    createviewdata::CreateViewData, ddl::DDL, deletedata::DeleteData, dml::DML,
-    insertdata::InsertData, modifydata::ModifyData, querydata::QueryData, sql::SQL,
+    altertabledata::{AlterAction, AlterTableData},
+    createindexdata::CreateIndexData,
+    createtabledata::{CreateTableData, FieldConstraint, ForeignKeyDef, KeyDef, RefAction},
+    createviewdata::CreateViewData,
+    ddl::DDL,
+    deletedata::DeleteData,
+    dml::DML,
+    dropdata::DropData,
+    insertdata::InsertData,
+    modifydata::ModifyData,
+    querydata::{JoinKind, QueryData},
+    queryexpr::{QueryExpr, SetOp},
+    sql::SQL,
 };
 use crate::{
-    query::{constant::Constant, expression::Expression, predicate::Predicate, term::Term},
+    materialize::{aggregationfn::Aggregate, recordcomparator::SortOrder},
+    query::{
+        constant::Constant,
+        expression::{ArithOp, Expression},
+        function::ScalarFn,
+        predicate::Predicate,
+        subquery::Subquery,
+        term::{CompOp, Term},
+    },
     record::schema::{FieldInfo, FieldType, Schema},
 };
 
@@ -26,6 +45,8 @@ where
 {
     string_cmp(s, |x, y| x.eq_ignore_ascii_case(&y))
         .map(|x| x.to_string())
+        // a keyword must not be a prefix of an identifier (e.g. NOT of notes)
+        .skip(not_followed_by(alpha_num().or(char('_'))))
         // lexeme
         .skip(spaces().silent())
 }
@@ -40,6 +61,16 @@ where
         .skip(spaces().silent())
 }
 
+fn kw_distinct<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    keyword("DISTINCT")
+        // lexeme
+        .skip(spaces().silent())
+}
+
 fn kw_from<Input>() -> impl Parser<Input, Output = String>
 where
     Input: Stream<Token = char>,
@@ -70,6 +101,26 @@ where
         .skip(spaces().silent())
 }
 
+fn kw_or<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    keyword("OR")
+        // lexeme
+        .skip(spaces().silent())
+}
+
+fn kw_not<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    keyword("NOT")
+        // lexeme
+        .skip(spaces().silent())
+}
+
 fn kw_insert<Input>() -> impl Parser<Input, Output = String>
 where
     Input: Stream<Token = char>,
@@ -230,440 +281,1701 @@ where
         .skip(spaces().silent())
 }
 
-fn kw_on<Input>() -> impl Parser<Input, Output = String>
+fn kw_drop<Input>() -> impl Parser<Input, Output = String>
 where
     Input: Stream<Token = char>,
     Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
 {
-    keyword("ON")
+    keyword("DROP")
         // lexeme
         .skip(spaces().silent())
 }
 
-fn delim_parenl<Input>() -> impl Parser<Input, Output = char>
+fn kw_alter<Input>() -> impl Parser<Input, Output = String>
 where
     Input: Stream<Token = char>,
     Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
 {
-    char('(')
+    keyword("ALTER")
         // lexeme
         .skip(spaces().silent())
 }
 
-fn delim_parenr<Input>() -> impl Parser<Input, Output = char>
+fn kw_add<Input>() -> impl Parser<Input, Output = String>
 where
     Input: Stream<Token = char>,
     Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
 {
-    char(')')
+    keyword("ADD")
         // lexeme
         .skip(spaces().silent())
 }
 
-fn delim_comma<Input>() -> impl Parser<Input, Output = char>
+fn kw_column<Input>() -> impl Parser<Input, Output = String>
 where
     Input: Stream<Token = char>,
     Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
 {
-    char(',')
+    keyword("COLUMN")
         // lexeme
         .skip(spaces().silent())
 }
 
-fn binop_eq<Input>() -> impl Parser<Input, Output = char>
+fn kw_rename<Input>() -> impl Parser<Input, Output = String>
 where
     Input: Stream<Token = char>,
     Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
 {
-    char('=')
+    keyword("RENAME")
         // lexeme
         .skip(spaces().silent())
 }
 
-fn terminate<Input>() -> impl Parser<Input, Output = char>
+fn kw_to<Input>() -> impl Parser<Input, Output = String>
 where
     Input: Stream<Token = char>,
     Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
 {
-    char(';')
+    keyword("TO")
         // lexeme
         .skip(spaces().silent())
 }
 
-/// token
-
-fn id_tok<Input>() -> impl Parser<Input, Output = String>
+fn kw_on<Input>() -> impl Parser<Input, Output = String>
 where
     Input: Stream<Token = char>,
     Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
 {
-    letter()
-        .and(many(alpha_num().or(char('_'))))
-        .map(|(x, mut xs): (char, Vec<char>)| {
-            xs.insert(0, x);
-            xs.into_iter().collect()
-        })
+    keyword("ON")
         // lexeme
         .skip(spaces().silent())
 }
 
-fn i32_tok<Input>() -> impl Parser<Input, Output = i32>
+fn kw_order<Input>() -> impl Parser<Input, Output = String>
 where
     Input: Stream<Token = char>,
     Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
 {
-    optional(char('-').or(char('+')))
-        .and(many1(digit()).map(|s: String| s.parse::<i32>()))
-        .map(|(s, v)| {
-            if let Some(sign) = s {
-                if sign == '-' {
-                    return v.unwrap_or_default() * -1;
-                }
-            }
-            v.unwrap_or_default()
-        })
+    keyword("ORDER")
         // lexeme
         .skip(spaces().silent())
 }
 
-fn str_tok<Input>() -> impl Parser<Input, Output = String>
+fn kw_by<Input>() -> impl Parser<Input, Output = String>
 where
     Input: Stream<Token = char>,
     Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
 {
-    let almost = satisfy(|c| c != '\'' && c != '\\');
-    let escaped = attempt(char('\\').with(any()));
-    let quote_quote = attempt(char('\'').skip(char('\'')));
-    let internal_string = almost.or(escaped).or(quote_quote);
-
-    between(
-        char('\''),
-        char('\''),
-        many(internal_string).map(|v: Vec<char>| v.into_iter().collect::<String>()),
-    )
-    // lexeme
-    .skip(spaces().silent())
+    keyword("BY")
+        // lexeme
+        .skip(spaces().silent())
 }
 
-fn bool_tok<Input>() -> impl Parser<Input, Output = bool>
+fn kw_asc<Input>() -> impl Parser<Input, Output = String>
 where
     Input: Stream<Token = char>,
     Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
 {
-    attempt(string("false"))
-        .map(|_| false)
-        .or(string("true").map(|_| true))
+    keyword("ASC")
         // lexeme
         .skip(spaces().silent())
 }
 
-/// Methods for parsing predicates and their components
-
-fn field<Input>() -> impl Parser<Input, Output = String>
+fn kw_desc<Input>() -> impl Parser<Input, Output = String>
 where
     Input: Stream<Token = char>,
     Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
 {
-    id_tok()
+    keyword("DESC")
+        // lexeme
+        .skip(spaces().silent())
 }
 
-fn constant<Input>() -> impl Parser<Input, Output = Constant>
+fn kw_group<Input>() -> impl Parser<Input, Output = String>
 where
     Input: Stream<Token = char>,
     Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
 {
-    attempt(str_tok())
-        .map(|sval| Constant::new_string(sval))
-        .or(attempt(i32_tok()).map(|ival| Constant::new_i32(ival))) // pick it up as the largest signed integer
-        .or(bool_tok().map(|bval| Constant::new_bool(bval)))
+    keyword("GROUP")
         // lexeme
         .skip(spaces().silent())
 }
 
-fn expression<Input>() -> impl Parser<Input, Output = Expression>
+fn kw_having<Input>() -> impl Parser<Input, Output = String>
 where
     Input: Stream<Token = char>,
     Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
 {
-    // try constant first, because field can get bool value too.
-    attempt(constant())
-        .map(|c| Expression::Val(c))
-        .or(field().map(|fldname| Expression::new_fldname(fldname)))
+    keyword("HAVING")
+        // lexeme
+        .skip(spaces().silent())
 }
 
-fn term<Input>() -> impl Parser<Input, Output = Term>
+fn kw_count<Input>() -> impl Parser<Input, Output = String>
 where
     Input: Stream<Token = char>,
     Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
 {
-    expression()
-        .skip(binop_eq())
-        .and(expression())
-        .map(|(lhs, rhs)| Term::new(lhs, rhs))
+    keyword("COUNT")
+        // lexeme
+        .skip(spaces().silent())
 }
 
-fn predicate<Input>() -> impl Parser<Input, Output = Predicate>
+fn kw_sum<Input>() -> impl Parser<Input, Output = String>
 where
     Input: Stream<Token = char>,
     Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
 {
-    let pred1 = term().map(|t| Predicate::new(t));
-    let conjoin = kw_and().map(|_| {
-        |mut l: Predicate, mut r: Predicate| {
-            l.conjoin_with(&mut r);
-            l
-        }
-    });
-
-    chainl1(pred1, conjoin)
+    keyword("SUM")
+        // lexeme
+        .skip(spaces().silent())
 }
 
-/// Methods for parsing queries
-
-pub fn query<Input>() -> impl Parser<Input, Output = QueryData>
+fn kw_avg<Input>() -> impl Parser<Input, Output = String>
 where
     Input: Stream<Token = char>,
     Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
 {
-    let fields = kw_select().with(select_list());
-    let tables = kw_from().with(table_list());
-    let where_clause = kw_where().with(predicate());
-
-    fields
-        .and(tables)
-        .and(optional(where_clause))
-        .skip(terminate())
-        .map(|((fs, ts), op)| {
-            let pred = op.unwrap_or(Predicate::new_empty());
-            QueryData::new(fs, ts, pred)
-        })
+    keyword("AVG")
+        // lexeme
+        .skip(spaces().silent())
 }
 
-fn select_list<Input>() -> impl Parser<Input, Output = Vec<String>>
+fn kw_min<Input>() -> impl Parser<Input, Output = String>
 where
     Input: Stream<Token = char>,
     Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
 {
-    let fld1 = field().map(|f| vec![f]);
-    let sep = delim_comma().map(|_| {
-        |mut x: Vec<String>, mut y: Vec<String>| {
-            x.append(&mut y);
-            x
-        }
-    });
-
-    chainl1(fld1, sep)
+    keyword("MIN")
+        // lexeme
+        .skip(spaces().silent())
 }
 
-fn table_list<Input>() -> impl Parser<Input, Output = Vec<String>>
+fn kw_max<Input>() -> impl Parser<Input, Output = String>
 where
     Input: Stream<Token = char>,
     Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
 {
-    let id_tok1 = id_tok().map(|f| vec![f]);
-    let sep = delim_comma().map(|_| {
-        |mut x: Vec<String>, mut y: Vec<String>| {
-            x.append(&mut y);
-            x
-        }
-    });
-
-    chainl1(id_tok1, sep)
+    keyword("MAX")
+        // lexeme
+        .skip(spaces().silent())
 }
 
-/// Methods for parsing the various update commands
-
-pub fn update_cmd<Input>() -> impl Parser<Input, Output = SQL>
+fn kw_limit<Input>() -> impl Parser<Input, Output = String>
 where
     Input: Stream<Token = char>,
     Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
 {
-    attempt(insert().map(|i| SQL::DML(DML::Insert(i))))
-        .or(attempt(delete().map(|d| SQL::DML(DML::Delete(d)))))
-        .or(attempt(modify().map(|m| SQL::DML(DML::Modify(m)))))
-        .or(ddl().map(|ddl| SQL::DDL(ddl)))
+    keyword("LIMIT")
+        // lexeme
+        .skip(spaces().silent())
 }
 
-fn ddl<Input>() -> impl Parser<Input, Output = DDL>
+fn kw_offset<Input>() -> impl Parser<Input, Output = String>
 where
     Input: Stream<Token = char>,
     Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
 {
-    attempt(create_table().map(|t| DDL::Table(t)))
-        .or(attempt(create_view().map(|v| DDL::View(v))))
-        .or(create_index().map(|i| DDL::Index(i)))
+    keyword("OFFSET")
+        // lexeme
+        .skip(spaces().silent())
 }
 
-/// Method for parsing delete commands
-
-pub fn delete<Input>() -> impl Parser<Input, Output = DeleteData>
+fn kw_join<Input>() -> impl Parser<Input, Output = String>
 where
     Input: Stream<Token = char>,
     Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
 {
-    let prelude = kw_delete().and(kw_from());
-    let where_clause = kw_where().with(predicate());
-
-    prelude
-        .with(id_tok())
-        .and(optional(where_clause))
-        .skip(terminate())
-        .map(|(tblname, opred)| {
-            let pred = opred.unwrap_or(Predicate::new_empty());
-            DeleteData::new(tblname, pred)
-        })
+    keyword("JOIN")
+        // lexeme
+        .skip(spaces().silent())
 }
 
-/// Methods for parsing insert commands
-
-pub fn insert<Input>() -> impl Parser<Input, Output = InsertData>
+fn kw_inner<Input>() -> impl Parser<Input, Output = String>
 where
     Input: Stream<Token = char>,
     Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
 {
-    let prelude = kw_insert().and(kw_into());
-    let fields = between(delim_parenl(), delim_parenr(), field_list());
-    let vals = kw_values().with(between(delim_parenl(), delim_parenr(), const_list()));
-
-    prelude
-        .with(id_tok())
-        .and(fields)
-        .and(vals)
-        .skip(terminate())
-        .map(|((t, fs), vs)| InsertData::new(t, fs, vs))
+    keyword("INNER")
+        // lexeme
+        .skip(spaces().silent())
 }
 
-fn field_list<Input>() -> impl Parser<Input, Output = Vec<String>>
+fn kw_left<Input>() -> impl Parser<Input, Output = String>
 where
     Input: Stream<Token = char>,
     Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
 {
-    sep_by1(field(), delim_comma())
+    keyword("LEFT")
+        // lexeme
+        .skip(spaces().silent())
 }
 
-fn const_list<Input>() -> impl Parser<Input, Output = Vec<Constant>>
+fn kw_right<Input>() -> impl Parser<Input, Output = String>
 where
     Input: Stream<Token = char>,
     Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
 {
-    sep_by1(constant(), delim_comma())
+    keyword("RIGHT")
+        // lexeme
+        .skip(spaces().silent())
 }
 
-/// Method for parsing modify commands
-
-pub fn modify<Input>() -> impl Parser<Input, Output = ModifyData>
+fn kw_outer<Input>() -> impl Parser<Input, Output = String>
 where
     Input: Stream<Token = char>,
     Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
 {
-    let sets = kw_set().with(field().skip(binop_eq()).and(expression()));
-    let where_clause = kw_where().with(predicate());
-
-    kw_update()
-        .with(id_tok())
-        .and(sets)
-        .and(optional(where_clause))
-        .skip(terminate())
-        .map(|((t, (f, e)), op)| {
-            let pred = op.unwrap_or(Predicate::new_empty());
-            ModifyData::new(t, f, e, pred)
-        })
+    keyword("OUTER")
+        // lexeme
+        .skip(spaces().silent())
 }
 
-/// Method for parsing create table commands
-
-pub fn create_table<Input>() -> impl Parser<Input, Output = CreateTableData>
+fn kw_in<Input>() -> impl Parser<Input, Output = String>
 where
     Input: Stream<Token = char>,
     Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
 {
-    let prelude = kw_create().and(kw_table());
-    let field_defs = between(delim_parenl(), delim_parenr(), field_defs());
-
-    prelude
-        .with(id_tok())
-        .and(field_defs)
-        .skip(terminate())
-        .map(|(tblname, fdefs)| {
-            let mut sch = Schema::new();
-            for (fldname, fi) in fdefs.iter() {
-                sch.add_field(fldname, fi.fld_type, fi.length)
-            }
-            CreateTableData::new(tblname, sch)
-        })
+    keyword("IN")
+        // lexeme
+        .skip(spaces().silent())
 }
 
-fn field_defs<Input>() -> impl Parser<Input, Output = Vec<(String, FieldInfo)>>
+fn kw_exists<Input>() -> impl Parser<Input, Output = String>
 where
     Input: Stream<Token = char>,
     Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
 {
-    sep_by(field_def(), delim_comma())
+    keyword("EXISTS")
+        // lexeme
+        .skip(spaces().silent())
 }
 
-fn field_def<Input>() -> impl Parser<Input, Output = (String, FieldInfo)>
+fn kw_if<Input>() -> impl Parser<Input, Output = String>
 where
     Input: Stream<Token = char>,
     Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
 {
-    id_tok().and(type_def())
+    keyword("IF")
+        // lexeme
+        .skip(spaces().silent())
 }
 
-fn type_def<Input>() -> impl Parser<Input, Output = FieldInfo>
+fn kw_union<Input>() -> impl Parser<Input, Output = String>
 where
     Input: Stream<Token = char>,
     Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
 {
-    let int16_def = kw_int16().map(|_| FieldInfo::new(FieldType::SMALLINT, 0));
-    let int32_def = kw_int32().map(|_| FieldInfo::new(FieldType::INTEGER, 0));
-    let varchar_def = kw_varchar()
-        .with(between(delim_parenl(), delim_parenr(), i32_tok()))
-        .map(|n| FieldInfo::new(FieldType::VARCHAR, n as usize));
-    let bool_def = kw_bool().map(|_| FieldInfo::new(FieldType::BOOL, 0));
-    let date_def = kw_date().map(|_| FieldInfo::new(FieldType::DATE, 0));
-
-    attempt(int32_def)
-        .or(attempt(int16_def))
-        .or(attempt(varchar_def))
-        .or(attempt(bool_def))
-        .or(date_def)
+    keyword("UNION")
+        // lexeme
+        .skip(spaces().silent())
 }
 
-/// Method for parsing create view commands
-
-pub fn create_view<Input>() -> impl Parser<Input, Output = CreateViewData>
+fn kw_all<Input>() -> impl Parser<Input, Output = String>
 where
     Input: Stream<Token = char>,
     Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
 {
-    let prelude = kw_create().and(kw_view());
-
-    prelude
-        .with(id_tok())
-        .and(kw_as().with(query()))
-        .map(|(v, vq)| CreateViewData::new(v, vq))
+    keyword("ALL")
+        // lexeme
+        .skip(spaces().silent())
 }
 
-/// Method for parsing create index commands
-
-pub fn create_index<Input>() -> impl Parser<Input, Output = CreateIndexData>
+fn kw_intersect<Input>() -> impl Parser<Input, Output = String>
 where
     Input: Stream<Token = char>,
     Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
 {
-    let prelude = kw_create().and(kw_index());
-
-    prelude
-        .with(id_tok())
-        .and(kw_on().with(id_tok()))
-        .and(between(delim_parenl(), delim_parenr(), field()))
-        .skip(terminate())
-        .map(|((idxname, tblname), fldname)| CreateIndexData::new(idxname, tblname, fldname))
+    keyword("INTERSECT")
+        // lexeme
+        .skip(spaces().silent())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+fn kw_except<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    keyword("EXCEPT")
+        // lexeme
+        .skip(spaces().silent())
+}
+
+fn kw_like<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    keyword("LIKE")
+        // lexeme
+        .skip(spaces().silent())
+}
+
+fn kw_escape<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    keyword("ESCAPE")
+        // lexeme
+        .skip(spaces().silent())
+}
+
+fn kw_is<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    keyword("IS")
+        // lexeme
+        .skip(spaces().silent())
+}
+
+fn kw_null<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    keyword("NULL")
+        // lexeme
+        .skip(spaces().silent())
+}
+
+fn kw_between<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    keyword("BETWEEN")
+        // lexeme
+        .skip(spaces().silent())
+}
+
+fn kw_case<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    keyword("CASE")
+        // lexeme
+        .skip(spaces().silent())
+}
+
+fn kw_when<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    keyword("WHEN")
+        // lexeme
+        .skip(spaces().silent())
+}
+
+fn kw_then<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    keyword("THEN")
+        // lexeme
+        .skip(spaces().silent())
+}
+
+fn kw_else<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    keyword("ELSE")
+        // lexeme
+        .skip(spaces().silent())
+}
+
+fn kw_end<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    keyword("END")
+        // lexeme
+        .skip(spaces().silent())
+}
+
+fn kw_coalesce<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    keyword("COALESCE")
+        // lexeme
+        .skip(spaces().silent())
+}
+
+fn kw_current_date<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    keyword("CURRENT_DATE")
+        // lexeme
+        .skip(spaces().silent())
+}
+
+fn kw_primary<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    keyword("PRIMARY")
+        // lexeme
+        .skip(spaces().silent())
+}
+
+fn kw_key<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    keyword("KEY")
+        // lexeme
+        .skip(spaces().silent())
+}
+
+fn kw_unique<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    keyword("UNIQUE")
+        // lexeme
+        .skip(spaces().silent())
+}
+
+fn kw_default<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    keyword("DEFAULT")
+        // lexeme
+        .skip(spaces().silent())
+}
+
+fn kw_check<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    keyword("CHECK")
+        // lexeme
+        .skip(spaces().silent())
+}
+
+fn kw_foreign<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    keyword("FOREIGN")
+        // lexeme
+        .skip(spaces().silent())
+}
+
+fn kw_references<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    keyword("REFERENCES")
+        // lexeme
+        .skip(spaces().silent())
+}
+
+fn kw_restrict<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    keyword("RESTRICT")
+        // lexeme
+        .skip(spaces().silent())
+}
+
+fn kw_cascade<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    keyword("CASCADE")
+        // lexeme
+        .skip(spaces().silent())
+}
+
+fn delim_parenl<Input>() -> impl Parser<Input, Output = char>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    char('(')
+        // lexeme
+        .skip(spaces().silent())
+}
+
+fn delim_parenr<Input>() -> impl Parser<Input, Output = char>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    char(')')
+        // lexeme
+        .skip(spaces().silent())
+}
+
+fn delim_comma<Input>() -> impl Parser<Input, Output = char>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    char(',')
+        // lexeme
+        .skip(spaces().silent())
+}
+
+fn binop_eq<Input>() -> impl Parser<Input, Output = char>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    char('=')
+        // lexeme
+        .skip(spaces().silent())
+}
+
+fn binop_comp<Input>() -> impl Parser<Input, Output = CompOp>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    // two-character operators must be tried before their one-character prefixes.
+    attempt(string("<=").map(|_| CompOp::Le))
+        .or(attempt(string("<>").map(|_| CompOp::Ne)))
+        .or(attempt(string("!=").map(|_| CompOp::Ne)))
+        .or(attempt(string(">=").map(|_| CompOp::Ge)))
+        .or(char('<').map(|_| CompOp::Lt))
+        .or(char('>').map(|_| CompOp::Gt))
+        .or(char('=').map(|_| CompOp::Eq))
+        // lexeme
+        .skip(spaces().silent())
+}
+
+fn terminate<Input>() -> impl Parser<Input, Output = char>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    char(';')
+        // lexeme
+        .skip(spaces().silent())
+}
+
+/// token
+
+fn id_tok<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    letter()
+        .and(many(alpha_num().or(char('_'))))
+        .map(|(x, mut xs): (char, Vec<char>)| {
+            xs.insert(0, x);
+            xs.into_iter().collect()
+        })
+        // lexeme
+        .skip(spaces().silent())
+}
+
+fn i32_tok<Input>() -> impl Parser<Input, Output = i32>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    optional(char('-').or(char('+')))
+        .and(many1(digit()))
+        .and_then(|(s, digits): (Option<char>, String)| {
+            // the sign is parsed with the digits, so that i32::MIN is in range.
+            let num = match s {
+                Some('-') => format!("-{}", digits),
+                _ => digits,
+            };
+            num.parse::<i32>()
+                .map_err(|_| StreamErrorFor::<Input>::unexpected_static_message("number too large"))
+        })
+        // lexeme
+        .skip(spaces().silent())
+}
+
+fn usize_tok<Input>() -> impl Parser<Input, Output = usize>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    many1(digit())
+        .and_then(|s: String| {
+            s.parse::<usize>()
+                .map_err(|_| StreamErrorFor::<Input>::unexpected_static_message("number too large"))
+        })
+        // lexeme
+        .skip(spaces().silent())
+}
+
+fn str_tok<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let almost = satisfy(|c| c != '\'' && c != '\\');
+    let escaped = attempt(char('\\').with(any()));
+    let quote_quote = attempt(char('\'').skip(char('\'')));
+    let internal_string = almost.or(escaped).or(quote_quote);
+
+    between(
+        char('\''),
+        char('\''),
+        many(internal_string).map(|v: Vec<char>| v.into_iter().collect::<String>()),
+    )
+    // lexeme
+    .skip(spaces().silent())
+}
+
+fn bool_tok<Input>() -> impl Parser<Input, Output = bool>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    attempt(string("false"))
+        .map(|_| false)
+        .or(string("true").map(|_| true))
+        // lexeme
+        .skip(spaces().silent())
+}
+
+// the keywords, which can't be an alias without AS,
+// because they may follow a field or a table.
+const RESERVED_WORDS: [&str; 21] = [
+    "SELECT",
+    "FROM",
+    "WHERE",
+    "AND",
+    "OR",
+    "NOT",
+    "GROUP",
+    "HAVING",
+    "ORDER",
+    "LIMIT",
+    "OFFSET",
+    "AS",
+    "JOIN",
+    "INNER",
+    "LEFT",
+    "RIGHT",
+    "OUTER",
+    "ON",
+    "UNION",
+    "INTERSECT",
+    "EXCEPT",
+];
+
+fn alias<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let bare = id_tok().and_then(|id: String| {
+        if RESERVED_WORDS.iter().any(|w| w.eq_ignore_ascii_case(&id)) {
+            Err(StreamErrorFor::<Input>::unexpected_static_message(
+                "reserved word",
+            ))
+        } else {
+            Ok(id)
+        }
+    });
+
+    attempt(kw_as()).with(id_tok()).or(attempt(bare))
+}
+
+/// Methods for parsing predicates and their components
+
+// a field name may be qualified by a table name or an alias, e.g. s.SName
+fn field<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    id_tok()
+        .and(optional(char('.').with(id_tok())))
+        .map(|(x, y)| match y {
+            Some(fldname) => format!("{}.{}", x, fldname),
+            None => x,
+        })
+}
+
+fn constant<Input>() -> impl Parser<Input, Output = Constant>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    attempt(str_tok())
+        .map(|sval| Constant::new_string(sval))
+        .or(attempt(i32_tok()).map(|ival| Constant::new_i32(ival))) // pick it up as the largest signed integer
+        .or(attempt(bool_tok()).map(|bval| Constant::new_bool(bval)))
+        .or(kw_null().map(|_| Constant::new_null()))
+        // lexeme
+        .skip(spaces().silent())
+}
+
+fn binop_add<Input>() -> impl Parser<Input, Output = ArithOp>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    char('+')
+        .map(|_| ArithOp::Add)
+        .or(char('-').map(|_| ArithOp::Sub))
+        // lexeme
+        .skip(spaces().silent())
+}
+
+fn binop_concat<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    attempt(string("||"))
+        .map(|x| x.to_string())
+        // lexeme
+        .skip(spaces().silent())
+}
+
+fn binop_mul<Input>() -> impl Parser<Input, Output = ArithOp>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    char('*')
+        .map(|_| ArithOp::Mul)
+        .or(char('/').map(|_| ArithOp::Div))
+        // lexeme
+        .skip(spaces().silent())
+}
+
+// an expression is recursive via parenthesised sub expressions,
+// so the parser type must be erased by the parser! macro.
+parser! {
+    fn expression[Input]()(Input) -> Expression
+    where [Input: Stream<Token = char>]
+    {
+        let arith = |op: ArithOp| {
+            move |lhs: Expression, rhs: Expression| Expression::new_arith(lhs, op, rhs)
+        };
+        let mul = chainl1(unary(), binop_mul().map(arith));
+        let add = chainl1(mul, binop_add().map(arith));
+        // a || b || c is taken as concat(a, b, c)
+        let concat = |_| {
+            |lhs: Expression, rhs: Expression| match lhs {
+                Expression::Function(ScalarFn::Concat, mut args) => {
+                    args.push(rhs);
+                    Expression::new_function(ScalarFn::Concat, args)
+                }
+                _ => Expression::new_function(ScalarFn::Concat, vec![lhs, rhs]),
+            }
+        };
+
+        chainl1(add, binop_concat().map(concat))
+    }
+}
+
+parser! {
+    fn unary[Input]()(Input) -> Expression
+    where [Input: Stream<Token = char>]
+    {
+        // try constant first, because field can get bool value too,
+        // and a negative number is a constant rather than a negation.
+        let val = attempt(constant()).map(|c| Expression::Val(c));
+        let neg = char('-')
+            .skip(spaces().silent())
+            .with(unary())
+            .map(|e| Expression::new_neg(e));
+        let sub = attempt(subquery()).map(|data| Expression::new_subquery(Subquery::new(data)));
+        let nested = between(delim_parenl(), delim_parenr(), expression());
+        let agg = attempt(aggregate()).map(|agg| Expression::new_aggregate(agg));
+        let case = attempt(case_expr());
+        let coalesce = attempt(kw_coalesce().with(between(
+            delim_parenl(),
+            delim_parenr(),
+            sep_by1(expression(), delim_comma()),
+        )))
+        .map(|exprs| Expression::new_coalesce(exprs));
+        let call = attempt(
+            (
+                id_tok(),
+                between(delim_parenl(), delim_parenr(), sep_by(expression(), delim_comma())),
+            )
+                .and_then(|(name, args): (String, Vec<Expression>)| {
+                    match ScalarFn::lookup(&name) {
+                        Some(func) if func.accepts(args.len()) => {
+                            Ok(Expression::new_function(func, args))
+                        }
+                        Some(_) => Err(StreamErrorFor::<Input>::unexpected_static_message(
+                            "wrong number of arguments",
+                        )),
+                        None => Err(StreamErrorFor::<Input>::unexpected_static_message(
+                            "unknown function",
+                        )),
+                    }
+                }),
+        );
+        let current_date = attempt(kw_current_date())
+            .map(|_| Expression::new_function(ScalarFn::CurrentDate, vec![]));
+        let fld = field().map(|fldname| Expression::new_fldname(fldname));
+
+        val.or(neg)
+            .or(sub)
+            .or(nested)
+            .or(agg)
+            .or(case)
+            .or(coalesce)
+            .or(call)
+            .or(current_date)
+            .or(fld)
+    }
+}
+
+// the searched CASE is tried first, because WHEN can be taken as a field name.
+fn case_expr<Input>() -> impl Parser<Input, Output = Expression>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let otherwise = || optional(attempt(kw_else()).with(expression()));
+    let searched_when = kw_when()
+        .with(predicate())
+        .skip(kw_then())
+        .and(expression());
+    let searched = attempt(kw_case().with(many1(searched_when)))
+        .and(otherwise())
+        .skip(kw_end())
+        .map(|(whens, otherwise)| Expression::new_case(whens, otherwise));
+    let simple_when = kw_when()
+        .with(expression())
+        .skip(kw_then())
+        .and(expression());
+    let simple = (
+        kw_case().with(expression()),
+        many1(simple_when),
+        otherwise(),
+    )
+        .skip(kw_end())
+        .map(|(operand, whens, otherwise)| Expression::new_simple_case(operand, whens, otherwise));
+
+    searched.or(simple)
+}
+
+fn aggregate<Input>() -> impl Parser<Input, Output = Aggregate>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let arg = || between(delim_parenl(), delim_parenr(), field());
+    let all = between(
+        delim_parenl(),
+        delim_parenr(),
+        char('*').skip(spaces().silent()),
+    );
+    let count_all = attempt(kw_count().with(all)).map(|_| Aggregate::CountAll);
+    let count = attempt(kw_count().with(arg())).map(|fldname| Aggregate::Count(fldname));
+    let sum = attempt(kw_sum().with(arg())).map(|fldname| Aggregate::Sum(fldname));
+    let avg = attempt(kw_avg().with(arg())).map(|fldname| Aggregate::Avg(fldname));
+    let min = attempt(kw_min().with(arg())).map(|fldname| Aggregate::Min(fldname));
+    let max = kw_max().with(arg()).map(|fldname| Aggregate::Max(fldname));
+
+    count_all.or(count).or(sum).or(avg).or(min).or(max)
+}
+
+fn term<Input>() -> impl Parser<Input, Output = Term>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let like_op = optional(attempt(kw_not()))
+        .skip(kw_like())
+        .map(|not| match not {
+            Some(_) => CompOp::NotLike,
+            None => CompOp::Like,
+        });
+    let like = attempt((expression(), like_op))
+        .and(expression())
+        .and(optional(escape_char()))
+        .map(|(((lhs, op), rhs), escape)| Term::new_with_escape(lhs, op, rhs, escape));
+    let is_op = kw_is()
+        .with(optional(attempt(kw_not())))
+        .skip(kw_null())
+        .map(|not| match not {
+            Some(_) => CompOp::IsNot,
+            None => CompOp::Is,
+        });
+    let is_null = attempt((expression(), is_op))
+        .map(|(lhs, op)| Term::new_with(lhs, op, Expression::new_val(Constant::new_null())));
+    let in_op = optional(attempt(kw_not()))
+        .skip(kw_in())
+        .map(|not| match not {
+            Some(_) => CompOp::NotIn,
+            None => CompOp::In,
+        });
+    let in_list = attempt((expression(), in_op))
+        .and(between(
+            delim_parenl(),
+            delim_parenr(),
+            sep_by1(expression(), delim_comma()),
+        ))
+        .map(|((lhs, op), list)| Term::new_in(lhs, op, list));
+    let between_op = optional(attempt(kw_not()))
+        .skip(kw_between())
+        .map(|not| match not {
+            Some(_) => CompOp::NotBetween,
+            None => CompOp::Between,
+        });
+    // the AND of BETWEEN is taken before the conjunction
+    let range = attempt((expression(), between_op))
+        .and(expression())
+        .skip(kw_and())
+        .and(expression())
+        .map(|(((lhs, op), lo), hi)| Term::new_between(lhs, op, lo, hi));
+    let comp = (expression(), binop_comp(), expression())
+        .map(|(lhs, op, rhs)| Term::new_with(lhs, op, rhs));
+
+    like.or(is_null).or(in_list).or(range).or(comp)
+}
+
+fn escape_char<Input>() -> impl Parser<Input, Output = char>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    kw_escape().with(str_tok()).and_then(|s: String| {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => Err(StreamErrorFor::<Input>::unexpected_static_message(
+                "escape must be a single character",
+            )),
+        }
+    })
+}
+
+// a predicate is recursive via parenthesised sub predicates,
+// so the parser type must be erased by the parser! macro.
+parser! {
+    fn predicate[Input]()(Input) -> Predicate
+    where [Input: Stream<Token = char>]
+    {
+        let disjoin = attempt(kw_or()).map(|_| {
+            |mut l: Vec<Predicate>, mut r: Vec<Predicate>| {
+                l.append(&mut r);
+                l
+            }
+        });
+
+        chainl1(conjunction().map(|p| vec![p]), disjoin).map(|mut ps| {
+            if ps.len() == 1 {
+                ps.pop().unwrap()
+            } else {
+                Predicate::new_or(ps)
+            }
+        })
+    }
+}
+
+fn conjunction<Input>() -> impl Parser<Input, Output = Predicate>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let conjoin = attempt(kw_and()).map(|_| {
+        |mut l: Predicate, mut r: Predicate| {
+            l.conjoin_with(&mut r);
+            l
+        }
+    });
+
+    chainl1(factor(), conjoin)
+}
+
+parser! {
+    fn factor[Input]()(Input) -> Predicate
+    where [Input: Stream<Token = char>]
+    {
+        let not = attempt(kw_not()).with(factor()).map(|p| Predicate::new_not(p));
+        let exists = attempt(kw_exists())
+            .with(subquery())
+            .map(|data| Predicate::new_exists(Subquery::new_exists(data)));
+        let nested = attempt(between(delim_parenl(), delim_parenr(), predicate()));
+        let in_sub = attempt((expression(), optional(attempt(kw_not())), kw_in(), subquery())).map(
+            |(lhs, not, _, data)| {
+                let p = Predicate::new_in(lhs, Subquery::new(data));
+                match not {
+                    Some(_) => Predicate::new_not(p),
+                    None => p,
+                }
+            },
+        );
+        let term1 = term().map(|t| Predicate::new(t));
+
+        not.or(exists).or(nested).or(in_sub).or(term1)
+    }
+}
+
+/// Methods for parsing queries
+
+pub fn query<Input>() -> impl Parser<Input, Output = QueryData>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    select_query().skip(terminate())
+}
+
+// my own extends
+// the select queries combined by the set operations
+pub fn query_expr<Input>() -> impl Parser<Input, Output = QueryExpr>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    set_query().skip(terminate())
+}
+
+// a query expression is recursive via the parentheses,
+// so the parser type must be erased by the parser! macro.
+// INTERSECT binds tighter than UNION and EXCEPT, as in the standard.
+parser! {
+    fn set_query[Input]()(Input) -> QueryExpr
+    where [Input: Stream<Token = char>]
+    {
+        let union_all = attempt(kw_union().with(kw_all())).map(|_| SetOp::UnionAll);
+        let union = kw_union().map(|_| SetOp::Union);
+        let except = kw_except().map(|_| SetOp::Except);
+        let setop = union_all
+            .or(union)
+            .or(except)
+            .map(|op| move |lhs, rhs| QueryExpr::new_set_op(op, lhs, rhs));
+
+        chainl1(intersect_query(), setop)
+    }
+}
+
+fn intersect_query<Input>() -> impl Parser<Input, Output = QueryExpr>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let intersect =
+        kw_intersect().map(|_| |lhs, rhs| QueryExpr::new_set_op(SetOp::Intersect, lhs, rhs));
+    let nested = between(delim_parenl(), delim_parenr(), set_query());
+    let operand = nested.or(select_query().map(QueryExpr::Select));
+
+    chainl1(operand, intersect)
+}
+
+// a parenthesised query without the terminator
+fn subquery<Input>() -> impl Parser<Input, Output = QueryData>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    between(delim_parenl(), delim_parenr(), select_query())
+}
+
+// a query is recursive via subqueries in the expressions,
+// so the parser type must be erased by the parser! macro.
+parser! {
+    fn select_query[Input]()(Input) -> QueryData
+    where [Input: Stream<Token = char>]
+    {
+        let fields = kw_select()
+            .with(optional(attempt(kw_distinct())))
+            .and(select_list());
+        let tables = kw_from().with(table_list());
+        let where_clause = kw_where().with(predicate());
+        let group_by_clause =
+            attempt(kw_group().with(kw_by())).with(sep_by1(field(), delim_comma()));
+        let having_clause = kw_having().with(predicate());
+        let order_by_clause = kw_order().with(kw_by()).with(sort_key_list());
+        let limit_clause = kw_limit()
+            .with(usize_tok())
+            .and(optional(kw_offset().with(usize_tok())))
+            .and_then(|(limit, ooffset): (usize, Option<usize>)| {
+                // the records up to the limit are counted from the offset
+                match limit.checked_add(ooffset.unwrap_or_default()) {
+                    Some(_) => Ok((limit, ooffset)),
+                    None => Err(StreamErrorFor::<Input>::unexpected_static_message(
+                        "number too large",
+                    )),
+                }
+            });
+
+        fields
+            .and(tables)
+            .and(optional(where_clause))
+            .and(optional(group_by_clause))
+            .and(optional(having_clause))
+            .and(optional(order_by_clause))
+            .and(optional(limit_clause))
+            .map(|(((((((od, fs), ts), op), ogb), oh), oob), ol)| {
+                let pred = op.unwrap_or(Predicate::new_empty());
+                let (exprs, aliases) = fs.into_iter().unzip();
+                let (table_refs, joins): (Vec<_>, Vec<_>) = ts.into_iter().unzip();
+                let (tables, table_aliases) = table_refs.into_iter().unzip();
+                let mut data = QueryData::new_with(exprs, tables, pred);
+                data.set_aliases(aliases);
+                data.set_table_aliases(table_aliases);
+                data.set_joins(joins);
+                data.set_distinct(od.is_some());
+                data.set_group_by(ogb.unwrap_or_default());
+                data.set_having(oh.unwrap_or(Predicate::new_empty()));
+                data.set_order_by(oob.unwrap_or_default());
+                if let Some((limit, ooffset)) = ol {
+                    data.set_limit(limit, ooffset.unwrap_or_default());
+                }
+                data
+            })
+    }
+}
+
+fn sort_key_list<Input>() -> impl Parser<Input, Output = Vec<(String, SortOrder)>>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let order =
+        attempt(kw_asc().map(|_| SortOrder::Asc)).or(attempt(kw_desc().map(|_| SortOrder::Desc)));
+    let key = field().and(optional(order).map(|o| o.unwrap_or(SortOrder::Asc)));
+
+    sep_by1(key, delim_comma())
+}
+
+// "*" or "t.*", which the planner expands into the fields of the tables
+fn wildcard<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let qualified = attempt(id_tok().skip(char('.')).skip(char('*')))
+        .map(|qualifier| format!("{}.*", qualifier));
+
+    qualified
+        .or(char('*').map(|_| "*".to_string()))
+        // lexeme
+        .skip(spaces().silent())
+}
+
+fn select_list<Input>() -> impl Parser<Input, Output = Vec<(Expression, Option<String>)>>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let item = attempt(wildcard())
+        .map(|w| (Expression::new_fldname(w), None))
+        .or(expression().and(optional(alias())));
+    let fld1 = item.map(|f| vec![f]);
+    let sep = delim_comma().map(|_| {
+        |mut x: Vec<(Expression, Option<String>)>, mut y: Vec<(Expression, Option<String>)>| {
+            x.append(&mut y);
+            x
+        }
+    });
+
+    chainl1(fld1, sep)
+}
+
+// each table is joined to the preceding tables, by a comma or a join clause.
+fn table_list<Input>(
+) -> impl Parser<Input, Output = Vec<((String, Option<String>), (JoinKind, Predicate))>>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let table_ref = || id_tok().and(optional(alias()));
+    let first = table_ref().map(|t| (t, (JoinKind::Cross, Predicate::new_empty())));
+    let cross = delim_comma()
+        .with(table_ref())
+        .map(|t| (t, (JoinKind::Cross, Predicate::new_empty())));
+    let join = (join_kind(), table_ref(), kw_on().with(predicate()))
+        .map(|(kind, t, pred)| (t, (kind, pred)));
+
+    first
+        .and(many(cross.or(join)))
+        .map(|(t, mut ts): (_, Vec<_>)| {
+            ts.insert(0, t);
+            ts
+        })
+}
+
+fn join_kind<Input>() -> impl Parser<Input, Output = JoinKind>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let inner = optional(attempt(kw_inner()))
+        .with(kw_join())
+        .map(|_| JoinKind::Inner);
+    let left = attempt(kw_left())
+        .skip(optional(attempt(kw_outer())))
+        .skip(kw_join())
+        .map(|_| JoinKind::Left);
+    let right = attempt(kw_right())
+        .skip(optional(attempt(kw_outer())))
+        .skip(kw_join())
+        .map(|_| JoinKind::Right);
+
+    left.or(right).or(inner)
+}
+
+/// Methods for parsing the various update commands
+
+pub fn update_cmd<Input>() -> impl Parser<Input, Output = SQL>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    attempt(insert().map(|i| SQL::DML(DML::Insert(i))))
+        .or(attempt(delete().map(|d| SQL::DML(DML::Delete(d)))))
+        .or(attempt(modify().map(|m| SQL::DML(DML::Modify(m)))))
+        .or(ddl().map(|ddl| SQL::DDL(ddl)))
+}
+
+fn ddl<Input>() -> impl Parser<Input, Output = DDL>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    attempt(create_table().map(|t| DDL::Table(t)))
+        .or(attempt(create_view().map(|v| DDL::View(v))))
+        .or(attempt(create_index().map(|i| DDL::Index(i))))
+        .or(attempt(drop_table().map(|d| DDL::DropTable(d))))
+        .or(attempt(drop_view().map(|d| DDL::DropView(d))))
+        .or(attempt(drop_index().map(|d| DDL::DropIndex(d))))
+        .or(alter_table().map(|a| DDL::AlterTable(a)))
+}
+
+/// Method for parsing delete commands
+
+pub fn delete<Input>() -> impl Parser<Input, Output = DeleteData>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let prelude = kw_delete().and(kw_from());
+    let where_clause = kw_where().with(predicate());
+
+    prelude
+        .with(id_tok())
+        .and(optional(where_clause))
+        .skip(terminate())
+        .map(|(tblname, opred)| {
+            let pred = opred.unwrap_or(Predicate::new_empty());
+            DeleteData::new(tblname, pred)
+        })
+}
+
+/// Methods for parsing insert commands
+
+pub fn insert<Input>() -> impl Parser<Input, Output = InsertData>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let prelude = kw_insert().and(kw_into());
+    let fields = between(delim_parenl(), delim_parenr(), field_list());
+    let row = between(delim_parenl(), delim_parenr(), const_list());
+    let rows = kw_values().with(sep_by1(row, delim_comma()));
+    // the records of a query are inserted, instead of the values
+    let source = rows
+        .map(|rows| (rows, None))
+        .or(set_query().map(|query| (vec![], Some(query))));
+
+    prelude
+        .with(id_tok())
+        .and(fields)
+        .and(source)
+        .skip(terminate())
+        .map(|((t, fs), (rows, query))| match query {
+            Some(query) => InsertData::new_query(t, fs, query),
+            None => InsertData::new_rows(t, fs, rows),
+        })
+}
+
+fn field_list<Input>() -> impl Parser<Input, Output = Vec<String>>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    sep_by1(field(), delim_comma())
+}
+
+fn const_list<Input>() -> impl Parser<Input, Output = Vec<Constant>>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    sep_by1(constant(), delim_comma())
+}
+
+/// Method for parsing modify commands
+
+pub fn modify<Input>() -> impl Parser<Input, Output = ModifyData>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let assignment = field().skip(binop_eq()).and(expression());
+    let sets = kw_set().with(sep_by1(assignment, delim_comma()));
+    let where_clause = kw_where().with(predicate());
+
+    kw_update()
+        .with(id_tok())
+        .and(sets)
+        .and(optional(where_clause))
+        .skip(terminate())
+        .map(|((t, sets), op)| {
+            let pred = op.unwrap_or(Predicate::new_empty());
+            ModifyData::new_with(t, sets, pred)
+        })
+}
+
+/// Method for parsing create table commands
+
+pub fn create_table<Input>() -> impl Parser<Input, Output = CreateTableData>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let prelude = kw_create().and(kw_table());
+    let table_elements = between(
+        delim_parenl(),
+        delim_parenr(),
+        sep_by(table_element(), delim_comma()),
+    );
+
+    prelude
+        .with(id_tok())
+        .and(table_elements)
+        .skip(terminate())
+        .map(|(tblname, elems): (String, Vec<TableElement>)| {
+            let mut sch = Schema::new();
+            let mut keys = vec![];
+            let mut constraints = vec![];
+            let mut foreign_keys = vec![];
+            for elem in elems.into_iter() {
+                match elem {
+                    TableElement::Column(fldname, fi, conss) => {
+                        sch.add_field(&fldname, fi.fld_type, fi.length);
+                        let mut not_null = false;
+                        let mut default = None;
+                        let mut check: Option<Predicate> = None;
+                        for cons in conss.into_iter() {
+                            match cons {
+                                ColumnConstraint::Key(true) => {
+                                    keys.push(KeyDef::PrimaryKey(fldname.clone()))
+                                }
+                                ColumnConstraint::Key(false) => {
+                                    keys.push(KeyDef::Unique(fldname.clone()))
+                                }
+                                ColumnConstraint::References(reftblname, reffldname, action) => {
+                                    foreign_keys.push(ForeignKeyDef::new(
+                                        fldname.clone(),
+                                        reftblname,
+                                        reffldname,
+                                        action,
+                                    ))
+                                }
+                                ColumnConstraint::NotNull => not_null = true,
+                                ColumnConstraint::Default(expr) => default = Some(expr),
+                                // the checks of a field must be all satisfied
+                                ColumnConstraint::Check(mut pred) => match check.as_mut() {
+                                    Some(p) => p.conjoin_with(&mut pred),
+                                    None => check = Some(pred),
+                                },
+                            }
+                        }
+                        let fc = FieldConstraint::new(fldname, not_null, default, check);
+                        if !fc.is_empty() {
+                            constraints.push(fc);
+                        }
+                    }
+                    TableElement::Key(key) => keys.push(key),
+                    TableElement::ForeignKey(fk) => foreign_keys.push(fk),
+                }
+            }
+            CreateTableData::new_with(tblname, sch, keys, constraints, foreign_keys)
+        })
+}
+
+// a field definition with its constraints, or a key on a field of the table
+enum TableElement {
+    Column(String, FieldInfo, Vec<ColumnConstraint>),
+    Key(KeyDef),
+    ForeignKey(ForeignKeyDef),
+}
+
+enum ColumnConstraint {
+    Key(bool),
+    References(String, String, RefAction),
+    NotNull,
+    Default(Expression),
+    Check(Predicate),
+}
+
+fn table_element<Input>() -> impl Parser<Input, Output = TableElement>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let table_key = key_kind()
+        .and(between(delim_parenl(), delim_parenr(), field()))
+        .map(|(primary, fldname)| match primary {
+            true => TableElement::Key(KeyDef::PrimaryKey(fldname)),
+            false => TableElement::Key(KeyDef::Unique(fldname)),
+        });
+    let foreign_key = kw_foreign()
+        .and(kw_key())
+        .with(between(delim_parenl(), delim_parenr(), field()))
+        .and(references())
+        .map(|(fldname, (reftblname, reffldname, action))| {
+            TableElement::ForeignKey(ForeignKeyDef::new(fldname, reftblname, reffldname, action))
+        });
+    let column_def = field_def()
+        .and(many(column_constraint()))
+        .map(|((fldname, fi), conss)| TableElement::Column(fldname, fi, conss));
+
+    attempt(table_key).or(attempt(foreign_key)).or(column_def)
+}
+
+fn column_constraint<Input>() -> impl Parser<Input, Output = ColumnConstraint>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let key = attempt(key_kind()).map(|primary| ColumnConstraint::Key(primary));
+    let references = references().map(|(reftblname, reffldname, action)| {
+        ColumnConstraint::References(reftblname, reffldname, action)
+    });
+    let not_null = attempt(kw_not().and(kw_null())).map(|_| ColumnConstraint::NotNull);
+    let default = kw_default()
+        .with(default_value())
+        .map(|expr| ColumnConstraint::Default(expr));
+    let check = kw_check()
+        .with(between(delim_parenl(), delim_parenr(), predicate()))
+        .map(|pred| ColumnConstraint::Check(pred));
+
+    key.or(references).or(not_null).or(default).or(check)
+}
+
+// REFERENCES tbl (fld) [ON DELETE RESTRICT | CASCADE | SET NULL]
+// the referenced record can't be deleted by default.
+fn references<Input>() -> impl Parser<Input, Output = (String, String, RefAction)>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let action = attempt(kw_restrict().map(|_| RefAction::Restrict))
+        .or(attempt(kw_cascade().map(|_| RefAction::Cascade)))
+        .or(kw_set().and(kw_null()).map(|_| RefAction::SetNull));
+    let on_delete = optional(kw_on().and(kw_delete()).with(action));
+
+    kw_references()
+        .with(id_tok())
+        .and(between(delim_parenl(), delim_parenr(), field()))
+        .and(on_delete)
+        .map(|((reftblname, reffldname), action)| {
+            (
+                reftblname,
+                reffldname,
+                action.unwrap_or(RefAction::Restrict),
+            )
+        })
+}
+
+// true for PRIMARY KEY, false for UNIQUE
+fn key_kind<Input>() -> impl Parser<Input, Output = bool>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    attempt(kw_primary().and(kw_key()).map(|_| true)).or(kw_unique().map(|_| false))
+}
+
+// my own extends
+// the default value of a field, which is kept in the catalog as text too.
+pub fn default_value<Input>() -> impl Parser<Input, Output = Expression>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    attempt(kw_current_date())
+        .map(|_| Expression::new_function(ScalarFn::CurrentDate, vec![]))
+        .or(constant().map(|c| Expression::Val(c)))
+}
+
+// my own extends
+// the check of a field, which is kept in the catalog as text.
+pub fn check_predicate<Input>() -> impl Parser<Input, Output = Predicate>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    predicate()
+}
+
+fn field_def<Input>() -> impl Parser<Input, Output = (String, FieldInfo)>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    id_tok().and(type_def())
+}
+
+fn type_def<Input>() -> impl Parser<Input, Output = FieldInfo>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let int16_def = kw_int16().map(|_| FieldInfo::new(FieldType::SMALLINT, 0));
+    let int32_def = kw_int32().map(|_| FieldInfo::new(FieldType::INTEGER, 0));
+    let varchar_def = kw_varchar()
+        .with(between(delim_parenl(), delim_parenr(), i32_tok()))
+        .map(|n| FieldInfo::new(FieldType::VARCHAR, n as usize));
+    let bool_def = kw_bool().map(|_| FieldInfo::new(FieldType::BOOL, 0));
+    let date_def = kw_date().map(|_| FieldInfo::new(FieldType::DATE, 0));
+
+    attempt(int32_def)
+        .or(attempt(int16_def))
+        .or(attempt(varchar_def))
+        .or(attempt(bool_def))
+        .or(date_def)
+}
+
+/// Method for parsing create view commands
+
+pub fn create_view<Input>() -> impl Parser<Input, Output = CreateViewData>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let prelude = kw_create().and(kw_view());
+
+    prelude
+        .with(id_tok())
+        .and(kw_as().with(query()))
+        .map(|(v, vq)| CreateViewData::new(v, vq))
+}
+
+/// Method for parsing create index commands
+
+pub fn create_index<Input>() -> impl Parser<Input, Output = CreateIndexData>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let prelude = kw_create().and(kw_index());
+
+    prelude
+        .with(id_tok())
+        .and(kw_on().with(id_tok()))
+        .and(between(delim_parenl(), delim_parenr(), field()))
+        .skip(terminate())
+        .map(|((idxname, tblname), fldname)| CreateIndexData::new(idxname, tblname, fldname))
+}
+
+/// Methods for parsing drop commands
+
+pub fn drop_table<Input>() -> impl Parser<Input, Output = DropData>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    kw_drop().and(kw_table()).with(drop_target())
+}
+
+pub fn drop_view<Input>() -> impl Parser<Input, Output = DropData>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    kw_drop().and(kw_view()).with(drop_target())
+}
+
+pub fn drop_index<Input>() -> impl Parser<Input, Output = DropData>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    kw_drop().and(kw_index()).with(drop_target())
+}
+
+fn drop_target<Input>() -> impl Parser<Input, Output = DropData>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let if_exists = attempt(kw_if().and(kw_exists()));
+
+    optional(if_exists)
+        .and(id_tok())
+        .skip(terminate())
+        .map(|(ife, name)| DropData::new(name, ife.is_some()))
+}
+
+/// Method for parsing alter table commands
+
+pub fn alter_table<Input>() -> impl Parser<Input, Output = AlterTableData>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let prelude = kw_alter().and(kw_table());
+    let add_column = kw_add()
+        .with(optional(kw_column()))
+        .with(field_def())
+        .map(|(fldname, fi)| AlterAction::AddColumn(fldname, fi));
+    let drop_column = kw_drop()
+        .with(optional(kw_column()))
+        .with(id_tok())
+        .map(|fldname| AlterAction::DropColumn(fldname));
+    let rename_table = kw_rename()
+        .and(kw_to())
+        .with(id_tok())
+        .map(|newname| AlterAction::RenameTable(newname));
+    let rename_column = kw_rename()
+        .with(optional(kw_column()))
+        .with(id_tok())
+        .and(kw_to().with(id_tok()))
+        .map(|(fldname, newname)| AlterAction::RenameColumn(fldname, newname));
+    let action = attempt(add_column)
+        .or(attempt(drop_column))
+        .or(attempt(rename_table))
+        .or(rename_column);
+
+    prelude
+        .with(id_tok())
+        .and(action)
+        .skip(terminate())
+        .map(|(tblname, action)| AlterTableData::new(tblname, action))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
     use combine::error::StringStreamError;
 
@@ -686,6 +1998,14 @@ mod tests {
         assert_eq!(parser.parse("42"), Ok((42, "")));
         assert_eq!(parser.parse("42 "), Ok((42, "")));
         assert_eq!(parser.parse("-42 "), Ok((-42, "")));
+        // the bounds of i32 are in range, but the numbers beyond are not
+        let min = i32::MIN.to_string();
+        assert_eq!(i32_tok().parse(min.as_str()), Ok((i32::MIN, "")));
+        let max = format!("+{}", i32::MAX);
+        assert_eq!(i32_tok().parse(max.as_str()), Ok((i32::MAX, "")));
+        assert!(parser.parse("2147483648").is_err());
+        assert!(parser.parse("-2147483649").is_err());
+        assert!(parser.parse("99999999999999999999").is_err());
     }
 
     #[test]
@@ -733,6 +2053,8 @@ mod tests {
         );
         assert_eq!(parser.parse("true"), Ok((Constant::Bool(true), "")));
         assert_eq!(parser.parse("false"), Ok((Constant::Bool(false), "")));
+        assert_eq!(parser.parse("NULL"), Ok((Constant::Null, "")));
+        assert!(parser.parse("nulls").is_err());
     }
 
     #[test]
@@ -769,6 +2091,175 @@ mod tests {
             parser.parse("fldname"),
             Ok((Expression::Fldname("fldname".to_string()), ""))
         );
+        let price = Expression::new_fldname("price".to_string());
+        let qty = Expression::new_fldname("qty".to_string());
+        let one = Expression::new_val(Constant::I32(1));
+        let expected = Expression::new_arith(
+            price.clone(),
+            ArithOp::Add,
+            Expression::new_arith(qty.clone(), ArithOp::Mul, one.clone()),
+        );
+        assert_eq!(parser.parse("price + qty * 1"), Ok((expected, "")));
+        let expected = Expression::new_arith(
+            Expression::new_arith(price.clone(), ArithOp::Sub, one.clone()),
+            ArithOp::Sub,
+            qty.clone(),
+        );
+        assert_eq!(parser.parse("price-1-qty"), Ok((expected, "")));
+        let expected = Expression::new_arith(
+            Expression::new_arith(price.clone(), ArithOp::Add, one.clone()),
+            ArithOp::Div,
+            Expression::new_neg(qty.clone()),
+        );
+        assert_eq!(
+            parser.parse("(price + 1) / -qty"),
+            Ok((expected.clone(), ""))
+        );
+        let displayed = expected.to_string();
+        assert_eq!(parser.parse(displayed.as_str()), Ok((expected, "")));
+        let expected = Expression::new_arith(
+            price.clone(),
+            ArithOp::Mul,
+            Expression::new_val(Constant::I32(-1)),
+        );
+        assert_eq!(parser.parse("price * -1 = 0"), Ok((expected, "= 0")));
+    }
+
+    #[test]
+    fn case_test() {
+        let mut parser = expression();
+        let grade = Expression::new_fldname("grade".to_string());
+        let qty = Expression::new_fldname("qty".to_string());
+        let str_val = |s: &str| Expression::new_val(Constant::String(s.to_string()));
+        let expected = Expression::new_case(
+            vec![(
+                Predicate::new(Term::new_with(
+                    grade.clone(),
+                    CompOp::Ge,
+                    Expression::new_val(Constant::I32(60)),
+                )),
+                str_val("pass"),
+            )],
+            Some(str_val("fail")),
+        );
+        assert_eq!(
+            parser.parse("CASE WHEN grade >= 60 THEN 'pass' ELSE 'fail' END"),
+            Ok((expected.clone(), ""))
+        );
+        let displayed = expected.to_string();
+        assert_eq!(parser.parse(displayed.as_str()), Ok((expected, "")));
+        let expected = Expression::new_simple_case(
+            grade.clone(),
+            vec![
+                (str_val("A"), Expression::new_val(Constant::I32(4))),
+                (str_val("B"), Expression::new_val(Constant::I32(3))),
+            ],
+            None,
+        );
+        assert_eq!(
+            parser.parse("case grade when 'A' then 4 when 'B' then 3 end"),
+            Ok((expected, ""))
+        );
+        let expected = Expression::new_arith(
+            Expression::new_coalesce(vec![qty.clone(), Expression::new_val(Constant::I32(0))]),
+            ArithOp::Add,
+            Expression::new_val(Constant::I32(1)),
+        );
+        assert_eq!(
+            parser.parse("COALESCE(qty, 0) + 1"),
+            Ok((expected.clone(), ""))
+        );
+        let displayed = expected.to_string();
+        assert_eq!(parser.parse(displayed.as_str()), Ok((expected, "")));
+        assert_eq!(
+            parser.parse("case_id"),
+            Ok((Expression::Fldname("case_id".to_string()), ""))
+        );
+    }
+
+    #[test]
+    fn function_test() {
+        let mut parser = expression();
+        let name = Expression::new_fldname("name".to_string());
+        let born = Expression::new_fldname("born".to_string());
+        let str_val = |s: &str| Expression::new_val(Constant::String(s.to_string()));
+        assert_eq!(
+            parser.parse("UPPER(name)"),
+            Ok((
+                Expression::new_function(ScalarFn::Upper, vec![name.clone()]),
+                ""
+            ))
+        );
+        let expected = Expression::new_function(
+            ScalarFn::Substr,
+            vec![
+                name.clone(),
+                Expression::new_val(Constant::I32(1)),
+                Expression::new_function(ScalarFn::Length, vec![name.clone()]),
+            ],
+        );
+        assert_eq!(
+            parser.parse("substr(name, 1, length(name))"),
+            Ok((expected.clone(), ""))
+        );
+        let displayed = expected.to_string();
+        assert_eq!(parser.parse(displayed.as_str()), Ok((expected, "")));
+        let expected = Expression::new_function(
+            ScalarFn::Concat,
+            vec![name.clone(), str_val(", "), str_val("jr.")],
+        );
+        assert_eq!(
+            parser.parse("name || ', ' || 'jr.'"),
+            Ok((expected.clone(), ""))
+        );
+        let displayed = expected.to_string();
+        assert_eq!(displayed, "concat(name, ', ', 'jr.')");
+        assert_eq!(parser.parse(displayed.as_str()), Ok((expected, "")));
+        let expected = Expression::new_function(
+            ScalarFn::DateAdd,
+            vec![
+                Expression::new_function(ScalarFn::CurrentDate, vec![]),
+                Expression::new_neg(born.clone()),
+            ],
+        );
+        assert_eq!(
+            parser.parse("DATE_ADD(CURRENT_DATE, -born)"),
+            Ok((expected.clone(), ""))
+        );
+        let displayed = expected.to_string();
+        assert_eq!(displayed, "date_add(current_date, -born)");
+        assert_eq!(parser.parse(displayed.as_str()), Ok((expected, "")));
+        // an unknown function or a wrong number of arguments isn't a call
+        assert_eq!(
+            parser.parse("reverse(name)"),
+            Ok((Expression::new_fldname("reverse".to_string()), "(name)"))
+        );
+        assert_eq!(
+            parser.parse("year(born, 1)"),
+            Ok((Expression::new_fldname("year".to_string()), "(born, 1)"))
+        );
+
+        let mut parser = query();
+        let sql = "SELECT upper(SName) AS Upper, SName || '!' FROM STUDENT \
+                   WHERE year(Born) = 2020 AND lower(SName) LIKE 'jo%';";
+        let (data, rest) = parser.parse(sql).unwrap();
+        assert_eq!(rest, "");
+        let displayed = format!("{};", data);
+        assert_eq!(query().parse(displayed.as_str()), Ok((data, "")));
+    }
+
+    #[test]
+    fn binop_comp_test() {
+        let mut parser = binop_comp();
+        assert_eq!(parser.parse(""), Err(StringStreamError::UnexpectedParse));
+        assert_eq!(parser.parse("="), Ok((CompOp::Eq, "")));
+        assert_eq!(parser.parse("<>"), Ok((CompOp::Ne, "")));
+        assert_eq!(parser.parse("!="), Ok((CompOp::Ne, "")));
+        assert_eq!(parser.parse("<"), Ok((CompOp::Lt, "")));
+        assert_eq!(parser.parse("<="), Ok((CompOp::Le, "")));
+        assert_eq!(parser.parse(">"), Ok((CompOp::Gt, "")));
+        assert_eq!(parser.parse(">=  42"), Ok((CompOp::Ge, "42")));
+        assert_eq!(parser.parse("< = 42"), Ok((CompOp::Lt, "= 42")));
     }
 
     #[test]
@@ -975,6 +2466,150 @@ mod tests {
                 ""
             ))
         );
+        assert_eq!(
+            parser.parse("age >= 20"),
+            Ok((
+                Term::new_with(
+                    Expression::Fldname("age".to_string()),
+                    CompOp::Ge,
+                    Expression::Val(Constant::I32(20))
+                ),
+                ""
+            ))
+        );
+        assert_eq!(
+            parser.parse("sname<>'joe'"),
+            Ok((
+                Term::new_with(
+                    Expression::Fldname("sname".to_string()),
+                    CompOp::Ne,
+                    Expression::Val(Constant::String("joe".to_string()))
+                ),
+                ""
+            ))
+        );
+    }
+
+    #[test]
+    fn like_test() {
+        let mut parser = term();
+        let like = |op: CompOp, pattern: &str, escape: Option<char>| {
+            Term::new_with_escape(
+                Expression::Fldname("sname".to_string()),
+                op,
+                Expression::Val(Constant::String(pattern.to_string())),
+                escape,
+            )
+        };
+        let expected = like(CompOp::Like, "j%", None);
+        assert_eq!(parser.parse("sname LIKE 'j%'"), Ok((expected.clone(), "")));
+        assert_eq!(expected.to_string(), "sname like 'j%'");
+        let expected = like(CompOp::NotLike, "_o%", None);
+        assert_eq!(
+            parser.parse("sname not like '_o%' and"),
+            Ok((expected.clone(), "and"))
+        );
+        let displayed = expected.to_string();
+        assert_eq!(term().parse(displayed.as_str()), Ok((expected, "")));
+        let expected = like(CompOp::Like, "50!%", Some('!'));
+        assert_eq!(
+            parser.parse("sname like '50!%' escape '!'"),
+            Ok((expected.clone(), ""))
+        );
+        let displayed = expected.to_string();
+        assert_eq!(displayed, "sname like '50!%' escape '!'");
+        assert_eq!(term().parse(displayed.as_str()), Ok((expected, "")));
+        assert!(parser.parse("sname like 'j%' escape '!!'").is_err());
+        // a field whose name starts with like
+        assert_eq!(
+            parser.parse("likes = 1"),
+            Ok((
+                Term::new(
+                    Expression::Fldname("likes".to_string()),
+                    Expression::Val(Constant::I32(1))
+                ),
+                ""
+            ))
+        );
+    }
+
+    #[test]
+    fn is_null_test() {
+        let mut parser = term();
+        let is_null = |op: CompOp| {
+            Term::new_with(
+                Expression::Fldname("sname".to_string()),
+                op,
+                Expression::Val(Constant::Null),
+            )
+        };
+        let expected = is_null(CompOp::Is);
+        assert_eq!(parser.parse("sname IS NULL"), Ok((expected.clone(), "")));
+        assert_eq!(expected.to_string(), "sname is null");
+        let expected = is_null(CompOp::IsNot);
+        assert_eq!(
+            parser.parse("sname is not null and"),
+            Ok((expected.clone(), "and"))
+        );
+        let displayed = expected.to_string();
+        assert_eq!(term().parse(displayed.as_str()), Ok((expected, "")));
+        assert!(parser.parse("sname is 1").is_err());
+        // a comparison with null is a term too, though it's never true
+        assert_eq!(
+            parser.parse("sname = null"),
+            Ok((
+                Term::new(
+                    Expression::Fldname("sname".to_string()),
+                    Expression::Val(Constant::Null)
+                ),
+                ""
+            ))
+        );
+    }
+
+    #[test]
+    fn in_between_test() {
+        let mut parser = term();
+        let fld = || Expression::Fldname("age".to_string());
+        let val = |n: i32| Expression::Val(Constant::I32(n));
+        let expected = Term::new_in(fld(), CompOp::In, vec![val(1), val(2), val(3)]);
+        assert_eq!(parser.parse("age IN (1, 2,3)"), Ok((expected.clone(), "")));
+        assert_eq!(expected.to_string(), "age in (1, 2, 3)");
+        let expected = Term::new_in(fld(), CompOp::NotIn, vec![val(1)]);
+        assert_eq!(
+            parser.parse("age not in (1) and"),
+            Ok((expected.clone(), "and"))
+        );
+        let displayed = expected.to_string();
+        assert_eq!(term().parse(displayed.as_str()), Ok((expected, "")));
+        assert!(parser.parse("age in ()").is_err());
+
+        let expected = Term::new_between(fld(), CompOp::Between, val(18), val(20));
+        assert_eq!(
+            parser.parse("age BETWEEN 18 AND 20 and"),
+            Ok((expected.clone(), "and"))
+        );
+        assert_eq!(expected.to_string(), "age between 18 and 20");
+        let expected = Term::new_between(
+            fld(),
+            CompOp::NotBetween,
+            val(18),
+            Expression::new_arith(val(18), ArithOp::Add, val(2)),
+        );
+        assert_eq!(
+            parser.parse("age not between 18 and 18 + 2"),
+            Ok((expected.clone(), ""))
+        );
+        let displayed = expected.to_string();
+        assert_eq!(term().parse(displayed.as_str()), Ok((expected, "")));
+        assert!(parser.parse("age between 18").is_err());
+
+        // the AND of BETWEEN doesn't separate the factors
+        let (pred, rest) = predicate()
+            .parse("age between 18 and 20 and sname in ('joe', 'amy')")
+            .unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(pred.factors().len(), 2);
     }
 
     #[test]
@@ -1072,6 +2707,43 @@ mod tests {
                 ""
             ))
         );
+        let eq = |fld: &str, val: &str| {
+            Predicate::new(Term::new(
+                Expression::Fldname(fld.to_string()),
+                Expression::Val(Constant::String(val.to_string())),
+            ))
+        };
+        // AND binds tighter than OR
+        let mut expected = eq("status", "A");
+        expected.conjoin_with(&mut eq("kind", "x"));
+        let expected = Predicate::new_or(vec![expected, eq("status", "B")]);
+        assert_eq!(
+            parser.parse("status = 'A' and kind = 'x' or status = 'B'"),
+            Ok((expected, ""))
+        );
+        let mut expected = eq("kind", "x");
+        expected.conjoin_with(&mut Predicate::new_or(vec![
+            eq("status", "A"),
+            eq("status", "B"),
+        ]));
+        assert_eq!(
+            parser.parse("kind = 'x' and (status = 'A' or status = 'B')"),
+            Ok((expected.clone(), ""))
+        );
+        // round trip via Display
+        let displayed = expected.to_string();
+        assert_eq!(parser.parse(displayed.as_str()), Ok((expected, "")));
+        let mut expected = Predicate::new_not(eq("status", "A"));
+        expected.conjoin_with(&mut Predicate::new_not(Predicate::new_or(vec![
+            eq("notes", "n"),
+            eq("orders", "o"),
+        ])));
+        assert_eq!(
+            parser.parse("NOT status = 'A' and not (notes = 'n' or orders = 'o')"),
+            Ok((expected.clone(), ""))
+        );
+        let displayed = expected.to_string();
+        assert_eq!(parser.parse(displayed.as_str()), Ok((expected, "")));
     }
 
     #[test]
@@ -1101,57 +2773,359 @@ mod tests {
             },
         );
         assert_eq!(
-            parser.parse("SELECT name FROM student WHERE age = 20;"),
+            parser.parse("SELECT name FROM student WHERE age = 20;"),
+            Ok((
+                QueryData::new(
+                    vec!["name".to_string()],
+                    vec!["student".to_string()],
+                    expected.clone(),
+                ),
+                ""
+            ))
+        );
+        let terms = vec![
+            Term::new(
+                Expression::Fldname("age".to_string()),
+                Expression::Val(Constant::I32(18)),
+            ),
+            Term::new(
+                Expression::Fldname("name".to_string()),
+                Expression::Val(Constant::String("joe".to_string())),
+            ),
+            Term::new(
+                Expression::Fldname("sex".to_string()),
+                Expression::Val(Constant::String("male".to_string())),
+            ),
+            Term::new(
+                Expression::Fldname("dev_id".to_string()),
+                Expression::Fldname("major_id".to_string()),
+            ),
+        ];
+        let expected = terms.iter().map(|t| Predicate::new(t.clone())).fold(
+            Predicate::new_empty(),
+            |mut p1, mut p2| {
+                p1.conjoin_with(&mut p2);
+                p1
+            },
+        );
+        assert_eq!(
+            parser.parse(
+                "SELECT name, age \
+                   FROM student, dept \
+                  WHERE age = 18 AND name = 'joe' \
+                    AND sex = 'male' AND dev_id = major_id \
+                 ;"
+            ),
+            Ok((
+                QueryData::new(
+                    vec!["name".to_string(), "age".to_string()],
+                    vec!["student".to_string(), "dept".to_string()],
+                    expected.clone(),
+                ),
+                ""
+            ))
+        );
+        let mut expected = QueryData::new(
+            vec!["name".to_string(), "age".to_string()],
+            vec!["student".to_string()],
+            Predicate::new_empty(),
+        );
+        expected.set_order_by(vec![
+            ("age".to_string(), SortOrder::Desc),
+            ("name".to_string(), SortOrder::Asc),
+        ]);
+        assert_eq!(
+            parser.parse("SELECT name, age FROM student ORDER BY age DESC, name;"),
+            Ok((expected.clone(), ""))
+        );
+        // round trip via Display, like a view definition
+        let displayed = format!("{};", expected);
+        assert_eq!(
+            displayed,
+            "select name, age from student order by age desc, name asc;"
+        );
+        assert_eq!(query().parse(displayed.as_str()), Ok((expected, "")));
+
+        let mut parser = query();
+        let mut expected = QueryData::new_with(
+            vec![
+                Expression::new_fldname("dept".to_string()),
+                Expression::new_aggregate(Aggregate::CountAll),
+                Expression::new_arith(
+                    Expression::new_aggregate(Aggregate::Max("age".to_string())),
+                    ArithOp::Add,
+                    Expression::new_val(Constant::I32(1)),
+                ),
+            ],
+            vec!["student".to_string()],
+            Predicate::new_empty(),
+        );
+        expected.set_group_by(vec!["dept".to_string()]);
+        expected.set_having(Predicate::new(Term::new_with(
+            Expression::new_aggregate(Aggregate::Avg("age".to_string())),
+            CompOp::Gt,
+            Expression::new_val(Constant::I32(20)),
+        )));
+        assert_eq!(
+            parser.parse(
+                "SELECT dept, COUNT(*), MAX(age) + 1 FROM student GROUP BY dept HAVING AVG(age) > 20;"
+            ),
+            Ok((expected.clone(), ""))
+        );
+        let displayed = format!("{};", expected);
+        assert_eq!(
+            displayed,
+            "select dept, count(*), max(age)+1 from student group by dept having avg(age)>20;"
+        );
+        assert_eq!(query().parse(displayed.as_str()), Ok((expected, "")));
+        let mut parser = query();
+        let mut expected = QueryData::new(
+            vec!["dept".to_string()],
+            vec!["student".to_string()],
+            Predicate::new_empty(),
+        );
+        expected.set_distinct(true);
+        assert_eq!(
+            parser.parse("SELECT DISTINCT dept FROM student;"),
+            Ok((expected.clone(), ""))
+        );
+        let displayed = format!("{};", expected);
+        assert_eq!(displayed, "select distinct dept from student;");
+        assert_eq!(query().parse(displayed.as_str()), Ok((expected, "")));
+        let mut parser = query();
+        let mut expected = QueryData::new(
+            vec!["name".to_string()],
+            vec!["student".to_string()],
+            Predicate::new_empty(),
+        );
+        expected.set_order_by(vec![("name".to_string(), SortOrder::Asc)]);
+        expected.set_limit(10, 20);
+        assert_eq!(
+            parser.parse("SELECT name FROM student ORDER BY name LIMIT 10 OFFSET 20;"),
+            Ok((expected.clone(), ""))
+        );
+        let displayed = format!("{};", expected);
+        assert_eq!(
+            displayed,
+            "select name from student order by name asc limit 10 offset 20;"
+        );
+        assert_eq!(query().parse(displayed.as_str()), Ok((expected, "")));
+        // a number too large is rejected, not regarded as unlimited
+        assert!(query()
+            .parse("SELECT name FROM student LIMIT 99999999999999999999;")
+            .is_err());
+        let sql = format!("SELECT name FROM student LIMIT {} OFFSET 1;", usize::MAX);
+        assert!(query().parse(sql.as_str()).is_err());
+        // a field can still be named like an aggregate function
+        let mut parser = query();
+        let expected = QueryData::new(
+            vec!["count".to_string()],
+            vec!["t".to_string()],
+            Predicate::new_empty(),
+        );
+        assert_eq!(parser.parse("SELECT count FROM t;"), Ok((expected, "")));
+        // aliases and qualified field names
+        let mut parser = query();
+        let mut expected = QueryData::new_with(
+            vec![
+                Expression::new_fldname("s.name".to_string()),
+                Expression::new_fldname("d.name".to_string()),
+            ],
+            vec!["student".to_string(), "dept".to_string()],
+            Predicate::new(Term::new_with(
+                Expression::new_fldname("s.dept_id".to_string()),
+                CompOp::Eq,
+                Expression::new_fldname("d.id".to_string()),
+            )),
+        );
+        expected.set_aliases(vec![Some("student".to_string()), Some("dept".to_string())]);
+        expected.set_table_aliases(vec![Some("s".to_string()), Some("d".to_string())]);
+        assert_eq!(
+            parser.parse(
+                "SELECT s.name AS student, d.name dept FROM student s, dept AS d WHERE s.dept_id = d.id;"
+            ),
+            Ok((expected.clone(), ""))
+        );
+        assert_eq!(expected.fields(), vec!["student", "dept"]);
+        assert_eq!(expected.qualifiers(), vec!["s", "d"]);
+        let displayed = format!("{};", expected);
+        assert_eq!(
+            displayed,
+            "select s.name as student, d.name as dept from student s, dept d where s.dept_id=d.id;"
+        );
+        assert_eq!(query().parse(displayed.as_str()), Ok((expected, "")));
+        // a keyword is not taken as an alias
+        let mut parser = query();
+        let mut expected = QueryData::new(
+            vec!["name".to_string()],
+            vec!["student".to_string()],
+            Predicate::new_empty(),
+        );
+        expected.set_limit(1, 0);
+        assert_eq!(
+            parser.parse("SELECT name FROM student LIMIT 1;"),
+            Ok((expected, ""))
+        );
+        // explicit joins
+        let mut parser = query();
+        let on = |lhs: &str, rhs: &str| {
+            Predicate::new(Term::new(
+                Expression::new_fldname(lhs.to_string()),
+                Expression::new_fldname(rhs.to_string()),
+            ))
+        };
+        let mut expected = QueryData::new(
+            vec!["name".to_string()],
+            vec![
+                "student".to_string(),
+                "dept".to_string(),
+                "enroll".to_string(),
+                "section".to_string(),
+            ],
+            Predicate::new_empty(),
+        );
+        expected.set_table_aliases(vec![Some("s".to_string()), None, None, None]);
+        expected.set_joins(vec![
+            (JoinKind::Cross, Predicate::new_empty()),
+            (JoinKind::Inner, on("s.major_id", "did")),
+            (JoinKind::Left, on("s.id", "student_id")),
+            (JoinKind::Right, on("section_id", "sect_id")),
+        ]);
+        assert_eq!(
+            parser.parse(
+                "SELECT name FROM student s JOIN dept ON s.major_id = did \
+                 LEFT OUTER JOIN enroll ON s.id = student_id \
+                 RIGHT JOIN section ON section_id = sect_id;"
+            ),
+            Ok((expected.clone(), ""))
+        );
+        assert!(expected.has_outer_join());
+        let displayed = format!("{};", expected);
+        assert_eq!(
+            displayed,
+            "select name from student s join dept on s.major_id=did \
+             left join enroll on s.id=student_id right join section on section_id=sect_id;"
+        );
+        assert_eq!(query().parse(displayed.as_str()), Ok((expected, "")));
+        assert_eq!(
+            query().parse("SELECT name FROM student JOIN dept;"),
+            Err(StringStreamError::UnexpectedParse)
+        );
+        // subqueries
+        let sub = |fldname: &str, tblname: &str| {
+            QueryData::new(
+                vec![fldname.to_string()],
+                vec![tblname.to_string()],
+                Predicate::new_empty(),
+            )
+        };
+        let mut pred = Predicate::new_in(
+            Expression::new_fldname("id".to_string()),
+            Subquery::new(sub("student_id", "enroll")),
+        );
+        pred.conjoin_with(&mut Predicate::new_not(Predicate::new_exists(
+            Subquery::new_exists(sub("did", "dept")),
+        )));
+        let expected = QueryData::new_with(
+            vec![
+                Expression::new_fldname("name".to_string()),
+                Expression::new_subquery(Subquery::new(sub("grade", "enroll"))),
+            ],
+            vec!["student".to_string()],
+            pred,
+        );
+        assert_eq!(
+            query().parse(
+                "SELECT name, (SELECT grade FROM enroll) FROM student \
+                 WHERE id IN (SELECT student_id FROM enroll) \
+                 AND NOT EXISTS (SELECT did FROM dept);"
+            ),
+            Ok((expected.clone(), ""))
+        );
+        let displayed = format!("{};", expected);
+        assert_eq!(query().parse(displayed.as_str()), Ok((expected, "")));
+        assert_eq!(
+            query().parse("SELECT name FROM student WHERE id NOT IN (SELECT sid FROM enroll);"),
+            query().parse("SELECT name FROM student WHERE NOT id IN (SELECT sid FROM enroll);")
+        );
+        // wildcards
+        let mut expected = QueryData::new(
+            vec!["s.*".to_string(), "dname".to_string(), "*".to_string()],
+            vec!["student".to_string(), "dept".to_string()],
+            Predicate::new_empty(),
+        );
+        expected.set_table_aliases(vec![Some("s".to_string()), None]);
+        assert_eq!(
+            query().parse("SELECT s.*, dname, * FROM student s, dept;"),
+            Ok((expected.clone(), ""))
+        );
+        assert!(expected.has_wildcard());
+        expected.expand_wildcards(&[
+            vec!["id".to_string(), "name".to_string()],
+            vec!["id".to_string(), "dname".to_string()],
+        ]);
+        assert_eq!(
+            expected.to_string(),
+            "select s.id, name, dname, s.id, name, dept.id, dname from student s, dept"
+        );
+        assert!(!expected.has_wildcard());
+        assert_eq!(
+            query().parse("SELECT count(*) FROM student;"),
             Ok((
-                QueryData::new(
-                    vec!["name".to_string()],
+                QueryData::new_with(
+                    vec![Expression::new_aggregate(Aggregate::CountAll)],
                     vec!["student".to_string()],
-                    expected.clone(),
+                    Predicate::new_empty(),
                 ),
                 ""
             ))
         );
-        let terms = vec![
-            Term::new(
-                Expression::Fldname("age".to_string()),
-                Expression::Val(Constant::I32(18)),
-            ),
-            Term::new(
-                Expression::Fldname("name".to_string()),
-                Expression::Val(Constant::String("joe".to_string())),
-            ),
-            Term::new(
-                Expression::Fldname("sex".to_string()),
-                Expression::Val(Constant::String("male".to_string())),
-            ),
-            Term::new(
-                Expression::Fldname("dev_id".to_string()),
-                Expression::Fldname("major_id".to_string()),
+    }
+
+    #[test]
+    fn query_expr_test() {
+        let select = |tblname: &str| {
+            QueryExpr::Select(QueryData::new(
+                vec!["id".to_string()],
+                vec![tblname.to_string()],
+                Predicate::new_empty(),
+            ))
+        };
+        let mut parser = query_expr();
+        assert_eq!(parser.parse("SELECT id FROM a;"), Ok((select("a"), "")));
+        // left associative, and INTERSECT binds tighter
+        let expected = QueryExpr::new_set_op(
+            SetOp::Except,
+            QueryExpr::new_set_op(
+                SetOp::UnionAll,
+                select("a"),
+                QueryExpr::new_set_op(SetOp::Intersect, select("b"), select("c")),
             ),
-        ];
-        let expected = terms.iter().map(|t| Predicate::new(t.clone())).fold(
-            Predicate::new_empty(),
-            |mut p1, mut p2| {
-                p1.conjoin_with(&mut p2);
-                p1
-            },
+            select("d"),
         );
         assert_eq!(
             parser.parse(
-                "SELECT name, age \
-                   FROM student, dept \
-                  WHERE age = 18 AND name = 'joe' \
-                    AND sex = 'male' AND dev_id = major_id \
-                 ;"
+                "SELECT id FROM a UNION ALL SELECT id FROM b \
+                 INTERSECT SELECT id FROM c EXCEPT SELECT id FROM d;"
             ),
-            Ok((
-                QueryData::new(
-                    vec!["name".to_string(), "age".to_string()],
-                    vec!["student".to_string(), "dept".to_string()],
-                    expected.clone(),
-                ),
-                ""
-            ))
+            Ok((expected.clone(), ""))
+        );
+        let displayed = format!("{};", expected);
+        assert_eq!(
+            displayed,
+            "(select id from a union all (select id from b intersect select id from c)) \
+             except select id from d;"
+        );
+        assert_eq!(query_expr().parse(displayed.as_str()), Ok((expected, "")));
+        let expected = QueryExpr::new_set_op(
+            SetOp::Intersect,
+            QueryExpr::new_set_op(SetOp::Union, select("a"), select("b")),
+            select("c"),
+        );
+        assert_eq!(
+            query_expr()
+                .parse("(SELECT id FROM a UNION SELECT id FROM b) INTERSECT SELECT id FROM c;"),
+            Ok((expected, ""))
         );
     }
 
@@ -1199,11 +3173,77 @@ mod tests {
                 ""
             ))
         );
+        // multiple records
+        let mut parser = insert();
+        assert_eq!(
+            parser.parse("INSERT INTO STUDENT (name, age) VALUES ('Darci', 20), ('Ed', 21);"),
+            Ok((
+                InsertData::new_rows(
+                    "STUDENT".to_string(),
+                    vec!["name".to_string(), "age".to_string()],
+                    vec![
+                        vec![Constant::String("Darci".to_string()), Constant::I32(20)],
+                        vec![Constant::String("Ed".to_string()), Constant::I32(21)],
+                    ]
+                ),
+                ""
+            ))
+        );
+        // the records of a query
+        let mut parser = insert();
+        assert_eq!(
+            parser.parse("INSERT INTO STUDENT (name, age) SELECT name, age FROM person;"),
+            Ok((
+                InsertData::new_query(
+                    "STUDENT".to_string(),
+                    vec!["name".to_string(), "age".to_string()],
+                    QueryExpr::Select(QueryData::new(
+                        vec!["name".to_string(), "age".to_string()],
+                        vec!["person".to_string()],
+                        Predicate::new_empty(),
+                    ))
+                ),
+                ""
+            ))
+        );
+        // a null value
+        let mut parser = insert();
+        assert_eq!(
+            parser.parse("INSERT INTO STUDENT (name, age) VALUES ('Darci', null);"),
+            Ok((
+                InsertData::new(
+                    "STUDENT".to_string(),
+                    vec!["name".to_string(), "age".to_string()],
+                    vec![Constant::String("Darci".to_string()), Constant::Null]
+                ),
+                ""
+            ))
+        );
+        let mut parser = insert();
+        assert!(parser
+            .parse("INSERT INTO STUDENT (name) VALUES ('Darci'),;")
+            .is_err());
     }
 
     #[test]
     fn modify_test() {
         let mut parser = modify();
+        assert_eq!(
+            parser.parse("UPDATE STUDENT SET age = age + 1;"),
+            Ok((
+                ModifyData::new(
+                    "STUDENT".to_string(),
+                    "age".to_string(),
+                    Expression::new_arith(
+                        Expression::Fldname("age".to_string()),
+                        ArithOp::Add,
+                        Expression::Val(Constant::I32(1))
+                    ),
+                    Predicate::new_empty(),
+                ),
+                ""
+            ))
+        );
         assert_eq!(
             parser.parse("UPDATE STUDENT SET age = 22;"),
             Ok((
@@ -1216,6 +3256,18 @@ mod tests {
                 ""
             ))
         );
+        assert_eq!(
+            parser.parse("UPDATE STUDENT SET age = NULL;"),
+            Ok((
+                ModifyData::new(
+                    "STUDENT".to_string(),
+                    "age".to_string(),
+                    Expression::Val(Constant::Null),
+                    Predicate::new_empty(),
+                ),
+                ""
+            ))
+        );
         assert_eq!(
             parser.parse("UPDATE STUDENT SET age = 22 WHERE age = 21;"),
             Ok((
@@ -1260,6 +3312,34 @@ mod tests {
                 ""
             ))
         );
+        // multiple assignments
+        assert_eq!(
+            parser.parse("UPDATE STUDENT SET age = age + 1, grade = 'B' WHERE age = 21;"),
+            Ok((
+                ModifyData::new_with(
+                    "STUDENT".to_string(),
+                    vec![
+                        (
+                            "age".to_string(),
+                            Expression::new_arith(
+                                Expression::Fldname("age".to_string()),
+                                ArithOp::Add,
+                                Expression::Val(Constant::I32(1))
+                            )
+                        ),
+                        (
+                            "grade".to_string(),
+                            Expression::Val(Constant::String("B".to_string()))
+                        ),
+                    ],
+                    Predicate::new(Term::new(
+                        Expression::Fldname("age".to_string()),
+                        Expression::Val(Constant::I32(21))
+                    ))
+                ),
+                ""
+            ))
+        );
     }
 
     #[test]
@@ -1276,6 +3356,183 @@ mod tests {
 	), Ok((CreateTableData::new("STUDENT".to_string(), expected), "")));
     }
 
+    #[test]
+    fn create_table_key_test() {
+        let mut parser = create_table();
+        let mut expected = Schema::new();
+        expected.add_i32_field("SId");
+        expected.add_string_field("SName", 10);
+        expected.add_string_field("Email", 20);
+
+        assert_eq!(
+            parser.parse(
+                "CREATE TABLE STUDENT (SId integer PRIMARY KEY, SName varchar(10), Email varchar(20) UNIQUE);"
+            ),
+            Ok((
+                CreateTableData::new_with(
+                    "STUDENT".to_string(),
+                    expected.clone(),
+                    vec![
+                        KeyDef::PrimaryKey("SId".to_string()),
+                        KeyDef::Unique("Email".to_string())
+                    ],
+                    vec![],
+                    vec![]
+                ),
+                ""
+            ))
+        );
+        assert_eq!(
+            parser.parse(
+                "create table STUDENT (SId integer, SName varchar(10), Email varchar(20), unique (Email), primary key (SId));"
+            ),
+            Ok((
+                CreateTableData::new_with(
+                    "STUDENT".to_string(),
+                    expected,
+                    vec![
+                        KeyDef::Unique("Email".to_string()),
+                        KeyDef::PrimaryKey("SId".to_string())
+                    ],
+                    vec![],
+                    vec![]
+                ),
+                ""
+            ))
+        );
+    }
+
+    #[test]
+    fn create_table_constraint_test() {
+        let mut parser = create_table();
+        let mut expected = Schema::new();
+        expected.add_i32_field("SId");
+        expected.add_string_field("SName", 10);
+        expected.add_i32_field("GradYear");
+        expected.add_date_field("Joined");
+
+        let mut check = Predicate::new(Term::new_with(
+            Expression::Fldname("GradYear".to_string()),
+            CompOp::Ge,
+            Expression::Val(Constant::I32(2000)),
+        ));
+        check.conjoin_with(&mut Predicate::new(Term::new_with(
+            Expression::Fldname("GradYear".to_string()),
+            CompOp::Lt,
+            Expression::Val(Constant::I32(2100)),
+        )));
+        assert_eq!(
+            parser.parse(
+                "CREATE TABLE STUDENT (SId integer PRIMARY KEY NOT NULL, SName varchar(10) not null default 'anon', \
+                 GradYear integer DEFAULT 2020 CHECK (GradYear >= 2000) CHECK (GradYear < 2100), \
+                 Joined date DEFAULT CURRENT_DATE);"
+            ),
+            Ok((
+                CreateTableData::new_with(
+                    "STUDENT".to_string(),
+                    expected,
+                    vec![KeyDef::PrimaryKey("SId".to_string())],
+                    vec![
+                        FieldConstraint::new("SId".to_string(), true, None, None),
+                        FieldConstraint::new(
+                            "SName".to_string(),
+                            true,
+                            Some(Expression::Val(Constant::String("anon".to_string()))),
+                            None
+                        ),
+                        FieldConstraint::new(
+                            "GradYear".to_string(),
+                            false,
+                            Some(Expression::Val(Constant::I32(2020))),
+                            Some(check)
+                        ),
+                        FieldConstraint::new(
+                            "Joined".to_string(),
+                            false,
+                            Some(Expression::new_function(ScalarFn::CurrentDate, vec![])),
+                            None
+                        ),
+                    ],
+                    vec![]
+                ),
+                ""
+            ))
+        );
+    }
+
+    #[test]
+    fn create_table_foreign_key_test() {
+        let mut parser = create_table();
+        let mut expected = Schema::new();
+        expected.add_i32_field("sid");
+        expected.add_i32_field("major_id");
+        expected.add_i32_field("advisor_id");
+
+        assert_eq!(
+            parser.parse(
+                "CREATE TABLE student (sid integer PRIMARY KEY, major_id integer REFERENCES dept (did), \
+                 advisor_id integer, FOREIGN KEY (advisor_id) REFERENCES prof (pid) ON DELETE SET NULL);"
+            ),
+            Ok((
+                CreateTableData::new_with(
+                    "student".to_string(),
+                    expected.clone(),
+                    vec![KeyDef::PrimaryKey("sid".to_string())],
+                    vec![],
+                    vec![
+                        ForeignKeyDef::new(
+                            "major_id".to_string(),
+                            "dept".to_string(),
+                            "did".to_string(),
+                            RefAction::Restrict
+                        ),
+                        ForeignKeyDef::new(
+                            "advisor_id".to_string(),
+                            "prof".to_string(),
+                            "pid".to_string(),
+                            RefAction::SetNull
+                        ),
+                    ]
+                ),
+                ""
+            ))
+        );
+        assert_eq!(
+            parser.parse(
+                "create table student (sid integer, major_id integer references dept (did) on delete cascade not null, \
+                 advisor_id integer references prof (pid) on delete restrict);"
+            ),
+            Ok((
+                CreateTableData::new_with(
+                    "student".to_string(),
+                    expected,
+                    vec![],
+                    vec![FieldConstraint::new(
+                        "major_id".to_string(),
+                        true,
+                        None,
+                        None
+                    )],
+                    vec![
+                        ForeignKeyDef::new(
+                            "major_id".to_string(),
+                            "dept".to_string(),
+                            "did".to_string(),
+                            RefAction::Cascade
+                        ),
+                        ForeignKeyDef::new(
+                            "advisor_id".to_string(),
+                            "prof".to_string(),
+                            "pid".to_string(),
+                            RefAction::Restrict
+                        ),
+                    ]
+                ),
+                ""
+            ))
+        );
+    }
+
     #[test]
     fn create_view_test() {
         let mut parser = create_view();
@@ -1314,6 +3571,112 @@ mod tests {
             ))
         );
     }
+
+    #[test]
+    fn drop_test() {
+        let mut parser = update_cmd();
+        assert_eq!(
+            parser.parse("DROP TABLE STUDENT;"),
+            Ok((
+                SQL::DDL(DDL::DropTable(DropData::new("STUDENT".to_string(), false))),
+                ""
+            ))
+        );
+        assert_eq!(
+            parser.parse("drop table if exists STUDENT;"),
+            Ok((
+                SQL::DDL(DDL::DropTable(DropData::new("STUDENT".to_string(), true))),
+                ""
+            ))
+        );
+        // a table may be named if
+        assert_eq!(
+            parser.parse("drop table if;"),
+            Ok((
+                SQL::DDL(DDL::DropTable(DropData::new("if".to_string(), false))),
+                ""
+            ))
+        );
+        assert_eq!(
+            parser.parse("DROP VIEW einstein;"),
+            Ok((
+                SQL::DDL(DDL::DropView(DropData::new("einstein".to_string(), false))),
+                ""
+            ))
+        );
+        assert_eq!(
+            parser.parse("DROP INDEX IF EXISTS idx_grad_year;"),
+            Ok((
+                SQL::DDL(DDL::DropIndex(DropData::new(
+                    "idx_grad_year".to_string(),
+                    true
+                ))),
+                ""
+            ))
+        );
+        assert!(parser.parse("DROP STUDENT;").is_err());
+        assert!(parser.parse("DROP TABLE STUDENT, DEPT;").is_err());
+    }
+
+    #[test]
+    fn alter_table_test() {
+        let mut parser = update_cmd();
+        let alter = |action| {
+            Ok((
+                SQL::DDL(DDL::AlterTable(AlterTableData::new(
+                    "STUDENT".to_string(),
+                    action,
+                ))),
+                "",
+            ))
+        };
+        assert_eq!(
+            parser.parse("ALTER TABLE STUDENT ADD COLUMN Email varchar(20);"),
+            alter(AlterAction::AddColumn(
+                "Email".to_string(),
+                FieldInfo::new(FieldType::VARCHAR, 20)
+            ))
+        );
+        assert_eq!(
+            parser.parse("alter table STUDENT add Age smallint;"),
+            alter(AlterAction::AddColumn(
+                "Age".to_string(),
+                FieldInfo::new(FieldType::SMALLINT, 0)
+            ))
+        );
+        assert_eq!(
+            parser.parse("ALTER TABLE STUDENT DROP COLUMN GradYear;"),
+            alter(AlterAction::DropColumn("GradYear".to_string()))
+        );
+        assert_eq!(
+            parser.parse("ALTER TABLE STUDENT DROP GradYear;"),
+            alter(AlterAction::DropColumn("GradYear".to_string()))
+        );
+        assert_eq!(
+            parser.parse("ALTER TABLE STUDENT RENAME COLUMN SName TO Name;"),
+            alter(AlterAction::RenameColumn(
+                "SName".to_string(),
+                "Name".to_string()
+            ))
+        );
+        assert_eq!(
+            parser.parse("ALTER TABLE STUDENT RENAME SName TO Name;"),
+            alter(AlterAction::RenameColumn(
+                "SName".to_string(),
+                "Name".to_string()
+            ))
+        );
+        assert_eq!(
+            parser.parse("ALTER TABLE STUDENT RENAME TO PUPIL;"),
+            alter(AlterAction::RenameTable("PUPIL".to_string()))
+        );
+        assert!(parser
+            .parse("ALTER TABLE STUDENT ADD COLUMN Email;")
+            .is_err());
+        assert!(parser
+            .parse("ALTER TABLE STUDENT RENAME COLUMN SName;")
+            .is_err());
+    }
     #[test]
     fn update_cmd_test() {
         let mut parser = update_cmd();
@@ -1,34 +1,120 @@
 use core::fmt;
 
-use crate::query::predicate::Predicate;
+use crate::{
+    materialize::{aggregationfn::Aggregate, recordcomparator::SortOrder},
+    query::{expression::Expression, predicate::Predicate},
+    record::schema::qualify_name,
+};
+
+// How a table in the from clause is joined to the preceding tables.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+pub enum JoinKind {
+    // a comma, i.e. the product
+    Cross,
+    Inner,
+    Left,
+    Right,
+}
+
+impl fmt::Display for JoinKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            JoinKind::Cross => write!(f, ","),
+            JoinKind::Inner => write!(f, "join"),
+            JoinKind::Left => write!(f, "left join"),
+            JoinKind::Right => write!(f, "right join"),
+        }
+    }
+}
 
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
 pub struct QueryData {
-    fields: Vec<String>,
+    distinct: bool,
+    exprs: Vec<Expression>,
+    aliases: Vec<Option<String>>,
     tables: Vec<String>,
+    table_aliases: Vec<Option<String>>,
+    // the join of each table, and its join condition
+    joins: Vec<(JoinKind, Predicate)>,
     pred: Predicate,
+    group_by: Vec<String>,
+    having: Predicate,
+    order_by: Vec<(String, SortOrder)>,
+    limit: Option<usize>,
+    offset: usize,
 }
 
 impl fmt::Display for QueryData {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut result = vec![];
         result.push("select");
+        if self.distinct {
+            result.push("distinct");
+        }
         let mut fs = vec![];
-        for fldname in self.fields.iter() {
-            fs.push(fldname.as_str());
+        for (expr, alias) in self.exprs.iter().zip(self.aliases.iter()) {
+            match alias {
+                Some(alias) => fs.push(format!("{} as {}", expr, alias)),
+                None => fs.push(expr.to_string()),
+            }
         }
         let fs_str = fs.join(", ");
         result.push(fs_str.as_str());
         result.push("from");
-        let mut ts = vec![];
-        for tblname in self.tables.iter() {
-            ts.push(tblname.as_str())
+        let mut ts_str = String::new();
+        for (i, (tblname, alias)) in self
+            .tables
+            .iter()
+            .zip(self.table_aliases.iter())
+            .enumerate()
+        {
+            let (kind, on) = &self.joins[i];
+            if i > 0 && *kind == JoinKind::Cross {
+                ts_str.push_str(", ");
+            } else if i > 0 {
+                ts_str.push_str(&format!(" {} ", kind));
+            }
+            ts_str.push_str(tblname);
+            if let Some(alias) = alias {
+                ts_str.push_str(&format!(" {}", alias));
+            }
+            if *kind != JoinKind::Cross {
+                ts_str.push_str(&format!(" on {}", on));
+            }
         }
-        let ts_str = ts.join(", ");
         result.push(ts_str.as_str());
-        result.push("where");
         let pred_str = self.pred.to_string();
-        result.push(&pred_str.as_str());
+        if !pred_str.is_empty() {
+            result.push("where");
+            result.push(pred_str.as_str());
+        }
+        let gs_str = self.group_by.join(", ");
+        if !self.group_by.is_empty() {
+            result.push("group by");
+            result.push(gs_str.as_str());
+        }
+        let having_str = self.having.to_string();
+        if !having_str.is_empty() {
+            result.push("having");
+            result.push(having_str.as_str());
+        }
+        let mut os = vec![];
+        for (fldname, order) in self.order_by.iter() {
+            os.push(format!("{} {}", fldname, order));
+        }
+        let os_str = os.join(", ");
+        if !os.is_empty() {
+            result.push("order by");
+            result.push(os_str.as_str());
+        }
+        let limit_str = match self.limit {
+            Some(limit) if self.offset > 0 => format!("limit {} offset {}", limit, self.offset),
+            Some(limit) => format!("limit {}", limit),
+            None => "".to_string(),
+        };
+        if !limit_str.is_empty() {
+            result.push(limit_str.as_str());
+        }
 
         // MEMO: I don't append a terminater, because we'll be able to use this
         // as sub-query at a future.
@@ -39,19 +125,191 @@ impl fmt::Display for QueryData {
 
 impl QueryData {
     pub fn new(fields: Vec<String>, tables: Vec<String>, pred: Predicate) -> Self {
+        let exprs = fields
+            .into_iter()
+            .map(|fldname| Expression::new_fldname(fldname))
+            .collect();
+        Self::new_with(exprs, tables, pred)
+    }
+    pub fn new_with(exprs: Vec<Expression>, tables: Vec<String>, pred: Predicate) -> Self {
         Self {
-            fields,
+            distinct: false,
+            aliases: vec![None; exprs.len()],
+            exprs,
+            table_aliases: vec![None; tables.len()],
+            joins: vec![(JoinKind::Cross, Predicate::new_empty()); tables.len()],
             tables,
             pred,
+            group_by: vec![],
+            having: Predicate::new_empty(),
+            order_by: vec![],
+            limit: None,
+            offset: 0,
         }
     }
-    pub fn fields(&self) -> &Vec<String> {
-        &self.fields
+    // an alias for each field of the select list, in the same order
+    pub fn set_aliases(&mut self, aliases: Vec<Option<String>>) {
+        self.aliases = aliases;
+    }
+    // an alias for each table of the from clause, in the same order
+    pub fn set_table_aliases(&mut self, table_aliases: Vec<Option<String>>) {
+        self.table_aliases = table_aliases;
+    }
+    // a join for each table of the from clause, in the same order.
+    // the join of the first table is ignored.
+    pub fn set_joins(&mut self, joins: Vec<(JoinKind, Predicate)>) {
+        self.joins = joins;
+    }
+    pub fn set_distinct(&mut self, distinct: bool) {
+        self.distinct = distinct;
+    }
+    pub fn set_limit(&mut self, limit: usize, offset: usize) {
+        self.limit = Some(limit);
+        self.offset = offset;
+    }
+    pub fn set_group_by(&mut self, group_by: Vec<String>) {
+        self.group_by = group_by;
+    }
+    pub fn set_having(&mut self, having: Predicate) {
+        self.having = having;
+    }
+    pub fn set_order_by(&mut self, order_by: Vec<(String, SortOrder)>) {
+        self.order_by = order_by;
+    }
+    // the names of output fields, which are the aliases if given
+    pub fn fields(&self) -> Vec<String> {
+        self.projection()
+            .into_iter()
+            .map(|(fldname, _)| fldname)
+            .collect()
+    }
+    // the output fields with their expressions
+    pub fn projection(&self) -> Vec<(String, Expression)> {
+        self.exprs
+            .iter()
+            .zip(self.aliases.iter())
+            .map(|(expr, alias)| match alias {
+                Some(alias) => (alias.clone(), expr.clone()),
+                None => (expr.to_string(), expr.clone()),
+            })
+            .collect()
+    }
+    pub fn exprs(&self) -> &Vec<Expression> {
+        &self.exprs
     }
     pub fn tables(&self) -> &Vec<String> {
         &self.tables
     }
+    // the names, by which the fields of each table are qualified
+    pub fn qualifiers(&self) -> Vec<String> {
+        self.tables
+            .iter()
+            .zip(self.table_aliases.iter())
+            .map(|(tblname, alias)| alias.clone().unwrap_or_else(|| tblname.clone()))
+            .collect()
+    }
+    pub fn joins(&self) -> &Vec<(JoinKind, Predicate)> {
+        &self.joins
+    }
+    pub fn has_outer_join(&self) -> bool {
+        self.joins
+            .iter()
+            .skip(1)
+            .any(|(kind, _)| *kind == JoinKind::Left || *kind == JoinKind::Right)
+    }
     pub fn pred(&self) -> &Predicate {
         &self.pred
     }
+    pub fn is_distinct(&self) -> bool {
+        self.distinct
+    }
+    pub fn group_by(&self) -> &Vec<String> {
+        &self.group_by
+    }
+    pub fn having(&self) -> &Predicate {
+        &self.having
+    }
+    pub fn order_by(&self) -> &Vec<(String, SortOrder)> {
+        &self.order_by
+    }
+    pub fn limit(&self) -> Option<usize> {
+        self.limit
+    }
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+    // the aggregate function calls in the select list and the having clause,
+    // without duplicates.
+    pub fn aggregates(&self) -> Vec<Aggregate> {
+        let mut result: Vec<Aggregate> = vec![];
+        let exprs_aggs = self.exprs.iter().flat_map(|e| e.aggregates());
+        for agg in exprs_aggs.chain(self.having.aggregates()) {
+            if !result.contains(&agg) {
+                result.push(agg);
+            }
+        }
+        result
+    }
+    pub fn is_grouped(&self) -> bool {
+        !self.group_by.is_empty() || !self.having.is_empty() || !self.aggregates().is_empty()
+    }
+    // the select list has "*" or "t.*", which must be expanded before planning.
+    pub fn has_wildcard(&self) -> bool {
+        self.exprs.iter().any(|e| wildcard_qualifier(e).is_some())
+    }
+    // replaces the wildcards of the select list with the fields of the tables,
+    // where fields[i] are the fields of the i-th table of the from clause.
+    // a field is qualified, only if another table has the same field.
+    pub fn expand_wildcards(&mut self, fields: &[Vec<String>]) {
+        let qualifiers = self.qualifiers();
+        let count = |fldname: &String| fields.iter().flatten().filter(|f| *f == fldname).count();
+        let mut exprs = vec![];
+        let mut aliases = vec![];
+        for (expr, alias) in self.exprs.iter().zip(self.aliases.iter()) {
+            let wildcard = match wildcard_qualifier(expr) {
+                Some(wildcard) => wildcard,
+                None => {
+                    exprs.push(expr.clone());
+                    aliases.push(alias.clone());
+                    continue;
+                }
+            };
+            for (qualifier, flds) in qualifiers.iter().zip(fields.iter()) {
+                if wildcard.is_some_and(|q| q != qualifier) {
+                    continue;
+                }
+                for fldname in flds {
+                    let name = match count(fldname) {
+                        1 => fldname.clone(),
+                        _ => qualify_name(qualifier, fldname),
+                    };
+                    exprs.push(Expression::new_fldname(name));
+                    aliases.push(None);
+                }
+            }
+        }
+        self.exprs = exprs;
+        self.aliases = aliases;
+    }
+    // the field names referred anywhere in the query, including the subqueries.
+    pub fn field_names(&self) -> Vec<String> {
+        let mut result: Vec<String> = self.exprs.iter().flat_map(|e| e.field_names()).collect();
+        for (_, on) in self.joins.iter() {
+            result.extend(on.field_names());
+        }
+        result.extend(self.pred.field_names());
+        result.extend(self.group_by.iter().cloned());
+        result.extend(self.having.field_names());
+        result.extend(self.order_by.iter().map(|(fldname, _)| fldname.clone()));
+        result
+    }
+}
+
+// the qualifier of "t.*", or None for "*".
+fn wildcard_qualifier(expr: &Expression) -> Option<Option<&str>> {
+    match expr {
+        Expression::Fldname(fldname) if fldname == "*" => Some(None),
+        Expression::Fldname(fldname) => fldname.strip_suffix(".*").map(Some),
+        _ => None,
+    }
 }
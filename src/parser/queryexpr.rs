@@ -0,0 +1,55 @@
+use core::fmt;
+
+use super::querydata::QueryData;
+
+// How the records of two queries are combined.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+pub enum SetOp {
+    Union,
+    // keeps the duplicates
+    UnionAll,
+    Intersect,
+    Except,
+}
+
+impl fmt::Display for SetOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SetOp::Union => write!(f, "union"),
+            SetOp::UnionAll => write!(f, "union all"),
+            SetOp::Intersect => write!(f, "intersect"),
+            SetOp::Except => write!(f, "except"),
+        }
+    }
+}
+
+// A select query, or the set operation on two query expressions.
+// The fields of the result are named after the left one.
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub enum QueryExpr {
+    Select(QueryData),
+    SetOp(SetOp, Box<QueryExpr>, Box<QueryExpr>),
+}
+
+impl fmt::Display for QueryExpr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            QueryExpr::Select(data) => write!(f, "{}", data),
+            QueryExpr::SetOp(op, lhs, rhs) => {
+                // the nested set operations are parenthesised,
+                // so that the order of them is kept.
+                let operand = |expr: &QueryExpr| match expr {
+                    QueryExpr::Select(data) => data.to_string(),
+                    QueryExpr::SetOp(..) => format!("({})", expr),
+                };
+                write!(f, "{} {} {}", operand(lhs), op, operand(rhs))
+            }
+        }
+    }
+}
+
+impl QueryExpr {
+    pub fn new_set_op(op: SetOp, lhs: QueryExpr, rhs: QueryExpr) -> Self {
+        QueryExpr::SetOp(op, Box::new(lhs), Box::new(rhs))
+    }
+}
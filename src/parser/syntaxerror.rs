@@ -0,0 +1,163 @@
+use combine::{
+    easy::{self, Error, Info},
+    eof,
+    stream::position::{self, SourcePosition},
+    EasyParser, Parser,
+};
+use core::fmt;
+
+// my own extends
+// a syntax error of a sql, at the position where the parser stopped.
+// the line and the column start with 1, and the token is empty at the end of the sql.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SqlSyntaxError {
+    line: i32,
+    column: i32,
+    token: String,
+    expected: Vec<String>,
+}
+
+impl std::error::Error for SqlSyntaxError {}
+impl fmt::Display for SqlSyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "syntax error at line {}, column {}: ",
+            self.line, self.column
+        )?;
+        match self.token.is_empty() {
+            true => write!(f, "unexpected end of input")?,
+            false => write!(f, "unexpected \"{}\"", self.token)?,
+        }
+        if !self.expected.is_empty() {
+            write!(f, ", expected {}", self.expected.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl SqlSyntaxError {
+    pub fn new(line: i32, column: i32, token: String, expected: Vec<String>) -> Self {
+        Self {
+            line,
+            column,
+            token,
+            expected,
+        }
+    }
+    // the offending token is cut from the sql, because combine reports just a char of it.
+    pub fn from_errors(sql: &str, errors: easy::Errors<char, &str, SourcePosition>) -> Self {
+        let line = errors.position.line;
+        let column = errors.position.column;
+        let rest: String = sql
+            .lines()
+            .nth((line - 1) as usize)
+            .unwrap_or("")
+            .chars()
+            .skip((column - 1) as usize)
+            .collect();
+        let token = match rest.chars().next() {
+            Some(c) if c.is_alphanumeric() || c == '_' => rest
+                .chars()
+                .take_while(|c| c.is_alphanumeric() || *c == '_')
+                .collect(),
+            Some(c) => c.to_string(),
+            None => "".to_string(),
+        };
+        let mut expected: Vec<String> = vec![];
+        for err in errors.errors.into_iter() {
+            let tok = match err {
+                Error::Expected(Info::Token(c)) => format!("'{}'", c),
+                // the identifiers begin with a letter
+                Error::Expected(Info::Static("letter")) => "identifier".to_string(),
+                Error::Expected(Info::Static(s)) => s.to_string(),
+                Error::Expected(Info::Range(s)) => s.to_string(),
+                Error::Expected(Info::Owned(s)) => s,
+                _ => continue,
+            };
+            if !expected.contains(&tok) {
+                expected.push(tok);
+            }
+        }
+
+        Self::new(line, column, token, expected)
+    }
+    pub fn line(&self) -> i32 {
+        self.line
+    }
+    pub fn column(&self) -> i32 {
+        self.column
+    }
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+    pub fn expected(&self) -> &Vec<String> {
+        &self.expected
+    }
+    // the line of the sql with a caret under the error.
+    pub fn caret(&self, sql: &str) -> String {
+        let text = sql.lines().nth((self.line - 1) as usize).unwrap_or("");
+        // tabs are kept, so the caret is under the column on the terminal
+        let indent: String = text
+            .chars()
+            .take((self.column - 1) as usize)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+
+        format!("{}\n{}^", text, indent)
+    }
+}
+
+// parses the whole sql, so the rest of the sql is an error too.
+pub fn parse_sql<'a, P>(parser: P, sql: &'a str) -> Result<P::Output, SqlSyntaxError>
+where
+    P: Parser<easy::Stream<position::Stream<&'a str, SourcePosition>>>,
+{
+    parser
+        .skip(eof())
+        .easy_parse(position::Stream::new(sql))
+        .map(|(output, _)| output)
+        .map_err(|errors| SqlSyntaxError::from_errors(sql, errors))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parser::{query_expr, update_cmd};
+
+    #[test]
+    fn unit_test() {
+        let sql = "SELECT SName FORM STUDENT;";
+        let err = parse_sql(query_expr(), sql).unwrap_err();
+        assert_eq!(
+            err,
+            SqlSyntaxError::new(1, 19, "STUDENT".to_string(), vec!["FROM".to_string()])
+        );
+        assert_eq!(
+            err.to_string(),
+            "syntax error at line 1, column 19: unexpected \"STUDENT\", expected FROM"
+        );
+        assert_eq!(
+            err.caret(sql),
+            "SELECT SName FORM STUDENT;\n                  ^"
+        );
+
+        // the rest of the sql isn't ignored
+        let sql = "SELECT SName FROM STUDENT; DROP TABLE STUDENT;";
+        let err = parse_sql(query_expr(), sql).unwrap_err();
+        assert_eq!(
+            err,
+            SqlSyntaxError::new(1, 28, "DROP".to_string(), vec!["end of input".to_string()])
+        );
+
+        let sql = "DELETE FROM STUDENT\n WHERE SId = ";
+        let err = parse_sql(update_cmd(), sql).unwrap_err();
+        assert_eq!((err.line(), err.column(), err.token()), (2, 14, ""));
+        assert!(err.expected().contains(&"identifier".to_string()));
+        assert_eq!(err.caret(sql), " WHERE SId = \n             ^");
+
+        let sql = "DELETE FROM STUDENT WHERE SId = 1;\n";
+        assert!(parse_sql(update_cmd(), sql).is_ok());
+    }
+}
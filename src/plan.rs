@@ -1,5 +1,8 @@
 pub mod basicqueryplanner;
 pub mod basicupdateplanner;
+pub mod limitplan;
+pub mod outerjoinplan;
+pub mod outerrecordplan;
 pub mod plan;
 pub mod planner;
 pub mod productplan;
@@ -7,4 +10,5 @@ pub mod projectplan;
 pub mod queryplanner;
 pub mod selectplan;
 pub mod tableplan;
+pub mod unionplan;
 pub mod updateplanner;
@@ -1,20 +1,68 @@
 use anyhow::Result;
-use combine::Parser;
+use core::fmt;
 use std::sync::{Arc, Mutex};
 
-use super::{plan::Plan, queryplanner::QueryPlanner};
+use super::{
+    plan::Plan,
+    queryplanner::{check_ambiguous_fields, expand_wildcards, QueryPlanner},
+};
 use crate::{
+    materialize::{
+        distinctplan::DistinctPlan, groupbyplan::GroupByPlan, setopplan::SetOpPlan,
+        sortplan::SortPlan,
+    },
     metadata::manager::MetadataMgr,
-    parser::{parser::query, querydata::QueryData},
+    parser::{
+        parser::query,
+        querydata::{JoinKind, QueryData},
+        queryexpr::SetOp,
+        syntaxerror::parse_sql,
+    },
     plan::{
+        limitplan::LimitPlan, outerjoinplan::OuterJoinPlan, outerrecordplan::OuterRecordPlan,
         productplan::ProductPlan, projectplan::ProjectPlan, selectplan::SelectPlan,
-        tableplan::TablePlan,
+        tableplan::TablePlan, unionplan::UnionPlan,
+    },
+    query::{
+        expression::Expression,
+        subquery::{OuterRecord, Subquery},
     },
+    record::schema::{qualify_name, unqualified_name, Schema},
     tx::transaction::Transaction,
 };
 
+#[derive(Debug)]
+pub enum BasicQueryPlannerError {
+    InvalidOrderBy,
+    SubqueryInJoin,
+    NotUnionCompatible,
+}
+
+impl std::error::Error for BasicQueryPlannerError {}
+impl fmt::Display for BasicQueryPlannerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BasicQueryPlannerError::InvalidOrderBy => {
+                write!(f, "order by fields must be in the select list of distinct")
+            }
+            BasicQueryPlannerError::SubqueryInJoin => {
+                write!(f, "subquery can't be used in a join condition")
+            }
+            BasicQueryPlannerError::NotUnionCompatible => {
+                write!(
+                    f,
+                    "queries of a set operation must have the same field types"
+                )
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct BasicQueryPlanner {
+    // static member (shared by all Materializeplan and Temptable)
+    next_table_num: Arc<Mutex<i32>>,
+
     mdm: Arc<Mutex<MetadataMgr>>,
 }
 
@@ -24,9 +72,62 @@ impl QueryPlanner for BasicQueryPlanner {
         data: QueryData,
         tx: Arc<Mutex<Transaction>>,
     ) -> Result<Arc<dyn Plan>> {
+        self.create_plan_with(data, None, tx)
+    }
+    fn create_subquery_plan(
+        &mut self,
+        sq: &Subquery,
+        env: Arc<Schema>,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<Arc<dyn Plan>> {
+        self.create_plan_with(sq.data().clone(), Some((env, sq.outer())), tx)
+    }
+    fn create_set_op_plan(
+        &mut self,
+        op: SetOp,
+        p1: Arc<dyn Plan>,
+        p2: Arc<dyn Plan>,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<Arc<dyn Plan>> {
+        if !p1.schema().is_union_compatible(&p2.schema()) {
+            return Err(From::from(BasicQueryPlannerError::NotUnionCompatible));
+        }
+        if op == SetOp::UnionAll {
+            return Ok(Arc::new(UnionPlan::new(p1, p2)));
+        }
+        let next_table_num = Arc::clone(&self.next_table_num);
+        Ok(Arc::new(SetOpPlan::new(next_table_num, tx, op, p1, p2)))
+    }
+}
+
+impl BasicQueryPlanner {
+    pub fn new(next_table_num: Arc<Mutex<i32>>, mdm: Arc<Mutex<MetadataMgr>>) -> Self {
+        Self {
+            next_table_num,
+            mdm,
+        }
+    }
+    fn create_plan_with(
+        &mut self,
+        data: QueryData,
+        outer: Option<(Arc<Schema>, OuterRecord)>,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<Arc<dyn Plan>> {
+        let mut data = data;
+        expand_wildcards(&mut data, Arc::clone(&self.mdm), Arc::clone(&tx))?;
+        check_ambiguous_fields(&data, Arc::clone(&self.mdm), Arc::clone(&tx))?;
+
+        if data
+            .joins()
+            .iter()
+            .any(|(_, on)| !on.subqueries().is_empty())
+        {
+            return Err(From::from(BasicQueryPlannerError::SubqueryInJoin));
+        }
+
         // Step 1: Create a plan for each mentioned table or view
         let mut plans: Vec<Arc<dyn Plan>> = vec![];
-        for tblname in data.tables() {
+        for (tblname, qualifier) in data.tables().iter().zip(data.qualifiers()) {
             let mut viewdef = self
                 .mdm
                 .lock()
@@ -34,34 +135,140 @@ impl QueryPlanner for BasicQueryPlanner {
                 .get_view_def(tblname, Arc::clone(&tx))?;
             if !viewdef.is_empty() {
                 // Recursively plan the view.
-                let mut parser = query();
                 // NOTE: query parser expect terminater.
                 viewdef = format!("{};", viewdef);
-                let (viewdata, _) = parser.parse(viewdef.as_str())?;
-                plans.push(self.create_plan(viewdata, Arc::clone(&tx))?);
+                let viewdata = parse_sql(query(), viewdef.as_str())?;
+                let viewplan = self.create_plan(viewdata, Arc::clone(&tx))?;
+                // qualify the output fields of the view, as the fields of a table
+                let fields = viewplan
+                    .schema()
+                    .fields()
+                    .iter()
+                    .map(|fldname| {
+                        let name = qualify_name(&qualifier, unqualified_name(fldname));
+                        (name, Expression::new_fldname(fldname.clone()))
+                    })
+                    .collect();
+                plans.push(Arc::new(ProjectPlan::new_with(viewplan, fields)?));
             } else {
-                plans.push(Arc::new(TablePlan::new(
+                plans.push(Arc::new(TablePlan::new_with(
                     tblname,
+                    &qualifier,
                     Arc::clone(&tx),
                     self.mdm.clone(),
                 )?))
             }
         }
-        // Step 2: Create the product of all table plans
+        // Step 2: Join all table plans in order,
+        // after the outer record which a correlated subquery refers to.
+        let mut inner = Schema::new();
+        for plan in plans.iter() {
+            inner.add_all(plan.schema());
+        }
         let mut p = plans.remove(0);
-        for nextplan in plans {
-            p = Arc::new(ProductPlan::new(Arc::clone(&p), nextplan));
+        if let Some((env, outer)) = outer {
+            if let Some(outerplan) = OuterRecordPlan::new(&data, Arc::new(inner), env, outer) {
+                p = Arc::new(ProductPlan::new(Arc::new(outerplan), p));
+            }
+        }
+        for (nextplan, (kind, on)) in plans.into_iter().zip(data.joins().iter().skip(1)) {
+            p = match kind {
+                JoinKind::Cross => Arc::new(ProductPlan::new(p, nextplan)),
+                JoinKind::Inner => Arc::new(SelectPlan::new(
+                    Arc::new(ProductPlan::new(p, nextplan)),
+                    on.clone(),
+                )),
+                JoinKind::Left => Arc::new(OuterJoinPlan::new(p, nextplan, on.clone())),
+                JoinKind::Right => Arc::new(OuterJoinPlan::new(nextplan, p, on.clone())),
+            };
         }
         // Step 3: Add a selection plan for the predicate
-        p = Arc::new(SelectPlan::new(Arc::clone(&p), data.pred().clone()));
+        let mut pred = data.pred().clone();
+        self.bind_subqueries(pred.subqueries_mut(), p.schema(), Arc::clone(&tx))?;
+        p = Arc::new(SelectPlan::new(Arc::clone(&p), pred));
 
-        // Step 4: Project on the field names
-        Ok(Arc::new(ProjectPlan::new(p, data.fields().clone())))
-    }
-}
+        // Step 4: Group the records and compute the aggregates,
+        // and add a selection plan for the having clause
+        if data.is_grouped() {
+            let aggfns = data
+                .aggregates()
+                .iter()
+                .map(|agg| agg.to_aggregation_fn())
+                .collect();
+            p = Arc::new(GroupByPlan::new(
+                Arc::clone(&self.next_table_num),
+                Arc::clone(&tx),
+                p,
+                data.group_by().clone(),
+                aggfns,
+            )?);
+            if !data.having().is_empty() {
+                let mut having = data.having().clone();
+                self.bind_subqueries(having.subqueries_mut(), p.schema(), Arc::clone(&tx))?;
+                p = Arc::new(SelectPlan::new(p, having));
+            }
+        }
 
-impl BasicQueryPlanner {
-    pub fn new(mdm: Arc<Mutex<MetadataMgr>>) -> Self {
-        Self { mdm }
+        // Step 5: Project on the field names, and remove the duplicates
+        let mut projection = data.projection();
+        let subqueries = projection
+            .iter_mut()
+            .flat_map(|(_, expr)| expr.subqueries_mut())
+            .collect();
+        self.bind_subqueries(subqueries, p.schema(), Arc::clone(&tx))?;
+        let mut projected: Arc<dyn Plan> =
+            Arc::new(ProjectPlan::new_with(Arc::clone(&p), projection.clone())?);
+        if data.is_distinct() {
+            projected = Arc::new(DistinctPlan::new(
+                Arc::clone(&self.next_table_num),
+                Arc::clone(&tx),
+                projected,
+            ));
+        }
+
+        // Step 6: Sort on the order by fields,
+        // below the projection if it sorts by fields which aren't projected.
+        let mut result = projected;
+        if !data.order_by().is_empty() {
+            let sch = result.schema();
+            if data
+                .order_by()
+                .iter()
+                .all(|(fldname, _)| sch.has_field(fldname))
+            {
+                result = self.create_sort_plan(result, &data, Arc::clone(&tx));
+            } else if data.is_distinct() {
+                // the order of the duplicates, which are removed, would be undefined
+                return Err(From::from(BasicQueryPlannerError::InvalidOrderBy));
+            } else {
+                p = self.create_sort_plan(p, &data, Arc::clone(&tx));
+                result = Arc::new(ProjectPlan::new_with(p, projection)?);
+            }
+        }
+
+        // Step 7: Limit the number of records
+        if let Some(limit) = data.limit() {
+            result = Arc::new(LimitPlan::new(result, limit, data.offset()));
+        }
+
+        Ok(result)
+    }
+    fn create_sort_plan(
+        &self,
+        p: Arc<dyn Plan>,
+        data: &QueryData,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Arc<dyn Plan> {
+        let mut plan = SortPlan::new_with(
+            Arc::clone(&self.next_table_num),
+            p,
+            data.order_by().clone(),
+            tx,
+        );
+        if let Some(limit) = data.limit().and_then(|n| n.checked_add(data.offset())) {
+            // only the first records are needed, so sort them in memory if they fit
+            plan.set_limit(limit);
+        }
+        Arc::new(plan)
     }
 }
@@ -1,16 +1,33 @@
 use anyhow::Result;
 use core::fmt;
-use std::sync::{Arc, Mutex};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
 
-use super::{selectplan::SelectPlan, tableplan::TablePlan, updateplanner::UpdatePlanner};
+use super::{
+    queryplanner::expand_wildcards,
+    selectplan::SelectPlan,
+    tableplan::TablePlan,
+    updateplanner::{
+        check_constraints, check_key, check_reference, check_unreferenced, delete_references,
+        fill_defaults, key_indexes, record_values, UpdatePlanner,
+    },
+};
 use crate::{
     metadata::manager::MetadataMgr,
     parser::{
-        createindexdata::CreateIndexData, createtabledata::CreateTableData,
-        createviewdata::CreateViewData, deletedata::DeleteData, insertdata::InsertData,
+        altertabledata::{AlterAction, AlterTableData},
+        createindexdata::CreateIndexData,
+        createtabledata::CreateTableData,
+        createviewdata::CreateViewData,
+        deletedata::DeleteData,
+        dropdata::DropData,
+        insertdata::InsertData,
         modifydata::ModifyData,
     },
     plan::plan::Plan,
+    query::constant::Constant,
     tx::transaction::Transaction,
 };
 
@@ -45,17 +62,39 @@ pub struct BasicUpdatePlanner {
 
 impl UpdatePlanner for BasicUpdatePlanner {
     fn execute_delete(&self, data: DeleteData, tx: Arc<Mutex<Transaction>>) -> Result<i32> {
+        let tblname = data.table_name();
         let p1 = Arc::new(TablePlan::new(
-            data.table_name(),
-            tx,
+            tblname,
+            Arc::clone(&tx),
             Arc::clone(&self.mdm),
         )?);
         let p2 = SelectPlan::new(p1, data.pred().clone());
+        let mut mdm = self.mdm.lock().unwrap();
+        let indexes = key_indexes(&mut mdm, tblname, Arc::clone(&tx))?;
+        let refs = mdm.get_referencing_keys(tblname, Arc::clone(&tx))?;
         if let Ok(s) = p2.open() {
             if let Ok(us) = s.lock().unwrap().to_update_scan() {
                 let mut count = 0;
                 while us.next() {
+                    // the referenced keys are kept for the referencing records
+                    let mut refvals = HashMap::new();
+                    for fk in refs.iter() {
+                        let fldname = fk.ref_field_name();
+                        refvals.insert(fldname.to_string(), us.get_val(fldname)?);
+                    }
+                    // the indexes of the keys are kept in sync with the table
+                    let rid = us.get_rid()?;
+                    for (fldname, ii) in indexes.iter() {
+                        // NOTE: convert the type here, because Index doesn't convert val.
+                        let fldtype = ii.table_schema().field_type(fldname);
+                        let val = us.get_val(fldname)?.as_field_type(fldtype)?;
+
+                        let idx = ii.open();
+                        idx.lock().unwrap().delete(val, rid)?;
+                        idx.lock().unwrap().close()?;
+                    }
                     us.delete()?;
+                    delete_references(&mut mdm, tblname, &refvals, Arc::clone(&tx))?;
                     count += 1;
                 }
                 us.close()?;
@@ -65,18 +104,71 @@ impl UpdatePlanner for BasicUpdatePlanner {
         Err(From::from(BasicUpdatePlannerError::DeleteAbort))
     }
     fn execute_modify(&self, data: ModifyData, tx: Arc<Mutex<Transaction>>) -> Result<i32> {
+        let tblname = data.table_name();
         let p1 = Arc::new(TablePlan::new(
-            data.table_name(),
-            tx,
+            tblname,
+            Arc::clone(&tx),
             Arc::clone(&self.mdm),
         )?);
+        let sch = p1.schema();
         let p2 = SelectPlan::new(p1, data.pred().clone());
+        let mut mdm = self.mdm.lock().unwrap();
+        let constraints = mdm.get_field_constraints(tblname, Arc::clone(&tx))?;
+        let keys = mdm.get_keys(tblname, Arc::clone(&tx))?;
+        let indexes = key_indexes(&mut mdm, tblname, Arc::clone(&tx))?;
+        let fks = mdm.get_foreign_keys(tblname, Arc::clone(&tx))?;
+        let refs = mdm.get_referencing_keys(tblname, Arc::clone(&tx))?;
         if let Ok(s) = p2.open() {
             if let Ok(us) = s.lock().unwrap().to_update_scan() {
                 let mut count = 0;
                 while us.next() {
-                    let val = data.new_value().evaluate(us.to_scan()?)?;
-                    us.set_val(data.target_field(), val)?;
+                    // all values are evaluated on the record before the update
+                    let mut vals = vec![];
+                    for (_, expr) in data.assignments() {
+                        vals.push(expr.evaluate(us.to_scan()?)?);
+                    }
+                    let rid = us.get_rid()?;
+                    // the constraints and the keys are checked before the record is updated
+                    if !constraints.is_empty() {
+                        let mut base = HashMap::new();
+                        for fldname in sch.fields() {
+                            base.insert(fldname.clone(), us.get_val(fldname)?);
+                        }
+                        let fldnames = data.assignments().iter().map(|(fldname, _)| fldname);
+                        let record = record_values(&sch, base, fldnames.zip(vals.iter()))?;
+                        check_constraints(tblname, &constraints, record)?;
+                    }
+                    for ((fldname, _), val) in data.assignments().iter().zip(vals.iter()) {
+                        if let Some(key) = keys.iter().find(|key| key.field_name() == fldname) {
+                            check_key(tblname, key, &indexes, val, Some(rid))?;
+                        }
+                        if let Some(fk) = fks.iter().find(|fk| fk.field_name() == fldname) {
+                            check_reference(&mut mdm, fk, val, Arc::clone(&tx))?;
+                        }
+                        // a referenced key can't be changed
+                        for fk in refs.iter().filter(|fk| fk.ref_field_name() == fldname) {
+                            let oldval = us.get_val(fldname)?;
+                            if oldval != *val {
+                                check_unreferenced(&mut mdm, fk, &oldval, Arc::clone(&tx))?;
+                            }
+                        }
+                    }
+                    for ((fldname, _), val) in data.assignments().iter().zip(vals) {
+                        let oldval = us.get_val(fldname)?;
+                        us.set_val(fldname, val.clone())?;
+                        // the indexes of the keys are kept in sync with the table
+                        if let Some(ii) = indexes.get(fldname) {
+                            // NOTE: convert the type here, because Index doesn't convert val.
+                            let fldtype = ii.table_schema().field_type(fldname);
+                            let oldval = oldval.as_field_type(fldtype)?;
+                            let val = val.as_field_type(fldtype)?;
+
+                            let idx = ii.open();
+                            idx.lock().unwrap().delete(oldval, rid)?;
+                            idx.lock().unwrap().insert(val, rid)?;
+                            idx.lock().unwrap().close()?;
+                        }
+                    }
                     count += 1;
                 }
                 us.close()?;
@@ -86,22 +178,63 @@ impl UpdatePlanner for BasicUpdatePlanner {
         Err(From::from(BasicUpdatePlannerError::ModifyAbort))
     }
     fn execute_insert(&self, data: InsertData, tx: Arc<Mutex<Transaction>>) -> Result<i32> {
+        let constraints = self
+            .mdm
+            .lock()
+            .unwrap()
+            .get_field_constraints(data.table_name(), Arc::clone(&tx))?;
+        let data = fill_defaults(data, &constraints)?;
+        let tblname = data.table_name();
         let p = Arc::new(TablePlan::new(
-            data.table_name(),
-            tx,
+            tblname,
+            Arc::clone(&tx),
             Arc::clone(&self.mdm),
         )?);
+        let sch = p.schema();
+        let mut mdm = self.mdm.lock().unwrap();
+        let keys = mdm.get_keys(tblname, Arc::clone(&tx))?;
+        let indexes = key_indexes(&mut mdm, tblname, Arc::clone(&tx))?;
+        let fks = mdm.get_foreign_keys(tblname, Arc::clone(&tx))?;
         if let Ok(s) = p.open() {
             if let Ok(us) = s.lock().unwrap().to_update_scan() {
-                us.insert()?;
-                let mut iter = data.vals().iter();
-                for fldname in data.fields() {
-                    if let Some(val) = iter.next() {
-                        us.set_val(fldname, val.clone())?;
+                for vals in data.rows() {
+                    // the constraints and the keys are checked before the record is inserted
+                    let record =
+                        record_values(&sch, HashMap::new(), data.fields().iter().zip(vals))?;
+                    check_constraints(tblname, &constraints, record)?;
+                    let val_of = |fldname: &str| {
+                        data.fields()
+                            .iter()
+                            .position(|f| f == fldname)
+                            .map_or(Constant::Null, |i| vals[i].clone())
+                    };
+                    for key in keys.iter() {
+                        check_key(tblname, key, &indexes, &val_of(key.field_name()), None)?;
+                    }
+                    for fk in fks.iter() {
+                        check_reference(&mut mdm, fk, &val_of(fk.field_name()), Arc::clone(&tx))?;
+                    }
+                    us.insert()?;
+                    let rid = us.get_rid()?;
+                    let mut iter = vals.iter();
+                    for fldname in data.fields() {
+                        if let Some(val) = iter.next() {
+                            us.set_val(fldname, val.clone())?;
+                            // the indexes of the keys are kept in sync with the table
+                            if let Some(ii) = indexes.get(fldname) {
+                                // NOTE: convert the type here, because Index doesn't convert val.
+                                let fldtype = ii.table_schema().field_type(fldname);
+                                let val = val.as_field_type(fldtype)?;
+
+                                let idx = ii.open();
+                                idx.lock().unwrap().insert(val, rid)?;
+                                idx.lock().unwrap().close()?;
+                            }
+                        }
                     }
                 }
                 us.close()?;
-                return Ok(1);
+                return Ok(data.rows().len() as i32);
             }
         }
         Err(From::from(BasicUpdatePlannerError::InsertAbort))
@@ -112,14 +245,38 @@ impl UpdatePlanner for BasicUpdatePlanner {
         tx: Arc<Mutex<Transaction>>,
     ) -> Result<i32> {
         let mdm = self.mdm.lock().unwrap();
-        mdm.create_table(data.table_name(), Arc::new(data.new_schema().clone()), tx)?;
+        mdm.create_table(
+            data.table_name(),
+            Arc::new(data.new_schema().clone()),
+            Arc::clone(&tx),
+        )?;
+        for key in data.keys() {
+            mdm.create_key(
+                data.table_name(),
+                key.field_name(),
+                key.is_primary(),
+                Arc::clone(&tx),
+            )?;
+        }
+        for fc in data.constraints() {
+            mdm.create_field_constraint(data.table_name(), fc, Arc::clone(&tx))?;
+        }
+        for fk in data.foreign_keys() {
+            mdm.create_foreign_key(data.table_name(), fk, Arc::clone(&tx))?;
+        }
         Ok(0)
     }
     fn execute_create_view(
         &self,
-        data: CreateViewData,
+        mut data: CreateViewData,
         tx: Arc<Mutex<Transaction>>,
     ) -> Result<i32> {
+        // the view keeps the fields at the creation, even if the tables are altered.
+        expand_wildcards(
+            data.query_data_mut(),
+            Arc::clone(&self.mdm),
+            Arc::clone(&tx),
+        )?;
         let mdm = self.mdm.lock().unwrap();
         mdm.create_view(data.view_name(), &data.view_def(), tx)?;
         Ok(0)
@@ -133,6 +290,47 @@ impl UpdatePlanner for BasicUpdatePlanner {
         mdm.create_index(data.index_name(), data.table_name(), data.field_name(), tx)?;
         Ok(0)
     }
+    fn execute_drop_table(&self, data: DropData, tx: Arc<Mutex<Transaction>>) -> Result<i32> {
+        let mut mdm = self.mdm.lock().unwrap();
+        if data.if_exists() && !mdm.has_table(data.name(), Arc::clone(&tx))? {
+            return Ok(0);
+        }
+        mdm.drop_table(data.name(), tx)?;
+        Ok(0)
+    }
+    fn execute_drop_view(&self, data: DropData, tx: Arc<Mutex<Transaction>>) -> Result<i32> {
+        let mdm = self.mdm.lock().unwrap();
+        if data.if_exists() && !mdm.has_view(data.name(), Arc::clone(&tx))? {
+            return Ok(0);
+        }
+        mdm.drop_view(data.name(), tx)?;
+        Ok(0)
+    }
+    fn execute_drop_index(&self, data: DropData, tx: Arc<Mutex<Transaction>>) -> Result<i32> {
+        let mdm = self.mdm.lock().unwrap();
+        if data.if_exists() && !mdm.has_index(data.name(), Arc::clone(&tx))? {
+            return Ok(0);
+        }
+        mdm.drop_index(data.name(), tx)?;
+        Ok(0)
+    }
+    fn execute_alter_table(
+        &self,
+        data: AlterTableData,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<i32> {
+        let mut mdm = self.mdm.lock().unwrap();
+        let tblname = data.table_name();
+        match data.action() {
+            AlterAction::AddColumn(fldname, fi) => mdm.add_field(tblname, fldname, *fi, tx)?,
+            AlterAction::DropColumn(fldname) => mdm.drop_field(tblname, fldname, tx)?,
+            AlterAction::RenameColumn(fldname, newname) => {
+                mdm.rename_field(tblname, fldname, newname, tx)?
+            }
+            AlterAction::RenameTable(newname) => mdm.rename_table(tblname, newname, tx)?,
+        }
+        Ok(0)
+    }
 }
 
 impl BasicUpdatePlanner {
@@ -140,3 +338,150 @@ impl BasicUpdatePlanner {
         Self { mdm }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use super::*;
+    use crate::{
+        metadata::constraintmanager::ConstraintError,
+        plan::{basicqueryplanner::BasicQueryPlanner, planner::Planner},
+        server::simpledb::SimpleDB,
+    };
+
+    fn values(
+        planner: &mut Planner,
+        query: &str,
+        fldname: &str,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<Vec<Constant>> {
+        let plan = planner.create_query_plan(query, tx)?;
+        let scan = plan.open()?;
+        let mut vals = vec![];
+        let mut iter = scan.lock().unwrap();
+        while iter.next() {
+            vals.push(iter.get_val(fldname)?);
+        }
+        iter.close()?;
+
+        Ok(vals)
+    }
+
+    #[test]
+    fn unit_test() -> Result<()> {
+        if Path::new("_test/basicupdateplanner").exists() {
+            fs::remove_dir_all("_test/basicupdateplanner")?;
+        }
+
+        let simpledb = SimpleDB::new("_test/basicupdateplanner")?;
+        let tx = Arc::new(Mutex::new(simpledb.new_tx()?));
+        let mdm = simpledb.metadata_mgr().unwrap();
+        let qp = BasicQueryPlanner::new(Arc::new(Mutex::new(0)), Arc::clone(&mdm));
+        let up = BasicUpdatePlanner::new(Arc::clone(&mdm));
+        let mut planner = Planner::new(Arc::new(Mutex::new(qp)), Arc::new(Mutex::new(up)));
+
+        // PRIMARY KEY and UNIQUE
+        let sqls = vec![
+            "CREATE TABLE MEMBERS (MId integer PRIMARY KEY, Email varchar(16), UNIQUE (Email));",
+            "INSERT INTO MEMBERS (MId, Email) VALUES (1, 'joe@a.com'), (2, NULL), (3, NULL);",
+            "UPDATE MEMBERS SET MId = 4 WHERE MId = 3;",
+            "DELETE FROM MEMBERS WHERE MId = 2;",
+        ];
+        for sql in sqls {
+            planner.execute_update(sql, Arc::clone(&tx))?;
+        }
+        let violations = vec![
+            (
+                "INSERT INTO MEMBERS (MId, Email) VALUES (1, 'bob@a.com');",
+                "duplicate key: MEMBERS.MId = 1",
+            ),
+            (
+                "INSERT INTO MEMBERS (Email) VALUES ('bob@a.com');",
+                "primary key can't be null: MEMBERS.MId",
+            ),
+            (
+                "INSERT INTO MEMBERS (MId, Email) VALUES (5, 'joe@a.com');",
+                "duplicate key: MEMBERS.Email = 'joe@a.com'",
+            ),
+            (
+                "UPDATE MEMBERS SET MId = 1 WHERE MId = 4;",
+                "duplicate key: MEMBERS.MId = 1",
+            ),
+        ];
+        for (sql, msg) in violations {
+            let err = planner.execute_update(sql, Arc::clone(&tx)).unwrap_err();
+            let violation = err.downcast_ref::<ConstraintError>().map(|e| e.to_string());
+            assert_eq!(violation, Some(msg.to_string()));
+        }
+        // the indexes of the keys are in sync, so the other planner sees the same keys
+        let mut index_planner = simpledb.planner()?;
+        let sql = "INSERT INTO MEMBERS (MId) VALUES (4);";
+        assert!(index_planner.execute_update(sql, Arc::clone(&tx)).is_err());
+        let sql = "INSERT INTO MEMBERS (MId) VALUES (2);";
+        index_planner.execute_update(sql, Arc::clone(&tx))?;
+        let query = "SELECT MId FROM MEMBERS;";
+        let ids = values(&mut planner, query, "MId", Arc::clone(&tx))?;
+        assert_eq!(
+            ids,
+            vec![Constant::I32(1), Constant::I32(2), Constant::I32(4)]
+        );
+
+        // FOREIGN KEY
+        let sqls = vec![
+            "CREATE TABLE DEPTS (DId integer PRIMARY KEY, DName varchar(10));",
+            "CREATE TABLE PUPILS (PId integer PRIMARY KEY, MajorId integer REFERENCES DEPTS (DId) ON DELETE SET NULL);",
+            "CREATE TABLE TAKES (TId integer, PupilId integer REFERENCES PUPILS (PId) ON DELETE CASCADE);",
+            "CREATE TABLE ADVISES (AId integer, DeptId integer REFERENCES DEPTS (DId));",
+            "INSERT INTO DEPTS (DId, DName) VALUES (10, 'compsci'), (20, 'math'), (30, 'drama');",
+            "INSERT INTO PUPILS (PId, MajorId) VALUES (1, 10), (2, 20), (3, NULL);",
+            "INSERT INTO TAKES (TId, PupilId) VALUES (1, 1), (2, 1), (3, 2);",
+            "INSERT INTO ADVISES (AId, DeptId) VALUES (1, 30);",
+            "UPDATE PUPILS SET MajorId = 20 WHERE PId = 3;",
+        ];
+        for sql in sqls {
+            planner.execute_update(sql, Arc::clone(&tx))?;
+        }
+        let violations = vec![
+            (
+                "INSERT INTO PUPILS (PId, MajorId) VALUES (4, 40);",
+                "referenced key not found: PUPILS.MajorId = 40",
+            ),
+            (
+                "UPDATE PUPILS SET MajorId = 40 WHERE PId = 1;",
+                "referenced key not found: PUPILS.MajorId = 40",
+            ),
+            (
+                "UPDATE DEPTS SET DId = 40 WHERE DId = 30;",
+                "key is referenced by ADVISES: DEPTS.DId = 30",
+            ),
+        ];
+        for (sql, msg) in violations {
+            let err = planner.execute_update(sql, Arc::clone(&tx)).unwrap_err();
+            let violation = err.downcast_ref::<ConstraintError>().map(|e| e.to_string());
+            assert_eq!(violation, Some(msg.to_string()));
+        }
+        // CASCADE
+        planner.execute_update("DELETE FROM PUPILS WHERE PId = 1;", Arc::clone(&tx))?;
+        let tids = values(
+            &mut planner,
+            "SELECT TId FROM TAKES;",
+            "TId",
+            Arc::clone(&tx),
+        )?;
+        assert_eq!(tids, vec![Constant::I32(3)]);
+        // SET NULL
+        planner.execute_update("DELETE FROM DEPTS WHERE DId = 20;", Arc::clone(&tx))?;
+        let query = "SELECT MajorId FROM PUPILS;";
+        let majors = values(&mut planner, query, "MajorId", Arc::clone(&tx))?;
+        assert_eq!(majors, vec![Constant::Null, Constant::Null]);
+        // RESTRICT
+        let sql = "DELETE FROM DEPTS WHERE DId = 30;";
+        let err = planner.execute_update(sql, Arc::clone(&tx)).unwrap_err();
+        assert!(err.downcast_ref::<ConstraintError>().is_some());
+
+        tx.lock().unwrap().commit()?;
+
+        Ok(())
+    }
+}
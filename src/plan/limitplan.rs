@@ -0,0 +1,84 @@
+use anyhow::Result;
+use std::sync::{Arc, Mutex};
+
+use super::plan::Plan;
+use crate::{
+    query::{limitscan::LimitScan, scan::Scan},
+    record::schema::Schema,
+    repr::planrepr::{Operation, PlanRepr},
+};
+
+#[derive(Clone)]
+pub struct LimitPlan {
+    p: Arc<dyn Plan>,
+    limit: usize,
+    offset: usize,
+}
+
+impl Plan for LimitPlan {
+    fn open(&self) -> Result<Arc<Mutex<dyn Scan>>> {
+        let s = self.p.open()?;
+        Ok(Arc::new(Mutex::new(LimitScan::new(
+            s,
+            self.limit,
+            self.offset,
+        ))))
+    }
+    fn blocks_accessed(&self) -> i32 {
+        self.p.blocks_accessed()
+    }
+    fn records_output(&self) -> i32 {
+        let offset = i32::try_from(self.offset).unwrap_or(i32::MAX);
+        let limit = i32::try_from(self.limit).unwrap_or(i32::MAX);
+        (self.p.records_output() - offset).clamp(0, limit)
+    }
+    fn distinct_values(&self, fldname: &str) -> i32 {
+        self.p.distinct_values(fldname).min(self.records_output())
+    }
+    fn schema(&self) -> Arc<Schema> {
+        self.p.schema()
+    }
+
+    fn repr(&self) -> Arc<dyn PlanRepr> {
+        Arc::new(LimitPlanRepr {
+            p: self.p.repr(),
+            limit: self.limit,
+            offset: self.offset,
+            r: self.blocks_accessed(),
+            w: self.records_output(),
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct LimitPlanRepr {
+    p: Arc<dyn PlanRepr>,
+    limit: usize,
+    offset: usize,
+    r: i32,
+    w: i32,
+}
+
+impl PlanRepr for LimitPlanRepr {
+    fn operation(&self) -> Operation {
+        Operation::LimitScan {
+            limit: self.limit,
+            offset: self.offset,
+        }
+    }
+    fn reads(&self) -> i32 {
+        self.r
+    }
+    fn writes(&self) -> i32 {
+        self.w
+    }
+    fn sub_plan_reprs(&self) -> Vec<Arc<dyn PlanRepr>> {
+        vec![Arc::clone(&self.p)]
+    }
+}
+
+impl LimitPlan {
+    pub fn new(p: Arc<dyn Plan>, limit: usize, offset: usize) -> Self {
+        Self { p, limit, offset }
+    }
+}
@@ -0,0 +1,96 @@
+use anyhow::Result;
+use std::{
+    cmp::max,
+    sync::{Arc, Mutex},
+};
+
+use super::{plan::Plan, productplan::ProductPlan};
+use crate::{
+    query::{outerjoinscan::OuterJoinScan, predicate::Predicate, scan::Scan},
+    record::schema::Schema,
+    repr::planrepr::{Operation, PlanRepr},
+};
+
+// The nested loop left outer join of p1 and p2.
+#[derive(Clone)]
+pub struct OuterJoinPlan {
+    p1: Arc<dyn Plan>,
+    p2: Arc<dyn Plan>,
+    pred: Predicate,
+    // for estimating the inner join
+    product: Arc<dyn Plan>,
+}
+
+impl Plan for OuterJoinPlan {
+    fn open(&self) -> Result<Arc<Mutex<dyn Scan>>> {
+        let s1 = self.p1.open()?;
+        let s2 = self.p2.open()?;
+        let scan = OuterJoinScan::new(s1, s2, self.pred.clone(), self.p2.schema())?;
+
+        Ok(Arc::new(Mutex::new(scan)))
+    }
+    fn blocks_accessed(&self) -> i32 {
+        self.product.blocks_accessed()
+    }
+    fn records_output(&self) -> i32 {
+        // every record of p1 is output at least once
+        let joined =
+            self.product.records_output() / self.pred.reduction_factor(Arc::clone(&self.product));
+        max(self.p1.records_output(), joined)
+    }
+    fn distinct_values(&self, fldname: &str) -> i32 {
+        self.product.distinct_values(fldname)
+    }
+    fn schema(&self) -> Arc<Schema> {
+        self.product.schema()
+    }
+
+    fn repr(&self) -> Arc<dyn PlanRepr> {
+        Arc::new(OuterJoinPlanRepr {
+            p1: self.p1.repr(),
+            p2: self.p2.repr(),
+            pred: self.pred.clone(),
+            r: self.blocks_accessed(),
+            w: self.records_output(),
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct OuterJoinPlanRepr {
+    p1: Arc<dyn PlanRepr>,
+    p2: Arc<dyn PlanRepr>,
+    pred: Predicate,
+    r: i32,
+    w: i32,
+}
+
+impl PlanRepr for OuterJoinPlanRepr {
+    fn operation(&self) -> Operation {
+        Operation::OuterJoinScan {
+            pred: self.pred.clone(),
+        }
+    }
+    fn reads(&self) -> i32 {
+        self.r
+    }
+    fn writes(&self) -> i32 {
+        self.w
+    }
+    fn sub_plan_reprs(&self) -> Vec<Arc<dyn PlanRepr>> {
+        vec![Arc::clone(&self.p1), Arc::clone(&self.p2)]
+    }
+}
+
+impl OuterJoinPlan {
+    pub fn new(p1: Arc<dyn Plan>, p2: Arc<dyn Plan>, pred: Predicate) -> Self {
+        let product = Arc::new(ProductPlan::new(Arc::clone(&p1), Arc::clone(&p2)));
+
+        Self {
+            p1,
+            p2,
+            pred,
+            product,
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use anyhow::Result;
+use std::sync::{Arc, Mutex};
+
+use super::plan::Plan;
+use crate::{
+    parser::querydata::QueryData,
+    query::{outerrecordscan::OuterRecordScan, scan::Scan, subquery::OuterRecord},
+    record::schema::Schema,
+    repr::planrepr::{Operation, PlanRepr},
+};
+
+// The fields of the outer query, which a correlated subquery refers to.
+// The planner joins it to the tables of the subquery.
+#[derive(Clone)]
+pub struct OuterRecordPlan {
+    outer: OuterRecord,
+    sch: Arc<Schema>,
+}
+
+impl Plan for OuterRecordPlan {
+    fn open(&self) -> Result<Arc<Mutex<dyn Scan>>> {
+        let scan = OuterRecordScan::new(Arc::clone(&self.outer), Arc::clone(&self.sch));
+        Ok(Arc::new(Mutex::new(scan)))
+    }
+    fn blocks_accessed(&self) -> i32 {
+        0
+    }
+    fn records_output(&self) -> i32 {
+        1
+    }
+    fn distinct_values(&self, _fldname: &str) -> i32 {
+        1
+    }
+    fn schema(&self) -> Arc<Schema> {
+        Arc::clone(&self.sch)
+    }
+
+    fn repr(&self) -> Arc<dyn PlanRepr> {
+        Arc::new(OuterRecordPlanRepr {
+            fields: self.sch.fields().clone(),
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct OuterRecordPlanRepr {
+    fields: Vec<String>,
+}
+
+impl PlanRepr for OuterRecordPlanRepr {
+    fn operation(&self) -> Operation {
+        Operation::OuterRecordScan {
+            fields: self.fields.clone(),
+        }
+    }
+    fn reads(&self) -> i32 {
+        0
+    }
+    fn writes(&self) -> i32 {
+        1
+    }
+    fn sub_plan_reprs(&self) -> Vec<Arc<dyn PlanRepr>> {
+        vec![]
+    }
+}
+
+impl OuterRecordPlan {
+    // the fields of the subquery, which aren't in its tables, are read from the outer
+    // record with the schema env, e.g. SId of "exists (select EId from ENROLL where StudentId = SId)".
+    // returns None, if the subquery isn't correlated.
+    pub fn new(
+        data: &QueryData,
+        inner: Arc<Schema>,
+        env: Arc<Schema>,
+        outer: OuterRecord,
+    ) -> Option<Self> {
+        let mut sch = Schema::new();
+        for fldname in data.field_names() {
+            if !inner.has_field(&fldname) && env.has_field(&fldname) && !sch.has_field(&fldname) {
+                sch.add_field(&fldname, env.field_type(&fldname), env.length(&fldname));
+            }
+        }
+        if sch.fields().is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            outer,
+            sch: Arc::new(sch),
+        })
+    }
+}
@@ -1,19 +1,21 @@
 use anyhow::Result;
-use combine::Parser;
 use core::fmt;
-use log::warn;
 use std::sync::{Arc, Mutex};
 
 use super::{plan::Plan, queryplanner::QueryPlanner, updateplanner::UpdatePlanner};
 use crate::{
-    parser::parser::{query, update_cmd},
-    parser::{ddl::DDL, dml::DML, sql::SQL},
+    parser::parser::{query_expr, update_cmd},
+    parser::syntaxerror::parse_sql,
+    parser::{ddl::DDL, dml::DML, insertdata::InsertData, queryexpr::QueryExpr, sql::SQL},
+    query::constant::Constant,
     tx::transaction::Transaction,
 };
 
 #[derive(Debug)]
 pub enum PlannerError {
     InvalidExecuteCommand,
+    UnsupportedSubquery,
+    InsertFieldCountMismatch,
 }
 
 impl std::error::Error for PlannerError {}
@@ -23,6 +25,12 @@ impl fmt::Display for PlannerError {
             PlannerError::InvalidExecuteCommand => {
                 write!(f, "invalid execute command")
             }
+            PlannerError::UnsupportedSubquery => {
+                write!(f, "subquery can't be used in an update command")
+            }
+            PlannerError::InsertFieldCountMismatch => {
+                write!(f, "each inserted record must have a value for each field")
+            }
         }
     }
 }
@@ -45,31 +53,52 @@ impl Planner {
         cmd: &str,
         tx: Arc<Mutex<Transaction>>,
     ) -> Result<Arc<dyn Plan>> {
-        let mut parser = query();
-        let (data, rest) = parser.parse(cmd)?;
-        if !rest.trim().is_empty() {
-            warn!("parser cant parse full text: {}", rest);
-        }
+        let expr = parse_sql(query_expr(), cmd)?;
         // TODO: code to verify the query should be here...
-        self.qplanner.lock().unwrap().create_plan(data, tx)
+        self.qplanner
+            .lock()
+            .unwrap()
+            .create_query_expr_plan(expr, tx)
     }
     pub fn execute_update(&mut self, cmd: &str, tx: Arc<Mutex<Transaction>>) -> Result<i32> {
-        let mut parser = update_cmd();
-        let (data, rest) = parser.parse(cmd)?;
-        if !rest.trim().is_empty() {
-            warn!("parser cant parse full text: {}", rest);
-        }
+        let data = parse_sql(update_cmd(), cmd)?;
         match data {
             SQL::DML(dml) => match dml {
                 DML::Insert(idata) => {
+                    let idata = match idata.query() {
+                        Some(query) => {
+                            let rows = self.read_rows(query.clone(), Arc::clone(&tx))?;
+                            let tblname = idata.table_name().to_string();
+                            InsertData::new_rows(tblname, idata.fields().clone(), rows)
+                        }
+                        None => idata,
+                    };
+                    if idata
+                        .rows()
+                        .iter()
+                        .any(|vals| vals.len() != idata.fields().len())
+                    {
+                        return Err(From::from(PlannerError::InsertFieldCountMismatch));
+                    }
                     let planner = self.uplanner.lock().unwrap();
                     return planner.execute_insert(idata, tx);
                 }
                 DML::Delete(ddata) => {
+                    if !ddata.pred().subqueries().is_empty() {
+                        return Err(From::from(PlannerError::UnsupportedSubquery));
+                    }
                     let planner = self.uplanner.lock().unwrap();
                     return planner.execute_delete(ddata, tx);
                 }
                 DML::Modify(mdata) => {
+                    if !mdata.pred().subqueries().is_empty()
+                        || mdata
+                            .assignments()
+                            .iter()
+                            .any(|(_, expr)| !expr.subqueries().is_empty())
+                    {
+                        return Err(From::from(PlannerError::UnsupportedSubquery));
+                    }
                     let planner = self.uplanner.lock().unwrap();
                     return planner.execute_modify(mdata, tx);
                 }
@@ -77,6 +106,12 @@ impl Planner {
             },
             SQL::DDL(ddl) => match ddl {
                 DDL::Table(ctdata) => {
+                    if ctdata.constraints().iter().any(|fc| {
+                        fc.check()
+                            .map_or(false, |pred| !pred.subqueries().is_empty())
+                    }) {
+                        return Err(From::from(PlannerError::UnsupportedSubquery));
+                    }
                     let p = self.uplanner.lock().unwrap();
                     return p.execute_create_table(ctdata, tx);
                 }
@@ -88,29 +123,82 @@ impl Planner {
                     let p = self.uplanner.lock().unwrap();
                     return p.execute_create_index(cidata, tx);
                 }
+                DDL::DropTable(ddata) => {
+                    let p = self.uplanner.lock().unwrap();
+                    return p.execute_drop_table(ddata, tx);
+                }
+                DDL::DropView(ddata) => {
+                    let p = self.uplanner.lock().unwrap();
+                    return p.execute_drop_view(ddata, tx);
+                }
+                DDL::DropIndex(ddata) => {
+                    let p = self.uplanner.lock().unwrap();
+                    return p.execute_drop_index(ddata, tx);
+                }
+                DDL::AlterTable(adata) => {
+                    let p = self.uplanner.lock().unwrap();
+                    return p.execute_alter_table(adata, tx);
+                }
             },
         }
     }
+    // reads all records of the query before the insertion,
+    // so that a query on the same table doesn't read the inserted records.
+    fn read_rows(
+        &mut self,
+        query: QueryExpr,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<Vec<Vec<Constant>>> {
+        let plan = self
+            .qplanner
+            .lock()
+            .unwrap()
+            .create_query_expr_plan(query, tx)?;
+        let sch = plan.schema();
+        let scan = plan.open()?;
+        let mut scan = scan.lock().unwrap();
+        let mut rows = vec![];
+        while scan.next() {
+            let mut vals = vec![];
+            for fldname in sch.fields() {
+                vals.push(scan.get_val(fldname)?);
+            }
+            rows.push(vals);
+        }
+        scan.close()?;
+
+        Ok(rows)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use anyhow::Result;
+    use chrono::NaiveDate;
     use std::sync::{Arc, Mutex};
     use std::{fs, path::Path};
 
-    use crate::server::simpledb::SimpleDB;
+    use crate::{
+        metadata::constraintmanager::ConstraintError, parser::syntaxerror::SqlSyntaxError,
+        plan::planner::Planner, query::constant::Constant, record::schema::FieldType,
+        repr::planrepr::Operation, server::simpledb::SimpleDB, tx::transaction::Transaction,
+    };
 
-    #[test]
-    fn unit_test() -> Result<()> {
-        if Path::new("_test/planner").exists() {
-            fs::remove_dir_all("_test/planner")?;
+    fn new_db(dir: &str) -> Result<(SimpleDB, Arc<Mutex<Transaction>>, Planner)> {
+        if Path::new(dir).exists() {
+            fs::remove_dir_all(dir)?;
         }
 
-        let simpledb = SimpleDB::new("_test/planner")?;
+        let simpledb = SimpleDB::new(dir)?;
 
         let tx = Arc::new(Mutex::new(simpledb.new_tx()?));
-        let mut planner = simpledb.planner()?;
+        let planner = simpledb.planner()?;
+
+        Ok((simpledb, tx, planner))
+    }
+
+    fn school_db(dir: &str) -> Result<(SimpleDB, Arc<Mutex<Transaction>>, Planner)> {
+        let (simpledb, tx, mut planner) = new_db(dir)?;
 
         // Setting Schema and Insert Init data
         let sqls = vec![
@@ -163,6 +251,29 @@ mod tests {
             println!("Done");
         }
 
+        Ok((simpledb, tx, planner))
+    }
+
+    fn scores_db(dir: &str) -> Result<(SimpleDB, Arc<Mutex<Transaction>>, Planner)> {
+        let (simpledb, tx, mut planner) = new_db(dir)?;
+
+        let sqls = vec![
+            "CREATE TABLE SCORES (ScId integer, Points integer);",
+            "CREATE INDEX idx_scores_pts ON SCORES (Points);",
+            "INSERT INTO SCORES (ScId, Points) \
+             VALUES (1, 10), (2, 20), (3, 30), (4, 20), (5, NULL), (6, 40);",
+        ];
+        for sql in sqls {
+            planner.execute_update(sql, Arc::clone(&tx))?;
+        }
+
+        Ok((simpledb, tx, planner))
+    }
+
+    #[test]
+    fn unit_test() -> Result<()> {
+        let (_simpledb, tx, mut planner) = school_db("_test/planner")?;
+
         // SELECT Table
         let query = "SELECT SName, DName, GradYear FROM STUDENT, DEPT WHERE MajorId = DId;";
         println!("Query: {}", query);
@@ -244,6 +355,19 @@ mod tests {
         iter.close()?;
         println!("Rows = {}", rows);
 
+        // UPDATE with arithmetic
+        let update = "UPDATE STUDENT SET GradYear = GradYear + 1 WHERE SId = 1;";
+        print!("Execute: {} ... ", update);
+        let c = planner.execute_update(update, Arc::clone(&tx))?;
+        println!("Affected rows = {}", c);
+        let query = "SELECT GradYear FROM STUDENT WHERE SId = 1;";
+        let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+        let scan = plan.open()?;
+        let mut iter = scan.lock().unwrap();
+        assert!(iter.next());
+        assert_eq!(iter.get_i32("GradYear")?, 2022);
+        iter.close()?;
+
         // DELETE
         let update = "DELETE FROM STUDENT WHERE MajorId = 30;";
         print!("Execute: {} ... ", update);
@@ -274,4 +398,1566 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn where_test() -> Result<()> {
+        let (_simpledb, tx, mut planner) = school_db("_test/plannerwhere")?;
+
+        // SELECT Table with range
+        let query =
+            "SELECT SName, GradYear FROM STUDENT WHERE GradYear >= 2021 AND SName <> 'max';";
+        println!("Query: {}", query);
+        let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+        let scan = plan.open()?;
+        let mut rows = 0;
+        let mut iter = scan.lock().unwrap();
+        println!("SName     GradYear");
+        println!("------------------");
+        while iter.next() {
+            rows += 1;
+            let name = iter.get_string("SName")?;
+            let year = iter.get_i32("GradYear")?;
+            assert!(year >= 2021);
+            assert_ne!(name, "max");
+            println!("{:<10}{:>8}", name, year);
+        }
+        iter.close()?;
+        println!("Rows = {}", rows);
+        assert_eq!(rows, 4);
+
+        // SELECT Table with disjunction
+        let query = "SELECT SName FROM STUDENT WHERE MajorId = 10 OR (GradYear = 2020 AND NOT SName = 'kim');";
+        println!("Query: {}", query);
+        let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+        let scan = plan.open()?;
+        let mut names = vec![];
+        let mut iter = scan.lock().unwrap();
+        while iter.next() {
+            names.push(iter.get_string("SName")?);
+        }
+        iter.close()?;
+        names.sort();
+        println!("SName = {:?}", names);
+        assert_eq!(names, vec!["amy", "bob", "joe", "lee", "max"]);
+
+        // SELECT Table with computed field
+        let query = "SELECT SName, GradYear - 2000 FROM STUDENT WHERE SId * 2 = 4;";
+        println!("Query: {}", query);
+        let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+        assert_eq!(
+            plan.schema().field_type("GradYear-2000"),
+            FieldType::INTEGER
+        );
+        let scan = plan.open()?;
+        let mut rows = 0;
+        let mut iter = scan.lock().unwrap();
+        while iter.next() {
+            rows += 1;
+            assert_eq!(iter.get_string("SName")?, "amy");
+            assert_eq!(iter.get_i32("GradYear-2000")?, 20);
+        }
+        iter.close()?;
+        assert_eq!(rows, 1);
+        // an error of the where clause stops the scan, and is returned by close
+        let queries = vec![
+            (
+                "SELECT SName FROM STUDENT WHERE SId / 0 = 1;",
+                "division by zero: SId/0",
+            ),
+            (
+                "SELECT SName FROM STUDENT WHERE SId * 1000000000 > 1;",
+                "arithmetic overflow: SId*1000000000",
+            ),
+            (
+                "SELECT Title FROM COURSE LEFT JOIN SECTION ON CId / 0 = CourseId;",
+                "division by zero: CId/0",
+            ),
+        ];
+        for (query, msg) in queries {
+            let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+            let scan = plan.open()?;
+            let mut iter = scan.lock().unwrap();
+            while iter.next() {}
+            let err = iter.close().unwrap_err();
+            assert_eq!(err.to_string(), msg);
+        }
+        let update = "DELETE FROM STUDENT WHERE SId / 0 = 1;";
+        assert!(planner.execute_update(update, Arc::clone(&tx)).is_err());
+
+        tx.lock().unwrap().commit()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn order_by_test() -> Result<()> {
+        let (_simpledb, tx, mut planner) = school_db("_test/plannerorder")?;
+
+        // SELECT Table with order by
+        let query = "SELECT SName, GradYear FROM STUDENT ORDER BY GradYear DESC, SName;";
+        println!("Query: {}", query);
+        let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+        let scan = plan.open()?;
+        let mut rows = vec![];
+        let mut iter = scan.lock().unwrap();
+        while iter.next() {
+            rows.push((iter.get_i32("GradYear")?, iter.get_string("SName")?));
+        }
+        iter.close()?;
+        println!("Rows = {:?}", rows);
+        let names = rows.iter().map(|(_, n)| n.as_str()).collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec!["max", "sue", "art", "joe", "lee", "amy", "bob", "kim", "pat"]
+        );
+
+        // SELECT Table with order by a field which isn't projected
+        let query = "SELECT SName FROM STUDENT WHERE MajorId = 10 ORDER BY SId DESC;";
+        println!("Query: {}", query);
+        let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+        let scan = plan.open()?;
+        let mut names = vec![];
+        let mut iter = scan.lock().unwrap();
+        while iter.next() {
+            names.push(iter.get_string("SName")?);
+        }
+        iter.close()?;
+        assert_eq!(names, vec!["lee", "max", "joe"]);
+
+        // SELECT Table with order by on empty result
+        let query = "SELECT SName FROM STUDENT WHERE MajorId = 99 ORDER BY SName;";
+        let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+        let scan = plan.open()?;
+        let mut iter = scan.lock().unwrap();
+        assert!(!iter.next());
+        iter.close()?;
+
+        // SELECT Table with limit and offset, sorted in memory
+        let query = "SELECT SName FROM STUDENT ORDER BY GradYear DESC, SName LIMIT 3 OFFSET 1;";
+        println!("Query: {}", query);
+        let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+        assert!(plan.records_output() <= 3);
+        let scan = plan.open()?;
+        let mut names = vec![];
+        let mut iter = scan.lock().unwrap();
+        while iter.next() {
+            names.push(iter.get_string("SName")?);
+        }
+        iter.close()?;
+        assert_eq!(names, vec!["sue", "art", "joe"]);
+
+        // SELECT Table with limit only
+        let query = "SELECT SName FROM STUDENT LIMIT 2;";
+        let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+        let scan = plan.open()?;
+        let mut rows = 0;
+        let mut iter = scan.lock().unwrap();
+        while iter.next() {
+            rows += 1;
+        }
+        iter.close()?;
+        assert_eq!(rows, 2);
+
+        tx.lock().unwrap().commit()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn group_by_test() -> Result<()> {
+        let (_simpledb, tx, mut planner) = school_db("_test/plannergroup")?;
+
+        // SELECT Table with group by, aggregates and having
+        let query = "SELECT MajorId, COUNT(*), MIN(SName), AVG(GradYear) FROM STUDENT \
+                     GROUP BY MajorId HAVING COUNT(*) > 2 ORDER BY MajorId DESC;";
+        println!("Query: {}", query);
+        let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+        assert_eq!(plan.schema().field_type("min(SName)"), FieldType::VARCHAR);
+        assert_eq!(plan.schema().length("min(SName)"), 10);
+        let scan = plan.open()?;
+        let mut rows = vec![];
+        let mut iter = scan.lock().unwrap();
+        while iter.next() {
+            rows.push((
+                iter.get_i32("MajorId")?,
+                iter.get_i32("count(*)")?,
+                iter.get_string("min(SName)")?,
+                iter.get_i32("avg(GradYear)")?,
+            ));
+        }
+        iter.close()?;
+        assert_eq!(
+            rows,
+            vec![
+                (20, 4, "amy".to_string(), 2020),
+                (10, 3, "joe".to_string(), 2021)
+            ]
+        );
+
+        // SELECT Table with aggregates on empty result
+        let query = "SELECT COUNT(*), MAX(GradYear) FROM STUDENT WHERE MajorId = 99;";
+        let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+        let scan = plan.open()?;
+        let mut iter = scan.lock().unwrap();
+        assert!(iter.next());
+        assert_eq!(iter.get_i32("count(*)")?, 0);
+        assert!(iter.is_null("max(GradYear)")?);
+        assert!(!iter.next());
+        iter.close()?;
+
+        tx.lock().unwrap().commit()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn distinct_test() -> Result<()> {
+        let (_simpledb, tx, mut planner) = school_db("_test/plannerdistinct")?;
+
+        // SELECT DISTINCT Table
+        let query =
+            "SELECT DISTINCT MajorId FROM STUDENT WHERE GradYear > 2019 ORDER BY MajorId DESC;";
+        println!("Query: {}", query);
+        let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+        let scan = plan.open()?;
+        let mut ids = vec![];
+        let mut iter = scan.lock().unwrap();
+        while iter.next() {
+            ids.push(iter.get_i32("MajorId")?);
+        }
+        iter.close()?;
+        assert_eq!(ids, vec![30, 20, 10]);
+        // with distinct, the order by fields must be selected
+        let query = "SELECT DISTINCT MajorId FROM STUDENT ORDER BY SName;";
+        assert!(planner.create_query_plan(query, Arc::clone(&tx)).is_err());
+
+        tx.lock().unwrap().commit()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn join_test() -> Result<()> {
+        let (_simpledb, tx, mut planner) = school_db("_test/plannerjoin")?;
+
+        // SELECT Table joined with itself, by aliases
+        let query = "SELECT s1.SName AS name, s2.SName classmate FROM STUDENT s1, STUDENT AS s2 \
+                     WHERE s1.MajorId = s2.MajorId AND s1.SId < s2.SId AND s1.GradYear = 2022 \
+                     ORDER BY name, classmate;";
+        println!("Query: {}", query);
+        let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+        assert_eq!(plan.schema().fields(), &vec!["name", "classmate"]);
+        let scan = plan.open()?;
+        let mut rows = vec![];
+        let mut iter = scan.lock().unwrap();
+        while iter.next() {
+            rows.push((iter.get_string("name")?, iter.get_string("classmate")?));
+        }
+        iter.close()?;
+        assert_eq!(
+            rows,
+            vec![
+                ("max".to_string(), "lee".to_string()),
+                ("sue".to_string(), "kim".to_string()),
+                ("sue".to_string(), "pat".to_string())
+            ]
+        );
+
+        // SELECT Table with fields qualified by the table names
+        let query = "SELECT STUDENT.SName FROM STUDENT, DEPT \
+                     WHERE STUDENT.MajorId = DEPT.DId AND DName = 'drama' ORDER BY SId;";
+        let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+        let scan = plan.open()?;
+        let mut names = vec![];
+        let mut iter = scan.lock().unwrap();
+        while iter.next() {
+            names.push(iter.get_string("SName")?);
+        }
+        iter.close()?;
+        assert_eq!(names, vec!["bob", "art"]);
+        // the field of the other table may be in the upper bound or in a later value
+        let queries = vec![
+            (
+                "SELECT SName FROM STUDENT, DEPT \
+                 WHERE MajorId = DId AND SId BETWEEN 4 AND DId / 10 + 3 ORDER BY SId;",
+                vec!["sue", "bob"],
+            ),
+            (
+                "SELECT SName FROM STUDENT, DEPT \
+                 WHERE MajorId = DId AND SId IN (1, DId / 10) ORDER BY SId;",
+                vec!["joe", "amy"],
+            ),
+        ];
+        for (query, expected) in queries {
+            println!("Query: {}", query);
+            let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+            let scan = plan.open()?;
+            let mut names = vec![];
+            let mut iter = scan.lock().unwrap();
+            while iter.next() {
+                names.push(iter.get_string("SName")?);
+            }
+            iter.close()?;
+            assert_eq!(names, expected);
+        }
+        // an ambiguous field can't be selected
+        let query = "SELECT SName FROM STUDENT s1, STUDENT s2;";
+        assert!(planner.create_query_plan(query, Arc::clone(&tx)).is_err());
+        // nor be compared in the where clause, or in a join condition
+        let query = "SELECT s1.SName FROM STUDENT s1, STUDENT s2 WHERE SId = 1;";
+        let err = planner
+            .create_query_plan(query, Arc::clone(&tx))
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "ambiguous column: SId");
+        let query = "SELECT s1.SName FROM STUDENT s1 JOIN STUDENT s2 ON MajorId = s2.MajorId;";
+        assert!(planner.create_query_plan(query, Arc::clone(&tx)).is_err());
+
+        // SELECT Table with explicit joins
+        let query = "SELECT SName FROM STUDENT JOIN DEPT ON MajorId = DId \
+                     WHERE DName = 'drama' ORDER BY SId;";
+        let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+        let scan = plan.open()?;
+        let mut names = vec![];
+        let mut iter = scan.lock().unwrap();
+        while iter.next() {
+            names.push(iter.get_string("SName")?);
+        }
+        iter.close()?;
+        assert_eq!(names, vec!["bob", "art"]);
+
+        tx.lock().unwrap().commit()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn outer_join_test() -> Result<()> {
+        let (_simpledb, tx, mut planner) = school_db("_test/planneroj")?;
+
+        // the courses without sections are padded
+        let query = "SELECT Title, Prof FROM COURSE c LEFT JOIN SECTION ON c.CId = CourseId \
+                     ORDER BY CId, Prof;";
+        println!("Query: {}", query);
+        let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+        let scan = plan.open()?;
+        let mut rows = vec![];
+        let mut iter = scan.lock().unwrap();
+        while iter.next() {
+            rows.push(format!(
+                "{}:{}",
+                iter.get_string("Title")?,
+                iter.get_string("Prof")?
+            ));
+        }
+        iter.close()?;
+        assert_eq!(
+            rows,
+            vec![
+                "db systems:turing",
+                "db systems:turing",
+                "compilers:",
+                "calculus:einstein",
+                "calculus:newton",
+                "algebra:",
+                "acting:",
+                "elocution:brando"
+            ]
+        );
+        // the where clause is applied after the outer join
+        let query = "SELECT SName, Grade FROM ENROLL RIGHT OUTER JOIN STUDENT ON StudentId = SId \
+                     WHERE GradYear = 2020 ORDER BY SName;";
+        println!("Query: {}", query);
+        let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+        let scan = plan.open()?;
+        let mut rows = vec![];
+        let mut iter = scan.lock().unwrap();
+        while iter.next() {
+            rows.push(format!(
+                "{}:{}",
+                iter.get_string("SName")?,
+                iter.get_string("Grade")?
+            ));
+        }
+        iter.close()?;
+        assert_eq!(rows, vec!["amy:B+", "bob:", "kim:A"]);
+
+        tx.lock().unwrap().commit()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn subquery_test() -> Result<()> {
+        let (_simpledb, tx, mut planner) = school_db("_test/plannersubquery")?;
+
+        // SELECT with subqueries
+        let queries = vec![
+            (
+                "SELECT SName FROM STUDENT WHERE SId IN (SELECT StudentId FROM ENROLL) \
+                 ORDER BY SId;",
+                vec!["joe", "amy", "sue", "kim"],
+            ),
+            (
+                "SELECT SName FROM STUDENT WHERE EXISTS \
+                 (SELECT EId FROM ENROLL WHERE StudentId = SId AND Grade = 'A') ORDER BY SId;",
+                vec!["joe", "sue", "kim"],
+            ),
+            (
+                "SELECT SName FROM STUDENT WHERE GradYear = 2020 AND NOT EXISTS \
+                 (SELECT EId FROM ENROLL WHERE StudentId = SId);",
+                vec!["bob"],
+            ),
+        ];
+        for (query, expected) in queries {
+            println!("Query: {}", query);
+            let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+            let scan = plan.open()?;
+            let mut names = vec![];
+            let mut iter = scan.lock().unwrap();
+            while iter.next() {
+                names.push(iter.get_string("SName")?);
+            }
+            iter.close()?;
+            assert_eq!(names, expected);
+        }
+        // a scalar subquery is evaluated for each record
+        let query = "SELECT SName, (SELECT count(EId) FROM ENROLL WHERE StudentId = SId) AS n \
+                     FROM STUDENT WHERE MajorId = 20 ORDER BY SId;";
+        println!("Query: {}", query);
+        let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+        let scan = plan.open()?;
+        let mut rows = vec![];
+        let mut iter = scan.lock().unwrap();
+        while iter.next() {
+            rows.push(format!(
+                "{}:{}",
+                iter.get_string("SName")?,
+                iter.get_val("n")?
+            ));
+        }
+        iter.close()?;
+        assert_eq!(rows, vec!["amy:1", "sue:2", "kim:1", "pat:0"]);
+        // the plans of the subqueries are shown in the plan tree
+        let query = "SELECT SName FROM STUDENT WHERE SId IN (SELECT StudentId FROM ENROLL);";
+        let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+        let mut reprs = vec![plan.repr()];
+        let mut found = false;
+        while let Some(repr) = reprs.pop() {
+            if let Operation::TableScan { tblname, .. } = repr.operation() {
+                found |= tblname.eq_ignore_ascii_case("enroll");
+            }
+            reprs.extend(repr.sub_plan_reprs());
+        }
+        assert!(found);
+        // an error of a subquery is returned by the scan, which evaluates it
+        let queries = vec![
+            (
+                "SELECT SName FROM STUDENT WHERE SId = (SELECT StudentId FROM ENROLL);",
+                "subquery must return a single record",
+            ),
+            (
+                "SELECT SName FROM STUDENT WHERE SId IN \
+                 (SELECT StudentId FROM ENROLL WHERE EId / 0 = 1);",
+                "division by zero: EId/0",
+            ),
+            (
+                "SELECT SName FROM STUDENT WHERE EXISTS \
+                 (SELECT EId FROM ENROLL WHERE EId / 0 = 1);",
+                "division by zero: EId/0",
+            ),
+        ];
+        for (query, msg) in queries {
+            let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+            let scan = plan.open()?;
+            let mut iter = scan.lock().unwrap();
+            while iter.next() {}
+            let err = iter.close().unwrap_err();
+            assert_eq!(err.to_string(), msg);
+        }
+        // subqueries can't be used in update commands
+        let update = "DELETE FROM STUDENT WHERE SId IN (SELECT StudentId FROM ENROLL);";
+        assert!(planner.execute_update(update, Arc::clone(&tx)).is_err());
+
+        tx.lock().unwrap().commit()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn set_operation_test() -> Result<()> {
+        let (_simpledb, tx, mut planner) = school_db("_test/plannersetop")?;
+
+        // SELECT with set operations
+        let graduates = "SELECT SName FROM STUDENT WHERE GradYear = 2020";
+        let math = "SELECT SName FROM STUDENT WHERE MajorId = 20";
+        let queries = vec![
+            (
+                format!("{} UNION {};", graduates, math),
+                vec!["amy", "bob", "kim", "pat", "sue"],
+            ),
+            (
+                format!("{} INTERSECT {};", graduates, math),
+                vec!["amy", "kim"],
+            ),
+            (format!("{} EXCEPT {};", graduates, math), vec!["bob"]),
+            // INTERSECT is applied first
+            (
+                format!(
+                    "SELECT SName FROM STUDENT WHERE GradYear = 2019 UNION {} INTERSECT {};",
+                    graduates, math
+                ),
+                vec!["amy", "kim", "pat"],
+            ),
+            (
+                format!(
+                    "(SELECT SName FROM STUDENT WHERE GradYear = 2019 UNION {}) INTERSECT {};",
+                    graduates, math
+                ),
+                vec!["amy", "kim", "pat"],
+            ),
+        ];
+        for (query, expected) in queries {
+            println!("Query: {}", query);
+            let plan = planner.create_query_plan(&query, Arc::clone(&tx))?;
+            let scan = plan.open()?;
+            let mut names = vec![];
+            let mut iter = scan.lock().unwrap();
+            while iter.next() {
+                names.push(iter.get_string("SName")?);
+            }
+            iter.close()?;
+            assert_eq!(names, expected);
+        }
+        // the duplicates are kept
+        let query = format!("{} UNION ALL {};", graduates, math);
+        println!("Query: {}", query);
+        let plan = planner.create_query_plan(&query, Arc::clone(&tx))?;
+        let scan = plan.open()?;
+        let mut rows = 0;
+        let mut iter = scan.lock().unwrap();
+        while iter.next() {
+            rows += 1;
+        }
+        iter.close()?;
+        assert_eq!(rows, 7);
+        // the fields are matched by position, and named after the left query
+        let query = "SELECT SId FROM STUDENT EXCEPT SELECT StudentId FROM ENROLL;";
+        println!("Query: {}", query);
+        let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+        let scan = plan.open()?;
+        let mut ids = vec![];
+        let mut iter = scan.lock().unwrap();
+        while iter.next() {
+            ids.push(iter.get_i32("SId")?);
+        }
+        iter.close()?;
+        assert_eq!(ids, vec![3, 5, 7, 8, 9]);
+
+        // the field types must match
+        let query = "SELECT SId FROM STUDENT UNION SELECT SName FROM STUDENT;";
+        assert!(planner.create_query_plan(query, Arc::clone(&tx)).is_err());
+        let query = "SELECT SId, SName FROM STUDENT UNION SELECT SId FROM STUDENT;";
+        assert!(planner.create_query_plan(query, Arc::clone(&tx)).is_err());
+
+        tx.lock().unwrap().commit()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn wildcard_test() -> Result<()> {
+        let (simpledb, tx, mut planner) = school_db("_test/plannerwildcard")?;
+
+        // SELECT with wildcards
+        let queries = vec![
+            ("SELECT * FROM DEPT;", vec!["DId", "DName"]),
+            (
+                "SELECT s.*, DName FROM STUDENT s, DEPT WHERE MajorId = DId;",
+                vec!["SId", "SName", "GradYear", "MajorId", "DName"],
+            ),
+            (
+                "SELECT * FROM DEPT d1 JOIN DEPT d2 ON d1.DId = d2.DId;",
+                vec!["d1.DId", "d1.DName", "d2.DId", "d2.DName"],
+            ),
+            (
+                "SELECT * FROM name_dep;",
+                vec!["SName", "DName", "GradYear", "MajorId"],
+            ),
+        ];
+        for (query, expected) in queries {
+            println!("Query: {}", query);
+            let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+            assert_eq!(plan.schema().fields(), &expected);
+        }
+        let query = "SELECT * FROM DEPT ORDER BY DId;";
+        let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+        let scan = plan.open()?;
+        let mut rows = vec![];
+        let mut iter = scan.lock().unwrap();
+        while iter.next() {
+            rows.push(format!(
+                "{}:{}",
+                iter.get_i32("DId")?,
+                iter.get_string("DName")?
+            ));
+        }
+        iter.close()?;
+        assert_eq!(rows, vec!["10:compsci", "20:math", "30:drama"]);
+        // the view is stored with the expanded fields
+        let view = "CREATE VIEW all_dept AS SELECT * FROM DEPT;";
+        planner.execute_update(view, Arc::clone(&tx))?;
+        let (_, viewdef) = simpledb.get_view_definitoin("all_dept", Arc::clone(&tx))?;
+        assert_eq!(viewdef, "select DId, DName from DEPT");
+        // a view longer than the catalog can hold is rejected, and the others are kept
+        let view = "CREATE VIEW all_enroll AS SELECT * FROM STUDENT, ENROLL, SECTION;";
+        let err = planner.execute_update(view, Arc::clone(&tx)).unwrap_err();
+        assert_eq!(err.to_string(), "view definition too long: all_enroll");
+        let (_, viewdef) = simpledb.get_view_definitoin("all_dept", Arc::clone(&tx))?;
+        assert_eq!(viewdef, "select DId, DName from DEPT");
+        // a broken definition in the catalog is an error, not read up to the broken part
+        let mdm = simpledb.metadata_mgr().unwrap();
+        mdm.lock().unwrap().create_view(
+            "broken_dept",
+            "select DId, DName from DEPT) x",
+            Arc::clone(&tx),
+        )?;
+        let query = "SELECT DId FROM broken_dept;";
+        let err = planner
+            .create_query_plan(query, Arc::clone(&tx))
+            .err()
+            .unwrap();
+        assert!(err.to_string().starts_with("syntax error"));
+
+        tx.lock().unwrap().commit()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn insert_test() -> Result<()> {
+        let (_simpledb, tx, mut planner) = school_db("_test/plannerinsert")?;
+
+        // INSERT multiple records
+        let sqls = vec![
+            "CREATE TABLE ALUMNI (AId integer, AName varchar(10), GradYear integer);",
+            "CREATE INDEX idx_alumni_year ON ALUMNI (GradYear);",
+        ];
+        for sql in sqls {
+            planner.execute_update(sql, Arc::clone(&tx))?;
+        }
+        let updates = vec![
+            (
+                "INSERT INTO ALUMNI (AId, AName, GradYear) \
+                 VALUES (101, 'ann', 2018), (102, 'ben', 2019), (103, 'cat', 2020);",
+                3,
+            ),
+            (
+                "INSERT INTO ALUMNI (AId, AName, GradYear) \
+                 SELECT SId, SName, GradYear FROM STUDENT WHERE GradYear = 2020;",
+                3,
+            ),
+            // the inserted records are not read again
+            (
+                "INSERT INTO ALUMNI (AId, AName, GradYear) \
+                 SELECT AId + 100, AName, GradYear FROM ALUMNI WHERE GradYear >= 2019;",
+                5,
+            ),
+        ];
+        for (update, expected) in updates {
+            print!("Execute: {} ... ", update);
+            let c = planner.execute_update(update, Arc::clone(&tx))?;
+            println!("Affected rows = {}", c);
+            assert_eq!(c, expected);
+        }
+        // the index is maintained for each record
+        let query = "SELECT AId FROM ALUMNI WHERE GradYear = 2020 ORDER BY AId;";
+        println!("Query: {}", query);
+        let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+        let scan = plan.open()?;
+        let mut ids = vec![];
+        let mut iter = scan.lock().unwrap();
+        while iter.next() {
+            ids.push(iter.get_i32("AId")?);
+        }
+        iter.close()?;
+        assert_eq!(ids, vec![2, 5, 6, 102, 103, 105, 106, 203]);
+        // UPDATE multiple fields, with the values of the record before the update
+        let update = "UPDATE ALUMNI SET AId = GradYear, GradYear = AId WHERE AName = 'ben';";
+        print!("Execute: {} ... ", update);
+        let c = planner.execute_update(update, Arc::clone(&tx))?;
+        println!("Affected rows = {}", c);
+        assert_eq!(c, 2);
+        let queries = vec![
+            ("SELECT AId FROM ALUMNI WHERE GradYear = 102;", vec![2019]),
+            ("SELECT AId FROM ALUMNI WHERE GradYear = 202;", vec![2019]),
+            ("SELECT AId FROM ALUMNI WHERE GradYear = 2019;", vec![]),
+        ];
+        for (query, expected) in queries {
+            println!("Query: {}", query);
+            let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+            let scan = plan.open()?;
+            let mut ids = vec![];
+            let mut iter = scan.lock().unwrap();
+            while iter.next() {
+                ids.push(iter.get_i32("AId")?);
+            }
+            iter.close()?;
+            assert_eq!(ids, expected);
+        }
+        let update = "INSERT INTO ALUMNI (AId, AName) VALUES (104, 'dan'), (105);";
+        assert!(planner.execute_update(update, Arc::clone(&tx)).is_err());
+        let update = "INSERT INTO ALUMNI (AId, AName) SELECT SId FROM STUDENT;";
+        assert!(planner.execute_update(update, Arc::clone(&tx)).is_err());
+
+        tx.lock().unwrap().commit()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn like_test() -> Result<()> {
+        let (_simpledb, tx, mut planner) = new_db("_test/plannerlike")?;
+
+        // SELECT with LIKE
+        let sqls = vec![
+            "CREATE TABLE PEOPLE (PId integer, PName varchar(10));",
+            "CREATE INDEX idx_people_name ON PEOPLE (PName);",
+            "INSERT INTO PEOPLE (PId, PName) \
+             VALUES (1, 'joe'), (2, 'john'), (3, 'jo_e'), (4, 'amy'), (5, 'jon'), (6, '50%');",
+        ];
+        for sql in sqls {
+            planner.execute_update(sql, Arc::clone(&tx))?;
+        }
+        let queries = vec![
+            (
+                "SELECT PId FROM PEOPLE WHERE PName LIKE 'jo%';",
+                vec![1, 2, 3, 5],
+            ),
+            ("SELECT PId FROM PEOPLE WHERE PName LIKE 'jo_';", vec![1, 5]),
+            ("SELECT PId FROM PEOPLE WHERE PName LIKE '%e';", vec![1, 3]),
+            (
+                "SELECT PId FROM PEOPLE WHERE PName NOT LIKE 'j%';",
+                vec![4, 6],
+            ),
+            (
+                "SELECT PId FROM PEOPLE WHERE PName LIKE 'jo!_%' ESCAPE '!';",
+                vec![3],
+            ),
+            (
+                "SELECT PId FROM PEOPLE WHERE PName LIKE '50!%' ESCAPE '!';",
+                vec![6],
+            ),
+        ];
+        for (query, expected) in queries {
+            println!("Query: {}", query);
+            let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+            let scan = plan.open()?;
+            let mut ids = vec![];
+            let mut iter = scan.lock().unwrap();
+            while iter.next() {
+                ids.push(iter.get_i32("PId")?);
+            }
+            iter.close()?;
+            ids.sort();
+            assert_eq!(ids, expected);
+        }
+        // the literal prefix is looked up by the index
+        let query = "SELECT PId FROM PEOPLE WHERE PName LIKE 'jo%';";
+        let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+        let mut reprs = vec![plan.repr()];
+        let mut found = false;
+        while let Some(repr) = reprs.pop() {
+            if let Operation::IndexRangeScan { loval, hival, .. } = repr.operation() {
+                assert_eq!(loval, Constant::String("jo".to_string()));
+                assert_eq!(hival, Constant::String("jp".to_string()));
+                found = true;
+            }
+            reprs.extend(repr.sub_plan_reprs());
+        }
+        assert!(found);
+
+        tx.lock().unwrap().commit()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn null_test() -> Result<()> {
+        let (_simpledb, tx, mut planner) = new_db("_test/plannernull")?;
+
+        // NULL
+        let sqls = vec![
+            "CREATE TABLE PETS (PetId integer, PetName varchar(10), Age integer);",
+            "CREATE INDEX idx_pets_age ON PETS (Age);",
+            "INSERT INTO PETS (PetId, PetName, Age) \
+             VALUES (1, 'rex', 3), (2, NULL, 5), (3, 'tom', null);",
+            // the fields not in the list are null
+            "INSERT INTO PETS (PetId) VALUES (4);",
+        ];
+        for sql in sqls {
+            planner.execute_update(sql, Arc::clone(&tx))?;
+        }
+        let queries = vec![
+            ("SELECT PetId FROM PETS WHERE Age IS NULL;", vec![3, 4]),
+            ("SELECT PetId FROM PETS WHERE Age IS NOT NULL;", vec![1, 2]),
+            ("SELECT PetId FROM PETS WHERE Age = NULL;", vec![]),
+            ("SELECT PetId FROM PETS WHERE Age <> 3;", vec![2]),
+            ("SELECT PetId FROM PETS WHERE NOT (Age = 3);", vec![2]),
+            (
+                "SELECT PetId FROM PETS WHERE Age = 3 OR PetName IS NULL;",
+                vec![1, 2, 4],
+            ),
+            ("SELECT PetId FROM PETS WHERE PetName LIKE '%';", vec![1, 3]),
+            ("SELECT PetId FROM PETS WHERE Age + 1 > 0;", vec![1, 2]),
+        ];
+        for (query, expected) in queries {
+            println!("Query: {}", query);
+            let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+            let scan = plan.open()?;
+            let mut ids = vec![];
+            let mut iter = scan.lock().unwrap();
+            while iter.next() {
+                ids.push(iter.get_i32("PetId")?);
+            }
+            iter.close()?;
+            ids.sort();
+            assert_eq!(ids, expected);
+        }
+        // the aggregates ignore the nulls
+        let query = "SELECT COUNT(Age), COUNT(*), SUM(Age), MIN(PetName) FROM PETS;";
+        let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+        let scan = plan.open()?;
+        let mut iter = scan.lock().unwrap();
+        assert!(iter.next());
+        assert_eq!(iter.get_i32("count(Age)")?, 2);
+        assert_eq!(iter.get_i32("count(*)")?, 4);
+        assert_eq!(iter.get_i32("sum(Age)")?, 8);
+        assert_eq!(iter.get_string("min(PetName)")?, "rex");
+        iter.close()?;
+        // the nulls are sorted first
+        let query = "SELECT PetId, Age FROM PETS ORDER BY Age;";
+        let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+        let scan = plan.open()?;
+        let mut rows = vec![];
+        let mut iter = scan.lock().unwrap();
+        while iter.next() {
+            rows.push((iter.get_i32("PetId")?, iter.get_val("Age")?));
+        }
+        iter.close()?;
+        assert_eq!(&rows[2..], &[(1, Constant::I32(3)), (2, Constant::I32(5))]);
+        assert!(rows[..2].iter().all(|(_, age)| age.is_null()));
+        // the indexed field is updated to null, and back
+        let update = "UPDATE PETS SET Age = NULL WHERE PetId = 1;";
+        assert_eq!(planner.execute_update(update, Arc::clone(&tx))?, 1);
+        let update = "UPDATE PETS SET Age = 7 WHERE PetId = 3;";
+        assert_eq!(planner.execute_update(update, Arc::clone(&tx))?, 1);
+        let queries = vec![
+            ("SELECT PetId FROM PETS WHERE Age IS NULL;", vec![1, 4]),
+            ("SELECT PetId FROM PETS WHERE Age = 3;", vec![]),
+            ("SELECT PetId FROM PETS WHERE Age = 7;", vec![3]),
+        ];
+        for (query, expected) in queries {
+            println!("Query: {}", query);
+            let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+            let scan = plan.open()?;
+            let mut ids = vec![];
+            let mut iter = scan.lock().unwrap();
+            while iter.next() {
+                ids.push(iter.get_i32("PetId")?);
+            }
+            iter.close()?;
+            ids.sort();
+            assert_eq!(ids, expected);
+        }
+
+        tx.lock().unwrap().commit()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_between_test() -> Result<()> {
+        let (_simpledb, tx, mut planner) = scores_db("_test/plannerin")?;
+
+        let queries = vec![
+            (
+                "SELECT ScId FROM SCORES WHERE Points IN (20, 40);",
+                vec![2, 4, 6],
+            ),
+            (
+                "SELECT ScId FROM SCORES WHERE Points IN (20, 20, 99);",
+                vec![2, 4],
+            ),
+            (
+                "SELECT ScId FROM SCORES WHERE Points IN (10, NULL);",
+                vec![1],
+            ),
+            (
+                "SELECT ScId FROM SCORES WHERE Points NOT IN (20, 40);",
+                vec![1, 3],
+            ),
+            (
+                "SELECT ScId FROM SCORES WHERE Points NOT IN (20, NULL);",
+                vec![],
+            ),
+            (
+                "SELECT ScId FROM SCORES WHERE Points BETWEEN 20 AND 30;",
+                vec![2, 3, 4],
+            ),
+            (
+                "SELECT ScId FROM SCORES WHERE Points BETWEEN 30 AND 20;",
+                vec![],
+            ),
+            (
+                "SELECT ScId FROM SCORES WHERE Points NOT BETWEEN 20 AND 30;",
+                vec![1, 6],
+            ),
+            (
+                "SELECT ScId FROM SCORES WHERE ScId BETWEEN 2 AND 3 AND Points IN (20, 30);",
+                vec![2, 3],
+            ),
+            (
+                "SELECT ScId FROM SCORES WHERE ScId + 1 IN (Points / 10, 5);",
+                vec![4],
+            ),
+        ];
+        for (query, expected) in queries {
+            println!("Query: {}", query);
+            let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+            let scan = plan.open()?;
+            let mut ids = vec![];
+            let mut iter = scan.lock().unwrap();
+            while iter.next() {
+                ids.push(iter.get_i32("ScId")?);
+            }
+            iter.close()?;
+            ids.sort();
+            assert_eq!(ids, expected);
+        }
+        // the values of IN are looked up one by one, and BETWEEN as a range
+        let cases = vec![
+            (
+                "SELECT ScId FROM SCORES WHERE Points IN (40, 20, 20);",
+                Operation::IndexInScan {
+                    idxname: "idx_scores_pts".to_string(),
+                    idxfldname: "Points".to_string(),
+                    vals: vec![Constant::I32(20), Constant::I32(40)],
+                },
+            ),
+            (
+                "SELECT ScId FROM SCORES WHERE Points BETWEEN 20 AND 30;",
+                Operation::IndexRangeScan {
+                    idxname: "idx_scores_pts".to_string(),
+                    idxfldname: "Points".to_string(),
+                    loval: Constant::I32(20),
+                    hival: Constant::I32(30),
+                    inclusive: true,
+                },
+            ),
+        ];
+        for (query, expected) in cases {
+            let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+            let mut reprs = vec![plan.repr()];
+            let mut found = false;
+            while let Some(repr) = reprs.pop() {
+                if repr.operation() == expected {
+                    found = true;
+                }
+                reprs.extend(repr.sub_plan_reprs());
+            }
+            assert!(found);
+        }
+
+        tx.lock().unwrap().commit()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn case_test() -> Result<()> {
+        let (_simpledb, tx, mut planner) = scores_db("_test/plannercase")?;
+
+        let query = "SELECT ScId, \
+                     CASE WHEN Points >= 30 THEN 'high' WHEN Points >= 20 THEN 'mid' END AS Lvl, \
+                     CASE ScId WHEN 1 THEN 'one' ELSE 'other' END AS Name, \
+                     COALESCE(Points, -1) AS Pts \
+                     FROM SCORES;";
+        println!("Query: {}", query);
+        let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+        assert_eq!(plan.schema().field_type("Lvl"), FieldType::VARCHAR);
+        assert_eq!(plan.schema().length("Name"), 5);
+        assert_eq!(plan.schema().field_type("Pts"), FieldType::INTEGER);
+        let scan = plan.open()?;
+        let mut rows = vec![];
+        let mut iter = scan.lock().unwrap();
+        while iter.next() {
+            rows.push((
+                iter.get_i32("ScId")?,
+                iter.get_val("Lvl")?,
+                iter.get_string("Name")?,
+                iter.get_i32("Pts")?,
+            ));
+        }
+        iter.close()?;
+        rows.sort();
+        let str_val = |s: &str| Constant::String(s.to_string());
+        assert_eq!(
+            rows,
+            vec![
+                (1, Constant::Null, "one".to_string(), 10),
+                (2, str_val("mid"), "other".to_string(), 20),
+                (3, str_val("high"), "other".to_string(), 30),
+                (4, str_val("mid"), "other".to_string(), 20),
+                (5, Constant::Null, "other".to_string(), -1),
+                (6, str_val("high"), "other".to_string(), 40),
+            ]
+        );
+        let queries = vec![
+            (
+                "SELECT ScId FROM SCORES \
+                 WHERE CASE WHEN Points > 20 THEN 'high' ELSE 'low' END = 'low';",
+                vec![1, 2, 4, 5],
+            ),
+            (
+                "SELECT ScId FROM SCORES WHERE COALESCE(Points, 0) < 20;",
+                vec![1, 5],
+            ),
+        ];
+        for (query, expected) in queries {
+            println!("Query: {}", query);
+            let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+            let scan = plan.open()?;
+            let mut ids = vec![];
+            let mut iter = scan.lock().unwrap();
+            while iter.next() {
+                ids.push(iter.get_i32("ScId")?);
+            }
+            iter.close()?;
+            ids.sort();
+            assert_eq!(ids, expected);
+        }
+
+        tx.lock().unwrap().commit()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn scalar_function_test() -> Result<()> {
+        let (_simpledb, tx, mut planner) = new_db("_test/plannerfunc")?;
+
+        // SELECT with scalar functions
+        let sqls = vec![
+            "CREATE TABLE EVENTS (EvId integer, EvName varchar(10), EvDate date);",
+            "INSERT INTO EVENTS (EvId, EvName, EvDate) \
+             VALUES (1, ' Gala ', '2024-02-28'), (2, 'fair', '2024-03-01'), (3, 'expo', NULL);",
+        ];
+        for sql in sqls {
+            planner.execute_update(sql, Arc::clone(&tx))?;
+        }
+        let query = "SELECT EvId, upper(trim(EvName)) AS U, EvName || '!' AS Bang, \
+                     year(EvDate) AS Y, date_add(EvDate, 2) AS Later FROM EVENTS;";
+        println!("Query: {}", query);
+        let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+        assert_eq!(plan.schema().length("Bang"), 11);
+        assert_eq!(plan.schema().field_type("Later"), FieldType::DATE);
+        let scan = plan.open()?;
+        let mut rows = vec![];
+        let mut iter = scan.lock().unwrap();
+        while iter.next() {
+            rows.push((
+                iter.get_i32("EvId")?,
+                iter.get_string("U")?,
+                iter.get_string("Bang")?,
+                iter.get_val("Y")?,
+                iter.get_val("Later")?,
+            ));
+        }
+        iter.close()?;
+        rows.sort();
+        let date = |y, m, d| Constant::Date(NaiveDate::from_ymd_opt(y, m, d).unwrap());
+        assert_eq!(
+            rows,
+            vec![
+                (
+                    1,
+                    "GALA".to_string(),
+                    " Gala !".to_string(),
+                    Constant::I32(2024),
+                    date(2024, 3, 1)
+                ),
+                (
+                    2,
+                    "FAIR".to_string(),
+                    "fair!".to_string(),
+                    Constant::I32(2024),
+                    date(2024, 3, 3)
+                ),
+                (
+                    3,
+                    "EXPO".to_string(),
+                    "expo!".to_string(),
+                    Constant::Null,
+                    Constant::Null
+                ),
+            ]
+        );
+        let update = "UPDATE EVENTS SET EvName = upper(EvName) WHERE day(EvDate) = 1;";
+        assert_eq!(planner.execute_update(update, Arc::clone(&tx))?, 1);
+        let view = "CREATE VIEW ev_names AS \
+                    SELECT EvId, EvName, EvDate FROM EVENTS WHERE substr(EvName, 2) <> 'AIR';";
+        planner.execute_update(view, Arc::clone(&tx))?;
+        let query = "SELECT EvId, lower(trim(EvName)) AS Lname FROM ev_names \
+                     WHERE EvDate < CURRENT_DATE;";
+        println!("Query: {}", query);
+        let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+        let scan = plan.open()?;
+        let mut rows = vec![];
+        let mut iter = scan.lock().unwrap();
+        while iter.next() {
+            rows.push((iter.get_i32("EvId")?, iter.get_string("Lname")?));
+        }
+        iter.close()?;
+        rows.sort();
+        assert_eq!(rows, vec![(1, "gala".to_string())]);
+        // the arguments are type-checked
+        let query = "SELECT upper(EvId) AS U FROM EVENTS;";
+        assert!(planner.create_query_plan(query, Arc::clone(&tx)).is_err());
+
+        tx.lock().unwrap().commit()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn drop_test() -> Result<()> {
+        let (_simpledb, tx, mut planner) = new_db("_test/plannerdrop")?;
+
+        let sqls = vec![
+            "CREATE TABLE EVENTS (EvId integer, EvName varchar(10), EvDate date);",
+            "CREATE VIEW ev_names AS SELECT EvId, EvName FROM EVENTS;",
+        ];
+        for sql in sqls {
+            planner.execute_update(sql, Arc::clone(&tx))?;
+        }
+
+        // DROP
+        let sqls = vec![
+            "CREATE INDEX idx_ev_id ON EVENTS (EvId);",
+            "DROP INDEX idx_ev_id;",
+            "DROP VIEW ev_names;",
+            "DROP TABLE EVENTS;",
+            "DROP TABLE IF EXISTS EVENTS;",
+            "DROP VIEW IF EXISTS ev_names;",
+            "DROP INDEX IF EXISTS idx_ev_id;",
+        ];
+        for sql in sqls {
+            print!("Execute: {} ... ", sql);
+            assert_eq!(planner.execute_update(sql, Arc::clone(&tx))?, 0);
+            println!("Done");
+        }
+        let sqls = vec![
+            "DROP TABLE EVENTS;",
+            "DROP VIEW ev_names;",
+            "DROP INDEX idx_ev_id;",
+            "DROP TABLE idxcat;",
+        ];
+        for sql in sqls {
+            assert!(planner.execute_update(sql, Arc::clone(&tx)).is_err());
+        }
+
+        tx.lock().unwrap().commit()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn alter_test() -> Result<()> {
+        let (_simpledb, tx, mut planner) = new_db("_test/planneralter")?;
+
+        // ALTER TABLE
+        let sqls = vec![
+            "CREATE TABLE BIRDS (BId integer, BName varchar(8));",
+            "CREATE TABLE NESTS (NId integer);",
+            "CREATE INDEX idx_bird_id ON BIRDS (BId);",
+            "INSERT INTO BIRDS (BId, BName) VALUES (1, 'tama'), (2, 'pochi'), (3, 'mike');",
+            "CREATE VIEW bird_names AS SELECT BName FROM BIRDS;",
+            "ALTER TABLE BIRDS ADD COLUMN Age integer;",
+            "UPDATE BIRDS SET Age = 3 WHERE BId = 2;",
+        ];
+        for sql in sqls {
+            planner.execute_update(sql, Arc::clone(&tx))?;
+        }
+        let query = "SELECT BId, BName, Age FROM BIRDS;";
+        let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+        let scan = plan.open()?;
+        let mut rows = vec![];
+        let mut iter = scan.lock().unwrap();
+        while iter.next() {
+            rows.push((
+                iter.get_i32("BId")?,
+                iter.get_string("BName")?,
+                iter.get_val("Age")?,
+            ));
+        }
+        iter.close()?;
+        rows.sort();
+        assert_eq!(
+            rows,
+            vec![
+                (1, "tama".to_string(), Constant::Null),
+                (2, "pochi".to_string(), Constant::I32(3)),
+                (3, "mike".to_string(), Constant::Null),
+            ]
+        );
+        let sqls = vec![
+            "ALTER TABLE BIRDS RENAME COLUMN BName TO Name;",
+            "ALTER TABLE BIRDS DROP COLUMN Age;",
+            "ALTER TABLE BIRDS RENAME TO FOWLS;",
+        ];
+        for sql in sqls {
+            print!("Execute: {} ... ", sql);
+            assert_eq!(planner.execute_update(sql, Arc::clone(&tx))?, 0);
+            println!("Done");
+        }
+        // the record is found by the index, which follows the table
+        let query = "SELECT BId, Name FROM FOWLS WHERE BId = 2;";
+        let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+        let mut reprs = vec![plan.repr()];
+        let mut found = false;
+        while let Some(repr) = reprs.pop() {
+            if let Operation::IndexSelectScan { idxname, .. } = repr.operation() {
+                assert_eq!(idxname, "idx_bird_id");
+                found = true;
+            }
+            reprs.extend(repr.sub_plan_reprs());
+        }
+        assert!(found);
+        let scan = plan.open()?;
+        let mut rows = vec![];
+        let mut iter = scan.lock().unwrap();
+        while iter.next() {
+            rows.push((iter.get_i32("BId")?, iter.get_string("Name")?));
+        }
+        iter.close()?;
+        assert_eq!(rows, vec![(2, "pochi".to_string())]);
+        assert!(!plan.schema().has_field("Age"));
+        let sqls = vec![
+            // the view on the renamed field was dropped
+            "DROP VIEW bird_names;",
+            "ALTER TABLE BIRDS ADD COLUMN Age integer;",
+            "ALTER TABLE FOWLS ADD COLUMN Name integer;",
+            "ALTER TABLE FOWLS RENAME TO NESTS;",
+        ];
+        for sql in sqls {
+            assert!(planner.execute_update(sql, Arc::clone(&tx)).is_err());
+        }
+
+        tx.lock().unwrap().commit()?;
+        // the temp tables, through which the records were rewritten, are removed
+        for entry in fs::read_dir("_test/planneralter")? {
+            let filename = entry?.file_name().into_string().unwrap();
+            assert!(!filename.starts_with("temp"), "{} is left", filename);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn key_test() -> Result<()> {
+        let (_simpledb, tx, mut planner) = new_db("_test/plannerkey")?;
+
+        // PRIMARY KEY and UNIQUE
+        let sqls = vec![
+            "CREATE TABLE MEMBERS (MId integer PRIMARY KEY, MName varchar(8), Email varchar(16), UNIQUE (Email));",
+            "INSERT INTO MEMBERS (MId, MName, Email) VALUES (1, 'joe', 'joe@a.com'), (2, 'amy', NULL), (3, 'max', NULL);",
+            "UPDATE MEMBERS SET MId = 4 WHERE MId = 3;",
+            "UPDATE MEMBERS SET Email = 'amy@a.com' WHERE MId = 2;",
+            // a record keeps its own key
+            "UPDATE MEMBERS SET Email = 'joe@a.com' WHERE MId = 1;",
+        ];
+        for sql in sqls {
+            planner.execute_update(sql, Arc::clone(&tx))?;
+        }
+        let violations = vec![
+            (
+                "INSERT INTO MEMBERS (MId, MName) VALUES (1, 'bob');",
+                "duplicate key: MEMBERS.MId = 1",
+            ),
+            (
+                "INSERT INTO MEMBERS (MName) VALUES ('bob');",
+                "primary key can't be null: MEMBERS.MId",
+            ),
+            (
+                "INSERT INTO MEMBERS (MId, Email) VALUES (5, 'amy@a.com');",
+                "duplicate key: MEMBERS.Email = 'amy@a.com'",
+            ),
+            (
+                "UPDATE MEMBERS SET MId = 1 WHERE MId = 2;",
+                "duplicate key: MEMBERS.MId = 1",
+            ),
+            (
+                "UPDATE MEMBERS SET MId = NULL WHERE MId = 4;",
+                "primary key can't be null: MEMBERS.MId",
+            ),
+        ];
+        for (sql, msg) in violations {
+            let err = planner.execute_update(sql, Arc::clone(&tx)).unwrap_err();
+            let violation = err.downcast_ref::<ConstraintError>().map(|e| e.to_string());
+            assert_eq!(violation, Some(msg.to_string()));
+        }
+        // the record is found by the index of the key
+        let query = "SELECT MId, MName, Email FROM MEMBERS WHERE MId = 4;";
+        let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+        let mut reprs = vec![plan.repr()];
+        let mut found = false;
+        while let Some(repr) = reprs.pop() {
+            if let Operation::IndexSelectScan { idxname, .. } = repr.operation() {
+                assert_eq!(idxname, "MEMBERS_pkey");
+                found = true;
+            }
+            reprs.extend(repr.sub_plan_reprs());
+        }
+        assert!(found);
+        let scan = plan.open()?;
+        let mut rows = vec![];
+        let mut iter = scan.lock().unwrap();
+        while iter.next() {
+            rows.push((
+                iter.get_i32("MId")?,
+                iter.get_string("MName")?,
+                iter.get_val("Email")?,
+            ));
+        }
+        iter.close()?;
+        assert_eq!(rows, vec![(4, "max".to_string(), Constant::Null)]);
+        let sqls = vec![
+            "DROP INDEX MEMBERS_pkey;",
+            "DROP INDEX MEMBERS_Email_ke;",
+            // a second index of a key field would not be maintained
+            "CREATE INDEX idx_members_id ON MEMBERS (MId);",
+            "CREATE TABLE GUESTS (GId integer PRIMARY KEY, GName varchar(8) PRIMARY KEY);",
+        ];
+        for sql in sqls {
+            assert!(planner.execute_update(sql, Arc::clone(&tx)).is_err());
+        }
+        // the keys follow the table
+        planner.execute_update("ALTER TABLE MEMBERS RENAME TO CLUB;", Arc::clone(&tx))?;
+        let sql = "INSERT INTO CLUB (MId, MName) VALUES (4, 'bob');";
+        let err = planner.execute_update(sql, Arc::clone(&tx)).unwrap_err();
+        assert!(err.downcast_ref::<ConstraintError>().is_some());
+
+        tx.lock().unwrap().commit()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn constraint_test() -> Result<()> {
+        let (_simpledb, tx, mut planner) = new_db("_test/plannerconstraint")?;
+
+        // NOT NULL, DEFAULT and CHECK
+        let sqls = vec![
+            "CREATE TABLE ORDERS (OId integer NOT NULL, Qty integer DEFAULT 1 CHECK (Qty > 0), Placed date DEFAULT CURRENT_DATE, Note varchar(8));",
+            "INSERT INTO ORDERS (OId) VALUES (1);",
+            "INSERT INTO ORDERS (OId, Qty, Note) VALUES (2, 5, 'rush');",
+            // a check which is unknown for null passes
+            "INSERT INTO ORDERS (OId, Qty) VALUES (3, NULL);",
+            "UPDATE ORDERS SET Qty = 7 WHERE OId = 2;",
+        ];
+        for sql in sqls {
+            planner.execute_update(sql, Arc::clone(&tx))?;
+        }
+        let violations = vec![
+            (
+                "INSERT INTO ORDERS (Qty) VALUES (2);",
+                "field can't be null: ORDERS.OId",
+            ),
+            (
+                "INSERT INTO ORDERS (OId, Qty) VALUES (4, 0);",
+                "check failed: ORDERS.Qty (Qty>0)",
+            ),
+            (
+                "UPDATE ORDERS SET Qty = Qty - 7 WHERE OId = 2;",
+                "check failed: ORDERS.Qty (Qty>0)",
+            ),
+            (
+                "UPDATE ORDERS SET OId = NULL WHERE OId = 1;",
+                "field can't be null: ORDERS.OId",
+            ),
+        ];
+        for (sql, msg) in violations {
+            let err = planner.execute_update(sql, Arc::clone(&tx)).unwrap_err();
+            let violation = err.downcast_ref::<ConstraintError>().map(|e| e.to_string());
+            assert_eq!(violation, Some(msg.to_string()));
+        }
+        let query = "SELECT OId, Qty, Placed FROM ORDERS;";
+        let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+        let scan = plan.open()?;
+        let mut rows = vec![];
+        let mut iter = scan.lock().unwrap();
+        while iter.next() {
+            rows.push((
+                iter.get_i32("OId")?,
+                iter.get_val("Qty")?,
+                iter.get_val("Placed")?,
+            ));
+        }
+        iter.close()?;
+        let today = Constant::Date(chrono::Local::now().date_naive());
+        assert_eq!(
+            rows,
+            vec![
+                (1, Constant::I32(1), today.clone()),
+                (2, Constant::I32(7), today.clone()),
+                (3, Constant::Null, today),
+            ]
+        );
+        let sqls = vec![
+            "ALTER TABLE ORDERS RENAME COLUMN Qty TO Amount;",
+            "CREATE TABLE BADCHECK (A integer CHECK (B > 0));",
+        ];
+        for sql in sqls {
+            assert!(planner.execute_update(sql, Arc::clone(&tx)).is_err());
+        }
+        // the check is dropped with its field
+        planner.execute_update("ALTER TABLE ORDERS DROP COLUMN Qty;", Arc::clone(&tx))?;
+        let sql = "INSERT INTO ORDERS (OId, Placed) VALUES (4, '2020-01-01');";
+        planner.execute_update(sql, Arc::clone(&tx))?;
+
+        tx.lock().unwrap().commit()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn foreign_key_test() -> Result<()> {
+        let (_simpledb, tx, mut planner) = new_db("_test/plannerfk")?;
+
+        // FOREIGN KEY
+        let sqls = vec![
+            "CREATE TABLE DEPTS (DId integer PRIMARY KEY, DName varchar(10));",
+            "CREATE TABLE PUPILS (PId integer PRIMARY KEY, MajorId integer REFERENCES DEPTS (DId) ON DELETE SET NULL);",
+            "CREATE TABLE TAKES (TId integer, PupilId integer, FOREIGN KEY (PupilId) REFERENCES PUPILS (PId) ON DELETE CASCADE);",
+            "CREATE TABLE ADVISES (AId integer, DeptId integer REFERENCES DEPTS (DId));",
+            "INSERT INTO DEPTS (DId, DName) VALUES (10, 'compsci'), (20, 'math'), (30, 'drama');",
+            "INSERT INTO PUPILS (PId, MajorId) VALUES (1, 10), (2, 20), (3, NULL);",
+            "INSERT INTO TAKES (TId, PupilId) VALUES (1, 1), (2, 1), (3, 2);",
+            "INSERT INTO ADVISES (AId, DeptId) VALUES (1, 30);",
+            "UPDATE PUPILS SET MajorId = 30 WHERE PId = 3;",
+        ];
+        for sql in sqls {
+            planner.execute_update(sql, Arc::clone(&tx))?;
+        }
+        let violations = vec![
+            (
+                "INSERT INTO PUPILS (PId, MajorId) VALUES (4, 40);",
+                "referenced key not found: PUPILS.MajorId = 40",
+            ),
+            (
+                "UPDATE PUPILS SET MajorId = 50 WHERE PId = 1;",
+                "referenced key not found: PUPILS.MajorId = 50",
+            ),
+            (
+                "UPDATE DEPTS SET DId = 11 WHERE DId = 10;",
+                "key is referenced by PUPILS: DEPTS.DId = 10",
+            ),
+            (
+                "DELETE FROM DEPTS WHERE DId = 30;",
+                "key is referenced by ADVISES: DEPTS.DId = 30",
+            ),
+        ];
+        for (sql, msg) in violations {
+            let err = planner.execute_update(sql, Arc::clone(&tx)).unwrap_err();
+            let violation = err.downcast_ref::<ConstraintError>().map(|e| e.to_string());
+            assert_eq!(violation, Some(msg.to_string()));
+        }
+        // the takes of the pupil are deleted, and the major of the pupils is set to null
+        let sqls = vec![
+            "DELETE FROM PUPILS WHERE PId = 1;",
+            "DELETE FROM DEPTS WHERE DId = 20;",
+        ];
+        for sql in sqls {
+            planner.execute_update(sql, Arc::clone(&tx))?;
+        }
+        let query = "SELECT TId, PupilId FROM TAKES;";
+        let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+        let scan = plan.open()?;
+        let mut rows = vec![];
+        let mut iter = scan.lock().unwrap();
+        while iter.next() {
+            rows.push((iter.get_i32("TId")?, iter.get_i32("PupilId")?));
+        }
+        iter.close()?;
+        assert_eq!(rows, vec![(3, 2)]);
+        let query = "SELECT PId, MajorId FROM PUPILS WHERE PId = 2;";
+        let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+        let scan = plan.open()?;
+        let mut rows = vec![];
+        let mut iter = scan.lock().unwrap();
+        while iter.next() {
+            rows.push((iter.get_i32("PId")?, iter.get_val("MajorId")?));
+        }
+        iter.close()?;
+        assert_eq!(rows, vec![(2, Constant::Null)]);
+        let sqls = vec![
+            "CREATE TABLE BADREF (X integer REFERENCES DEPTS (DName));",
+            "CREATE TABLE BADREF (X varchar(5) REFERENCES DEPTS (DId));",
+            "CREATE TABLE BADREF (X integer REFERENCES NOWHERE (DId));",
+            "DROP TABLE DEPTS;",
+            "ALTER TABLE DEPTS DROP COLUMN DId;",
+        ];
+        for sql in sqls {
+            assert!(planner.execute_update(sql, Arc::clone(&tx)).is_err());
+        }
+        // the foreign keys follow the referenced table
+        planner.execute_update("ALTER TABLE DEPTS RENAME TO DIVS;", Arc::clone(&tx))?;
+        let sql = "INSERT INTO PUPILS (PId, MajorId) VALUES (5, 20);";
+        let err = planner.execute_update(sql, Arc::clone(&tx)).unwrap_err();
+        assert!(err.downcast_ref::<ConstraintError>().is_some());
+        let sql = "INSERT INTO PUPILS (PId, MajorId) VALUES (5, 10);";
+        planner.execute_update(sql, Arc::clone(&tx))?;
+
+        tx.lock().unwrap().commit()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn syntax_error_test() -> Result<()> {
+        let (_simpledb, tx, mut planner) = new_db("_test/plannersyntax")?;
+
+        let sqls = vec![
+            "CREATE TABLE PUPILS (PId integer);",
+            "INSERT INTO PUPILS (PId) VALUES (1), (5);",
+        ];
+        for sql in sqls {
+            planner.execute_update(sql, Arc::clone(&tx))?;
+        }
+
+        // syntax errors
+        let query = "SELECT PId FROM PUPILS; DELETE FROM PUPILS;";
+        let err = planner
+            .create_query_plan(query, Arc::clone(&tx))
+            .err()
+            .unwrap();
+        let err = err.downcast_ref::<SqlSyntaxError>().unwrap();
+        assert_eq!((err.line(), err.column(), err.token()), (1, 25, "DELETE"));
+        // a typo doesn't run a shorter statement
+        let sql = "DELETE FROM PUPILS WHERE PId = 5 OR PId = ;";
+        let err = planner.execute_update(sql, Arc::clone(&tx)).unwrap_err();
+        let err = err.downcast_ref::<SqlSyntaxError>().unwrap();
+        assert_eq!((err.line(), err.column(), err.token()), (1, 43, ";"));
+        let query = "SELECT PId FROM PUPILS WHERE PId = 5;";
+        let plan = planner.create_query_plan(query, Arc::clone(&tx))?;
+        let scan = plan.open()?;
+        let mut iter = scan.lock().unwrap();
+        assert!(iter.next());
+        iter.close()?;
+
+        tx.lock().unwrap().commit()?;
+
+        Ok(())
+    }
 }
@@ -3,7 +3,7 @@ use std::sync::{Arc, Mutex};
 
 use super::plan::Plan;
 use crate::{
-    query::{projectscan::ProjectScan, scan::Scan},
+    query::{expression::Expression, projectscan::ProjectScan, scan::Scan},
     record::schema::Schema,
     repr::planrepr::{Operation, PlanRepr},
 };
@@ -11,15 +11,16 @@ use crate::{
 #[derive(Clone)]
 pub struct ProjectPlan {
     p: Arc<dyn Plan>,
+    fields: Vec<(String, Expression)>,
     schema: Arc<Schema>,
 }
 
 impl Plan for ProjectPlan {
     fn open(&self) -> Result<Arc<Mutex<dyn Scan>>> {
         let s = self.p.open()?;
-        Ok(Arc::new(Mutex::new(ProjectScan::new(
+        Ok(Arc::new(Mutex::new(ProjectScan::new_with(
             s,
-            self.schema.fields().clone(),
+            self.fields.clone(),
         ))))
     }
     fn blocks_accessed(&self) -> i32 {
@@ -29,7 +30,15 @@ impl Plan for ProjectPlan {
         self.p.records_output()
     }
     fn distinct_values(&self, fldname: &str) -> i32 {
-        self.p.distinct_values(fldname)
+        let expr = self
+            .schema
+            .resolve(fldname)
+            .and_then(|f| self.fields.iter().find(|(name, _)| name == f));
+        match expr {
+            Some((_, Expression::Fldname(name))) => self.p.distinct_values(name),
+            // a computed field may take as many values as there are records
+            _ => self.p.records_output(),
+        }
     }
     fn schema(&self) -> Arc<Schema> {
         Arc::clone(&self.schema)
@@ -38,6 +47,13 @@ impl Plan for ProjectPlan {
     fn repr(&self) -> Arc<dyn PlanRepr> {
         Arc::new(ProjectPlanRepr {
             p: self.p.repr(),
+            // the plans of the scalar subqueries are shown under the projection
+            subplans: self
+                .fields
+                .iter()
+                .flat_map(|(_, expr)| expr.subqueries())
+                .filter_map(|sq| sq.plan().map(|p| p.repr()))
+                .collect(),
             r: self.blocks_accessed(),
             w: self.records_output(),
         })
@@ -47,6 +63,7 @@ impl Plan for ProjectPlan {
 #[derive(Clone)]
 pub struct ProjectPlanRepr {
     p: Arc<dyn PlanRepr>,
+    subplans: Vec<Arc<dyn PlanRepr>>,
     r: i32,
     w: i32,
 }
@@ -62,22 +79,45 @@ impl PlanRepr for ProjectPlanRepr {
         self.w
     }
     fn sub_plan_reprs(&self) -> Vec<Arc<dyn PlanRepr>> {
-        vec![Arc::clone(&self.p)]
+        let mut reprs = vec![Arc::clone(&self.p)];
+        reprs.extend(self.subplans.iter().cloned());
+        reprs
     }
 }
 
 impl ProjectPlan {
     pub fn new(p: Arc<dyn Plan>, fieldlist: Vec<String>) -> Self {
         let mut schema = Schema::new();
-        for fldname in fieldlist {
-            schema.add(&fldname, p.schema())
+        for fldname in fieldlist.iter() {
+            schema.add(fldname, p.schema())
         }
+        let fields = fieldlist
+            .into_iter()
+            .map(|fldname| (fldname.clone(), Expression::new_fldname(fldname)))
+            .collect();
 
         Self {
             p,
+            fields,
             schema: Arc::new(schema),
         }
     }
+    // the type of a computed field is inferred from its expression.
+    // each field is named, e.g. by its alias.
+    pub fn new_with(p: Arc<dyn Plan>, fields: Vec<(String, Expression)>) -> Result<Self> {
+        let mut schema = Schema::new();
+        let sch = p.schema();
+        for (fldname, expr) in fields.iter() {
+            let info = expr.field_info(&sch)?;
+            schema.add_field(fldname, info.fld_type, info.length);
+        }
+
+        Ok(Self {
+            p,
+            fields,
+            schema: Arc::new(schema),
+        })
+    }
 }
 
 #[cfg(test)]
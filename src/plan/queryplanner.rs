@@ -1,8 +1,20 @@
 use anyhow::Result;
+use core::fmt;
 use std::sync::{Arc, Mutex};
 
 use super::plan::Plan;
-use crate::{parser::querydata::QueryData, tx::transaction::Transaction};
+use crate::{
+    metadata::manager::MetadataMgr,
+    parser::{
+        parser::query,
+        querydata::QueryData,
+        queryexpr::{QueryExpr, SetOp},
+        syntaxerror::parse_sql,
+    },
+    query::subquery::Subquery,
+    record::schema::{unqualified_name, Schema},
+    tx::transaction::Transaction,
+};
 
 pub trait QueryPlanner {
     fn create_plan(
@@ -10,4 +22,140 @@ pub trait QueryPlanner {
         data: QueryData,
         tx: Arc<Mutex<Transaction>>,
     ) -> Result<Arc<dyn Plan>>;
+    // my own extends
+    // the plan of a subquery, which is evaluated on the records with the schema env.
+    // the fields of env, which it refers to, are read from the outer record of it.
+    fn create_subquery_plan(
+        &mut self,
+        sq: &Subquery,
+        env: Arc<Schema>,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<Arc<dyn Plan>>;
+    fn bind_subqueries(
+        &mut self,
+        subqueries: Vec<&mut Subquery>,
+        env: Arc<Schema>,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<()> {
+        for sq in subqueries {
+            let plan = self.create_subquery_plan(sq, Arc::clone(&env), Arc::clone(&tx))?;
+            sq.bind(plan)?;
+        }
+
+        Ok(())
+    }
+    // the plan of the set operation on the plans of two queries,
+    // whose schemas must be union compatible.
+    fn create_set_op_plan(
+        &mut self,
+        op: SetOp,
+        p1: Arc<dyn Plan>,
+        p2: Arc<dyn Plan>,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<Arc<dyn Plan>>;
+    fn create_query_expr_plan(
+        &mut self,
+        expr: QueryExpr,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<Arc<dyn Plan>> {
+        match expr {
+            QueryExpr::Select(data) => self.create_plan(data, tx),
+            QueryExpr::SetOp(op, lhs, rhs) => {
+                let p1 = self.create_query_expr_plan(*lhs, Arc::clone(&tx))?;
+                let p2 = self.create_query_expr_plan(*rhs, Arc::clone(&tx))?;
+                self.create_set_op_plan(op, p1, p2, tx)
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum QueryPlannerError {
+    AmbiguousField(String),
+}
+
+impl std::error::Error for QueryPlannerError {}
+impl fmt::Display for QueryPlannerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            QueryPlannerError::AmbiguousField(fldname) => {
+                write!(f, "ambiguous column: {}", fldname)
+            }
+        }
+    }
+}
+
+// expands the wildcards of the select list,
+// against the fields of the tables and the views in the catalog.
+pub fn expand_wildcards(
+    data: &mut QueryData,
+    mdm: Arc<Mutex<MetadataMgr>>,
+    tx: Arc<Mutex<Transaction>>,
+) -> Result<()> {
+    if !data.has_wildcard() {
+        return Ok(());
+    }
+    let fields = table_fields(data, mdm, tx)?;
+    data.expand_wildcards(&fields);
+
+    Ok(())
+}
+
+// my own extends
+// an unqualified field of the where clause or a join condition must be in one table only,
+// e.g. "SId" in "FROM STUDENT s1, STUDENT s2". the factors with subqueries are checked
+// when the subqueries are planned, because their fields may refer to the inner tables.
+pub fn check_ambiguous_fields(
+    data: &QueryData,
+    mdm: Arc<Mutex<MetadataMgr>>,
+    tx: Arc<Mutex<Transaction>>,
+) -> Result<()> {
+    if data.tables().len() < 2 {
+        return Ok(());
+    }
+    let fields = table_fields(data, mdm, tx)?;
+    let preds = data.joins().iter().map(|(_, on)| on).chain([data.pred()]);
+    for fct in preds.flat_map(|pred| pred.factors()) {
+        if !fct.subqueries().is_empty() {
+            continue;
+        }
+        for fldname in fct.field_names() {
+            if fldname.contains('.') {
+                continue;
+            }
+            let count = fields
+                .iter()
+                .filter(|flds| flds.iter().any(|f| unqualified_name(f) == fldname))
+                .count();
+            if count > 1 {
+                return Err(From::from(QueryPlannerError::AmbiguousField(fldname)));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// the fields of each table and view of the query, in the catalog.
+fn table_fields(
+    data: &QueryData,
+    mdm: Arc<Mutex<MetadataMgr>>,
+    tx: Arc<Mutex<Transaction>>,
+) -> Result<Vec<Vec<String>>> {
+    let mut fields = vec![];
+    for tblname in data.tables() {
+        let mdm = mdm.lock().unwrap();
+        let viewdef = mdm.get_view_def(tblname, Arc::clone(&tx))?;
+        if viewdef.is_empty() {
+            let layout = mdm.get_layout(tblname, Arc::clone(&tx))?;
+            fields.push(layout.schema().fields().clone());
+        } else {
+            // NOTE: query parser expect terminater.
+            let viewdef = format!("{};", viewdef);
+            let viewdata = parse_sql(query(), viewdef.as_str())?;
+            fields.push(viewdata.fields());
+        }
+    }
+
+    Ok(fields)
 }
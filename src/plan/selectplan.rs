@@ -49,6 +49,13 @@ impl Plan for SelectPlan {
         Arc::new(SelectPlanRepr {
             p: self.p.repr(),
             pred: self.pred.clone(),
+            // the plans of the subqueries are shown under the selection
+            subplans: self
+                .pred
+                .subqueries()
+                .iter()
+                .filter_map(|sq| sq.plan().map(|p| p.repr()))
+                .collect(),
             r: self.blocks_accessed(),
             w: self.records_output(),
         })
@@ -59,6 +66,7 @@ impl Plan for SelectPlan {
 pub struct SelectPlanRepr {
     p: Arc<dyn PlanRepr>,
     pred: Predicate,
+    subplans: Vec<Arc<dyn PlanRepr>>,
     r: i32,
     w: i32,
 }
@@ -76,7 +84,9 @@ impl PlanRepr for SelectPlanRepr {
         self.w
     }
     fn sub_plan_reprs(&self) -> Vec<Arc<dyn PlanRepr>> {
-        vec![Arc::clone(&self.p)]
+        let mut reprs = vec![Arc::clone(&self.p)];
+        reprs.extend(self.subplans.iter().cloned());
+        reprs
     }
 }
 
@@ -91,6 +91,17 @@ impl TablePlan {
             si,
         })
     }
+    // the fields are qualified by the alias or the table name, e.g. "s.SName".
+    pub fn new_with(
+        tblname: &str,
+        qualifier: &str,
+        tx: Arc<Mutex<Transaction>>,
+        md: Arc<Mutex<MetadataMgr>>,
+    ) -> Result<Self> {
+        let mut plan = Self::new(tblname, tx, md)?;
+        plan.layout = Arc::new(plan.layout.qualified(qualifier));
+        Ok(plan)
+    }
 }
 
 #[cfg(test)]
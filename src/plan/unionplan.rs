@@ -0,0 +1,104 @@
+use anyhow::Result;
+use std::sync::{Arc, Mutex};
+
+use super::plan::Plan;
+use crate::{
+    query::{scan::Scan, unionscan::UnionScan},
+    record::schema::Schema,
+    repr::planrepr::{Operation, PlanRepr},
+};
+
+// The records of p1 and p2, including the duplicates (i.e. UNION ALL).
+// The schemas of them must be union compatible.
+#[derive(Clone)]
+pub struct UnionPlan {
+    p1: Arc<dyn Plan>,
+    p2: Arc<dyn Plan>,
+    schema: Arc<Schema>,
+}
+
+impl Plan for UnionPlan {
+    fn open(&self) -> Result<Arc<Mutex<dyn Scan>>> {
+        let s1 = self.p1.open()?;
+        let s2 = self.p2.open()?;
+        let scan = UnionScan::new(
+            s1,
+            s2,
+            self.p1.schema().fields().clone(),
+            self.p2.schema().fields().clone(),
+        );
+
+        Ok(Arc::new(Mutex::new(scan)))
+    }
+    fn blocks_accessed(&self) -> i32 {
+        self.p1.blocks_accessed() + self.p2.blocks_accessed()
+    }
+    fn records_output(&self) -> i32 {
+        self.p1.records_output() + self.p2.records_output()
+    }
+    fn distinct_values(&self, fldname: &str) -> i32 {
+        match self.schema.resolve(fldname) {
+            Some(f) => {
+                let pos = self.schema.fields().iter().position(|x| x == f).unwrap();
+                let fldname2 = self.p2.schema().fields()[pos].clone();
+                self.p1.distinct_values(f) + self.p2.distinct_values(&fldname2)
+            }
+            None => 1,
+        }
+    }
+    fn schema(&self) -> Arc<Schema> {
+        Arc::clone(&self.schema)
+    }
+
+    fn repr(&self) -> Arc<dyn PlanRepr> {
+        Arc::new(UnionPlanRepr {
+            p1: self.p1.repr(),
+            p2: self.p2.repr(),
+            r: self.blocks_accessed(),
+            w: self.records_output(),
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct UnionPlanRepr {
+    p1: Arc<dyn PlanRepr>,
+    p2: Arc<dyn PlanRepr>,
+    r: i32,
+    w: i32,
+}
+
+impl PlanRepr for UnionPlanRepr {
+    fn operation(&self) -> Operation {
+        Operation::UnionScan
+    }
+    fn reads(&self) -> i32 {
+        self.r
+    }
+    fn writes(&self) -> i32 {
+        self.w
+    }
+    fn sub_plan_reprs(&self) -> Vec<Arc<dyn PlanRepr>> {
+        vec![Arc::clone(&self.p1), Arc::clone(&self.p2)]
+    }
+}
+
+impl UnionPlan {
+    pub fn new(p1: Arc<dyn Plan>, p2: Arc<dyn Plan>) -> Self {
+        let schema = Arc::new(union_schema(&p1.schema(), &p2.schema()));
+
+        Self { p1, p2, schema }
+    }
+}
+
+// the schema of the records of a set operation, whose fields are named after sch1.
+// a string field is as long as the longer one of the two.
+pub fn union_schema(sch1: &Schema, sch2: &Schema) -> Schema {
+    let mut sch = Schema::new();
+    for (f1, f2) in sch1.fields().iter().zip(sch2.fields().iter()) {
+        let length = sch1.length(f1).max(sch2.length(f2));
+        sch.add_field(f1, sch1.field_type(f1), length);
+    }
+
+    sch
+}
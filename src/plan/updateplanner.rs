@@ -1,12 +1,27 @@
 use anyhow::Result;
-use std::sync::{Arc, Mutex};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
 
 use crate::{
+    metadata::{
+        constraintmanager::{ConstraintError, ForeignKeyInfo, KeyInfo},
+        indexmanager::IndexInfo,
+        manager::MetadataMgr,
+    },
     parser::{
-        createindexdata::CreateIndexData, createtabledata::CreateTableData,
-        createviewdata::CreateViewData, deletedata::DeleteData, insertdata::InsertData,
+        altertabledata::AlterTableData,
+        createindexdata::CreateIndexData,
+        createtabledata::{CreateTableData, FieldConstraint, RefAction},
+        createviewdata::CreateViewData,
+        deletedata::DeleteData,
+        dropdata::DropData,
+        insertdata::InsertData,
         modifydata::ModifyData,
     },
+    query::{constant::Constant, scan::Scan, updatescan::UpdateScan, valuesscan::ValuesScan},
+    record::{rid::RID, schema::Schema, tablescan::TableScan},
     tx::transaction::Transaction,
 };
 
@@ -26,4 +41,381 @@ pub trait UpdatePlanner {
         data: CreateIndexData,
         tx: Arc<Mutex<Transaction>>,
     ) -> Result<i32>;
+    // my own extends
+    fn execute_drop_table(&self, data: DropData, tx: Arc<Mutex<Transaction>>) -> Result<i32>;
+    fn execute_drop_view(&self, data: DropData, tx: Arc<Mutex<Transaction>>) -> Result<i32>;
+    fn execute_drop_index(&self, data: DropData, tx: Arc<Mutex<Transaction>>) -> Result<i32>;
+    fn execute_alter_table(&self, data: AlterTableData, tx: Arc<Mutex<Transaction>>)
+        -> Result<i32>;
+}
+
+// my own extends
+// the fields, which aren't in the insert, are filled with their defaults.
+// a default is evaluated once for all records, e.g. CURRENT_DATE.
+pub fn fill_defaults(data: InsertData, constraints: &[FieldConstraint]) -> Result<InsertData> {
+    let empty: Arc<Mutex<dyn Scan>> = Arc::new(Mutex::new(ValuesScan::new(HashMap::new())));
+    let mut fields = data.fields().clone();
+    let mut defaults = vec![];
+    for fc in constraints {
+        if let Some(expr) = fc.default() {
+            if !fields.iter().any(|fldname| fldname == fc.field_name()) {
+                fields.push(fc.field_name().to_string());
+                defaults.push(expr.evaluate(Arc::clone(&empty))?);
+            }
+        }
+    }
+    if defaults.is_empty() {
+        return Ok(data);
+    }
+    let rows = data
+        .rows()
+        .iter()
+        .map(|vals| vals.iter().chain(defaults.iter()).cloned().collect())
+        .collect();
+
+    Ok(InsertData::new_rows(
+        data.table_name().to_string(),
+        fields,
+        rows,
+    ))
+}
+
+// my own extends
+// the values of the record to be written, which are converted to the types of the fields.
+// the other fields keep the values of the base record, or are null.
+pub fn record_values<'a, I>(
+    sch: &Schema,
+    mut base: HashMap<String, Constant>,
+    vals: I,
+) -> Result<HashMap<String, Constant>>
+where
+    I: Iterator<Item = (&'a String, &'a Constant)>,
+{
+    for (fldname, val) in vals {
+        // an unknown field is left to UpdateScan, which fails on it.
+        if let Some(f) = sch.resolve(fldname) {
+            base.insert(f.clone(), val.as_field_type(sch.field_type(f))?);
+        }
+    }
+    for fldname in sch.fields() {
+        base.entry(fldname.clone()).or_insert(Constant::Null);
+    }
+
+    Ok(base)
+}
+
+// my own extends
+// checks NOT NULL and CHECK of the fields on the values of a record, before it is written.
+// a check fails only if it is false, so it is satisfied by nulls as in the standard.
+pub fn check_constraints(
+    tblname: &str,
+    constraints: &[FieldConstraint],
+    record: HashMap<String, Constant>,
+) -> Result<()> {
+    let s: Arc<Mutex<dyn Scan>> = Arc::new(Mutex::new(ValuesScan::new(record)));
+    for fc in constraints {
+        let fldname = fc.field_name();
+        if fc.is_not_null() && s.lock().unwrap().is_null(fldname)? {
+            return Err(From::from(ConstraintError::NullField(
+                tblname.to_string(),
+                fldname.to_string(),
+            )));
+        }
+        if let Some(pred) = fc.check() {
+            if pred.truth(Arc::clone(&s))? == Some(false) {
+                return Err(From::from(ConstraintError::CheckFailed(
+                    tblname.to_string(),
+                    fldname.to_string(),
+                    pred.to_string(),
+                )));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// my own extends
+// the indexes backing the keys of the table, which are maintained by every planner,
+// because the keys are checked on them. an index is found by the name of the key's index.
+pub fn key_indexes(
+    md: &mut MetadataMgr,
+    tblname: &str,
+    tx: Arc<Mutex<Transaction>>,
+) -> Result<HashMap<String, IndexInfo>> {
+    let keys = md.get_keys(tblname, Arc::clone(&tx))?;
+    let indexes = md.get_index_info(tblname, tx)?;
+
+    Ok(indexes
+        .into_iter()
+        .filter(|(fldname, ii)| {
+            keys.iter()
+                .any(|key| key.field_name() == fldname && key.index_name() == ii.index_name())
+        })
+        .collect())
+}
+
+// my own extends
+// the value of a key must not be null for a primary key, and must not be
+// in the index of the key for another record than rid.
+// nulls are not compared, so a unique key may be null for many records.
+pub fn check_key(
+    tblname: &str,
+    key: &KeyInfo,
+    indexes: &HashMap<String, IndexInfo>,
+    val: &Constant,
+    rid: Option<RID>,
+) -> Result<()> {
+    let fldname = key.field_name();
+    if val.is_null() {
+        if key.is_primary() {
+            return Err(From::from(ConstraintError::NullKey(
+                tblname.to_string(),
+                fldname.to_string(),
+            )));
+        }
+        return Ok(());
+    }
+    // the key is never left unchecked, so a missing index of it is an error.
+    let ii = indexes
+        .get(fldname)
+        .filter(|ii| ii.index_name() == key.index_name())
+        .ok_or_else(|| {
+            ConstraintError::MissingKeyIndex(tblname.to_string(), fldname.to_string())
+        })?;
+    // NOTE: convert the type here, because Index doesn't convert val.
+    let fldtype = ii.table_schema().field_type(fldname);
+    let val = val.as_field_type(fldtype)?;
+
+    let idx = ii.open();
+    let mut idx = idx.lock().unwrap();
+    idx.before_first(val.clone())?;
+    let mut found = false;
+    while idx.next() {
+        if Some(idx.get_data_rid()?) != rid {
+            found = true;
+            break;
+        }
+    }
+    idx.close()?;
+    if found {
+        return Err(From::from(ConstraintError::DuplicateKey(
+            tblname.to_string(),
+            fldname.to_string(),
+            val,
+        )));
+    }
+
+    Ok(())
+}
+
+// my own extends
+// the referenced key must be in the index of the key, unless the value is null.
+pub fn check_reference(
+    md: &mut MetadataMgr,
+    fk: &ForeignKeyInfo,
+    val: &Constant,
+    tx: Arc<Mutex<Transaction>>,
+) -> Result<()> {
+    if val.is_null() {
+        return Ok(());
+    }
+    let reffldname = fk.ref_field_name();
+    let indexes = key_indexes(md, fk.ref_table_name(), tx)?;
+    let ii = indexes.get(reffldname).ok_or_else(|| {
+        ConstraintError::MissingKeyIndex(fk.ref_table_name().to_string(), reffldname.to_string())
+    })?;
+    // NOTE: convert the type here, because Index doesn't convert val.
+    let fldtype = ii.table_schema().field_type(reffldname);
+    let val = val.as_field_type(fldtype)?;
+
+    let idx = ii.open();
+    let mut idx = idx.lock().unwrap();
+    idx.before_first(val.clone())?;
+    let found = idx.next();
+    idx.close()?;
+    if !found {
+        return Err(From::from(ConstraintError::MissingReference(
+            fk.table_name().to_string(),
+            fk.field_name().to_string(),
+            val,
+        )));
+    }
+
+    Ok(())
+}
+
+// my own extends
+// the key must not be referenced by any record of the foreign key.
+pub fn check_unreferenced(
+    md: &mut MetadataMgr,
+    fk: &ForeignKeyInfo,
+    val: &Constant,
+    tx: Arc<Mutex<Transaction>>,
+) -> Result<()> {
+    if !val.is_null() && find_reference(md, fk, val, tx)?.is_some() {
+        return Err(From::from(ConstraintError::ReferencedKey(
+            fk.ref_table_name().to_string(),
+            fk.ref_field_name().to_string(),
+            val.clone(),
+            fk.table_name().to_string(),
+        )));
+    }
+
+    Ok(())
+}
+
+// a record which references the key, found by the index of the field if it exists.
+fn find_reference(
+    md: &mut MetadataMgr,
+    fk: &ForeignKeyInfo,
+    val: &Constant,
+    tx: Arc<Mutex<Transaction>>,
+) -> Result<Option<RID>> {
+    let tblname = fk.table_name();
+    let fldname = fk.field_name();
+    let indexes = md.get_index_info(tblname, Arc::clone(&tx))?;
+    if let Some(ii) = indexes.get(fldname) {
+        // NOTE: convert the type here, because Index doesn't convert val.
+        let fldtype = ii.table_schema().field_type(fldname);
+        let val = val.as_field_type(fldtype)?;
+
+        let idx = ii.open();
+        let mut idx = idx.lock().unwrap();
+        idx.before_first(val)?;
+        let mut result = None;
+        if idx.next() {
+            result = Some(idx.get_data_rid()?);
+        }
+        idx.close()?;
+        return Ok(result);
+    }
+
+    let layout = md.get_layout(tblname, Arc::clone(&tx))?;
+    let mut ts = TableScan::new(tx, tblname, layout)?;
+    let mut result = None;
+    while ts.next() {
+        if ts.get_val(fldname)? == *val {
+            result = Some(ts.get_rid()?);
+            break;
+        }
+    }
+    ts.close()?;
+
+    Ok(result)
+}
+
+// my own extends
+// applies the actions of the foreign keys on the records, which reference the deleted record.
+// refvals has the values of the referenced keys of the deleted record.
+// the records are found one by one, because a cascade may delete the other ones.
+pub fn delete_references(
+    md: &mut MetadataMgr,
+    tblname: &str,
+    refvals: &HashMap<String, Constant>,
+    tx: Arc<Mutex<Transaction>>,
+) -> Result<()> {
+    for fk in md.get_referencing_keys(tblname, Arc::clone(&tx))? {
+        let val = match refvals.get(fk.ref_field_name()) {
+            Some(val) if !val.is_null() => val,
+            _ => continue,
+        };
+        match fk.on_delete() {
+            RefAction::Restrict => check_unreferenced(md, &fk, val, Arc::clone(&tx))?,
+            RefAction::Cascade => {
+                while let Some(rid) = find_reference(md, &fk, val, Arc::clone(&tx))? {
+                    delete_record(md, fk.table_name(), rid, Arc::clone(&tx))?;
+                }
+            }
+            RefAction::SetNull => {
+                while let Some(rid) = find_reference(md, &fk, val, Arc::clone(&tx))? {
+                    set_null_reference(md, &fk, rid, Arc::clone(&tx))?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// the record is deleted before its references, so a cycle of cascades ends.
+fn delete_record(
+    md: &mut MetadataMgr,
+    tblname: &str,
+    rid: RID,
+    tx: Arc<Mutex<Transaction>>,
+) -> Result<()> {
+    let layout = md.get_layout(tblname, Arc::clone(&tx))?;
+    let indexes = md.get_index_info(tblname, Arc::clone(&tx))?;
+    let mut ts = TableScan::new(Arc::clone(&tx), tblname, Arc::clone(&layout))?;
+    ts.move_to_rid(rid)?;
+    let mut vals = HashMap::new();
+    for fldname in layout.schema().fields() {
+        vals.insert(fldname.clone(), ts.get_val(fldname)?);
+    }
+    for (fldname, ii) in indexes.iter() {
+        // NOTE: convert the type here, because Index doesn't convert val.
+        let fldtype = ii.table_schema().field_type(fldname);
+        let val = vals[fldname].as_field_type(fldtype)?;
+
+        let idx = ii.open();
+        idx.lock().unwrap().delete(val, rid)?;
+        idx.lock().unwrap().close()?;
+    }
+    ts.delete()?;
+    ts.close()?;
+
+    delete_references(md, tblname, &vals, tx)
+}
+
+// the field of the foreign key is set to null, if its constraints allow it.
+fn set_null_reference(
+    md: &mut MetadataMgr,
+    fk: &ForeignKeyInfo,
+    rid: RID,
+    tx: Arc<Mutex<Transaction>>,
+) -> Result<()> {
+    let tblname = fk.table_name();
+    let fldname = fk.field_name();
+    let constraints = md.get_field_constraints(tblname, Arc::clone(&tx))?;
+    if constraints
+        .iter()
+        .any(|fc| fc.field_name() == fldname && fc.is_not_null())
+    {
+        return Err(From::from(ConstraintError::NullField(
+            tblname.to_string(),
+            fldname.to_string(),
+        )));
+    }
+    let indexes = md.get_index_info(tblname, Arc::clone(&tx))?;
+    for key in md.get_keys(tblname, Arc::clone(&tx))? {
+        if key.field_name() == fldname {
+            check_key(tblname, &key, &indexes, &Constant::Null, Some(rid))?;
+        }
+    }
+
+    let layout = md.get_layout(tblname, Arc::clone(&tx))?;
+    let mut ts = TableScan::new(Arc::clone(&tx), tblname, layout)?;
+    ts.move_to_rid(rid)?;
+    let oldval = ts.get_val(fldname)?;
+    // the field may be a referenced key too
+    for reffk in md.get_referencing_keys(tblname, Arc::clone(&tx))? {
+        if reffk.ref_field_name() == fldname {
+            check_unreferenced(md, &reffk, &oldval, Arc::clone(&tx))?;
+        }
+    }
+    ts.set_val(fldname, Constant::Null)?;
+    ts.close()?;
+    if let Some(ii) = indexes.get(fldname) {
+        // NOTE: convert the type here, because Index doesn't convert val.
+        let fldtype = ii.table_schema().field_type(fldname);
+        let oldval = oldval.as_field_type(fldtype)?;
+
+        let idx = ii.open();
+        idx.lock().unwrap().delete(oldval, rid)?;
+        idx.lock().unwrap().insert(Constant::Null, rid)?;
+        idx.lock().unwrap().close()?;
+    }
+
+    Ok(())
 }
@@ -1,12 +1,20 @@
 pub mod constant;
 pub mod expression;
+pub mod factor;
+pub mod function;
+pub mod limitscan;
+pub mod outerjoinscan;
+pub mod outerrecordscan;
 pub mod predicate;
 pub mod productscan;
 pub mod projectscan;
 pub mod scan;
 pub mod selectscan;
+pub mod subquery;
 pub mod term;
+pub mod unionscan;
 pub mod updatescan;
+pub mod valuesscan;
 
 #[cfg(test)]
 pub(crate) mod tests {
@@ -2,6 +2,7 @@ use anyhow::Result;
 use chrono::NaiveDate;
 use core::fmt;
 use log::debug;
+use std::cmp::Ordering;
 
 use crate::record::schema::FieldType;
 
@@ -21,13 +22,17 @@ impl fmt::Display for ConstantError {
     }
 }
 
-#[derive(Debug, Clone, Ord, PartialOrd, Hash)]
+#[derive(Debug, Clone, Hash)]
 pub enum Constant {
     I16(i16),
     I32(i32),
     String(String),
     Bool(bool),
     Date(NaiveDate),
+    // my own extends
+    // the SQL NULL, which is equal to itself only for grouping and sorting.
+    // the comparisons in a predicate treat it as unknown (see Term).
+    Null,
 }
 impl PartialEq for Constant {
     fn eq(&self, other: &Self) -> bool {
@@ -56,11 +61,43 @@ impl PartialEq for Constant {
                 Constant::Date(r) => *l == *r,
                 _ => false,
             },
+            Constant::Null => matches!(other, Constant::Null),
         }
     }
 }
 impl Eq for Constant {}
 
+// NOTE: the ordering must agree with PartialEq,
+// so numeric values are compared across I16/I32,
+// and a date is compared with a string as its 'YYYY-MM-DD' form.
+// Values which can't be compared by their contents are ordered by their kinds,
+// and NULL is ordered before any other value.
+impl Ord for Constant {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Constant::I16(l), Constant::I16(r)) => l.cmp(r),
+            (Constant::I16(l), Constant::I32(r)) => (*l as i32).cmp(r),
+            (Constant::I32(l), Constant::I16(r)) => l.cmp(&(*r as i32)),
+            (Constant::I32(l), Constant::I32(r)) => l.cmp(r),
+            (Constant::String(l), Constant::String(r)) => l.cmp(r),
+            (Constant::String(l), Constant::Date(r)) => {
+                l.as_str().cmp(r.format("%Y-%m-%d").to_string().as_str())
+            }
+            (Constant::Date(l), Constant::String(r)) => {
+                l.format("%Y-%m-%d").to_string().as_str().cmp(r.as_str())
+            }
+            (Constant::Date(l), Constant::Date(r)) => l.cmp(r),
+            (Constant::Bool(l), Constant::Bool(r)) => l.cmp(r),
+            (l, r) => l.kind().cmp(&r.kind()),
+        }
+    }
+}
+impl PartialOrd for Constant {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl fmt::Display for Constant {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -69,6 +106,7 @@ impl fmt::Display for Constant {
             Constant::String(sval) => write!(f, "'{}'", sval),
             Constant::Bool(bval) => write!(f, "{}", bval),
             Constant::Date(dval) => write!(f, "{}", dval.format("%Y-%m-%d")),
+            Constant::Null => write!(f, "null"),
         }
     }
 }
@@ -89,6 +127,12 @@ impl Constant {
     pub fn new_date(dval: NaiveDate) -> Self {
         Constant::Date(dval)
     }
+    pub fn new_null() -> Self {
+        Constant::Null
+    }
+    pub fn is_null(&self) -> bool {
+        matches!(self, Constant::Null)
+    }
     pub fn as_i16(&self) -> Result<i16> {
         match self {
             Constant::I16(ival) => Ok(*ival),
@@ -132,8 +176,20 @@ impl Constant {
             _ => Err(From::from(ConstantError::TypeError)),
         }
     }
+    fn kind(&self) -> i32 {
+        match self {
+            Constant::Null => -1,
+            Constant::I16(_) | Constant::I32(_) => 0,
+            Constant::String(_) | Constant::Date(_) => 1,
+            Constant::Bool(_) => 2,
+        }
+    }
     // extends by exercise 3.17
+    // NULL is a value of any type.
     pub fn as_field_type(&self, fldtype: FieldType) -> Result<Self> {
+        if self.is_null() {
+            return Ok(Constant::Null);
+        }
         match fldtype {
             FieldType::SMALLINT => self.as_i16().map(|x| Constant::I16(x)),
             FieldType::INTEGER => self.as_i32().map(|x| Constant::I32(x)),
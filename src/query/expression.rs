@@ -2,12 +2,21 @@ use anyhow::Result;
 use core::fmt;
 use std::sync::{Arc, Mutex};
 
-use super::{constant::Constant, scan::Scan};
-use crate::record::schema::Schema;
+use super::{
+    constant::Constant, function::ScalarFn, predicate::Predicate, scan::Scan, subquery::Subquery,
+    term::CompOp,
+};
+use crate::{
+    materialize::aggregationfn::Aggregate,
+    record::schema::{FieldInfo, FieldType, Schema},
+};
 
 #[derive(Debug)]
 pub enum ExpressionError {
     InvalidExpression,
+    TypeMismatch(String),
+    ArithmeticOverflow(String),
+    DivisionByZero(String),
 }
 
 impl std::error::Error for ExpressionError {}
@@ -17,6 +26,59 @@ impl fmt::Display for ExpressionError {
             ExpressionError::InvalidExpression => {
                 write!(f, "invalid expression")
             }
+            ExpressionError::TypeMismatch(expr) => {
+                write!(f, "type mismatch: {}", expr)
+            }
+            ExpressionError::ArithmeticOverflow(expr) => {
+                write!(f, "arithmetic overflow: {}", expr)
+            }
+            ExpressionError::DivisionByZero(expr) => {
+                write!(f, "division by zero: {}", expr)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum ArithOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl fmt::Display for ArithOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ArithOp::Add => write!(f, "+"),
+            ArithOp::Sub => write!(f, "-"),
+            ArithOp::Mul => write!(f, "*"),
+            ArithOp::Div => write!(f, "/"),
+        }
+    }
+}
+
+impl ArithOp {
+    fn precedence(&self) -> i32 {
+        match self {
+            ArithOp::Add | ArithOp::Sub => 1,
+            ArithOp::Mul | ArithOp::Div => 2,
+        }
+    }
+    fn apply_i16(&self, lhs: i16, rhs: i16) -> Option<i16> {
+        match self {
+            ArithOp::Add => lhs.checked_add(rhs),
+            ArithOp::Sub => lhs.checked_sub(rhs),
+            ArithOp::Mul => lhs.checked_mul(rhs),
+            ArithOp::Div => lhs.checked_div(rhs),
+        }
+    }
+    fn apply_i32(&self, lhs: i32, rhs: i32) -> Option<i32> {
+        match self {
+            ArithOp::Add => lhs.checked_add(rhs),
+            ArithOp::Sub => lhs.checked_sub(rhs),
+            ArithOp::Mul => lhs.checked_mul(rhs),
+            ArithOp::Div => lhs.checked_div(rhs),
         }
     }
 }
@@ -25,13 +87,84 @@ impl fmt::Display for ExpressionError {
 pub enum Expression {
     Val(Constant),
     Fldname(String),
+    Arith(Box<Expression>, ArithOp, Box<Expression>),
+    Neg(Box<Expression>),
+    Aggregate(Aggregate),
+    // a scalar subquery
+    Subquery(Subquery),
+    // my own extends
+    // CASE WHEN pred THEN result ... [ELSE result] END
+    Case(Vec<(Predicate, Expression)>, Option<Box<Expression>>),
+    // CASE operand WHEN value THEN result ... [ELSE result] END
+    SimpleCase(
+        Box<Expression>,
+        Vec<(Expression, Expression)>,
+        Option<Box<Expression>>,
+    ),
+    // COALESCE(expr, ...), the first value which isn't null
+    Coalesce(Vec<Expression>),
+    // a call of the scalar function
+    Function(ScalarFn, Vec<Expression>),
 }
 
+// NOTE: parentheses are only written where the precedence requires them,
+// so that the string can be parsed back into the same expression.
 impl fmt::Display for Expression {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Expression::Val(val) => write!(f, "{}", val.to_string()),
             Expression::Fldname(fldname) => write!(f, "{}", fldname),
+            Expression::Arith(lhs, op, rhs) => {
+                if lhs.precedence() < op.precedence() {
+                    write!(f, "({})", lhs)?;
+                } else {
+                    write!(f, "{}", lhs)?;
+                }
+                write!(f, "{}", op)?;
+                if rhs.precedence() <= op.precedence() {
+                    write!(f, "({})", rhs)
+                } else {
+                    write!(f, "{}", rhs)
+                }
+            }
+            Expression::Neg(expr) => match expr.as_ref() {
+                Expression::Fldname(fldname) => write!(f, "-{}", fldname),
+                _ => write!(f, "-({})", expr),
+            },
+            Expression::Aggregate(agg) => write!(f, "{}", agg),
+            Expression::Subquery(sq) => write!(f, "{}", sq),
+            Expression::Case(whens, otherwise) => {
+                write!(f, "case")?;
+                for (pred, result) in whens.iter() {
+                    write!(f, " when {} then {}", pred, result)?;
+                }
+                if let Some(expr) = otherwise {
+                    write!(f, " else {}", expr)?;
+                }
+                write!(f, " end")
+            }
+            Expression::SimpleCase(operand, whens, otherwise) => {
+                write!(f, "case {}", operand)?;
+                for (val, result) in whens.iter() {
+                    write!(f, " when {} then {}", val, result)?;
+                }
+                if let Some(expr) = otherwise {
+                    write!(f, " else {}", expr)?;
+                }
+                write!(f, " end")
+            }
+            Expression::Coalesce(exprs) => {
+                let args = exprs.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+                write!(f, "coalesce({})", args.join(", "))
+            }
+            // CURRENT_DATE is written without parentheses as in SQL
+            Expression::Function(ScalarFn::CurrentDate, args) if args.is_empty() => {
+                write!(f, "{}", ScalarFn::CurrentDate)
+            }
+            Expression::Function(func, args) => {
+                let args = args.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+                write!(f, "{}({})", func, args.join(", "))
+            }
         }
     }
 }
@@ -43,34 +176,573 @@ impl Expression {
     pub fn new_fldname(fldname: String) -> Self {
         Expression::Fldname(fldname)
     }
+    pub fn new_arith(lhs: Expression, op: ArithOp, rhs: Expression) -> Self {
+        Expression::Arith(Box::new(lhs), op, Box::new(rhs))
+    }
+    pub fn new_neg(expr: Expression) -> Self {
+        Expression::Neg(Box::new(expr))
+    }
+    pub fn new_aggregate(agg: Aggregate) -> Self {
+        Expression::Aggregate(agg)
+    }
+    pub fn new_subquery(sq: Subquery) -> Self {
+        Expression::Subquery(sq)
+    }
+    pub fn new_case(whens: Vec<(Predicate, Expression)>, otherwise: Option<Expression>) -> Self {
+        Expression::Case(whens, otherwise.map(Box::new))
+    }
+    pub fn new_simple_case(
+        operand: Expression,
+        whens: Vec<(Expression, Expression)>,
+        otherwise: Option<Expression>,
+    ) -> Self {
+        Expression::SimpleCase(Box::new(operand), whens, otherwise.map(Box::new))
+    }
+    pub fn new_coalesce(exprs: Vec<Expression>) -> Self {
+        Expression::Coalesce(exprs)
+    }
+    pub fn new_function(func: ScalarFn, args: Vec<Expression>) -> Self {
+        Expression::Function(func, args)
+    }
     pub fn is_fldname(&self) -> bool {
         match self {
-            Expression::Val(_) => false,
             Expression::Fldname(_) => true,
+            _ => false,
         }
     }
     pub fn as_constant(&self) -> Option<&Constant> {
         match self {
             Expression::Val(c) => Some(&c),
-            Expression::Fldname(_) => None,
+            _ => None,
         }
     }
     pub fn as_field_name(&self) -> Result<&str> {
         match self {
-            Expression::Val(_) => Err(From::from(ExpressionError::InvalidExpression)),
             Expression::Fldname(s) => Ok(&s),
+            _ => Err(From::from(ExpressionError::InvalidExpression)),
         }
     }
     pub fn evaluate(&self, s: Arc<Mutex<dyn Scan>>) -> Result<Constant> {
         match self {
             Expression::Val(val) => Ok(val.clone()),
             Expression::Fldname(fldname) => s.lock().unwrap().get_val(fldname),
+            Expression::Arith(lhs, op, rhs) => {
+                let lhsval = lhs.evaluate(Arc::clone(&s))?;
+                let rhsval = rhs.evaluate(Arc::clone(&s))?;
+                self.calculate(*op, lhsval, rhsval)
+            }
+            Expression::Neg(expr) => match expr.evaluate(s)? {
+                Constant::I16(v) => v.checked_neg().map(|x| Constant::I16(x)).ok_or_else(|| {
+                    From::from(ExpressionError::ArithmeticOverflow(self.to_string()))
+                }),
+                Constant::I32(v) => v.checked_neg().map(|x| Constant::I32(x)).ok_or_else(|| {
+                    From::from(ExpressionError::ArithmeticOverflow(self.to_string()))
+                }),
+                Constant::Null => Ok(Constant::Null),
+                _ => Err(From::from(ExpressionError::TypeMismatch(self.to_string()))),
+            },
+            // the value is computed by the group by, and read from its output field
+            Expression::Aggregate(agg) => s.lock().unwrap().get_val(&agg.field_name()),
+            Expression::Subquery(sq) => sq.scalar(s),
+            // the result of the first WHEN which is true, not unknown
+            Expression::Case(whens, otherwise) => {
+                for (pred, result) in whens.iter() {
+                    if pred.truth(Arc::clone(&s))? == Some(true) {
+                        return result.evaluate(s);
+                    }
+                }
+                match otherwise {
+                    Some(expr) => expr.evaluate(s),
+                    None => Ok(Constant::Null),
+                }
+            }
+            // the result of the first WHEN value which equals the operand
+            Expression::SimpleCase(operand, whens, otherwise) => {
+                let opval = operand.evaluate(Arc::clone(&s))?;
+                for (val, result) in whens.iter() {
+                    let val = val.evaluate(Arc::clone(&s))?;
+                    if CompOp::Eq.apply(&opval, &val) == Some(true) {
+                        return result.evaluate(s);
+                    }
+                }
+                match otherwise {
+                    Some(expr) => expr.evaluate(s),
+                    None => Ok(Constant::Null),
+                }
+            }
+            Expression::Coalesce(exprs) => {
+                for expr in exprs.iter() {
+                    let val = expr.evaluate(Arc::clone(&s))?;
+                    if !val.is_null() {
+                        return Ok(val);
+                    }
+                }
+                Ok(Constant::Null)
+            }
+            Expression::Function(func, args) => {
+                let mut vals = vec![];
+                for arg in args.iter() {
+                    vals.push(arg.evaluate(Arc::clone(&s))?);
+                }
+                func.apply(&vals)
+            }
         }
     }
+    fn calculate(&self, op: ArithOp, lhsval: Constant, rhsval: Constant) -> Result<Constant> {
+        let result = match (lhsval, rhsval) {
+            (Constant::Null, _) | (_, Constant::Null) => return Ok(Constant::Null),
+            (Constant::I16(l), Constant::I16(r)) => {
+                if op == ArithOp::Div && r == 0 {
+                    return Err(From::from(ExpressionError::DivisionByZero(
+                        self.to_string(),
+                    )));
+                }
+                op.apply_i16(l, r).map(|x| Constant::I16(x))
+            }
+            (
+                l @ (Constant::I16(_) | Constant::I32(_)),
+                r @ (Constant::I16(_) | Constant::I32(_)),
+            ) => {
+                let l = l.as_i32()?;
+                let r = r.as_i32()?;
+                if op == ArithOp::Div && r == 0 {
+                    return Err(From::from(ExpressionError::DivisionByZero(
+                        self.to_string(),
+                    )));
+                }
+                op.apply_i32(l, r).map(|x| Constant::I32(x))
+            }
+            _ => return Err(From::from(ExpressionError::TypeMismatch(self.to_string()))),
+        };
+        result.ok_or_else(|| From::from(ExpressionError::ArithmeticOverflow(self.to_string())))
+    }
     pub fn applies_to(&self, sch: Arc<Schema>) -> bool {
         match self {
             Expression::Val(_) => true,
             Expression::Fldname(fldname) => sch.has_field(fldname),
+            Expression::Arith(lhs, _, rhs) => {
+                lhs.applies_to(Arc::clone(&sch)) && rhs.applies_to(Arc::clone(&sch))
+            }
+            Expression::Neg(expr) => expr.applies_to(sch),
+            Expression::Aggregate(agg) => sch.has_field(&agg.field_name()),
+            // the outer fields of a subquery are bound by the planner
+            Expression::Subquery(_) => true,
+            Expression::Case(whens, _) => {
+                whens
+                    .iter()
+                    .all(|(pred, _)| pred.applies_to(Arc::clone(&sch)))
+                    && self
+                        .children()
+                        .iter()
+                        .all(|e| e.applies_to(Arc::clone(&sch)))
+            }
+            Expression::SimpleCase(_, _, _)
+            | Expression::Coalesce(_)
+            | Expression::Function(_, _) => self
+                .children()
+                .iter()
+                .all(|e| e.applies_to(Arc::clone(&sch))),
         }
     }
+    // infers the type of the value, which the expression will be evaluated to.
+    pub fn field_info(&self, sch: &Schema) -> Result<FieldInfo> {
+        match self {
+            Expression::Val(val) => Ok(match val {
+                Constant::I16(_) => FieldInfo::new(FieldType::SMALLINT, 0),
+                Constant::I32(_) => FieldInfo::new(FieldType::INTEGER, 0),
+                Constant::String(s) => FieldInfo::new(FieldType::VARCHAR, s.len()),
+                Constant::Bool(_) => FieldInfo::new(FieldType::BOOL, 0),
+                Constant::Date(_) => FieldInfo::new(FieldType::DATE, 0),
+                // a null literal has no type of its own
+                Constant::Null => FieldInfo::new(FieldType::INTEGER, 0),
+            }),
+            Expression::Fldname(fldname) => {
+                if !sch.has_field(fldname) {
+                    return Err(From::from(ExpressionError::InvalidExpression));
+                }
+                Ok(FieldInfo::new(sch.field_type(fldname), sch.length(fldname)))
+            }
+            Expression::Arith(lhs, _, rhs) => {
+                let lhsinfo = lhs.field_info(sch)?;
+                let rhsinfo = rhs.field_info(sch)?;
+                match (lhsinfo.fld_type, rhsinfo.fld_type) {
+                    (FieldType::SMALLINT, FieldType::SMALLINT) => {
+                        Ok(FieldInfo::new(FieldType::SMALLINT, 0))
+                    }
+                    (
+                        FieldType::SMALLINT | FieldType::INTEGER,
+                        FieldType::SMALLINT | FieldType::INTEGER,
+                    ) => Ok(FieldInfo::new(FieldType::INTEGER, 0)),
+                    _ => Err(From::from(ExpressionError::TypeMismatch(self.to_string()))),
+                }
+            }
+            Expression::Neg(expr) => {
+                let info = expr.field_info(sch)?;
+                match info.fld_type {
+                    FieldType::SMALLINT | FieldType::INTEGER => Ok(info),
+                    _ => Err(From::from(ExpressionError::TypeMismatch(self.to_string()))),
+                }
+            }
+            Expression::Aggregate(agg) => {
+                let fldname = agg.field_name();
+                if !sch.has_field(&fldname) {
+                    return Err(From::from(ExpressionError::InvalidExpression));
+                }
+                Ok(FieldInfo::new(
+                    sch.field_type(&fldname),
+                    sch.length(&fldname),
+                ))
+            }
+            Expression::Subquery(sq) => sq.field_info(),
+            Expression::Case(whens, otherwise) => {
+                let mut results = whens.iter().map(|(_, e)| e).collect::<Vec<_>>();
+                results.extend(otherwise.as_deref());
+                self.common_field_info(&results, sch)
+            }
+            Expression::SimpleCase(_, whens, otherwise) => {
+                let mut results = whens.iter().map(|(_, e)| e).collect::<Vec<_>>();
+                results.extend(otherwise.as_deref());
+                self.common_field_info(&results, sch)
+            }
+            Expression::Coalesce(exprs) => {
+                self.common_field_info(&exprs.iter().collect::<Vec<_>>(), sch)
+            }
+            Expression::Function(func, args) => {
+                let mut infos = vec![];
+                for arg in args.iter() {
+                    let info = arg.field_info(sch)?;
+                    // a null literal is an argument of any type
+                    match arg {
+                        Expression::Val(Constant::Null) => infos.push(None),
+                        _ => infos.push(Some(info)),
+                    }
+                }
+                func.field_info(&infos)
+            }
+        }
+    }
+    // the type which all the results can take, ignoring the null literals.
+    // the integers are widened, and a string is as long as the longest one.
+    fn common_field_info(&self, exprs: &[&Expression], sch: &Schema) -> Result<FieldInfo> {
+        let mut result: Option<FieldInfo> = None;
+        for expr in exprs.iter() {
+            let info = expr.field_info(sch)?;
+            if let Expression::Val(Constant::Null) = expr {
+                continue;
+            }
+            result = Some(match result {
+                None => info,
+                Some(prev) => match (prev.fld_type, info.fld_type) {
+                    (FieldType::SMALLINT, FieldType::INTEGER)
+                    | (FieldType::INTEGER, FieldType::SMALLINT) => {
+                        FieldInfo::new(FieldType::INTEGER, 0)
+                    }
+                    (l, r) if l == r => FieldInfo::new(l, prev.length.max(info.length)),
+                    _ => return Err(From::from(ExpressionError::TypeMismatch(self.to_string()))),
+                },
+            });
+        }
+
+        // all the results are null
+        Ok(result.unwrap_or(FieldInfo::new(FieldType::INTEGER, 0)))
+    }
+    // the sub expressions, not including the predicates of the searched CASE.
+    fn children(&self) -> Vec<&Expression> {
+        match self {
+            Expression::Val(_)
+            | Expression::Fldname(_)
+            | Expression::Aggregate(_)
+            | Expression::Subquery(_) => vec![],
+            Expression::Arith(lhs, _, rhs) => vec![lhs, rhs],
+            Expression::Neg(expr) => vec![expr],
+            Expression::Case(whens, otherwise) => {
+                let mut result = whens.iter().map(|(_, e)| e).collect::<Vec<_>>();
+                result.extend(otherwise.as_deref());
+                result
+            }
+            Expression::SimpleCase(operand, whens, otherwise) => {
+                let mut result = vec![operand.as_ref()];
+                for (val, e) in whens.iter() {
+                    result.push(val);
+                    result.push(e);
+                }
+                result.extend(otherwise.as_deref());
+                result
+            }
+            Expression::Coalesce(exprs) | Expression::Function(_, exprs) => exprs.iter().collect(),
+        }
+    }
+    // collects the aggregate function calls in the expression.
+    pub fn aggregates(&self) -> Vec<Aggregate> {
+        match self {
+            // the aggregates of a subquery are computed by itself
+            Expression::Val(_) | Expression::Fldname(_) | Expression::Subquery(_) => vec![],
+            Expression::Arith(lhs, _, rhs) => {
+                let mut result = lhs.aggregates();
+                result.extend(rhs.aggregates());
+                result
+            }
+            Expression::Neg(expr) => expr.aggregates(),
+            Expression::Aggregate(agg) => vec![agg.clone()],
+            Expression::Case(whens, _) => {
+                let mut result = whens
+                    .iter()
+                    .flat_map(|(pred, _)| pred.aggregates())
+                    .collect::<Vec<_>>();
+                result.extend(self.children().iter().flat_map(|e| e.aggregates()));
+                result
+            }
+            Expression::SimpleCase(_, _, _)
+            | Expression::Coalesce(_)
+            | Expression::Function(_, _) => self
+                .children()
+                .iter()
+                .flat_map(|e| e.aggregates())
+                .collect(),
+        }
+    }
+    // collects the field names in the expression, including the subqueries.
+    pub fn field_names(&self) -> Vec<String> {
+        match self {
+            Expression::Val(_) | Expression::Aggregate(_) => vec![],
+            Expression::Fldname(fldname) => vec![fldname.clone()],
+            Expression::Arith(lhs, _, rhs) => {
+                let mut result = lhs.field_names();
+                result.extend(rhs.field_names());
+                result
+            }
+            Expression::Neg(expr) => expr.field_names(),
+            Expression::Subquery(sq) => sq.data().field_names(),
+            Expression::Case(whens, _) => {
+                let mut result = whens
+                    .iter()
+                    .flat_map(|(pred, _)| pred.field_names())
+                    .collect::<Vec<_>>();
+                result.extend(self.children().iter().flat_map(|e| e.field_names()));
+                result
+            }
+            Expression::SimpleCase(_, _, _)
+            | Expression::Coalesce(_)
+            | Expression::Function(_, _) => self
+                .children()
+                .iter()
+                .flat_map(|e| e.field_names())
+                .collect(),
+        }
+    }
+    pub fn subqueries(&self) -> Vec<&Subquery> {
+        match self {
+            Expression::Val(_) | Expression::Fldname(_) | Expression::Aggregate(_) => vec![],
+            Expression::Arith(lhs, _, rhs) => {
+                let mut result = lhs.subqueries();
+                result.extend(rhs.subqueries());
+                result
+            }
+            Expression::Neg(expr) => expr.subqueries(),
+            Expression::Subquery(sq) => vec![sq],
+            Expression::Case(whens, _) => {
+                let mut result = whens
+                    .iter()
+                    .flat_map(|(pred, _)| pred.subqueries())
+                    .collect::<Vec<_>>();
+                result.extend(self.children().into_iter().flat_map(|e| e.subqueries()));
+                result
+            }
+            Expression::SimpleCase(_, _, _)
+            | Expression::Coalesce(_)
+            | Expression::Function(_, _) => self
+                .children()
+                .into_iter()
+                .flat_map(|e| e.subqueries())
+                .collect(),
+        }
+    }
+    pub fn subqueries_mut(&mut self) -> Vec<&mut Subquery> {
+        match self {
+            Expression::Val(_) | Expression::Fldname(_) | Expression::Aggregate(_) => vec![],
+            Expression::Arith(lhs, _, rhs) => {
+                let mut result = lhs.subqueries_mut();
+                result.extend(rhs.subqueries_mut());
+                result
+            }
+            Expression::Neg(expr) => expr.subqueries_mut(),
+            Expression::Subquery(sq) => vec![sq],
+            Expression::Case(whens, otherwise) => {
+                let mut result = vec![];
+                for (pred, e) in whens.iter_mut() {
+                    result.extend(pred.subqueries_mut());
+                    result.extend(e.subqueries_mut());
+                }
+                if let Some(e) = otherwise {
+                    result.extend(e.subqueries_mut());
+                }
+                result
+            }
+            Expression::SimpleCase(operand, whens, otherwise) => {
+                let mut result = operand.subqueries_mut();
+                for (val, e) in whens.iter_mut() {
+                    result.extend(val.subqueries_mut());
+                    result.extend(e.subqueries_mut());
+                }
+                if let Some(e) = otherwise {
+                    result.extend(e.subqueries_mut());
+                }
+                result
+            }
+            Expression::Coalesce(exprs) | Expression::Function(_, exprs) => {
+                exprs.iter_mut().flat_map(|e| e.subqueries_mut()).collect()
+            }
+        }
+    }
+    fn precedence(&self) -> i32 {
+        match self {
+            Expression::Arith(_, op, _) => op.precedence(),
+            _ => 3,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::term::Term;
+
+    #[test]
+    fn display_test() {
+        let a = Expression::new_fldname("a".to_string());
+        let b = Expression::new_fldname("b".to_string());
+        let one = Expression::new_val(Constant::I32(1));
+        let e = Expression::new_arith(a.clone(), ArithOp::Add, one.clone());
+        assert_eq!(e.to_string(), "a+1");
+        let e = Expression::new_arith(e, ArithOp::Mul, b.clone());
+        assert_eq!(e.to_string(), "(a+1)*b");
+        let e = Expression::new_arith(b.clone(), ArithOp::Sub, e);
+        assert_eq!(e.to_string(), "b-(a+1)*b");
+        let e = Expression::new_arith(
+            Expression::new_arith(a.clone(), ArithOp::Sub, b.clone()),
+            ArithOp::Sub,
+            Expression::new_arith(a.clone(), ArithOp::Sub, b.clone()),
+        );
+        assert_eq!(e.to_string(), "a-b-(a-b)");
+        assert_eq!(Expression::new_neg(a.clone()).to_string(), "-a");
+        assert_eq!(Expression::new_neg(one).to_string(), "-(1)");
+    }
+
+    #[test]
+    fn field_info_test() {
+        let mut sch = Schema::new();
+        sch.add_i16_field("s");
+        sch.add_i32_field("i");
+        sch.add_string_field("v", 10);
+        let s = Expression::new_fldname("s".to_string());
+        let i = Expression::new_fldname("i".to_string());
+        let v = Expression::new_fldname("v".to_string());
+
+        let e = Expression::new_arith(s.clone(), ArithOp::Add, s.clone());
+        assert_eq!(e.field_info(&sch).unwrap().fld_type, FieldType::SMALLINT);
+        let e = Expression::new_arith(s.clone(), ArithOp::Mul, i.clone());
+        assert_eq!(e.field_info(&sch).unwrap().fld_type, FieldType::INTEGER);
+        let e = Expression::new_neg(e);
+        assert_eq!(e.field_info(&sch).unwrap().fld_type, FieldType::INTEGER);
+        let e = Expression::new_arith(v.clone(), ArithOp::Add, i.clone());
+        assert!(e.field_info(&sch).is_err());
+        assert_eq!(
+            v.field_info(&sch).unwrap(),
+            FieldInfo::new(FieldType::VARCHAR, 10)
+        );
+        let e = Expression::new_function(
+            ScalarFn::Concat,
+            vec![v.clone(), Expression::new_val(Constant::Null), v.clone()],
+        );
+        assert_eq!(
+            e.field_info(&sch).unwrap(),
+            FieldInfo::new(FieldType::VARCHAR, 20)
+        );
+        let e = Expression::new_function(ScalarFn::Upper, vec![i.clone()]);
+        assert!(e.field_info(&sch).is_err());
+    }
+
+    #[test]
+    fn case_test() {
+        let mut sch = Schema::new();
+        sch.add_i16_field("s");
+        sch.add_i32_field("i");
+        sch.add_string_field("v", 10);
+        let s = Expression::new_fldname("s".to_string());
+        let i = Expression::new_fldname("i".to_string());
+        let v = Expression::new_fldname("v".to_string());
+        let null = Expression::new_val(Constant::Null);
+        let one = Expression::new_val(Constant::I32(1));
+        let abc = Expression::new_val(Constant::String("abc".to_string()));
+        let pred = Predicate::new(Term::new(i.clone(), one.clone()));
+
+        let e = Expression::new_case(vec![(pred.clone(), s.clone())], Some(i.clone()));
+        assert_eq!(e.to_string(), "case when i=1 then s else i end");
+        assert_eq!(
+            e.field_info(&sch).unwrap(),
+            FieldInfo::new(FieldType::INTEGER, 0)
+        );
+        let e = Expression::new_case(vec![(pred.clone(), abc.clone())], Some(v.clone()));
+        assert_eq!(
+            e.field_info(&sch).unwrap(),
+            FieldInfo::new(FieldType::VARCHAR, 10)
+        );
+        let e = Expression::new_case(vec![(pred, abc.clone())], Some(i.clone()));
+        assert!(e.field_info(&sch).is_err());
+
+        let e = Expression::new_simple_case(s.clone(), vec![(one, abc.clone())], None);
+        assert_eq!(e.to_string(), "case s when 1 then 'abc' end");
+        assert_eq!(
+            e.field_info(&sch).unwrap(),
+            FieldInfo::new(FieldType::VARCHAR, 3)
+        );
+        assert!(e.applies_to(Arc::new(sch.clone())));
+
+        let e = Expression::new_coalesce(vec![null.clone(), v.clone(), abc]);
+        assert_eq!(e.to_string(), "coalesce(null, v, 'abc')");
+        assert_eq!(
+            e.field_info(&sch).unwrap(),
+            FieldInfo::new(FieldType::VARCHAR, 10)
+        );
+        assert_eq!(e.field_names(), vec!["v".to_string()]);
+        let e = Expression::new_coalesce(vec![null]);
+        assert_eq!(
+            e.field_info(&sch).unwrap(),
+            FieldInfo::new(FieldType::INTEGER, 0)
+        );
+    }
+
+    #[test]
+    fn calculate_test() {
+        let e = Expression::new_val(Constant::I32(0));
+        assert_eq!(
+            e.calculate(ArithOp::Add, Constant::I16(1), Constant::I32(2))
+                .unwrap(),
+            Constant::I32(3)
+        );
+        assert_eq!(
+            e.calculate(ArithOp::Div, Constant::I16(7), Constant::I16(2))
+                .unwrap(),
+            Constant::I16(3)
+        );
+        assert!(e
+            .calculate(ArithOp::Add, Constant::I16(i16::MAX), Constant::I16(1))
+            .is_err());
+        assert!(e
+            .calculate(ArithOp::Mul, Constant::I32(i32::MAX), Constant::I32(2))
+            .is_err());
+        assert!(e
+            .calculate(ArithOp::Div, Constant::I32(1), Constant::I32(0))
+            .is_err());
+        assert!(e
+            .calculate(ArithOp::Div, Constant::I32(i32::MIN), Constant::I32(-1))
+            .is_err());
+        assert!(e
+            .calculate(
+                ArithOp::Sub,
+                Constant::String("a".to_string()),
+                Constant::I32(1)
+            )
+            .is_err());
+    }
 }
@@ -0,0 +1,189 @@
+use anyhow::Result;
+use core::fmt;
+use std::sync::{Arc, Mutex};
+
+use super::{
+    constant::Constant, expression::Expression, predicate::Predicate, scan::Scan,
+    subquery::Subquery, term::Term,
+};
+use crate::{materialize::aggregationfn::Aggregate, plan::plan::Plan, record::schema::Schema};
+
+// A factor is one conjunct of a predicate.
+// Disjunctions and negations hold whole predicates,
+// so that parenthesised boolean expressions can be nested arbitrarily.
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Factor {
+    Term(Term),
+    Or(Vec<Predicate>),
+    Not(Box<Predicate>),
+    In(Expression, Subquery),
+    Exists(Subquery),
+}
+
+impl fmt::Display for Factor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Factor::Term(t) => write!(f, "{}", t),
+            Factor::Or(preds) => {
+                let mut result = vec![];
+                for p in preds.iter() {
+                    result.push(p.to_string());
+                }
+                write!(f, "({})", result.join(" or "))
+            }
+            Factor::Not(p) => write!(f, "not ({})", p),
+            Factor::In(expr, sq) => write!(f, "{} in {}", expr, sq),
+            Factor::Exists(sq) => write!(f, "exists {}", sq),
+        }
+    }
+}
+
+impl Factor {
+    pub fn is_satisfied(&self, s: Arc<Mutex<dyn Scan>>) -> Result<bool> {
+        Ok(self.truth(s)? == Some(true))
+    }
+    // my own extends
+    // the three-valued truth of the factor, where None is unknown.
+    pub fn truth(&self, s: Arc<Mutex<dyn Scan>>) -> Result<Option<bool>> {
+        match self {
+            Factor::Term(t) => t.truth(s),
+            Factor::Or(preds) => {
+                let mut result = Some(false);
+                for p in preds.iter() {
+                    match p.truth(Arc::clone(&s))? {
+                        Some(true) => return Ok(Some(true)),
+                        Some(false) => {}
+                        None => result = None,
+                    }
+                }
+                Ok(result)
+            }
+            Factor::Not(p) => Ok(p.truth(s)?.map(|b| !b)),
+            Factor::In(expr, sq) => {
+                let val = expr.evaluate(Arc::clone(&s))?;
+                sq.contains(s, &val)
+            }
+            Factor::Exists(sq) => Ok(Some(sq.exists(s)?)),
+        }
+    }
+    pub fn applies_to(&self, sch: Arc<Schema>) -> bool {
+        match self {
+            Factor::Term(t) => t.applies_to(sch),
+            Factor::Or(preds) => preds.iter().all(|p| p.applies_to(Arc::clone(&sch))),
+            Factor::Not(p) => p.applies_to(sch),
+            // the outer fields of a subquery are bound by the planner
+            Factor::In(expr, _) => expr.applies_to(sch),
+            Factor::Exists(_) => true,
+        }
+    }
+    pub fn reduction_factor(&self, p: Arc<dyn Plan>) -> i32 {
+        match self {
+            Factor::Term(t) => t.reduction_factor(p),
+            Factor::Or(preds) => {
+                // P(a or b) = 1 - (1 - P(a)) * (1 - P(b)), assuming independence
+                let mut unselected = 1.0;
+                for pred in preds.iter() {
+                    unselected *= 1.0 - selectivity(pred.reduction_factor(Arc::clone(&p)));
+                }
+                to_reduction_factor(1.0 - unselected)
+            }
+            Factor::Not(pred) => {
+                to_reduction_factor(1.0 - selectivity(pred.reduction_factor(Arc::clone(&p))))
+            }
+            // the result of a subquery is unknown until it's executed
+            Factor::In(_, _) | Factor::Exists(_) => 1,
+        }
+    }
+    pub fn equates_with_constant(&self, fldname: &str) -> Option<&Constant> {
+        match self {
+            Factor::Term(t) => t.equates_with_constant(fldname),
+            _ => None,
+        }
+    }
+    pub fn equates_with_field(&self, fldname: &str) -> Option<&str> {
+        match self {
+            Factor::Term(t) => t.equates_with_field(fldname),
+            _ => None,
+        }
+    }
+    pub fn like_range(&self, fldname: &str) -> Option<(Constant, Constant)> {
+        match self {
+            Factor::Term(t) => t.like_range(fldname),
+            _ => None,
+        }
+    }
+    pub fn in_values(&self, fldname: &str) -> Option<Vec<Constant>> {
+        match self {
+            Factor::Term(t) => t.in_values(fldname),
+            _ => None,
+        }
+    }
+    pub fn between_range(&self, fldname: &str) -> Option<(Constant, Constant)> {
+        match self {
+            Factor::Term(t) => t.between_range(fldname),
+            _ => None,
+        }
+    }
+    pub fn aggregates(&self) -> Vec<Aggregate> {
+        match self {
+            Factor::Term(t) => t.aggregates(),
+            Factor::Or(preds) => preds.iter().flat_map(|p| p.aggregates()).collect(),
+            Factor::Not(p) => p.aggregates(),
+            Factor::In(expr, _) => expr.aggregates(),
+            Factor::Exists(_) => vec![],
+        }
+    }
+    pub fn field_names(&self) -> Vec<String> {
+        match self {
+            Factor::Term(t) => t.field_names(),
+            Factor::Or(preds) => preds.iter().flat_map(|p| p.field_names()).collect(),
+            Factor::Not(p) => p.field_names(),
+            Factor::In(expr, sq) => {
+                let mut result = expr.field_names();
+                result.extend(sq.data().field_names());
+                result
+            }
+            Factor::Exists(sq) => sq.data().field_names(),
+        }
+    }
+    pub fn subqueries(&self) -> Vec<&Subquery> {
+        match self {
+            Factor::Term(t) => t.subqueries(),
+            Factor::Or(preds) => preds.iter().flat_map(|p| p.subqueries()).collect(),
+            Factor::Not(p) => p.subqueries(),
+            Factor::In(expr, sq) => {
+                let mut result = expr.subqueries();
+                result.push(sq);
+                result
+            }
+            Factor::Exists(sq) => vec![sq],
+        }
+    }
+    pub fn subqueries_mut(&mut self) -> Vec<&mut Subquery> {
+        match self {
+            Factor::Term(t) => t.subqueries_mut(),
+            Factor::Or(preds) => preds.iter_mut().flat_map(|p| p.subqueries_mut()).collect(),
+            Factor::Not(p) => p.subqueries_mut(),
+            Factor::In(expr, sq) => {
+                let mut result = expr.subqueries_mut();
+                result.push(sq);
+                result
+            }
+            Factor::Exists(sq) => vec![sq],
+        }
+    }
+}
+
+fn selectivity(reduction_factor: i32) -> f64 {
+    if reduction_factor == i32::MAX {
+        return 0.0;
+    }
+    1.0 / reduction_factor.max(1) as f64
+}
+
+fn to_reduction_factor(selectivity: f64) -> i32 {
+    if selectivity <= 0.0 {
+        return i32::MAX;
+    }
+    (1.0 / selectivity).round().clamp(1.0, i32::MAX as f64) as i32
+}
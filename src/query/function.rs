@@ -0,0 +1,339 @@
+use anyhow::Result;
+use chrono::{Datelike, Duration, Local};
+use core::fmt;
+
+use super::constant::Constant;
+use crate::record::schema::{FieldInfo, FieldType};
+
+#[derive(Debug)]
+pub enum FunctionError {
+    ArgumentCount(String, usize),
+    TypeMismatch(String),
+    OutOfRange(String),
+}
+
+impl std::error::Error for FunctionError {}
+impl fmt::Display for FunctionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FunctionError::ArgumentCount(name, n) => {
+                write!(f, "wrong number of arguments: {} of {}", n, name)
+            }
+            FunctionError::TypeMismatch(name) => {
+                write!(f, "type mismatch: arguments of {}", name)
+            }
+            FunctionError::OutOfRange(name) => {
+                write!(f, "out of range: {}", name)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum ScalarFn {
+    Upper,
+    Lower,
+    Length,
+    Substr,
+    Trim,
+    Concat,
+    Year,
+    Month,
+    Day,
+    CurrentDate,
+    DateAdd,
+}
+
+// the registry of the scalar functions, whose names are case insensitive.
+const REGISTRY: [(&str, ScalarFn); 11] = [
+    ("upper", ScalarFn::Upper),
+    ("lower", ScalarFn::Lower),
+    ("length", ScalarFn::Length),
+    ("substr", ScalarFn::Substr),
+    ("trim", ScalarFn::Trim),
+    ("concat", ScalarFn::Concat),
+    ("year", ScalarFn::Year),
+    ("month", ScalarFn::Month),
+    ("day", ScalarFn::Day),
+    ("current_date", ScalarFn::CurrentDate),
+    ("date_add", ScalarFn::DateAdd),
+];
+
+// The types of the parameters.
+// An INTEGER parameter takes a SMALLINT too, and a DATE parameter takes
+// a VARCHAR formatted as %Y-%m-%d. The last `optional` parameters may be
+// omitted, and the last parameter repeats if the function is variadic.
+struct Signature {
+    params: &'static [FieldType],
+    optional: usize,
+    variadic: bool,
+}
+
+impl fmt::Display for ScalarFn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl ScalarFn {
+    pub fn lookup(name: &str) -> Option<ScalarFn> {
+        REGISTRY
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, f)| *f)
+    }
+    pub fn name(&self) -> &'static str {
+        REGISTRY.iter().find(|(_, f)| f == self).unwrap().0
+    }
+    fn signature(&self) -> Signature {
+        let (params, optional, variadic): (&'static [FieldType], usize, bool) = match self {
+            ScalarFn::Upper | ScalarFn::Lower | ScalarFn::Length | ScalarFn::Trim => {
+                (&[FieldType::VARCHAR], 0, false)
+            }
+            ScalarFn::Substr => (
+                &[FieldType::VARCHAR, FieldType::INTEGER, FieldType::INTEGER],
+                1,
+                false,
+            ),
+            ScalarFn::Concat => (&[FieldType::VARCHAR, FieldType::VARCHAR], 0, true),
+            ScalarFn::Year | ScalarFn::Month | ScalarFn::Day => (&[FieldType::DATE], 0, false),
+            ScalarFn::CurrentDate => (&[], 0, false),
+            ScalarFn::DateAdd => (&[FieldType::DATE, FieldType::INTEGER], 0, false),
+        };
+
+        Signature {
+            params,
+            optional,
+            variadic,
+        }
+    }
+    pub fn accepts(&self, nargs: usize) -> bool {
+        let sig = self.signature();
+        let min = sig.params.len() - sig.optional;
+        nargs >= min && (sig.variadic || nargs <= sig.params.len())
+    }
+    fn param_type(&self, i: usize) -> FieldType {
+        let params = self.signature().params;
+        params[i.min(params.len() - 1)]
+    }
+    // type-checks the arguments, and infers the type of the result.
+    // None is a null literal, which is an argument of any type.
+    pub fn field_info(&self, args: &[Option<FieldInfo>]) -> Result<FieldInfo> {
+        if !self.accepts(args.len()) {
+            return Err(From::from(FunctionError::ArgumentCount(
+                self.name().to_string(),
+                args.len(),
+            )));
+        }
+        for (i, arg) in args.iter().enumerate() {
+            let Some(info) = arg else {
+                continue;
+            };
+            let ok = match (self.param_type(i), info.fld_type) {
+                (FieldType::INTEGER, FieldType::SMALLINT | FieldType::INTEGER) => true,
+                (FieldType::DATE, FieldType::DATE | FieldType::VARCHAR) => true,
+                (param, actual) => param == actual,
+            };
+            if !ok {
+                return Err(From::from(FunctionError::TypeMismatch(
+                    self.name().to_string(),
+                )));
+            }
+        }
+
+        let length = |arg: &Option<FieldInfo>| arg.map_or(0, |info| info.length);
+        Ok(match self {
+            ScalarFn::Upper | ScalarFn::Lower | ScalarFn::Trim | ScalarFn::Substr => {
+                FieldInfo::new(FieldType::VARCHAR, length(&args[0]))
+            }
+            ScalarFn::Concat => FieldInfo::new(FieldType::VARCHAR, args.iter().map(length).sum()),
+            ScalarFn::Length | ScalarFn::Year | ScalarFn::Month | ScalarFn::Day => {
+                FieldInfo::new(FieldType::INTEGER, 0)
+            }
+            ScalarFn::CurrentDate | ScalarFn::DateAdd => FieldInfo::new(FieldType::DATE, 0),
+        })
+    }
+    // a null argument makes the result null.
+    pub fn apply(&self, args: &[Constant]) -> Result<Constant> {
+        if !self.accepts(args.len()) {
+            return Err(From::from(FunctionError::ArgumentCount(
+                self.name().to_string(),
+                args.len(),
+            )));
+        }
+        if args.iter().any(|arg| arg.is_null()) {
+            return Ok(Constant::Null);
+        }
+
+        let mismatch = |_| -> anyhow::Error {
+            From::from(FunctionError::TypeMismatch(self.name().to_string()))
+        };
+        let out_of_range =
+            || -> anyhow::Error { From::from(FunctionError::OutOfRange(self.name().to_string())) };
+        match self {
+            ScalarFn::Upper => {
+                let s = args[0].as_string().map_err(mismatch)?;
+                Ok(Constant::String(s.to_uppercase()))
+            }
+            ScalarFn::Lower => {
+                let s = args[0].as_string().map_err(mismatch)?;
+                Ok(Constant::String(s.to_lowercase()))
+            }
+            ScalarFn::Length => {
+                let s = args[0].as_string().map_err(mismatch)?;
+                Ok(Constant::I32(s.chars().count() as i32))
+            }
+            // the position starts from 1, and the characters before it are not counted.
+            ScalarFn::Substr => {
+                let s = args[0].as_string().map_err(mismatch)?;
+                let start = args[1].as_i32().map_err(mismatch)?;
+                let end = match args.get(2) {
+                    Some(len) => {
+                        let len = len.as_i32().map_err(mismatch)?;
+                        if len < 0 {
+                            return Err(out_of_range());
+                        }
+                        start.saturating_add(len)
+                    }
+                    None => i32::MAX,
+                };
+                let skip = (start.max(1) - 1) as usize;
+                let take = (end.max(1) - start.max(1)).max(0) as usize;
+                Ok(Constant::String(s.chars().skip(skip).take(take).collect()))
+            }
+            ScalarFn::Trim => {
+                let s = args[0].as_string().map_err(mismatch)?;
+                Ok(Constant::String(s.trim().to_string()))
+            }
+            ScalarFn::Concat => {
+                let mut result = String::new();
+                for arg in args.iter() {
+                    result.push_str(arg.as_string().map_err(mismatch)?);
+                }
+                Ok(Constant::String(result))
+            }
+            ScalarFn::Year => {
+                let d = args[0].as_date().map_err(mismatch)?;
+                Ok(Constant::I32(d.year()))
+            }
+            ScalarFn::Month => {
+                let d = args[0].as_date().map_err(mismatch)?;
+                Ok(Constant::I32(d.month() as i32))
+            }
+            ScalarFn::Day => {
+                let d = args[0].as_date().map_err(mismatch)?;
+                Ok(Constant::I32(d.day() as i32))
+            }
+            ScalarFn::CurrentDate => Ok(Constant::Date(Local::now().date_naive())),
+            ScalarFn::DateAdd => {
+                let d = args[0].as_date().map_err(mismatch)?;
+                let days = args[1].as_i32().map_err(mismatch)?;
+                d.checked_add_signed(Duration::days(days as i64))
+                    .map(|d| Constant::Date(d))
+                    .ok_or_else(out_of_range)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    #[test]
+    fn field_info_test() {
+        let varchar = |n| Some(FieldInfo::new(FieldType::VARCHAR, n));
+        let smallint = Some(FieldInfo::new(FieldType::SMALLINT, 0));
+        let date = Some(FieldInfo::new(FieldType::DATE, 0));
+
+        assert_eq!(ScalarFn::lookup("UPPER"), Some(ScalarFn::Upper));
+        assert_eq!(ScalarFn::lookup("Date_Add"), Some(ScalarFn::DateAdd));
+        assert_eq!(ScalarFn::lookup("reverse"), None);
+        assert_eq!(
+            ScalarFn::Upper.field_info(&[varchar(10)]).unwrap(),
+            FieldInfo::new(FieldType::VARCHAR, 10)
+        );
+        assert!(ScalarFn::Upper.field_info(&[smallint]).is_err());
+        assert!(ScalarFn::Upper
+            .field_info(&[varchar(10), varchar(10)])
+            .is_err());
+        assert_eq!(
+            ScalarFn::Substr
+                .field_info(&[varchar(10), smallint])
+                .unwrap(),
+            FieldInfo::new(FieldType::VARCHAR, 10)
+        );
+        assert!(ScalarFn::Substr.field_info(&[varchar(10)]).is_err());
+        assert_eq!(
+            ScalarFn::Concat
+                .field_info(&[varchar(3), None, varchar(4)])
+                .unwrap(),
+            FieldInfo::new(FieldType::VARCHAR, 7)
+        );
+        assert_eq!(
+            ScalarFn::Year.field_info(&[date]).unwrap().fld_type,
+            FieldType::INTEGER
+        );
+        assert_eq!(
+            ScalarFn::DateAdd
+                .field_info(&[varchar(10), smallint])
+                .unwrap()
+                .fld_type,
+            FieldType::DATE
+        );
+        assert!(ScalarFn::DateAdd.field_info(&[date, date]).is_err());
+        assert!(ScalarFn::CurrentDate.field_info(&[]).is_ok());
+    }
+
+    #[test]
+    fn apply_test() {
+        let s = |s: &str| Constant::String(s.to_string());
+        let i = |i: i32| Constant::I32(i);
+        let d = |y, m, d| Constant::Date(NaiveDate::from_ymd_opt(y, m, d).unwrap());
+
+        assert_eq!(ScalarFn::Upper.apply(&[s("Joe")]).unwrap(), s("JOE"));
+        assert_eq!(ScalarFn::Lower.apply(&[s("Joe")]).unwrap(), s("joe"));
+        assert_eq!(ScalarFn::Length.apply(&[s("joe")]).unwrap(), i(3));
+        assert_eq!(ScalarFn::Trim.apply(&[s("  joe ")]).unwrap(), s("joe"));
+        assert_eq!(
+            ScalarFn::Substr.apply(&[s("compsci"), i(5)]).unwrap(),
+            s("sci")
+        );
+        assert_eq!(
+            ScalarFn::Substr.apply(&[s("compsci"), i(2), i(3)]).unwrap(),
+            s("omp")
+        );
+        assert_eq!(
+            ScalarFn::Substr.apply(&[s("compsci"), i(0), i(2)]).unwrap(),
+            s("c")
+        );
+        assert!(ScalarFn::Substr
+            .apply(&[s("compsci"), i(1), i(-1)])
+            .is_err());
+        assert_eq!(
+            ScalarFn::Concat.apply(&[s("a"), s("b"), s("c")]).unwrap(),
+            s("abc")
+        );
+        assert_eq!(
+            ScalarFn::Concat.apply(&[s("a"), Constant::Null]).unwrap(),
+            Constant::Null
+        );
+        assert_eq!(ScalarFn::Year.apply(&[d(2024, 2, 28)]).unwrap(), i(2024));
+        assert_eq!(ScalarFn::Month.apply(&[s("2024-02-28")]).unwrap(), i(2));
+        assert_eq!(ScalarFn::Day.apply(&[d(2024, 2, 28)]).unwrap(), i(28));
+        assert_eq!(
+            ScalarFn::DateAdd.apply(&[d(2024, 2, 28), i(2)]).unwrap(),
+            d(2024, 3, 1)
+        );
+        assert_eq!(
+            ScalarFn::DateAdd
+                .apply(&[d(2024, 3, 1), Constant::I16(-1)])
+                .unwrap(),
+            d(2024, 2, 29)
+        );
+        assert!(ScalarFn::Year.apply(&[i(2024)]).is_err());
+        assert!(ScalarFn::Upper.apply(&[]).is_err());
+    }
+}
@@ -0,0 +1,150 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use core::fmt;
+use std::sync::{Arc, Mutex};
+
+use super::{constant::Constant, scan::Scan, updatescan::UpdateScan};
+use crate::{materialize::sortscan::SortScan, record::tablescan::TableScan};
+
+#[derive(Debug)]
+pub enum LimitScanError {
+    DowncastError,
+}
+
+impl std::error::Error for LimitScanError {}
+impl fmt::Display for LimitScanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LimitScanError::DowncastError => {
+                write!(f, "downcast error")
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct LimitScan {
+    s: Arc<Mutex<dyn Scan>>,
+    limit: usize,
+    offset: usize,
+    // the number of records read from the underlying scan
+    pos: usize,
+}
+
+impl Scan for LimitScan {
+    fn before_first(&mut self) -> Result<()> {
+        self.pos = 0;
+        self.s.lock().unwrap().before_first()
+    }
+    fn next(&mut self) -> bool {
+        while self.pos < self.offset {
+            if !self.s.lock().unwrap().next() {
+                return false;
+            }
+            self.pos += 1;
+        }
+        // stop before pulling the next record from the underlying scan
+        if self.pos - self.offset >= self.limit {
+            return false;
+        }
+        if !self.s.lock().unwrap().next() {
+            return false;
+        }
+        self.pos += 1;
+
+        true
+    }
+    fn get_i16(&mut self, fldname: &str) -> Result<i16> {
+        self.s.lock().unwrap().get_i16(fldname)
+    }
+    fn get_i32(&mut self, fldname: &str) -> Result<i32> {
+        self.s.lock().unwrap().get_i32(fldname)
+    }
+    fn get_string(&mut self, fldname: &str) -> Result<String> {
+        self.s.lock().unwrap().get_string(fldname)
+    }
+    fn get_bool(&mut self, fldname: &str) -> Result<bool> {
+        self.s.lock().unwrap().get_bool(fldname)
+    }
+    fn get_date(&mut self, fldname: &str) -> Result<NaiveDate> {
+        self.s.lock().unwrap().get_date(fldname)
+    }
+    fn get_val(&mut self, fldname: &str) -> Result<Constant> {
+        self.s.lock().unwrap().get_val(fldname)
+    }
+    fn has_field(&self, fldname: &str) -> bool {
+        self.s.lock().unwrap().has_field(fldname)
+    }
+    fn close(&mut self) -> Result<()> {
+        self.s.lock().unwrap().close()
+    }
+    fn to_update_scan(&mut self) -> Result<&mut dyn UpdateScan> {
+        Err(From::from(LimitScanError::DowncastError))
+    }
+    fn as_table_scan(&mut self) -> Result<&mut TableScan> {
+        Err(From::from(LimitScanError::DowncastError))
+    }
+    fn as_sort_scan(&mut self) -> Result<&mut SortScan> {
+        Err(From::from(LimitScanError::DowncastError))
+    }
+}
+
+impl LimitScan {
+    pub fn new(s: Arc<Mutex<dyn Scan>>, limit: usize, offset: usize) -> Self {
+        Self {
+            s,
+            limit,
+            offset,
+            pos: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use crate::{metadata::manager::MetadataMgr, server::simpledb::SimpleDB};
+
+    use super::super::tests;
+    use super::*;
+
+    #[test]
+    fn unit_test() -> Result<()> {
+        if Path::new("_test/limitscan").exists() {
+            fs::remove_dir_all("_test/limitscan")?;
+        }
+
+        let simpledb = SimpleDB::new_with("_test/limitscan", 400, 8);
+
+        let tx = Arc::new(Mutex::new(simpledb.new_tx()?));
+        let mut mdm = MetadataMgr::new(true, Arc::clone(&tx))?;
+
+        tests::init_sampledb(&mut mdm, Arc::clone(&tx))?;
+
+        let layout = mdm.get_layout("STUDENT", Arc::clone(&tx))?;
+        let ts = TableScan::new(Arc::clone(&tx), "STUDENT", layout)?;
+        let mut s = LimitScan::new(Arc::new(Mutex::new(ts)), 3, 2);
+        let mut ids = vec![];
+        while s.next() {
+            ids.push(s.get_i32("SId")?);
+        }
+        assert_eq!(ids, vec![3, 4, 5]);
+        // it can be scanned again
+        s.before_first()?;
+        assert!(s.next());
+        assert_eq!(s.get_i32("SId")?, 3);
+        s.close()?;
+
+        // the offset can go past the end
+        let layout = mdm.get_layout("STUDENT", Arc::clone(&tx))?;
+        let ts = TableScan::new(Arc::clone(&tx), "STUDENT", layout)?;
+        let mut s = LimitScan::new(Arc::new(Mutex::new(ts)), 3, 20);
+        assert!(!s.next());
+        s.close()?;
+
+        tx.lock().unwrap().commit()?;
+
+        Ok(())
+    }
+}
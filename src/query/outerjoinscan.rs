@@ -0,0 +1,237 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use core::fmt;
+use std::sync::{Arc, Mutex};
+
+use super::{
+    constant::Constant, predicate::Predicate, productscan::ProductScan, scan::Scan,
+    updatescan::UpdateScan,
+};
+use crate::{
+    materialize::sortscan::SortScan,
+    record::{schema::Schema, tablescan::TableScan},
+};
+
+#[derive(Debug)]
+pub enum OuterJoinScanError {
+    DowncastError,
+}
+
+impl std::error::Error for OuterJoinScanError {}
+impl fmt::Display for OuterJoinScanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OuterJoinScanError::DowncastError => {
+                write!(f, "downcast error")
+            }
+        }
+    }
+}
+
+// The nested loop left outer join.
+// A record of s1, which matches no record of s2, is output once,
+// and the fields of s2 are padded with the default values.
+#[derive(Clone)]
+pub struct OuterJoinScan {
+    s1: Arc<Mutex<dyn Scan>>,
+    s2: Arc<Mutex<dyn Scan>>,
+    // the current records of both sides, on which the predicate is evaluated
+    joined: Arc<Mutex<dyn Scan>>,
+    pred: Predicate,
+    sch2: Arc<Schema>,
+    // the error of the predicate, which stops the scan and is returned by close
+    err: Arc<Mutex<Option<anyhow::Error>>>,
+    hasmore1: bool,
+    matched: bool,
+    padded: bool,
+}
+
+impl Scan for OuterJoinScan {
+    fn before_first(&mut self) -> Result<()> {
+        self.s1.lock().unwrap().before_first()?;
+        self.hasmore1 = self.s1.lock().unwrap().next();
+        self.s2.lock().unwrap().before_first()?;
+        self.matched = false;
+        self.padded = false;
+
+        Ok(())
+    }
+    fn next(&mut self) -> bool {
+        while self.hasmore1 && self.err.lock().unwrap().is_none() {
+            if !self.padded {
+                while self.s2.lock().unwrap().next() {
+                    match self.pred.is_satisfied(Arc::clone(&self.joined)) {
+                        Ok(true) => {
+                            self.matched = true;
+                            return true;
+                        }
+                        Ok(false) => {}
+                        Err(e) => {
+                            *self.err.lock().unwrap() = Some(e);
+                            return false;
+                        }
+                    }
+                }
+                if !self.matched {
+                    self.padded = true;
+                    return true;
+                }
+            }
+            self.hasmore1 = self.s1.lock().unwrap().next();
+            self.s2.lock().unwrap().before_first().unwrap();
+            self.matched = false;
+            self.padded = false;
+        }
+
+        false
+    }
+    fn get_i16(&mut self, fldname: &str) -> Result<i16> {
+        self.get_val(fldname)?.as_i16()
+    }
+    fn get_i32(&mut self, fldname: &str) -> Result<i32> {
+        self.get_val(fldname)?.as_i32()
+    }
+    fn get_string(&mut self, fldname: &str) -> Result<String> {
+        self.get_val(fldname)?.as_string().map(|s| s.to_string())
+    }
+    fn get_bool(&mut self, fldname: &str) -> Result<bool> {
+        self.get_val(fldname)?.as_bool()
+    }
+    fn get_date(&mut self, fldname: &str) -> Result<NaiveDate> {
+        self.get_val(fldname)?.as_date()
+    }
+    fn get_val(&mut self, fldname: &str) -> Result<Constant> {
+        if let Some(val) = self.padding(fldname) {
+            return Ok(val);
+        }
+        self.joined.lock().unwrap().get_val(fldname)
+    }
+    fn has_field(&self, fldname: &str) -> bool {
+        self.joined.lock().unwrap().has_field(fldname)
+    }
+    fn close(&mut self) -> Result<()> {
+        self.s1.lock().unwrap().close()?;
+        self.s2.lock().unwrap().close()?;
+        match self.err.lock().unwrap().take() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+    fn to_update_scan(&mut self) -> Result<&mut dyn UpdateScan> {
+        Err(From::from(OuterJoinScanError::DowncastError))
+    }
+    fn as_table_scan(&mut self) -> Result<&mut TableScan> {
+        Err(From::from(OuterJoinScanError::DowncastError))
+    }
+    fn as_sort_scan(&mut self) -> Result<&mut SortScan> {
+        Err(From::from(OuterJoinScanError::DowncastError))
+    }
+}
+
+impl OuterJoinScan {
+    pub fn new(
+        s1: Arc<Mutex<dyn Scan>>,
+        s2: Arc<Mutex<dyn Scan>>,
+        pred: Predicate,
+        sch2: Arc<Schema>,
+    ) -> Result<Self> {
+        let joined = Arc::new(Mutex::new(ProductScan::new(
+            Arc::clone(&s1),
+            Arc::clone(&s2),
+        )));
+        let mut scan = Self {
+            s1,
+            s2,
+            joined,
+            pred,
+            sch2,
+            err: Arc::new(Mutex::new(None)),
+            hasmore1: false,
+            matched: false,
+            padded: false,
+        };
+        scan.before_first()?;
+
+        Ok(scan)
+    }
+    fn padding(&self, fldname: &str) -> Option<Constant> {
+        if self.padded
+            && !self.s1.lock().unwrap().has_field(fldname)
+            && self.sch2.has_field(fldname)
+        {
+            return Some(Constant::Null);
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use super::super::{expression::Expression, term::Term, tests};
+    use super::*;
+    use crate::{metadata::manager::MetadataMgr, server::simpledb::SimpleDB};
+
+    #[test]
+    fn unit_test() -> Result<()> {
+        if Path::new("_test/outerjoinscan").exists() {
+            fs::remove_dir_all("_test/outerjoinscan")?;
+        }
+
+        let simpledb = SimpleDB::new_with("_test/outerjoinscan", 400, 8);
+
+        let tx = Arc::new(Mutex::new(simpledb.new_tx()?));
+        let mut mdm = MetadataMgr::new(true, Arc::clone(&tx))?;
+
+        tests::init_sampledb(&mut mdm, Arc::clone(&tx))?;
+
+        // the COURSE node
+        let layout = mdm.get_layout("COURSE", Arc::clone(&tx))?;
+        let ts1 = TableScan::new(Arc::clone(&tx), "COURSE", layout)?;
+        // the SECTION node
+        let layout = mdm.get_layout("SECTION", Arc::clone(&tx))?;
+        let sch2 = layout.schema();
+        let ts2 = TableScan::new(Arc::clone(&tx), "SECTION", layout)?;
+
+        // the Outer Join node
+        let pred = Predicate::new(Term::new(
+            Expression::new_fldname("CId".to_string()),
+            Expression::new_fldname("CourseId".to_string()),
+        ));
+        let mut s = OuterJoinScan::new(
+            Arc::new(Mutex::new(ts1)),
+            Arc::new(Mutex::new(ts2)),
+            pred,
+            sch2,
+        )?;
+        println!("SELECT Title, Prof FROM COURSE LEFT JOIN SECTION ON CId = CourseId");
+        let mut rows = vec![];
+        while s.next() {
+            let title = s.get_string("Title")?;
+            let prof = s.get_val("Prof")?;
+            println!("{} {}", title, prof);
+            rows.push((title, prof, s.get_val("SectId")?));
+        }
+        s.close()?;
+        assert_eq!(rows.len(), 8);
+        let padded = rows
+            .iter()
+            .filter(|(_, prof, _)| prof.is_null())
+            .map(|(title, _, sectid)| (title.as_str(), sectid.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            padded,
+            vec![
+                ("compilers", Constant::Null),
+                ("algebra", Constant::Null),
+                ("acting", Constant::Null)
+            ]
+        );
+
+        tx.lock().unwrap().commit()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,105 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use core::fmt;
+use std::sync::Arc;
+
+use super::{constant::Constant, scan::Scan, subquery::OuterRecord, updatescan::UpdateScan};
+use crate::{
+    materialize::sortscan::SortScan,
+    record::{schema::Schema, tablescan::TableScan},
+};
+
+#[derive(Debug)]
+pub enum OuterRecordScanError {
+    DowncastError,
+    NoOuterRecord,
+    FieldNotFound(String),
+}
+
+impl std::error::Error for OuterRecordScanError {}
+impl fmt::Display for OuterRecordScanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OuterRecordScanError::DowncastError => {
+                write!(f, "downcast error")
+            }
+            OuterRecordScanError::NoOuterRecord => {
+                write!(f, "no outer record")
+            }
+            OuterRecordScanError::FieldNotFound(fldname) => {
+                write!(f, "field not found: {}", fldname)
+            }
+        }
+    }
+}
+
+// The single record, which consists of the fields of the current record
+// of the outer scan, referred by a correlated subquery.
+#[derive(Clone)]
+pub struct OuterRecordScan {
+    outer: OuterRecord,
+    sch: Arc<Schema>,
+    done: bool,
+}
+
+impl Scan for OuterRecordScan {
+    fn before_first(&mut self) -> Result<()> {
+        self.done = false;
+        Ok(())
+    }
+    fn next(&mut self) -> bool {
+        let hasmore = !self.done;
+        self.done = true;
+        hasmore
+    }
+    fn get_i16(&mut self, fldname: &str) -> Result<i16> {
+        self.get_val(fldname)?.as_i16()
+    }
+    fn get_i32(&mut self, fldname: &str) -> Result<i32> {
+        self.get_val(fldname)?.as_i32()
+    }
+    fn get_string(&mut self, fldname: &str) -> Result<String> {
+        self.get_val(fldname)?.as_string().map(|s| s.to_string())
+    }
+    fn get_bool(&mut self, fldname: &str) -> Result<bool> {
+        self.get_val(fldname)?.as_bool()
+    }
+    fn get_date(&mut self, fldname: &str) -> Result<NaiveDate> {
+        self.get_val(fldname)?.as_date()
+    }
+    fn get_val(&mut self, fldname: &str) -> Result<Constant> {
+        let fldname = self
+            .sch
+            .resolve(fldname)
+            .ok_or_else(|| OuterRecordScanError::FieldNotFound(fldname.to_string()))?;
+        match self.outer.lock().unwrap().as_ref() {
+            Some(s) => s.lock().unwrap().get_val(fldname),
+            None => Err(From::from(OuterRecordScanError::NoOuterRecord)),
+        }
+    }
+    fn has_field(&self, fldname: &str) -> bool {
+        self.sch.has_field(fldname)
+    }
+    fn close(&mut self) -> Result<()> {
+        Ok(())
+    }
+    fn to_update_scan(&mut self) -> Result<&mut dyn UpdateScan> {
+        Err(From::from(OuterRecordScanError::DowncastError))
+    }
+    fn as_table_scan(&mut self) -> Result<&mut TableScan> {
+        Err(From::from(OuterRecordScanError::DowncastError))
+    }
+    fn as_sort_scan(&mut self) -> Result<&mut SortScan> {
+        Err(From::from(OuterRecordScanError::DowncastError))
+    }
+}
+
+impl OuterRecordScan {
+    pub fn new(outer: OuterRecord, sch: Arc<Schema>) -> Self {
+        Self {
+            outer,
+            sch,
+            done: false,
+        }
+    }
+}
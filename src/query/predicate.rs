@@ -1,19 +1,24 @@
+use anyhow::Result;
 use core::fmt;
 use std::sync::{Arc, Mutex};
 
-use super::{scan::Scan, term::Term};
-use crate::{plan::plan::Plan, query::constant::Constant, record::schema::Schema};
+use super::{expression::Expression, factor::Factor, scan::Scan, subquery::Subquery, term::Term};
+use crate::{
+    materialize::aggregationfn::Aggregate, plan::plan::Plan, query::constant::Constant,
+    record::schema::Schema,
+};
 
+// A predicate is a conjunction of factors.
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct Predicate {
-    terms: Vec<Term>,
+    factors: Vec<Factor>,
 }
 
 impl fmt::Display for Predicate {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut result = vec![];
-        for t in self.terms.iter() {
-            result.push(t.to_string());
+        for fct in self.factors.iter() {
+            result.push(fct.to_string());
         }
         write!(f, "{}", result.join(" and "))
     }
@@ -21,37 +26,72 @@ impl fmt::Display for Predicate {
 
 impl Predicate {
     pub fn new_empty() -> Self {
-        Self { terms: vec![] }
+        Self { factors: vec![] }
     }
     pub fn new(t: Term) -> Self {
-        Self { terms: vec![t] }
+        Self {
+            factors: vec![Factor::Term(t)],
+        }
+    }
+    pub fn new_or(disjuncts: Vec<Predicate>) -> Self {
+        Self {
+            factors: vec![Factor::Or(disjuncts)],
+        }
+    }
+    pub fn new_not(pred: Predicate) -> Self {
+        Self {
+            factors: vec![Factor::Not(Box::new(pred))],
+        }
+    }
+    pub fn new_in(expr: Expression, sq: Subquery) -> Self {
+        Self {
+            factors: vec![Factor::In(expr, sq)],
+        }
+    }
+    pub fn new_exists(sq: Subquery) -> Self {
+        Self {
+            factors: vec![Factor::Exists(sq)],
+        }
     }
     pub fn conjoin_with(&mut self, pred: &mut Predicate) {
-        self.terms.append(&mut pred.terms)
+        self.factors.append(&mut pred.factors)
+    }
+    pub fn is_satisfied(&self, s: Arc<Mutex<dyn Scan>>) -> Result<bool> {
+        Ok(self.truth(s)? == Some(true))
     }
-    pub fn is_satisfied(&self, s: Arc<Mutex<dyn Scan>>) -> bool {
-        for t in self.terms.iter() {
-            if !t.is_satisfied(Arc::clone(&s)) {
-                return false;
+    // my own extends
+    // the three-valued truth of the conjunction, where None is unknown.
+    pub fn truth(&self, s: Arc<Mutex<dyn Scan>>) -> Result<Option<bool>> {
+        let mut result = Some(true);
+        for fct in self.factors.iter() {
+            match fct.truth(Arc::clone(&s))? {
+                Some(false) => return Ok(Some(false)),
+                Some(true) => {}
+                None => result = None,
             }
         }
-        true
+        Ok(result)
+    }
+    pub fn applies_to(&self, sch: Arc<Schema>) -> bool {
+        self.factors
+            .iter()
+            .all(|fct| fct.applies_to(Arc::clone(&sch)))
     }
     pub fn reduction_factor(&self, p: Arc<dyn Plan>) -> i32 {
-        let mut factor = 1;
-        for t in self.terms.iter() {
-            factor *= t.reduction_factor(Arc::clone(&p));
+        let mut factor: i32 = 1;
+        for fct in self.factors.iter() {
+            factor = factor.saturating_mul(fct.reduction_factor(Arc::clone(&p)));
         }
         factor
     }
     pub fn select_sub_pred(&self, sch: Arc<Schema>) -> Option<Predicate> {
         let mut result = Predicate::new_empty();
-        for t in self.terms.iter() {
-            if t.applies_to(Arc::clone(&sch)) {
-                result.terms.push(t.clone());
+        for fct in self.factors.iter() {
+            if fct.applies_to(Arc::clone(&sch)) {
+                result.factors.push(fct.clone());
             }
         }
-        if result.terms.is_empty() {
+        if result.factors.is_empty() {
             return None;
         } else {
             return Some(result);
@@ -62,41 +102,138 @@ impl Predicate {
         let mut newsch = Schema::new();
         newsch.add_all(Arc::clone(&sch1));
         newsch.add_all(Arc::clone(&sch2));
-        for t in self.terms.iter() {
-            if !t.applies_to(Arc::clone(&sch1))
-                && !t.applies_to(Arc::clone(&sch2))
-                && t.applies_to(Arc::new(newsch.clone()))
+        for fct in self.factors.iter() {
+            if !fct.applies_to(Arc::clone(&sch1))
+                && !fct.applies_to(Arc::clone(&sch2))
+                && fct.applies_to(Arc::new(newsch.clone()))
             {
-                result.terms.push(t.clone());
+                result.factors.push(fct.clone());
             }
         }
-        if result.terms.is_empty() {
+        if result.factors.is_empty() {
             return None;
         } else {
             return Some(result);
         }
     }
     pub fn equates_with_constant(&self, fldname: &str) -> Option<&Constant> {
-        for t in self.terms.iter() {
-            if let Some(c) = t.equates_with_constant(fldname) {
+        for fct in self.factors.iter() {
+            if let Some(c) = fct.equates_with_constant(fldname) {
                 return Some(c);
             }
         }
         None
     }
     pub fn equates_with_field(&self, fldname: &str) -> Option<&str> {
-        for t in self.terms.iter() {
-            if let Some(s) = t.equates_with_field(fldname) {
+        for fct in self.factors.iter() {
+            if let Some(s) = fct.equates_with_field(fldname) {
                 return Some(s);
             }
         }
         None
     }
     // my own extends
-    pub fn init_with_terms(&mut self, terms: Vec<Term>) {
-        self.terms = terms;
+    pub fn like_range(&self, fldname: &str) -> Option<(Constant, Constant)> {
+        self.factors.iter().find_map(|fct| fct.like_range(fldname))
+    }
+    pub fn in_values(&self, fldname: &str) -> Option<Vec<Constant>> {
+        self.factors.iter().find_map(|fct| fct.in_values(fldname))
+    }
+    pub fn between_range(&self, fldname: &str) -> Option<(Constant, Constant)> {
+        self.factors
+            .iter()
+            .find_map(|fct| fct.between_range(fldname))
+    }
+    pub fn init_with_factors(&mut self, factors: Vec<Factor>) {
+        self.factors = factors;
     }
-    pub fn terms(&self) -> &Vec<Term> {
-        &self.terms
+    pub fn factors(&self) -> &Vec<Factor> {
+        &self.factors
+    }
+    pub fn is_empty(&self) -> bool {
+        self.factors.is_empty()
+    }
+    pub fn aggregates(&self) -> Vec<Aggregate> {
+        self.factors
+            .iter()
+            .flat_map(|fct| fct.aggregates())
+            .collect()
+    }
+    pub fn field_names(&self) -> Vec<String> {
+        self.factors
+            .iter()
+            .flat_map(|fct| fct.field_names())
+            .collect()
+    }
+    pub fn subqueries(&self) -> Vec<&Subquery> {
+        self.factors
+            .iter()
+            .flat_map(|fct| fct.subqueries())
+            .collect()
+    }
+    pub fn subqueries_mut(&mut self) -> Vec<&mut Subquery> {
+        self.factors
+            .iter_mut()
+            .flat_map(|fct| fct.subqueries_mut())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::{expression::Expression, term::CompOp};
+
+    fn term(fldname: &str, op: CompOp, val: i32) -> Term {
+        Term::new_with(
+            Expression::new_fldname(fldname.to_string()),
+            op,
+            Expression::new_val(Constant::I32(val)),
+        )
+    }
+
+    #[test]
+    fn display_test() {
+        let mut pred = Predicate::new(term("a", CompOp::Eq, 1));
+        let mut or = Predicate::new_or(vec![
+            Predicate::new(term("b", CompOp::Lt, 2)),
+            Predicate::new(term("c", CompOp::Ge, 3)),
+        ]);
+        let mut not = Predicate::new_not(Predicate::new(term("d", CompOp::Eq, 4)));
+        pred.conjoin_with(&mut or);
+        pred.conjoin_with(&mut not);
+        assert_eq!(pred.to_string(), "a=1 and (b<2 or c>=3) and not (d=4)");
+    }
+
+    #[test]
+    fn sub_pred_test() {
+        let mut sch1 = Schema::new();
+        sch1.add_i32_field("a");
+        sch1.add_i32_field("b");
+        let mut sch2 = Schema::new();
+        sch2.add_i32_field("c");
+
+        let mut pred = Predicate::new(term("a", CompOp::Eq, 1));
+        let mut or1 = Predicate::new_or(vec![
+            Predicate::new(term("a", CompOp::Eq, 2)),
+            Predicate::new(term("b", CompOp::Eq, 3)),
+        ]);
+        let mut or2 = Predicate::new_or(vec![
+            Predicate::new(term("b", CompOp::Eq, 4)),
+            Predicate::new(term("c", CompOp::Eq, 5)),
+        ]);
+        pred.conjoin_with(&mut or1);
+        pred.conjoin_with(&mut or2);
+
+        let sch1 = Arc::new(sch1);
+        let sch2 = Arc::new(sch2);
+        let select = pred.select_sub_pred(Arc::clone(&sch1)).unwrap();
+        assert_eq!(select.to_string(), "a=1 and (a=2 or b=3)");
+        assert!(pred.select_sub_pred(Arc::clone(&sch2)).is_none());
+        let join = pred.join_sub_pred(sch1, sch2).unwrap();
+        assert_eq!(join.to_string(), "(b=4 or c=5)");
+        // only terms directly in the conjunction can be used for an index lookup
+        assert_eq!(pred.equates_with_constant("a"), Some(&Constant::I32(1)));
+        assert_eq!(pred.equates_with_constant("b"), None);
     }
 }
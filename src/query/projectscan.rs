@@ -3,9 +3,12 @@ use chrono::NaiveDate;
 use core::fmt;
 use std::sync::{Arc, Mutex};
 
-use crate::{materialize::sortscan::SortScan, record::tablescan::TableScan};
+use crate::{
+    materialize::sortscan::SortScan,
+    record::{schema::resolve_field, tablescan::TableScan},
+};
 
-use super::{constant::Constant, scan::Scan, updatescan::UpdateScan};
+use super::{constant::Constant, expression::Expression, scan::Scan, updatescan::UpdateScan};
 
 #[derive(Debug)]
 pub enum ProjectScanError {
@@ -31,6 +34,8 @@ impl fmt::Display for ProjectScanError {
 pub struct ProjectScan {
     s: Arc<Mutex<dyn Scan>>,
     fieldlist: Vec<String>,
+    // the expression of each output field, in the same order as fieldlist
+    exprs: Vec<Expression>,
 }
 
 impl Scan for ProjectScan {
@@ -41,61 +46,63 @@ impl Scan for ProjectScan {
         self.s.lock().unwrap().next()
     }
     fn get_i16(&mut self, fldname: &str) -> Result<i16> {
-        if self.has_field(fldname) {
-            self.s.lock().unwrap().get_i16(fldname)
-        } else {
-            Err(From::from(ProjectScanError::FieldNotFoundError(
+        match self.expression(fldname) {
+            Some(Expression::Fldname(name)) => self.s.lock().unwrap().get_i16(name),
+            Some(expr) => expr.evaluate(Arc::clone(&self.s))?.as_i16(),
+            None => Err(From::from(ProjectScanError::FieldNotFoundError(
                 fldname.to_string(),
-            )))
+            ))),
         }
     }
     fn get_i32(&mut self, fldname: &str) -> anyhow::Result<i32> {
-        if self.has_field(fldname) {
-            self.s.lock().unwrap().get_i32(fldname)
-        } else {
-            Err(From::from(ProjectScanError::FieldNotFoundError(
+        match self.expression(fldname) {
+            Some(Expression::Fldname(name)) => self.s.lock().unwrap().get_i32(name),
+            Some(expr) => expr.evaluate(Arc::clone(&self.s))?.as_i32(),
+            None => Err(From::from(ProjectScanError::FieldNotFoundError(
                 fldname.to_string(),
-            )))
+            ))),
         }
     }
     fn get_string(&mut self, fldname: &str) -> anyhow::Result<String> {
-        if self.has_field(fldname) {
-            self.s.lock().unwrap().get_string(fldname)
-        } else {
-            Err(From::from(ProjectScanError::FieldNotFoundError(
+        match self.expression(fldname) {
+            Some(Expression::Fldname(name)) => self.s.lock().unwrap().get_string(name),
+            Some(expr) => expr
+                .evaluate(Arc::clone(&self.s))?
+                .as_string()
+                .map(|s| s.to_string()),
+            None => Err(From::from(ProjectScanError::FieldNotFoundError(
                 fldname.to_string(),
-            )))
+            ))),
         }
     }
     fn get_bool(&mut self, fldname: &str) -> Result<bool> {
-        if self.has_field(fldname) {
-            self.s.lock().unwrap().get_bool(fldname)
-        } else {
-            Err(From::from(ProjectScanError::FieldNotFoundError(
+        match self.expression(fldname) {
+            Some(Expression::Fldname(name)) => self.s.lock().unwrap().get_bool(name),
+            Some(expr) => expr.evaluate(Arc::clone(&self.s))?.as_bool(),
+            None => Err(From::from(ProjectScanError::FieldNotFoundError(
                 fldname.to_string(),
-            )))
+            ))),
         }
     }
     fn get_date(&mut self, fldname: &str) -> Result<NaiveDate> {
-        if self.has_field(fldname) {
-            self.s.lock().unwrap().get_date(fldname)
-        } else {
-            Err(From::from(ProjectScanError::FieldNotFoundError(
+        match self.expression(fldname) {
+            Some(Expression::Fldname(name)) => self.s.lock().unwrap().get_date(name),
+            Some(expr) => expr.evaluate(Arc::clone(&self.s))?.as_date(),
+            None => Err(From::from(ProjectScanError::FieldNotFoundError(
                 fldname.to_string(),
-            )))
+            ))),
         }
     }
     fn get_val(&mut self, fldname: &str) -> Result<Constant> {
-        if self.has_field(fldname) {
-            self.s.lock().unwrap().get_val(fldname)
-        } else {
-            Err(From::from(ProjectScanError::FieldNotFoundError(
+        match self.expression(fldname) {
+            Some(expr) => expr.evaluate(Arc::clone(&self.s)),
+            None => Err(From::from(ProjectScanError::FieldNotFoundError(
                 fldname.to_string(),
-            )))
+            ))),
         }
     }
     fn has_field(&self, fldname: &str) -> bool {
-        self.fieldlist.contains(&fldname.to_string())
+        resolve_field(&self.fieldlist, fldname).is_some()
     }
     fn close(&mut self) -> anyhow::Result<()> {
         self.s.lock().unwrap().close()
@@ -113,7 +120,31 @@ impl Scan for ProjectScan {
 
 impl ProjectScan {
     pub fn new(s: Arc<Mutex<dyn Scan>>, fieldlist: Vec<String>) -> Self {
-        Self { s, fieldlist }
+        let exprs = fieldlist
+            .iter()
+            .map(|fldname| Expression::new_fldname(fldname.clone()))
+            .collect();
+        Self {
+            s,
+            fieldlist,
+            exprs,
+        }
+    }
+    // each output field is named by its alias or its expression, e.g. "price*qty"
+    pub fn new_with(s: Arc<Mutex<dyn Scan>>, fields: Vec<(String, Expression)>) -> Self {
+        let (fieldlist, exprs) = fields.into_iter().unzip();
+        Self {
+            s,
+            fieldlist,
+            exprs,
+        }
+    }
+    fn expression(&self, fldname: &str) -> Option<&Expression> {
+        let fldname = resolve_field(&self.fieldlist, fldname)?;
+        self.fieldlist
+            .iter()
+            .position(|f| f == fldname)
+            .map(|i| &self.exprs[i])
     }
 }
 
@@ -123,7 +154,7 @@ mod tests {
 
     use crate::{metadata::manager::MetadataMgr, server::simpledb::SimpleDB};
 
-    use super::super::tests;
+    use super::super::{expression::ArithOp, tests};
     use super::*;
 
     #[test]
@@ -150,6 +181,36 @@ mod tests {
         while s2.next() {
             println!("{} {}", s2.get_string("SName")?, s2.get_i32("MajorId")?);
         }
+        s2.close()?;
+
+        // the Project node with a computed field
+        let layout = mdm.get_layout("STUDENT", Arc::clone(&tx))?;
+        let ts = TableScan::new(Arc::clone(&tx), "STUDENT", layout)?;
+        let fields = vec![
+            (
+                "GradYear".to_string(),
+                Expression::new_fldname("GradYear".to_string()),
+            ),
+            (
+                "GradYear+1".to_string(),
+                Expression::new_arith(
+                    Expression::new_fldname("GradYear".to_string()),
+                    ArithOp::Add,
+                    Expression::new_val(Constant::I32(1)),
+                ),
+            ),
+        ];
+        let mut s3 = ProjectScan::new_with(Arc::new(Mutex::new(ts)), fields);
+        println!("SELECT GradYear, GradYear+1 FROM STUDENT");
+        while s3.next() {
+            let year = s3.get_i32("GradYear")?;
+            let next_year = s3.get_i32("GradYear+1")?;
+            println!("{} {}", year, next_year);
+            assert_eq!(year + 1, next_year);
+            assert_eq!(s3.get_val("GradYear+1")?, Constant::I32(year + 1));
+        }
+        assert!(!s3.has_field("MajorId"));
+        s3.close()?;
 
         tx.lock().unwrap().commit()?;
 
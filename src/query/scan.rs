@@ -13,6 +13,11 @@ pub trait Scan {
     fn get_bool(&mut self, fldname: &str) -> Result<bool>;
     fn get_date(&mut self, fldname: &str) -> Result<NaiveDate>;
     fn get_val(&mut self, fldname: &str) -> Result<Constant>;
+    // my own extends
+    // the typed getters of a null field return an arbitrary value, or an error.
+    fn is_null(&mut self, fldname: &str) -> Result<bool> {
+        Ok(self.get_val(fldname)?.is_null())
+    }
     fn has_field(&self, fldname: &str) -> bool;
     fn close(&mut self) -> Result<()>;
 
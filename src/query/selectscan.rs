@@ -30,6 +30,8 @@ impl fmt::Display for SelectScanError {
 pub struct SelectScan {
     s: Arc<Mutex<dyn Scan>>,
     pred: Predicate,
+    // the error of the predicate, e.g. a division by zero, which stops the scan
+    err: Arc<Mutex<Option<anyhow::Error>>>,
 }
 
 impl Scan for SelectScan {
@@ -37,9 +39,17 @@ impl Scan for SelectScan {
         self.s.lock().unwrap().before_first()
     }
     fn next(&mut self) -> bool {
+        if self.err.lock().unwrap().is_some() {
+            return false;
+        }
         while self.s.lock().unwrap().next() {
-            if self.pred.is_satisfied(Arc::clone(&self.s)) {
-                return true;
+            match self.pred.is_satisfied(Arc::clone(&self.s)) {
+                Ok(true) => return true,
+                Ok(false) => {}
+                Err(e) => {
+                    *self.err.lock().unwrap() = Some(e);
+                    return false;
+                }
             }
         }
         false
@@ -65,8 +75,13 @@ impl Scan for SelectScan {
     fn has_field(&self, fldname: &str) -> bool {
         self.s.lock().unwrap().has_field(fldname)
     }
+    // the error which stopped the scan is returned here, because next can't return it.
     fn close(&mut self) -> Result<()> {
-        self.s.lock().unwrap().close()
+        self.s.lock().unwrap().close()?;
+        match self.err.lock().unwrap().take() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
     }
     // downcast
     fn to_update_scan(&mut self) -> Result<&mut dyn UpdateScan> {
@@ -129,7 +144,11 @@ impl UpdateScan for SelectScan {
 
 impl SelectScan {
     pub fn new(s: Arc<Mutex<dyn Scan>>, pred: Predicate) -> Self {
-        Self { s, pred }
+        Self {
+            s,
+            pred,
+            err: Arc::new(Mutex::new(None)),
+        }
     }
 }
 
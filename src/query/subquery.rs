@@ -0,0 +1,204 @@
+use anyhow::Result;
+use core::fmt;
+use std::{
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+    sync::{Arc, Mutex},
+};
+
+use super::{constant::Constant, scan::Scan, term::CompOp};
+use crate::{
+    parser::querydata::QueryData,
+    plan::plan::Plan,
+    record::schema::{FieldInfo, Schema},
+};
+
+#[derive(Debug)]
+pub enum SubqueryError {
+    NotPlanned,
+    NotSingleField,
+    NotSingleRecord,
+}
+
+impl std::error::Error for SubqueryError {}
+impl fmt::Display for SubqueryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SubqueryError::NotPlanned => {
+                write!(f, "subquery is not planned")
+            }
+            SubqueryError::NotSingleField => {
+                write!(f, "subquery must select a single field")
+            }
+            SubqueryError::NotSingleRecord => {
+                write!(f, "subquery must return a single record")
+            }
+        }
+    }
+}
+
+// The current record of the outer scan, which a correlated subquery reads.
+// It's shared by the subquery and the OuterRecordScan in the plan of it.
+pub type OuterRecord = Arc<Mutex<Option<Arc<Mutex<dyn Scan>>>>>;
+
+// A query nested in an expression or a predicate.
+// The planner binds the plan of it, which is executed again for each outer record.
+#[derive(Clone)]
+pub struct Subquery {
+    data: Box<QueryData>,
+    // a scalar subquery or the subquery of IN selects a single field
+    single: bool,
+    plan: Option<Arc<dyn Plan>>,
+    outer: OuterRecord,
+}
+
+impl fmt::Display for Subquery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({})", self.data)
+    }
+}
+
+impl fmt::Debug for Subquery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Subquery({:?})", self.data)
+    }
+}
+
+// NOTE: subqueries are compared by their queries, regardless of the plans.
+impl PartialEq for Subquery {
+    fn eq(&self, other: &Self) -> bool {
+        self.data == other.data && self.single == other.single
+    }
+}
+
+impl Eq for Subquery {}
+
+impl PartialOrd for Subquery {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Subquery {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.data, self.single).cmp(&(&other.data, other.single))
+    }
+}
+
+impl Hash for Subquery {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.data.to_string().hash(state);
+        self.single.hash(state);
+    }
+}
+
+impl Subquery {
+    pub fn new(data: QueryData) -> Self {
+        Self {
+            data: Box::new(data),
+            single: true,
+            plan: None,
+            outer: Arc::new(Mutex::new(None)),
+        }
+    }
+    // the subquery of EXISTS may select any fields.
+    pub fn new_exists(data: QueryData) -> Self {
+        Self {
+            single: false,
+            ..Self::new(data)
+        }
+    }
+    pub fn data(&self) -> &QueryData {
+        &self.data
+    }
+    pub fn outer(&self) -> OuterRecord {
+        Arc::clone(&self.outer)
+    }
+    pub fn plan(&self) -> Option<Arc<dyn Plan>> {
+        self.plan.clone()
+    }
+    pub fn bind(&mut self, plan: Arc<dyn Plan>) -> Result<()> {
+        if self.single && plan.schema().fields().len() != 1 {
+            return Err(From::from(SubqueryError::NotSingleField));
+        }
+        self.plan = Some(plan);
+
+        Ok(())
+    }
+    pub fn field_info(&self) -> Result<FieldInfo> {
+        let sch = self.schema()?;
+        let fldname = &sch.fields()[0];
+        Ok(FieldInfo::new(sch.field_type(fldname), sch.length(fldname)))
+    }
+    pub fn exists(&self, s: Arc<Mutex<dyn Scan>>) -> Result<bool> {
+        let scan = self.open(s)?;
+        let mut scan = scan.lock().unwrap();
+        let found = scan.next();
+        scan.close()?;
+
+        Ok(found)
+    }
+    // whether the value is one of the values of the subquery.
+    // it's unknown (None), if it's not found but the value or any of the values are null.
+    pub fn contains(&self, s: Arc<Mutex<dyn Scan>>, val: &Constant) -> Result<Option<bool>> {
+        let fldname = self.schema()?.fields()[0].clone();
+        let scan = self.open(s)?;
+        let mut scan = scan.lock().unwrap();
+        let found = find_value(&mut *scan, &fldname, val);
+        // the scan is closed even if it failed
+        scan.close()?;
+
+        found
+    }
+    pub fn scalar(&self, s: Arc<Mutex<dyn Scan>>) -> Result<Constant> {
+        let fldname = self.schema()?.fields()[0].clone();
+        let scan = self.open(s)?;
+        let mut scan = scan.lock().unwrap();
+        let val = single_value(&mut *scan, &fldname);
+        // the scan is closed even if it failed
+        scan.close()?;
+
+        val
+    }
+    fn schema(&self) -> Result<Arc<Schema>> {
+        match &self.plan {
+            Some(plan) => Ok(plan.schema()),
+            None => Err(From::from(SubqueryError::NotPlanned)),
+        }
+    }
+    // opens the plan on the current record of s
+    fn open(&self, s: Arc<Mutex<dyn Scan>>) -> Result<Arc<Mutex<dyn Scan>>> {
+        let plan = self
+            .plan
+            .as_ref()
+            .ok_or_else(|| SubqueryError::NotPlanned)?;
+        *self.outer.lock().unwrap() = Some(s);
+        plan.open()
+    }
+}
+
+fn find_value(scan: &mut dyn Scan, fldname: &str, val: &Constant) -> Result<Option<bool>> {
+    let mut found = Some(false);
+    while found != Some(true) && scan.next() {
+        match CompOp::Eq.apply(val, &scan.get_val(fldname)?) {
+            Some(true) => found = Some(true),
+            Some(false) => {}
+            None => found = None,
+        }
+    }
+
+    Ok(found)
+}
+
+// the value of the only record, or null if there is no record.
+fn single_value(scan: &mut dyn Scan, fldname: &str) -> Result<Constant> {
+    if !scan.next() {
+        return Ok(Constant::Null);
+    }
+    let val = scan.get_val(fldname)?;
+    if scan.next() {
+        return Err(From::from(SubqueryError::NotSingleRecord));
+    }
+
+    Ok(val)
+}
@@ -1,37 +1,302 @@
+use anyhow::Result;
 use core::fmt;
 use std::{
     cmp::*,
     sync::{Arc, Mutex},
 };
 
-use super::{constant::Constant, expression::Expression, scan::Scan};
-use crate::{plan::plan::Plan, record::schema::Schema};
+use super::{constant::Constant, expression::Expression, scan::Scan, subquery::Subquery};
+use crate::{materialize::aggregationfn::Aggregate, plan::plan::Plan, record::schema::Schema};
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum CompOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Like,
+    NotLike,
+    // IS [NOT] NULL, which compares NULL as a value
+    Is,
+    IsNot,
+    // [NOT] IN (list) and [NOT] BETWEEN, which have more operands than rhs
+    In,
+    NotIn,
+    Between,
+    NotBetween,
+}
+
+impl fmt::Display for CompOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CompOp::Eq => write!(f, "="),
+            CompOp::Ne => write!(f, "<>"),
+            CompOp::Lt => write!(f, "<"),
+            CompOp::Le => write!(f, "<="),
+            CompOp::Gt => write!(f, ">"),
+            CompOp::Ge => write!(f, ">="),
+            CompOp::Like => write!(f, " like "),
+            CompOp::NotLike => write!(f, " not like "),
+            CompOp::Is => write!(f, " is "),
+            CompOp::IsNot => write!(f, " is not "),
+            CompOp::In => write!(f, " in "),
+            CompOp::NotIn => write!(f, " not in "),
+            CompOp::Between => write!(f, " between "),
+            CompOp::NotBetween => write!(f, " not between "),
+        }
+    }
+}
+
+impl CompOp {
+    // the three-valued result of the comparison.
+    // it's unknown (None), if either of the values is null, except for IS [NOT].
+    // IN and BETWEEN compare with rhs alone, as the list [rhs] and the range [rhs, rhs].
+    pub fn apply(&self, lhs: &Constant, rhs: &Constant) -> Option<bool> {
+        match self {
+            CompOp::Is => return Some(lhs == rhs),
+            CompOp::IsNot => return Some(lhs != rhs),
+            _ if lhs.is_null() || rhs.is_null() => return None,
+            _ => {}
+        }
+        Some(match self {
+            CompOp::Eq | CompOp::In | CompOp::Between => lhs == rhs,
+            CompOp::Ne | CompOp::NotIn | CompOp::NotBetween => lhs != rhs,
+            CompOp::Lt => lhs < rhs,
+            CompOp::Le => lhs <= rhs,
+            CompOp::Gt => lhs > rhs,
+            CompOp::Ge => lhs >= rhs,
+            CompOp::Like => like(lhs, rhs, None),
+            CompOp::NotLike => !like(lhs, rhs, None),
+            CompOp::Is | CompOp::IsNot => unreachable!(),
+        })
+    }
+}
+
+// the selectivity of a range comparison is unknown without histograms,
+// so assume that it keeps a third of the records.
+pub const RANGE_REDUCTION_FACTOR: i32 = 3;
+
+// the reduction factor of a field equal to any of numvals values,
+// each of which keeps 1/distinct_values of the records.
+pub fn values_reduction_factor(distinct_values: i32, numvals: i32) -> i32 {
+    (distinct_values / numvals.max(1)).max(1)
+}
+
+// the reduction factor of a field in the range [lo, hi].
+// the range of the integers or the dates is counted as the list of the values in it,
+// and the other ranges are assumed to be the third of the records.
+pub fn range_reduction_factor(distinct_values: i32, lo: &Constant, hi: &Constant) -> i32 {
+    let width = match (lo, hi) {
+        (Constant::I16(_) | Constant::I32(_), Constant::I16(_) | Constant::I32(_)) => {
+            hi.as_i32().unwrap() as i64 - lo.as_i32().unwrap() as i64 + 1
+        }
+        (Constant::Date(lo), Constant::Date(hi)) => (*hi - *lo).num_days() + 1,
+        _ => return RANGE_REDUCTION_FACTOR,
+    };
+    if width <= 0 {
+        return i32::MAX;
+    }
+    values_reduction_factor(distinct_values, width.min(i32::MAX as i64) as i32)
+}
 
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct Term {
     lhs: Expression,
+    op: CompOp,
     rhs: Expression,
+    // the escape character of the pattern of LIKE
+    escape: Option<char>,
+    // the operands following rhs: the rest of the list of IN,
+    // or the upper bound of BETWEEN, whose lower bound is rhs.
+    rest: Vec<Expression>,
 }
 
 impl fmt::Display for Term {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}={}", self.lhs.to_string(), self.rhs.to_string())
+        match self.op {
+            CompOp::In | CompOp::NotIn => {
+                let list = self
+                    .operands()
+                    .iter()
+                    .map(|e| e.to_string())
+                    .collect::<Vec<_>>();
+                return write!(f, "{}{}({})", self.lhs, self.op, list.join(", "));
+            }
+            CompOp::Between | CompOp::NotBetween => {
+                return write!(
+                    f,
+                    "{}{}{} and {}",
+                    self.lhs, self.op, self.rhs, self.rest[0]
+                );
+            }
+            _ => {}
+        }
+        write!(
+            f,
+            "{}{}{}",
+            self.lhs.to_string(),
+            self.op,
+            self.rhs.to_string()
+        )?;
+        if let Some(c) = self.escape {
+            write!(f, " escape '{}'", c)?;
+        }
+
+        Ok(())
     }
 }
 
 impl Term {
     pub fn new(lhs: Expression, rhs: Expression) -> Self {
-        Self { lhs, rhs }
+        Self::new_with(lhs, CompOp::Eq, rhs)
+    }
+    pub fn new_with(lhs: Expression, op: CompOp, rhs: Expression) -> Self {
+        Self::new_with_escape(lhs, op, rhs, None)
+    }
+    pub fn new_with_escape(
+        lhs: Expression,
+        op: CompOp,
+        rhs: Expression,
+        escape: Option<char>,
+    ) -> Self {
+        Self {
+            lhs,
+            op,
+            rhs,
+            escape,
+            rest: vec![],
+        }
+    }
+    // lhs [NOT] IN (list), where the list is not empty.
+    pub fn new_in(lhs: Expression, op: CompOp, mut list: Vec<Expression>) -> Self {
+        let rhs = list.remove(0);
+        Self {
+            lhs,
+            op,
+            rhs,
+            escape: None,
+            rest: list,
+        }
+    }
+    // lhs [NOT] BETWEEN lo AND hi
+    pub fn new_between(lhs: Expression, op: CompOp, lo: Expression, hi: Expression) -> Self {
+        Self {
+            lhs,
+            op,
+            rhs: lo,
+            escape: None,
+            rest: vec![hi],
+        }
+    }
+    pub fn is_satisfied(&self, s: Arc<Mutex<dyn Scan>>) -> Result<bool> {
+        Ok(self.truth(s)? == Some(true))
+    }
+    // my own extends
+    // the three-valued truth of the term, where None is unknown.
+    // an error of the operands, e.g. a division by zero, is returned.
+    pub fn truth(&self, s: Arc<Mutex<dyn Scan>>) -> Result<Option<bool>> {
+        let lhsval = self.lhs.evaluate(Arc::clone(&s))?;
+        let vals = self
+            .operands()
+            .iter()
+            .map(|e| e.evaluate(Arc::clone(&s)))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(self.compare_all(&lhsval, &vals))
+    }
+    // compares lhs with the values of all the operands of the right hand side.
+    fn compare_all(&self, lhs: &Constant, vals: &[Constant]) -> Option<bool> {
+        match self.op {
+            CompOp::In | CompOp::NotIn => {
+                // unknown, if no value equals lhs but some comparison is unknown
+                let mut result = Some(false);
+                for val in vals.iter() {
+                    match CompOp::Eq.apply(lhs, val) {
+                        Some(true) => {
+                            result = Some(true);
+                            break;
+                        }
+                        Some(false) => {}
+                        None => result = None,
+                    }
+                }
+                match self.op {
+                    CompOp::NotIn => result.map(|b| !b),
+                    _ => result,
+                }
+            }
+            CompOp::Between | CompOp::NotBetween => {
+                let result = match (
+                    CompOp::Ge.apply(lhs, &vals[0]),
+                    CompOp::Le.apply(lhs, &vals[1]),
+                ) {
+                    (Some(false), _) | (_, Some(false)) => Some(false),
+                    (Some(true), Some(true)) => Some(true),
+                    _ => None,
+                };
+                match self.op {
+                    CompOp::NotBetween => result.map(|b| !b),
+                    _ => result,
+                }
+            }
+            _ => self.compare(lhs, &vals[0]),
+        }
     }
-    pub fn is_satisfied(&self, s: Arc<Mutex<dyn Scan>>) -> bool {
-        let lhsval = self.lhs.evaluate(Arc::clone(&s));
-        let rhsval = self.rhs.evaluate(Arc::clone(&s));
-        lhsval.unwrap() == rhsval.unwrap()
+    fn compare(&self, lhs: &Constant, rhs: &Constant) -> Option<bool> {
+        match self.op {
+            CompOp::Like | CompOp::NotLike if lhs.is_null() || rhs.is_null() => None,
+            CompOp::Like => Some(like(lhs, rhs, self.escape)),
+            CompOp::NotLike => Some(!like(lhs, rhs, self.escape)),
+            _ => self.op.apply(lhs, rhs),
+        }
     }
     pub fn applies_to(&self, sch: Arc<Schema>) -> bool {
-        self.lhs.applies_to(Arc::clone(&sch)) && self.rhs.applies_to(Arc::clone(&sch))
+        self.lhs.applies_to(Arc::clone(&sch))
+            && self.rhs.applies_to(Arc::clone(&sch))
+            && self.rest.iter().all(|e| e.applies_to(Arc::clone(&sch)))
     }
     pub fn reduction_factor(&self, p: Arc<dyn Plan>) -> i32 {
+        match self.op {
+            CompOp::In | CompOp::NotIn | CompOp::Between | CompOp::NotBetween => {
+                return self.list_reduction_factor(p);
+            }
+            CompOp::Eq | CompOp::Is => {}
+            CompOp::Ne | CompOp::IsNot => {
+                if let (Expression::Val(lhs_val), Expression::Val(rhs_val)) = (&self.lhs, &self.rhs)
+                {
+                    if self.compare(lhs_val, rhs_val) != Some(true) {
+                        return i32::MAX;
+                    }
+                }
+                return 1;
+            }
+            CompOp::Like | CompOp::NotLike => {
+                if let (Expression::Val(lhs_val), Expression::Val(rhs_val)) = (&self.lhs, &self.rhs)
+                {
+                    if self.compare(lhs_val, rhs_val) == Some(true) {
+                        return 1;
+                    } else {
+                        return i32::MAX;
+                    }
+                }
+                if self.op == CompOp::NotLike {
+                    return 1;
+                }
+                return RANGE_REDUCTION_FACTOR;
+            }
+            _ => {
+                if let (Expression::Val(lhs_val), Expression::Val(rhs_val)) = (&self.lhs, &self.rhs)
+                {
+                    if self.compare(lhs_val, rhs_val) == Some(true) {
+                        return 1;
+                    } else {
+                        return i32::MAX;
+                    }
+                }
+                return RANGE_REDUCTION_FACTOR;
+            }
+        }
         match (&self.lhs, &self.rhs) {
             (Expression::Fldname(lhs_name), Expression::Fldname(rhs_name)) => {
                 return max(p.distinct_values(&lhs_name), p.distinct_values(&rhs_name));
@@ -43,16 +308,74 @@ impl Term {
                 return p.distinct_values(&rhs_name);
             }
             (Expression::Val(lhs_val), Expression::Val(rhs_val)) => {
-                if lhs_val == rhs_val {
+                if self.compare(lhs_val, rhs_val) == Some(true) {
                     return 1;
                 } else {
                     return i32::MAX;
                 }
             }
+            // a field compared with a computed value behaves like a field compared with a constant.
+            (Expression::Fldname(lhs_name), _) => {
+                return p.distinct_values(&lhs_name);
+            }
+            (_, Expression::Fldname(rhs_name)) => {
+                return p.distinct_values(&rhs_name);
+            }
+            _ => return 1,
         }
     }
+    // the reduction factor of IN and BETWEEN, which is estimated
+    // from the distinct values of the field in the statistics of the plan.
+    fn list_reduction_factor(&self, p: Arc<dyn Plan>) -> i32 {
+        let vals = self
+            .operands()
+            .iter()
+            .map(|e| e.as_constant().cloned())
+            .collect::<Option<Vec<_>>>();
+        let negated = matches!(self.op, CompOp::NotIn | CompOp::NotBetween);
+        match (&self.lhs, vals) {
+            (Expression::Val(lhs_val), Some(vals)) => {
+                if self.compare_all(lhs_val, &vals) == Some(true) {
+                    1
+                } else {
+                    i32::MAX
+                }
+            }
+            _ if negated => 1,
+            (Expression::Fldname(fldname), Some(vals)) => {
+                let dv = p.distinct_values(fldname);
+                if self.op == CompOp::Between {
+                    return range_reduction_factor(dv, &vals[0], &vals[1]);
+                }
+                // a null in the list matches nothing
+                let mut vals = vals
+                    .into_iter()
+                    .filter(|v| !v.is_null())
+                    .collect::<Vec<_>>();
+                vals.sort();
+                vals.dedup();
+                if vals.is_empty() {
+                    return i32::MAX;
+                }
+                values_reduction_factor(dv, vals.len() as i32)
+            }
+            (Expression::Fldname(fldname), None) => match self.op {
+                CompOp::Between => RANGE_REDUCTION_FACTOR,
+                _ => values_reduction_factor(
+                    p.distinct_values(fldname),
+                    self.operands().len() as i32,
+                ),
+            },
+            _ => 1,
+        }
+    }
+    // NOTE: a field never equals NULL, so it isn't looked up by an index.
     pub fn equates_with_constant(&self, fldname: &str) -> Option<&Constant> {
+        if self.op != CompOp::Eq {
+            return None;
+        }
         match (&self.lhs, &self.rhs) {
+            (_, Expression::Val(Constant::Null)) | (Expression::Val(Constant::Null), _) => None,
             (Expression::Fldname(lhs_name), Expression::Val(_)) => {
                 if lhs_name == fldname {
                     return self.rhs.as_constant();
@@ -69,6 +392,9 @@ impl Term {
         }
     }
     pub fn equates_with_field(&self, fldname: &str) -> Option<&str> {
+        if self.op != CompOp::Eq {
+            return None;
+        }
         match (&self.lhs, &self.rhs) {
             (Expression::Fldname(lhs_name), Expression::Fldname(rhs_name)) => {
                 if lhs_name == fldname {
@@ -82,11 +408,375 @@ impl Term {
             _ => None,
         }
     }
+    // the range [lo, hi) of the values of the field, which can match the pattern,
+    // if the term is "fldname LIKE pattern" and the pattern has a literal prefix.
+    pub fn like_range(&self, fldname: &str) -> Option<(Constant, Constant)> {
+        if self.op != CompOp::Like {
+            return None;
+        }
+        match (&self.lhs, &self.rhs) {
+            (Expression::Fldname(lhs_name), Expression::Val(Constant::String(pattern)))
+                if lhs_name == fldname =>
+            {
+                let prefix = like_prefix(pattern, self.escape);
+                let upper = prefix_upper_bound(&prefix)?;
+                Some((Constant::String(prefix), Constant::String(upper)))
+            }
+            _ => None,
+        }
+    }
+    // the distinct non-null values of the list, if the term is "fldname IN (constants)".
+    pub fn in_values(&self, fldname: &str) -> Option<Vec<Constant>> {
+        if self.op != CompOp::In || self.lhs.as_field_name().ok() != Some(fldname) {
+            return None;
+        }
+        let mut vals = vec![];
+        for e in self.operands() {
+            match e.as_constant()? {
+                Constant::Null => {}
+                c => vals.push(c.clone()),
+            }
+        }
+        vals.sort();
+        vals.dedup();
+        if vals.is_empty() {
+            return None;
+        }
+        Some(vals)
+    }
+    // the range [lo, hi] of the values of the field,
+    // if the term is "fldname BETWEEN lo AND hi" with the constant bounds.
+    pub fn between_range(&self, fldname: &str) -> Option<(Constant, Constant)> {
+        if self.op != CompOp::Between || self.lhs.as_field_name().ok() != Some(fldname) {
+            return None;
+        }
+        match (self.rhs.as_constant()?, self.rest[0].as_constant()?) {
+            (Constant::Null, _) | (_, Constant::Null) => None,
+            (lo, hi) => Some((lo.clone(), hi.clone())),
+        }
+    }
     // my own extends
     pub fn lhs(&self) -> &Expression {
         &self.lhs
     }
+    pub fn op(&self) -> CompOp {
+        self.op
+    }
     pub fn rhs(&self) -> &Expression {
         &self.rhs
     }
+    pub fn escape(&self) -> Option<char> {
+        self.escape
+    }
+    pub fn rest(&self) -> &Vec<Expression> {
+        &self.rest
+    }
+    // rhs followed by the rest of the operands
+    pub fn operands(&self) -> Vec<&Expression> {
+        let mut result = vec![&self.rhs];
+        result.extend(self.rest.iter());
+        result
+    }
+    pub fn aggregates(&self) -> Vec<Aggregate> {
+        let mut result = self.lhs.aggregates();
+        for e in self.operands() {
+            result.extend(e.aggregates());
+        }
+        result
+    }
+    pub fn field_names(&self) -> Vec<String> {
+        let mut result = self.lhs.field_names();
+        for e in self.operands() {
+            result.extend(e.field_names());
+        }
+        result
+    }
+    pub fn subqueries(&self) -> Vec<&Subquery> {
+        let mut result = self.lhs.subqueries();
+        for e in self.operands() {
+            result.extend(e.subqueries());
+        }
+        result
+    }
+    pub fn subqueries_mut(&mut self) -> Vec<&mut Subquery> {
+        let mut result = self.lhs.subqueries_mut();
+        result.extend(self.rhs.subqueries_mut());
+        for e in self.rest.iter_mut() {
+            result.extend(e.subqueries_mut());
+        }
+        result
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum PatternChar {
+    // %
+    AnySeq,
+    // _
+    AnyChar,
+    Literal(char),
+}
+
+fn compile_pattern(pattern: &str, escape: Option<char>) -> Vec<PatternChar> {
+    let mut result = vec![];
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if Some(c) == escape {
+            // a trailing escape character matches itself
+            result.push(PatternChar::Literal(chars.next().unwrap_or(c)));
+            continue;
+        }
+        result.push(match c {
+            '%' => PatternChar::AnySeq,
+            '_' => PatternChar::AnyChar,
+            _ => PatternChar::Literal(c),
+        });
+    }
+
+    result
+}
+
+// whether the string value matches the LIKE pattern.
+// the values of the other types never match.
+fn like(val: &Constant, pattern: &Constant, escape: Option<char>) -> bool {
+    match (val, pattern) {
+        (Constant::String(s), Constant::String(p)) => {
+            let s = s.chars().collect::<Vec<_>>();
+            matches_pattern(&s, &compile_pattern(p, escape))
+        }
+        _ => false,
+    }
+}
+
+fn matches_pattern(s: &[char], pattern: &[PatternChar]) -> bool {
+    let (mut i, mut j) = (0, 0);
+    // the positions just after the last % and the character matched by it
+    let mut backtrack: Option<(usize, usize)> = None;
+    while i < s.len() {
+        match pattern.get(j) {
+            Some(PatternChar::AnySeq) => {
+                j += 1;
+                backtrack = Some((j, i));
+                continue;
+            }
+            Some(PatternChar::AnyChar) => {
+                i += 1;
+                j += 1;
+                continue;
+            }
+            Some(PatternChar::Literal(c)) if *c == s[i] => {
+                i += 1;
+                j += 1;
+                continue;
+            }
+            _ => {}
+        }
+        // let the last % match one more character
+        match backtrack {
+            Some((pj, si)) => {
+                j = pj;
+                i = si + 1;
+                backtrack = Some((pj, si + 1));
+            }
+            None => return false,
+        }
+    }
+
+    pattern[j..].iter().all(|p| *p == PatternChar::AnySeq)
+}
+
+// the literal characters before the first wildcard of the pattern
+fn like_prefix(pattern: &str, escape: Option<char>) -> String {
+    compile_pattern(pattern, escape)
+        .into_iter()
+        .map_while(|p| match p {
+            PatternChar::Literal(c) => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+// the least string greater than all the strings starting with the prefix.
+fn prefix_upper_bound(prefix: &str) -> Option<String> {
+    let mut chars = prefix.chars().collect::<Vec<_>>();
+    while let Some(c) = chars.pop() {
+        // skips the surrogates
+        let next = (c as u32 + 1..=char::MAX as u32).find_map(char::from_u32);
+        if let Some(next) = next {
+            chars.push(next);
+            return Some(chars.into_iter().collect());
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn like_str(s: &str, pattern: &str, escape: Option<char>) -> bool {
+        like(
+            &Constant::String(s.to_string()),
+            &Constant::String(pattern.to_string()),
+            escape,
+        )
+    }
+
+    #[test]
+    fn like_test() {
+        assert!(like_str("joe", "joe", None));
+        assert!(!like_str("joe", "jo", None));
+        assert!(like_str("joe", "j%", None));
+        assert!(like_str("joe", "%e", None));
+        assert!(like_str("joe", "%o%", None));
+        assert!(like_str("joe", "j_e", None));
+        assert!(!like_str("joe", "j_", None));
+        assert!(like_str("", "%", None));
+        assert!(like_str("abcabd", "%ab_", None));
+        assert!(like_str("aXbXc", "a%b%c", None));
+        assert!(!like_str("aXbX", "a%b%c", None));
+        assert!(like_str("50%", "50!%", Some('!')));
+        assert!(!like_str("500", "50!%", Some('!')));
+        assert!(like_str("a_b", "a!_b", Some('!')));
+        assert!(!like_str("axb", "a!_b", Some('!')));
+        assert!(!like(
+            &Constant::I32(1),
+            &Constant::String("%".to_string()),
+            None
+        ));
+    }
+
+    #[test]
+    fn like_range_test() {
+        let term = |pattern: &str, escape: Option<char>| {
+            Term::new_with_escape(
+                Expression::Fldname("SName".to_string()),
+                CompOp::Like,
+                Expression::Val(Constant::String(pattern.to_string())),
+                escape,
+            )
+        };
+        let range = |lo: &str, hi: &str| {
+            Some((
+                Constant::String(lo.to_string()),
+                Constant::String(hi.to_string()),
+            ))
+        };
+        assert_eq!(term("jo%", None).like_range("SName"), range("jo", "jp"));
+        assert_eq!(term("j_e", None).like_range("SName"), range("j", "k"));
+        assert_eq!(
+            term("a!%%", Some('!')).like_range("SName"),
+            range("a%", "a&")
+        );
+        assert_eq!(term("%e", None).like_range("SName"), None);
+        assert_eq!(term("jo%", None).like_range("DName"), None);
+        assert_eq!(
+            Term::new_with(
+                Expression::Fldname("SName".to_string()),
+                CompOp::NotLike,
+                Expression::Val(Constant::String("jo%".to_string())),
+            )
+            .like_range("SName"),
+            None
+        );
+    }
+
+    #[test]
+    fn null_compare_test() {
+        let term = |op: CompOp| {
+            Term::new_with(
+                Expression::Fldname("A".to_string()),
+                op,
+                Expression::Fldname("B".to_string()),
+            )
+        };
+        let one = Constant::I32(1);
+        let null = Constant::Null;
+        assert_eq!(term(CompOp::Eq).compare(&one, &one), Some(true));
+        assert_eq!(term(CompOp::Eq).compare(&one, &null), None);
+        assert_eq!(term(CompOp::Eq).compare(&null, &null), None);
+        assert_eq!(term(CompOp::Ne).compare(&null, &one), None);
+        assert_eq!(term(CompOp::Lt).compare(&null, &one), None);
+        assert_eq!(term(CompOp::Like).compare(&null, &null), None);
+        assert_eq!(term(CompOp::NotLike).compare(&null, &null), None);
+        assert_eq!(term(CompOp::Is).compare(&null, &null), Some(true));
+        assert_eq!(term(CompOp::Is).compare(&one, &null), Some(false));
+        assert_eq!(term(CompOp::IsNot).compare(&one, &null), Some(true));
+        assert_eq!(term(CompOp::IsNot).compare(&null, &null), Some(false));
+    }
+
+    #[test]
+    fn in_between_test() {
+        let fld = || Expression::Fldname("A".to_string());
+        let in_list = Term::new_in(fld(), CompOp::In, vec![fld(), fld()]);
+        let not_in = Term::new_in(fld(), CompOp::NotIn, vec![fld(), fld()]);
+        let (one, two, three) = (Constant::I32(1), Constant::I32(2), Constant::I32(3));
+        let null = Constant::Null;
+        assert_eq!(
+            in_list.compare_all(&one, &[two.clone(), one.clone()]),
+            Some(true)
+        );
+        assert_eq!(
+            in_list.compare_all(&one, &[two.clone(), three.clone()]),
+            Some(false)
+        );
+        assert_eq!(
+            in_list.compare_all(&one, &[null.clone(), one.clone()]),
+            Some(true)
+        );
+        assert_eq!(
+            in_list.compare_all(&one, &[null.clone(), two.clone()]),
+            None
+        );
+        assert_eq!(
+            not_in.compare_all(&one, &[two.clone(), three.clone()]),
+            Some(true)
+        );
+        assert_eq!(not_in.compare_all(&one, &[null.clone(), two.clone()]), None);
+        assert_eq!(not_in.compare_all(&null, &[one.clone(), two.clone()]), None);
+
+        let between = Term::new_between(fld(), CompOp::Between, fld(), fld());
+        let not_between = Term::new_between(fld(), CompOp::NotBetween, fld(), fld());
+        assert_eq!(
+            between.compare_all(&two, &[one.clone(), three.clone()]),
+            Some(true)
+        );
+        assert_eq!(
+            between.compare_all(&one, &[one.clone(), one.clone()]),
+            Some(true)
+        );
+        assert_eq!(
+            between.compare_all(&three, &[one.clone(), two.clone()]),
+            Some(false)
+        );
+        // the other bound decides it, if one of them is null
+        assert_eq!(
+            between.compare_all(&three, &[null.clone(), two.clone()]),
+            Some(false)
+        );
+        assert_eq!(
+            between.compare_all(&two, &[null.clone(), three.clone()]),
+            None
+        );
+        assert_eq!(
+            not_between.compare_all(&three, &[one.clone(), two.clone()]),
+            Some(true)
+        );
+        assert_eq!(
+            not_between.compare_all(&null, &[one.clone(), two.clone()]),
+            None
+        );
+
+        assert_eq!(range_reduction_factor(100, &one, &three), 33);
+        assert_eq!(range_reduction_factor(2, &one, &three), 1);
+        assert_eq!(range_reduction_factor(100, &three, &one), i32::MAX);
+        let (a, b) = (
+            Constant::String("a".to_string()),
+            Constant::String("b".to_string()),
+        );
+        assert_eq!(range_reduction_factor(100, &a, &b), RANGE_REDUCTION_FACTOR);
+        assert_eq!(values_reduction_factor(100, 4), 25);
+        assert_eq!(values_reduction_factor(3, 4), 1);
+    }
 }
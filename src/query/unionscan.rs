@@ -0,0 +1,116 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use core::fmt;
+use std::sync::{Arc, Mutex};
+
+use super::{constant::Constant, scan::Scan, updatescan::UpdateScan};
+use crate::{
+    materialize::sortscan::SortScan,
+    record::{schema::resolve_field, tablescan::TableScan},
+};
+
+#[derive(Debug)]
+pub enum UnionScanError {
+    DowncastError,
+    FieldNotFound(String),
+}
+
+impl std::error::Error for UnionScanError {}
+impl fmt::Display for UnionScanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UnionScanError::DowncastError => {
+                write!(f, "downcast error")
+            }
+            UnionScanError::FieldNotFound(fldname) => {
+                write!(f, "field not found: {}", fldname)
+            }
+        }
+    }
+}
+
+// The records of s1, followed by the records of s2.
+// The fields of s2 are read by the position of the fields of s1.
+#[derive(Clone)]
+pub struct UnionScan {
+    s1: Arc<Mutex<dyn Scan>>,
+    s2: Arc<Mutex<dyn Scan>>,
+    fields1: Vec<String>,
+    fields2: Vec<String>,
+    onfirst: bool,
+}
+
+impl UnionScan {
+    pub fn new(
+        s1: Arc<Mutex<dyn Scan>>,
+        s2: Arc<Mutex<dyn Scan>>,
+        fields1: Vec<String>,
+        fields2: Vec<String>,
+    ) -> Self {
+        Self {
+            s1,
+            s2,
+            fields1,
+            fields2,
+            onfirst: true,
+        }
+    }
+}
+
+impl Scan for UnionScan {
+    fn before_first(&mut self) -> Result<()> {
+        self.onfirst = true;
+        self.s1.lock().unwrap().before_first()?;
+        self.s2.lock().unwrap().before_first()
+    }
+    fn next(&mut self) -> bool {
+        if self.onfirst {
+            if self.s1.lock().unwrap().next() {
+                return true;
+            }
+            self.onfirst = false;
+        }
+        self.s2.lock().unwrap().next()
+    }
+    fn get_i16(&mut self, fldname: &str) -> Result<i16> {
+        self.get_val(fldname)?.as_i16()
+    }
+    fn get_i32(&mut self, fldname: &str) -> Result<i32> {
+        self.get_val(fldname)?.as_i32()
+    }
+    fn get_string(&mut self, fldname: &str) -> Result<String> {
+        self.get_val(fldname)?.as_string().map(|s| s.to_string())
+    }
+    fn get_bool(&mut self, fldname: &str) -> Result<bool> {
+        self.get_val(fldname)?.as_bool()
+    }
+    fn get_date(&mut self, fldname: &str) -> Result<NaiveDate> {
+        self.get_val(fldname)?.as_date()
+    }
+    fn get_val(&mut self, fldname: &str) -> Result<Constant> {
+        let pos = resolve_field(&self.fields1, fldname)
+            .and_then(|f| self.fields1.iter().position(|x| x == f))
+            .ok_or_else(|| UnionScanError::FieldNotFound(fldname.to_string()))?;
+        if self.onfirst {
+            self.s1.lock().unwrap().get_val(&self.fields1[pos])
+        } else {
+            self.s2.lock().unwrap().get_val(&self.fields2[pos])
+        }
+    }
+    fn has_field(&self, fldname: &str) -> bool {
+        resolve_field(&self.fields1, fldname).is_some()
+    }
+    fn close(&mut self) -> Result<()> {
+        self.s1.lock().unwrap().close()?;
+        self.s2.lock().unwrap().close()
+    }
+    fn to_update_scan(&mut self) -> Result<&mut dyn UpdateScan> {
+        Err(From::from(UnionScanError::DowncastError))
+    }
+    fn as_table_scan(&mut self) -> Result<&mut TableScan> {
+        Err(From::from(UnionScanError::DowncastError))
+    }
+    fn as_sort_scan(&mut self) -> Result<&mut SortScan> {
+        Err(From::from(UnionScanError::DowncastError))
+    }
+}
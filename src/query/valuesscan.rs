@@ -0,0 +1,91 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use core::fmt;
+use std::collections::HashMap;
+
+use super::{constant::Constant, scan::Scan, updatescan::UpdateScan};
+use crate::{materialize::sortscan::SortScan, record::tablescan::TableScan};
+
+#[derive(Debug)]
+pub enum ValuesScanError {
+    DowncastError,
+    FieldNotFound(String),
+}
+
+impl std::error::Error for ValuesScanError {}
+impl fmt::Display for ValuesScanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValuesScanError::DowncastError => {
+                write!(f, "downcast error")
+            }
+            ValuesScanError::FieldNotFound(fldname) => {
+                write!(f, "field not found: {}", fldname)
+            }
+        }
+    }
+}
+
+// The single record of the values, which are about to be written to a table.
+// The constraints of the fields are checked on it, before the record is written.
+#[derive(Debug, Clone)]
+pub struct ValuesScan {
+    vals: HashMap<String, Constant>,
+    done: bool,
+}
+
+impl Scan for ValuesScan {
+    fn before_first(&mut self) -> Result<()> {
+        self.done = false;
+        Ok(())
+    }
+    fn next(&mut self) -> bool {
+        let hasmore = !self.done;
+        self.done = true;
+        hasmore
+    }
+    fn get_i16(&mut self, fldname: &str) -> Result<i16> {
+        self.get_val(fldname)?.as_i16()
+    }
+    fn get_i32(&mut self, fldname: &str) -> Result<i32> {
+        self.get_val(fldname)?.as_i32()
+    }
+    fn get_string(&mut self, fldname: &str) -> Result<String> {
+        self.get_val(fldname)?.as_string().map(|s| s.to_string())
+    }
+    fn get_bool(&mut self, fldname: &str) -> Result<bool> {
+        self.get_val(fldname)?.as_bool()
+    }
+    fn get_date(&mut self, fldname: &str) -> Result<NaiveDate> {
+        self.get_val(fldname)?.as_date()
+    }
+    fn get_val(&mut self, fldname: &str) -> Result<Constant> {
+        match self.vals.get(fldname) {
+            Some(val) => Ok(val.clone()),
+            None => Err(From::from(ValuesScanError::FieldNotFound(
+                fldname.to_string(),
+            ))),
+        }
+    }
+    fn has_field(&self, fldname: &str) -> bool {
+        self.vals.contains_key(fldname)
+    }
+    fn close(&mut self) -> Result<()> {
+        Ok(())
+    }
+    fn to_update_scan(&mut self) -> Result<&mut dyn UpdateScan> {
+        Err(From::from(ValuesScanError::DowncastError))
+    }
+    fn as_table_scan(&mut self) -> Result<&mut TableScan> {
+        Err(From::from(ValuesScanError::DowncastError))
+    }
+    fn as_sort_scan(&mut self) -> Result<&mut SortScan> {
+        Err(From::from(ValuesScanError::DowncastError))
+    }
+}
+
+impl ValuesScan {
+    pub fn new(vals: HashMap<String, Constant>) -> Self {
+        Self { vals, done: false }
+    }
+}
@@ -44,6 +44,9 @@ impl<'a> ResultSetAdapter for EmbeddedResultSet<'a> {
         self.s.lock().unwrap().next()
     }
     fn get_i16(&mut self, fldname: &str) -> Result<Self::Int16Value> {
+        if self.is_null(fldname)? {
+            return Ok(Default::default());
+        }
         self.s.lock().unwrap().get_i16(fldname).or_else(|_| {
             self.conn.rollback().and_then(|_| {
                 Err(From::from(ResultSetError::UnknownField(
@@ -53,6 +56,9 @@ impl<'a> ResultSetAdapter for EmbeddedResultSet<'a> {
         })
     }
     fn get_i32(&mut self, fldname: &str) -> Result<Self::Int32Value> {
+        if self.is_null(fldname)? {
+            return Ok(Default::default());
+        }
         self.s.lock().unwrap().get_i32(fldname).or_else(|_| {
             self.conn.rollback().and_then(|_| {
                 Err(From::from(ResultSetError::UnknownField(
@@ -62,6 +68,9 @@ impl<'a> ResultSetAdapter for EmbeddedResultSet<'a> {
         })
     }
     fn get_string(&mut self, fldname: &str) -> Result<Self::StringValue> {
+        if self.is_null(fldname)? {
+            return Ok(Default::default());
+        }
         self.s.lock().unwrap().get_string(fldname).or_else(|_| {
             self.conn.rollback().and_then(|_| {
                 Err(From::from(ResultSetError::UnknownField(
@@ -71,6 +80,9 @@ impl<'a> ResultSetAdapter for EmbeddedResultSet<'a> {
         })
     }
     fn get_bool(&mut self, fldname: &str) -> Result<Self::BoolValue> {
+        if self.is_null(fldname)? {
+            return Ok(Default::default());
+        }
         self.s.lock().unwrap().get_bool(fldname).or_else(|_| {
             self.conn.rollback().and_then(|_| {
                 Err(From::from(ResultSetError::UnknownField(
@@ -80,6 +92,9 @@ impl<'a> ResultSetAdapter for EmbeddedResultSet<'a> {
         })
     }
     fn get_date(&mut self, fldname: &str) -> Result<Self::DateValue> {
+        if self.is_null(fldname)? {
+            return Ok(Default::default());
+        }
         self.s.lock().unwrap().get_date(fldname).or_else(|_| {
             self.conn.rollback().and_then(|_| {
                 Err(From::from(ResultSetError::UnknownField(
@@ -88,13 +103,23 @@ impl<'a> ResultSetAdapter for EmbeddedResultSet<'a> {
             })
         })
     }
+    fn is_null(&mut self, fldname: &str) -> Result<Self::BoolValue> {
+        self.s.lock().unwrap().is_null(fldname).or_else(|_| {
+            self.conn.rollback().and_then(|_| {
+                Err(From::from(ResultSetError::UnknownField(
+                    fldname.to_string(),
+                )))
+            })
+        })
+    }
     fn get_meta_data(&self) -> Result<Self::Meta> {
         Ok(EmbeddedMetaData::new(Arc::clone(&self.sch)))
     }
     fn close(&mut self) -> Result<Self::Res> {
         match self.s.lock().unwrap().close() {
             Ok(_) => self.conn.close(),
-            Err(_) => Err(From::from(ResultSetError::CloseFailed)),
+            // e.g. the error of a predicate, which stopped the scan
+            Err(e) => self.conn.rollback().and_then(|_| Err(e)),
         }
     }
 }
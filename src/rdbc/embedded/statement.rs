@@ -3,10 +3,23 @@ use anyhow::Result;
 use super::connection::EmbeddedConnection;
 use super::planrepr::EmbeddedPlanRepr;
 use super::resultset::EmbeddedResultSet;
+use crate::metadata::constraintmanager::ConstraintError;
+use crate::parser::syntaxerror::SqlSyntaxError;
 use crate::plan::planner::Planner;
 use crate::rdbc::connectionadapter::ConnectionAdapter;
 use crate::rdbc::statementadapter::{StatementAdapter, StatementError};
 
+// a syntax error and a violation of the constraints are reported as they are
+fn statement_error(e: anyhow::Error) -> StatementError {
+    if let Some(err) = e.downcast_ref::<SqlSyntaxError>() {
+        return StatementError::SyntaxError(err.clone());
+    }
+    match e.downcast_ref::<ConstraintError>() {
+        Some(violation) => StatementError::ConstraintViolation(violation.to_string()),
+        None => StatementError::RuntimeError,
+    }
+}
+
 pub struct EmbeddedStatement<'a> {
     conn: &'a mut EmbeddedConnection,
     planner: Planner,
@@ -28,10 +41,10 @@ impl<'a> EmbeddedStatement<'a> {
         let tx = self.conn.get_transaction();
         match self.planner.create_query_plan(&self.sql, tx) {
             Ok(pln) => Ok(EmbeddedPlanRepr::new(pln.repr())),
-            Err(_) => self
+            Err(e) => self
                 .conn
                 .rollback()
-                .and_then(|_| Err(From::from(StatementError::RuntimeError))),
+                .and_then(|_| Err(From::from(statement_error(e)))),
         }
     }
 }
@@ -45,20 +58,20 @@ impl<'a> StatementAdapter<'a> for EmbeddedStatement<'a> {
         let tx = self.conn.get_transaction();
         match self.planner.create_query_plan(&self.sql, tx) {
             Ok(pln) => EmbeddedResultSet::new(pln, &mut self.conn),
-            Err(_) => self
+            Err(e) => self
                 .conn
                 .rollback()
-                .and_then(|_| Err(From::from(StatementError::RuntimeError))),
+                .and_then(|_| Err(From::from(statement_error(e)))),
         }
     }
     fn execute_update(&mut self) -> Result<Self::Aeffected> {
         let tx = self.conn.get_transaction();
         match self.planner.execute_update(&self.sql, tx) {
             Ok(affected) => self.conn.commit().and_then(|_| Ok(affected)),
-            Err(_) => self
+            Err(e) => self
                 .conn
                 .rollback()
-                .and_then(|_| Err(From::from(StatementError::RuntimeError))),
+                .and_then(|_| Err(From::from(statement_error(e)))),
         }
     }
     fn close(&mut self) -> Result<Self::Res> {
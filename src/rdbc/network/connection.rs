@@ -156,7 +156,7 @@ impl<'a> ConnectionAdapter<'a> for NetworkConnection {
         request.get().set_sql(sql);
         let stmt = request.send().pipeline.get_stmt();
 
-        Ok(Self::Stmt::new(stmt))
+        Ok(Self::Stmt::new(stmt, sql))
     }
     fn close(&mut self) -> Result<Self::Res> {
         let request = self.conn.close_request();
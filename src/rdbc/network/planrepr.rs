@@ -2,7 +2,9 @@ use chrono::NaiveDate;
 use itertools::Itertools;
 use std::sync::Arc;
 
-use crate::{query, remote_capnp::remote_statement, repr, repr::planrepr::PlanRepr};
+use crate::{
+    materialize, parser, query, remote_capnp::remote_statement, repr, repr::planrepr::PlanRepr,
+};
 
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub enum Constant {
@@ -11,6 +13,7 @@ pub enum Constant {
     String(String),
     Bool(bool),
     Date(NaiveDate),
+    Null,
 }
 impl<'a> From<remote_statement::constant::Reader<'a>> for Constant {
     fn from(c: remote_statement::constant::Reader<'a>) -> Self {
@@ -26,6 +29,7 @@ impl<'a> From<remote_statement::constant::Reader<'a>> for Constant {
                 let day = v.get_day() as u32;
                 Self::Date(NaiveDate::from_ymd_opt(year, month, day).unwrap())
             }
+            remote_statement::constant::Null(()) => Self::Null,
         }
     }
 }
@@ -37,54 +41,170 @@ impl From<Constant> for query::constant::Constant {
             Constant::String(s) => Self::String(s),
             Constant::Bool(v) => Self::Bool(v),
             Constant::Date(v) => Self::Date(v),
+            Constant::Null => Self::Null,
         }
     }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct Predicate {
-    terms: Vec<Term>,
+    factors: Vec<Factor>,
 }
 impl<'a> From<remote_statement::predicate::Reader<'a>> for Predicate {
     fn from(pred: remote_statement::predicate::Reader<'a>) -> Self {
-        let terms = pred
-            .get_terms()
+        let factors = pred
+            .get_factors()
             .unwrap()
             .into_iter()
-            .map(|t| Term::from(t))
+            .map(|f| Factor::from(f))
             .collect_vec();
-        Self { terms }
+        Self { factors }
     }
 }
 impl From<Predicate> for query::predicate::Predicate {
     fn from(pred: Predicate) -> Self {
-        let terms = pred.terms.into_iter().map(|t| t.into()).collect_vec();
+        let factors = pred.factors.into_iter().map(|f| f.into()).collect_vec();
         let mut result = Self::new_empty();
-        result.init_with_terms(terms);
+        result.init_with_factors(factors);
         result
     }
 }
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Factor {
+    Term(Term),
+    Or(Vec<Predicate>),
+    Not(Box<Predicate>),
+    In(Expression, String),
+    Exists(String),
+}
+impl<'a> From<remote_statement::factor::Reader<'a>> for Factor {
+    fn from(f: remote_statement::factor::Reader<'a>) -> Self {
+        match f.which().unwrap() {
+            remote_statement::factor::Term(t) => Self::Term(Term::from(t.unwrap())),
+            remote_statement::factor::Disjunction(ps) => {
+                let preds = ps
+                    .unwrap()
+                    .into_iter()
+                    .map(|p| Predicate::from(p))
+                    .collect_vec();
+                Self::Or(preds)
+            }
+            remote_statement::factor::Negation(p) => {
+                Self::Not(Box::new(Predicate::from(p.unwrap())))
+            }
+            remote_statement::factor::InSubquery(i) => {
+                let i = i.unwrap();
+                let lhs = Expression::from(i.get_lhs().unwrap());
+                let sq = i.get_subquery().unwrap().to_string().unwrap();
+                Self::In(lhs, sq)
+            }
+            remote_statement::factor::Exists(sq) => Self::Exists(sq.unwrap().to_string().unwrap()),
+        }
+    }
+}
+impl From<Factor> for query::factor::Factor {
+    fn from(f: Factor) -> Self {
+        match f {
+            Factor::Term(t) => Self::Term(t.into()),
+            Factor::Or(preds) => Self::Or(preds.into_iter().map(|p| p.into()).collect_vec()),
+            Factor::Not(pred) => Self::Not(Box::new((*pred).into())),
+            Factor::In(expr, sq) => Self::In(
+                expr.into(),
+                query::subquery::Subquery::new(parse_query(&sq)),
+            ),
+            Factor::Exists(sq) => {
+                Self::Exists(query::subquery::Subquery::new_exists(parse_query(&sq)))
+            }
+        }
+    }
+}
+// a subquery is sent as the query string.
+fn parse_query(sq: &str) -> parser::querydata::QueryData {
+    // NOTE: query parser expect terminater.
+    parser::syntaxerror::parse_sql(parser::parser::query(), format!("{};", sq).as_str()).unwrap()
+}
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct Term {
     lhs: Expression,
+    op: query::term::CompOp,
     rhs: Expression,
+    escape: Option<char>,
+    rest: Vec<Expression>,
 }
 impl<'a> From<remote_statement::term::Reader<'a>> for Term {
     fn from(t: remote_statement::term::Reader<'a>) -> Self {
         let lhs = Expression::from(t.get_lhs().unwrap());
+        let op = query::term::CompOp::from(t.get_op().unwrap());
         let rhs = Expression::from(t.get_rhs().unwrap());
-        Self { lhs, rhs }
+        // no escape character is sent as the empty text
+        let escape = t.get_escape().unwrap().to_string().unwrap().chars().next();
+        let rest = t
+            .get_rest()
+            .unwrap()
+            .into_iter()
+            .map(|e| Expression::from(e))
+            .collect_vec();
+        Self {
+            lhs,
+            op,
+            rhs,
+            escape,
+            rest,
+        }
     }
 }
 impl From<Term> for query::term::Term {
     fn from(t: Term) -> Self {
-        Self::new(t.lhs.into(), t.rhs.into())
+        let mut rest = t.rest.into_iter().map(|e| e.into()).collect_vec();
+        match t.op {
+            query::term::CompOp::In | query::term::CompOp::NotIn => {
+                let mut list = vec![t.rhs.into()];
+                list.append(&mut rest);
+                Self::new_in(t.lhs.into(), t.op, list)
+            }
+            query::term::CompOp::Between | query::term::CompOp::NotBetween => {
+                Self::new_between(t.lhs.into(), t.op, t.rhs.into(), rest.remove(0))
+            }
+            _ => Self::new_with_escape(t.lhs.into(), t.op, t.rhs.into(), t.escape),
+        }
+    }
+}
+impl From<remote_statement::CompOp> for query::term::CompOp {
+    fn from(op: remote_statement::CompOp) -> Self {
+        match op {
+            remote_statement::CompOp::Eq => Self::Eq,
+            remote_statement::CompOp::Ne => Self::Ne,
+            remote_statement::CompOp::Lt => Self::Lt,
+            remote_statement::CompOp::Le => Self::Le,
+            remote_statement::CompOp::Gt => Self::Gt,
+            remote_statement::CompOp::Ge => Self::Ge,
+            remote_statement::CompOp::Like => Self::Like,
+            remote_statement::CompOp::NotLike => Self::NotLike,
+            remote_statement::CompOp::Is => Self::Is,
+            remote_statement::CompOp::IsNot => Self::IsNot,
+            remote_statement::CompOp::In => Self::In,
+            remote_statement::CompOp::NotIn => Self::NotIn,
+            remote_statement::CompOp::Between => Self::Between,
+            remote_statement::CompOp::NotBetween => Self::NotBetween,
+        }
     }
 }
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub enum Expression {
     Val(Constant),
     Fldname(String),
+    Arith(Box<Expression>, query::expression::ArithOp, Box<Expression>),
+    Neg(Box<Expression>),
+    Aggregate(materialize::aggregationfn::Aggregate),
+    Subquery(String),
+    Case(Vec<(Predicate, Expression)>, Option<Box<Expression>>),
+    SimpleCase(
+        Box<Expression>,
+        Vec<(Expression, Expression)>,
+        Option<Box<Expression>>,
+    ),
+    Coalesce(Vec<Expression>),
+    Function(query::function::ScalarFn, Vec<Expression>),
 }
 impl<'a> From<remote_statement::expression::Reader<'a>> for Expression {
     fn from(expr: remote_statement::expression::Reader<'a>) -> Self {
@@ -97,6 +217,101 @@ impl<'a> From<remote_statement::expression::Reader<'a>> for Expression {
                 let s = s.unwrap().to_string().unwrap();
                 Self::Fldname(s)
             }
+            remote_statement::expression::Arith(a) => {
+                let a = a.unwrap();
+                let lhs = Expression::from(a.get_lhs().unwrap());
+                let op = query::expression::ArithOp::from(a.get_op().unwrap());
+                let rhs = Expression::from(a.get_rhs().unwrap());
+                Self::Arith(Box::new(lhs), op, Box::new(rhs))
+            }
+            remote_statement::expression::Neg(e) => {
+                let e = Expression::from(e.unwrap());
+                Self::Neg(Box::new(e))
+            }
+            remote_statement::expression::Aggregate(a) => {
+                let a = a.unwrap();
+                let fldname = a.get_fldname().unwrap().to_string().unwrap();
+                let agg = match a.get_func().unwrap() {
+                    remote_statement::AggregateFn::CountAll => {
+                        materialize::aggregationfn::Aggregate::CountAll
+                    }
+                    remote_statement::AggregateFn::Count => {
+                        materialize::aggregationfn::Aggregate::Count(fldname)
+                    }
+                    remote_statement::AggregateFn::Sum => {
+                        materialize::aggregationfn::Aggregate::Sum(fldname)
+                    }
+                    remote_statement::AggregateFn::Avg => {
+                        materialize::aggregationfn::Aggregate::Avg(fldname)
+                    }
+                    remote_statement::AggregateFn::Min => {
+                        materialize::aggregationfn::Aggregate::Min(fldname)
+                    }
+                    remote_statement::AggregateFn::Max => {
+                        materialize::aggregationfn::Aggregate::Max(fldname)
+                    }
+                };
+                Self::Aggregate(agg)
+            }
+            remote_statement::expression::Subquery(sq) => {
+                Self::Subquery(sq.unwrap().to_string().unwrap())
+            }
+            remote_statement::expression::SearchedCase(c) => {
+                let c = c.unwrap();
+                let whens = c
+                    .get_whens()
+                    .unwrap()
+                    .into_iter()
+                    .map(|w| {
+                        let cond = Predicate::from(w.get_cond().unwrap());
+                        let result = Expression::from(w.get_result().unwrap());
+                        (cond, result)
+                    })
+                    .collect_vec();
+                // no else is sent as the unset pointer
+                let otherwise = c
+                    .has_otherwise()
+                    .then(|| Box::new(Expression::from(c.get_otherwise().unwrap())));
+                Self::Case(whens, otherwise)
+            }
+            remote_statement::expression::SimpleCase(c) => {
+                let c = c.unwrap();
+                let operand = Expression::from(c.get_operand().unwrap());
+                let whens = c
+                    .get_whens()
+                    .unwrap()
+                    .into_iter()
+                    .map(|w| {
+                        let val = Expression::from(w.get_val().unwrap());
+                        let result = Expression::from(w.get_result().unwrap());
+                        (val, result)
+                    })
+                    .collect_vec();
+                let otherwise = c
+                    .has_otherwise()
+                    .then(|| Box::new(Expression::from(c.get_otherwise().unwrap())));
+                Self::SimpleCase(Box::new(operand), whens, otherwise)
+            }
+            remote_statement::expression::Coalesce(es) => {
+                let exprs = es
+                    .unwrap()
+                    .into_iter()
+                    .map(|e| Expression::from(e))
+                    .collect_vec();
+                Self::Coalesce(exprs)
+            }
+            remote_statement::expression::Function(c) => {
+                let c = c.unwrap();
+                let name = c.get_name().unwrap().to_string().unwrap();
+                let func = query::function::ScalarFn::lookup(&name).unwrap();
+                let args = c
+                    .get_args()
+                    .unwrap()
+                    .into_iter()
+                    .map(|e| Expression::from(e))
+                    .collect_vec();
+                Self::Function(func, args)
+            }
         }
     }
 }
@@ -105,6 +320,43 @@ impl From<Expression> for query::expression::Expression {
         match expr {
             Expression::Val(v) => Self::Val(v.into()),
             Expression::Fldname(s) => Self::Fldname(s),
+            Expression::Arith(lhs, op, rhs) => Self::new_arith((*lhs).into(), op, (*rhs).into()),
+            Expression::Neg(e) => Self::new_neg((*e).into()),
+            Expression::Aggregate(agg) => Self::new_aggregate(agg),
+            Expression::Subquery(sq) => {
+                Self::new_subquery(query::subquery::Subquery::new(parse_query(&sq)))
+            }
+            Expression::Case(whens, otherwise) => Self::new_case(
+                whens
+                    .into_iter()
+                    .map(|(p, e)| (p.into(), e.into()))
+                    .collect_vec(),
+                otherwise.map(|e| (*e).into()),
+            ),
+            Expression::SimpleCase(operand, whens, otherwise) => Self::new_simple_case(
+                (*operand).into(),
+                whens
+                    .into_iter()
+                    .map(|(v, e)| (v.into(), e.into()))
+                    .collect_vec(),
+                otherwise.map(|e| (*e).into()),
+            ),
+            Expression::Coalesce(exprs) => {
+                Self::new_coalesce(exprs.into_iter().map(|e| e.into()).collect_vec())
+            }
+            Expression::Function(func, args) => {
+                Self::new_function(func, args.into_iter().map(|e| e.into()).collect_vec())
+            }
+        }
+    }
+}
+impl From<remote_statement::ArithOp> for query::expression::ArithOp {
+    fn from(op: remote_statement::ArithOp) -> Self {
+        match op {
+            remote_statement::ArithOp::Add => Self::Add,
+            remote_statement::ArithOp::Sub => Self::Sub,
+            remote_statement::ArithOp::Mul => Self::Mul,
+            remote_statement::ArithOp::Div => Self::Div,
         }
     }
 }
@@ -115,6 +367,7 @@ pub enum Operation {
         idxname: String,
         idxfldname: String,
         joinfld: String,
+        outer: bool,
     },
     IndexSelectScan {
         idxname: String,
@@ -123,12 +376,13 @@ pub enum Operation {
     },
     GroupByScan {
         fields: Vec<String>,
-        aggfns: Vec<(String, Constant)>,
+        aggfns: Vec<String>,
     },
     Materialize,
     MergeJoinScan {
         fldname1: String,
         fldname2: String,
+        outer: bool,
     },
     SortScan {
         compflds: Vec<String>,
@@ -142,6 +396,33 @@ pub enum Operation {
     TableScan {
         tblname: String,
     },
+    DistinctScan,
+    LimitScan {
+        limit: usize,
+        offset: usize,
+    },
+    OuterJoinScan {
+        pred: Predicate,
+    },
+    OuterRecordScan {
+        fields: Vec<String>,
+    },
+    UnionScan,
+    SetOpScan {
+        op: String,
+    },
+    IndexRangeScan {
+        idxname: String,
+        idxfldname: String,
+        loval: Constant,
+        hival: Constant,
+        inclusive: bool,
+    },
+    IndexInScan {
+        idxname: String,
+        idxfldname: String,
+        vals: Vec<Constant>,
+    },
 }
 impl<'a> From<remote_statement::plan_repr::operation::Reader<'a>> for Operation {
     fn from(op: remote_statement::plan_repr::operation::Reader) -> Self {
@@ -151,10 +432,12 @@ impl<'a> From<remote_statement::plan_repr::operation::Reader<'a>> for Operation
                 let idxname = v.get_idxname().unwrap().to_string().unwrap();
                 let idxfldname = v.get_idxfldname().unwrap().to_string().unwrap();
                 let joinfld = v.get_joinfld().unwrap().to_string().unwrap();
+                let outer = v.get_outer();
                 Self::IndexJoinScan {
                     idxname,
                     idxfldname,
                     joinfld,
+                    outer,
                 }
             }
             remote_statement::plan_repr::operation::IndexSelectScan(v) => {
@@ -180,11 +463,7 @@ impl<'a> From<remote_statement::plan_repr::operation::Reader<'a>> for Operation
                     .get_aggfns()
                     .unwrap()
                     .into_iter()
-                    .map(|f| {
-                        let fst = f.get_fst().unwrap().to_string().unwrap();
-                        let snd = Constant::from(f.get_snd().unwrap());
-                        (fst, snd)
-                    })
+                    .map(|s| s.unwrap().to_string().unwrap())
                     .collect_vec();
                 Self::GroupByScan { fields, aggfns }
             }
@@ -193,7 +472,12 @@ impl<'a> From<remote_statement::plan_repr::operation::Reader<'a>> for Operation
                 let v = v.unwrap();
                 let fldname1 = v.get_fldname1().unwrap().to_string().unwrap();
                 let fldname2 = v.get_fldname2().unwrap().to_string().unwrap();
-                Self::MergeJoinScan { fldname1, fldname2 }
+                let outer = v.get_outer();
+                Self::MergeJoinScan {
+                    fldname1,
+                    fldname2,
+                    outer,
+                }
             }
             remote_statement::plan_repr::operation::SortScan(v) => {
                 let v = v.unwrap();
@@ -219,6 +503,64 @@ impl<'a> From<remote_statement::plan_repr::operation::Reader<'a>> for Operation
                 let tblname = v.unwrap().get_tblname().unwrap().to_string().unwrap();
                 Self::TableScan { tblname }
             }
+            remote_statement::plan_repr::operation::DistinctScan(_) => Self::DistinctScan,
+            remote_statement::plan_repr::operation::LimitScan(v) => {
+                let v = v.unwrap();
+                let limit = v.get_limit() as usize;
+                let offset = v.get_offset() as usize;
+                Self::LimitScan { limit, offset }
+            }
+            remote_statement::plan_repr::operation::OuterJoinScan(v) => {
+                let v = v.unwrap();
+                let pred = Predicate::from(v.get_pred().unwrap());
+                Self::OuterJoinScan { pred }
+            }
+            remote_statement::plan_repr::operation::OuterRecordScan(v) => {
+                let v = v.unwrap();
+                let fields = v
+                    .get_fields()
+                    .unwrap()
+                    .into_iter()
+                    .map(|s| s.unwrap().to_string().unwrap())
+                    .collect_vec();
+                Self::OuterRecordScan { fields }
+            }
+            remote_statement::plan_repr::operation::UnionScan(_) => Self::UnionScan,
+            remote_statement::plan_repr::operation::SetOpScan(v) => {
+                let op = v.unwrap().get_op().unwrap().to_string().unwrap();
+                Self::SetOpScan { op }
+            }
+            remote_statement::plan_repr::operation::IndexRangeScan(v) => {
+                let v = v.unwrap();
+                let idxname = v.get_idxname().unwrap().to_string().unwrap();
+                let idxfldname = v.get_idxfldname().unwrap().to_string().unwrap();
+                let loval = Constant::from(v.get_loval().unwrap());
+                let hival = Constant::from(v.get_hival().unwrap());
+                let inclusive = v.get_inclusive();
+                Self::IndexRangeScan {
+                    idxname,
+                    idxfldname,
+                    loval,
+                    hival,
+                    inclusive,
+                }
+            }
+            remote_statement::plan_repr::operation::IndexInScan(v) => {
+                let v = v.unwrap();
+                let idxname = v.get_idxname().unwrap().to_string().unwrap();
+                let idxfldname = v.get_idxfldname().unwrap().to_string().unwrap();
+                let vals = v
+                    .get_vals()
+                    .unwrap()
+                    .into_iter()
+                    .map(|c| Constant::from(c))
+                    .collect_vec();
+                Self::IndexInScan {
+                    idxname,
+                    idxfldname,
+                    vals,
+                }
+            }
         }
     }
 }
@@ -230,10 +572,12 @@ impl From<Operation> for repr::planrepr::Operation {
                 idxname,
                 idxfldname,
                 joinfld,
+                outer,
             } => Self::IndexJoinScan {
                 idxname,
                 idxfldname,
                 joinfld,
+                outer,
             },
             Operation::IndexSelectScan {
                 idxname,
@@ -244,20 +588,51 @@ impl From<Operation> for repr::planrepr::Operation {
                 idxfldname,
                 val: val.into(),
             },
-            Operation::GroupByScan { fields, aggfns } => Self::GroupByScan {
-                fields,
-                aggfns: aggfns.into_iter().map(|(s, v)| (s, v.into())).collect_vec(),
-            },
+            Operation::GroupByScan { fields, aggfns } => Self::GroupByScan { fields, aggfns },
             Operation::Materialize => Self::Materialize,
-            Operation::MergeJoinScan { fldname1, fldname2 } => {
-                Self::MergeJoinScan { fldname1, fldname2 }
-            }
+            Operation::MergeJoinScan {
+                fldname1,
+                fldname2,
+                outer,
+            } => Self::MergeJoinScan {
+                fldname1,
+                fldname2,
+                outer,
+            },
             Operation::SortScan { compflds } => Self::SortScan { compflds },
             Operation::MultibufferProductScan => Self::MultibufferProductScan,
             Operation::ProductScan => Self::ProductScan,
             Operation::ProjectScan => Self::ProjectScan,
             Operation::SelectScan { pred } => Self::SelectScan { pred: pred.into() },
             Operation::TableScan { tblname } => Self::TableScan { tblname },
+            Operation::DistinctScan => Self::DistinctScan,
+            Operation::LimitScan { limit, offset } => Self::LimitScan { limit, offset },
+            Operation::OuterJoinScan { pred } => Self::OuterJoinScan { pred: pred.into() },
+            Operation::OuterRecordScan { fields } => Self::OuterRecordScan { fields },
+            Operation::UnionScan => Self::UnionScan,
+            Operation::SetOpScan { op } => Self::SetOpScan { op },
+            Operation::IndexRangeScan {
+                idxname,
+                idxfldname,
+                loval,
+                hival,
+                inclusive,
+            } => Self::IndexRangeScan {
+                idxname,
+                idxfldname,
+                loval: loval.into(),
+                hival: hival.into(),
+                inclusive,
+            },
+            Operation::IndexInScan {
+                idxname,
+                idxfldname,
+                vals,
+            } => Self::IndexInScan {
+                idxname,
+                idxfldname,
+                vals: vals.into_iter().map(|c| c.into()).collect_vec(),
+            },
         }
     }
 }
@@ -101,6 +101,7 @@ pub enum Value {
     String(String),
     Bool(bool),
     Date(NaiveDate),
+    Null,
 }
 
 pub struct NetworkResultSet {
@@ -141,6 +142,9 @@ impl NetworkResultSet {
                 Some(Value::Date(v)) => {
                     result.insert(fldname, Value::Date(*v));
                 }
+                Some(Value::Null) => {
+                    result.insert(fldname, Value::Null);
+                }
                 None => {
                     panic!("field missing: {}", fldname);
                 }
@@ -189,6 +193,9 @@ impl NetworkResultSet {
                     Some(Value::Date(v)) => {
                         result.insert(fldname, Value::Date(*v));
                     }
+                    Some(Value::Null) => {
+                        result.insert(fldname, Value::Null);
+                    }
                     None => {
                         panic!("field missing: {} at index {}", fldname, i);
                     }
@@ -224,6 +231,7 @@ impl NetworkResultSet {
                     let day = v.get_day() as u32;
                     Value::Date(NaiveDate::from_ymd_opt(year, month, day).unwrap())
                 }
+                remote_result_set::value::Null(()) => Value::Null,
             };
             result.insert(key, val);
         }
@@ -282,6 +290,13 @@ impl ResultSetAdapter for NetworkResultSet {
 
         Ok(Self::DateValue::new(val))
     }
+    fn is_null(&mut self, fldname: &str) -> Result<Self::BoolValue> {
+        let mut request = self.resultset.is_null_request();
+        request.get().set_fldname(fldname);
+        let val = request.send().pipeline.get_val();
+
+        Ok(Self::BoolValue::new(val))
+    }
     fn get_meta_data(&self) -> Result<Self::Meta> {
         let request = self.resultset.get_metadata_request();
         let meta = request.send().pipeline.get_metadata();
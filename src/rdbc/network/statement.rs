@@ -2,22 +2,61 @@ use anyhow::Result;
 
 use super::{connection::ResponseImpl, planrepr::NetworkPlanRepr, resultset::NetworkResultSet};
 use crate::{
-    rdbc::statementadapter::StatementAdapter,
+    parser::syntaxerror::SqlSyntaxError,
+    rdbc::statementadapter::{StatementAdapter, StatementError},
     remote_capnp::{affected, remote_statement},
 };
 
+// a violation of the constraints on the server is reported as it is,
+// and the other errors are left as they are.
+fn from_remote_error(e: capnp::Error) -> anyhow::Error {
+    let violation = StatementError::ConstraintViolation("".to_string()).to_string();
+    match e.extra.split_once(&violation) {
+        Some((_, msg)) => From::from(StatementError::ConstraintViolation(msg.to_string())),
+        None => From::from(e),
+    }
+}
+
+// the syntax error of the last execution of the statement on the server, if found.
+async fn fetch_syntax_error(stmt: &remote_statement::Client) -> Result<Option<SqlSyntaxError>> {
+    let request = stmt.syntax_error_request();
+    let reply = request.send().promise.await?;
+    let reply = reply.get()?;
+    if !reply.get_found() {
+        return Ok(None);
+    }
+    let err = reply.get_error()?;
+    let toks = err.get_expected()?;
+    let mut expected = vec![];
+    for i in 0..toks.len() {
+        expected.push(toks.get(i)?.to_str()?.to_string());
+    }
+
+    Ok(Some(SqlSyntaxError::new(
+        err.get_line(),
+        err.get_column(),
+        err.get_token()?.to_str()?.to_string(),
+        expected,
+    )))
+}
+
 pub struct AffectedImpl {
     client: affected::Client,
+    stmt: remote_statement::Client,
 }
 impl AffectedImpl {
-    pub fn new(client: affected::Client) -> Self {
-        Self { client }
+    pub fn new(client: affected::Client, stmt: remote_statement::Client) -> Self {
+        Self { client, stmt }
     }
     pub async fn affected(&self) -> Result<i32> {
         let request = self.client.read_request();
-        let reply = request.send().promise.await?;
-
-        Ok(reply.get()?.get_affected())
+        match request.send().promise.await {
+            Ok(reply) => Ok(reply.get()?.get_affected()),
+            Err(e) => match fetch_syntax_error(&self.stmt).await? {
+                Some(err) => Err(From::from(StatementError::SyntaxError(err))),
+                None => Err(from_remote_error(e)),
+            },
+        }
     }
     pub async fn committed_tx(&self) -> Result<i32> {
         let request = self.client.committed_tx_request();
@@ -29,11 +68,23 @@ impl AffectedImpl {
 
 pub struct NetworkStatement {
     stmt: remote_statement::Client,
+    sql: String,
 }
 
 impl NetworkStatement {
-    pub fn new(stmt: remote_statement::Client) -> Self {
-        Self { stmt }
+    pub fn new(stmt: remote_statement::Client, sql: &str) -> Self {
+        Self {
+            stmt,
+            sql: sql.to_string(),
+        }
+    }
+    pub fn sql(&self) -> &str {
+        &self.sql
+    }
+    // my own extends
+    // the query fails on the first call to the result set, so the syntax error is asked then.
+    pub async fn syntax_error(&self) -> Result<Option<SqlSyntaxError>> {
+        fetch_syntax_error(&self.stmt).await
     }
     pub async fn explain_plan(&mut self) -> Result<NetworkPlanRepr> {
         let request = self.stmt.explain_plan_request();
@@ -63,7 +114,7 @@ impl<'a> StatementAdapter<'a> for NetworkStatement {
         let request = self.stmt.execute_update_request();
         let affected = request.send().pipeline.get_affected();
 
-        Ok(AffectedImpl::new(affected))
+        Ok(AffectedImpl::new(affected, self.stmt.clone()))
     }
     fn close(&mut self) -> Result<Self::Res> {
         let request = self.stmt.close_request();
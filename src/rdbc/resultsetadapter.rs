@@ -47,6 +47,9 @@ pub trait ResultSetAdapter {
     fn get_string(&mut self, fldname: &str) -> Result<Self::StringValue>;
     fn get_bool(&mut self, fldname: &str) -> Result<Self::BoolValue>;
     fn get_date(&mut self, fldname: &str) -> Result<Self::DateValue>;
+    // my own extends
+    // the typed getters of a null field return the default value of the type.
+    fn is_null(&mut self, fldname: &str) -> Result<Self::BoolValue>;
     fn get_meta_data(&self) -> Result<Self::Meta>;
     fn close(&mut self) -> Result<Self::Res>;
 }
@@ -2,10 +2,13 @@ use anyhow::Result;
 use core::fmt;
 
 use super::resultsetadapter::ResultSetAdapter;
+use crate::parser::syntaxerror::SqlSyntaxError;
 
 #[derive(Debug)]
 pub enum StatementError {
     RuntimeError,
+    ConstraintViolation(String),
+    SyntaxError(SqlSyntaxError),
 }
 
 impl std::error::Error for StatementError {}
@@ -15,6 +18,12 @@ impl fmt::Display for StatementError {
             StatementError::RuntimeError => {
                 write!(f, "runtime error")
             }
+            StatementError::ConstraintViolation(msg) => {
+                write!(f, "constraint violation: {}", msg)
+            }
+            StatementError::SyntaxError(err) => {
+                write!(f, "{}", err)
+            }
         }
     }
 }
@@ -1,6 +1,6 @@
 use std::{collections::HashMap, mem, sync::Arc};
 
-use super::schema::{FieldType, Schema};
+use super::schema::{qualify_name, FieldType, Schema};
 use crate::file::page::Page;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -14,6 +14,7 @@ impl Layout {
     pub fn new(schema: Arc<Schema>) -> Self {
         let mut offsets = HashMap::new();
         let mut pos = mem::size_of::<i32>(); // space for the empty/inuse flag
+        pos += null_bitmap_size(schema.fields().len()); // space for the null flags
         for fldname in schema.fields() {
             offsets.insert(fldname.to_string(), pos);
             pos += lengthin_bytes(&schema, fldname.to_string())
@@ -37,11 +38,62 @@ impl Layout {
         Arc::clone(&self.schema)
     }
     pub fn offset(&self, fldname: &str) -> usize {
-        *self.offsets.get(fldname).unwrap()
+        match self.offsets.get(fldname) {
+            Some(offset) => *offset,
+            None => *self
+                .offsets
+                .get(self.schema.resolve(fldname).unwrap())
+                .unwrap(),
+        }
     }
     pub fn slot_size(&self) -> usize {
         self.slotsize
     }
+    // my own extends
+    // the offset of the word of the null bitmap, and the bit in it, of the field.
+    // the bitmap follows the empty/inuse flag, and the bits are in the order of the schema fields.
+    pub fn null_flag(&self, fldname: &str) -> (usize, i32) {
+        let fldname = match self.offsets.contains_key(fldname) {
+            true => fldname,
+            false => self.schema.resolve(fldname).unwrap(),
+        };
+        let pos = self
+            .schema
+            .fields()
+            .iter()
+            .position(|f| f == fldname)
+            .unwrap();
+        let offset = mem::size_of::<i32>() * (1 + pos / NULL_FLAGS_PER_WORD);
+        let bit = 1 << (pos % NULL_FLAGS_PER_WORD);
+
+        (offset, bit)
+    }
+    pub fn null_bitmap_offsets(&self) -> Vec<usize> {
+        let nwords = null_bitmap_size(self.schema.fields().len()) / mem::size_of::<i32>();
+        (0..nwords)
+            .map(|i| mem::size_of::<i32>() * (1 + i))
+            .collect()
+    }
+    // my own extends
+    // the same record format, of which fields are qualified (e.g. "s.id").
+    pub fn qualified(&self, qualifier: &str) -> Layout {
+        let offsets = self
+            .offsets
+            .iter()
+            .map(|(fldname, offset)| (qualify_name(qualifier, fldname), *offset))
+            .collect();
+        Self {
+            schema: Arc::new(self.schema.qualified(qualifier)),
+            offsets,
+            slotsize: self.slotsize,
+        }
+    }
+}
+
+const NULL_FLAGS_PER_WORD: usize = 32;
+
+fn null_bitmap_size(nfields: usize) -> usize {
+    nfields.div_ceil(NULL_FLAGS_PER_WORD) * mem::size_of::<i32>()
 }
 
 fn lengthin_bytes(schema: &Schema, fldname: String) -> usize {
@@ -54,3 +106,29 @@ fn lengthin_bytes(schema: &Schema, fldname: String) -> usize {
         FieldType::DATE => mem::size_of::<u32>(), // NOTE: u16(year) + u8(month) + u8(day)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn null_flag_test() {
+        let mut sch = Schema::new();
+        for i in 0..33 {
+            sch.add_i32_field(&format!("f{}", i));
+        }
+        let layout = Layout::new(Arc::new(sch));
+        // the flag, and two words of the null bitmap
+        assert_eq!(layout.offset("f0"), 12);
+        assert_eq!(layout.slot_size(), 12 + 33 * 4);
+        assert_eq!(layout.null_bitmap_offsets(), vec![4, 8]);
+        assert_eq!(layout.null_flag("f0"), (4, 1));
+        assert_eq!(layout.null_flag("f5"), (4, 1 << 5));
+        assert_eq!(layout.null_flag("f31"), (4, i32::MIN));
+        assert_eq!(layout.null_flag("f32"), (8, 1));
+
+        let qualified = layout.qualified("t");
+        assert_eq!(qualified.null_flag("t.f32"), (8, 1));
+        assert_eq!(qualified.null_flag("f5"), (4, 1 << 5));
+    }
+}
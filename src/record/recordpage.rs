@@ -2,7 +2,11 @@ use anyhow::Result;
 use chrono::NaiveDate;
 use num_derive::FromPrimitive;
 use num_traits::FromPrimitive;
-use std::sync::{Arc, Mutex};
+use std::{
+    collections::HashSet,
+    mem,
+    sync::{Arc, Mutex},
+};
 
 use super::{layout::Layout, schema::FieldType};
 use crate::{file::block_id::BlockId, tx::transaction::Transaction};
@@ -18,13 +22,20 @@ pub struct RecordPage {
     tx: Arc<Mutex<Transaction>>,
     blk: BlockId,
     layout: Arc<Layout>,
+    // the slots inserted by this page, whose header is restored as a whole by a rollback
+    fresh: HashSet<i32>,
 }
 
 impl RecordPage {
     pub fn new(tx: Arc<Mutex<Transaction>>, blk: BlockId, layout: Arc<Layout>) -> Result<Self> {
         tx.lock().unwrap().pin(&blk)?;
 
-        Ok(Self { tx, blk, layout })
+        Ok(Self {
+            tx,
+            blk,
+            layout,
+            fresh: HashSet::new(),
+        })
     }
     pub fn get_i16(&mut self, slot: i32, fldname: &str) -> Result<i16> {
         let fldpos = self.offset(slot) + self.layout.offset(fldname) as i32;
@@ -52,31 +63,48 @@ impl RecordPage {
         tx.get_date(&self.blk, fldpos)
     }
     pub fn set_i16(&mut self, slot: i32, fldname: &str, val: i16) -> Result<()> {
+        self.set_null_flag(slot, fldname, false)?;
         let fldpos = self.offset(slot) + self.layout.offset(fldname) as i32;
         let mut tx = self.tx.lock().unwrap();
         tx.set_i16(&self.blk, fldpos as i32, val, true)
     }
     pub fn set_i32(&mut self, slot: i32, fldname: &str, val: i32) -> Result<()> {
+        self.set_null_flag(slot, fldname, false)?;
         let fldpos = self.offset(slot) + self.layout.offset(fldname) as i32;
         let mut tx = self.tx.lock().unwrap();
         tx.set_i32(&self.blk, fldpos as i32, val, true)
     }
     pub fn set_string(&mut self, slot: i32, fldname: &str, val: String) -> Result<()> {
+        self.set_null_flag(slot, fldname, false)?;
         let fldpos = self.offset(slot) + self.layout.offset(fldname) as i32;
         let mut tx = self.tx.lock().unwrap();
         tx.set_string(&self.blk, fldpos, &val, true)
     }
     pub fn set_bool(&mut self, slot: i32, fldname: &str, val: bool) -> Result<()> {
+        self.set_null_flag(slot, fldname, false)?;
         let fldpos = self.offset(slot) + self.layout.offset(fldname) as i32;
         let mut tx = self.tx.lock().unwrap();
         tx.set_bool(&self.blk, fldpos as i32, val, true)
     }
     pub fn set_date(&mut self, slot: i32, fldname: &str, val: NaiveDate) -> Result<()> {
+        self.set_null_flag(slot, fldname, false)?;
         let fldpos = self.offset(slot) + self.layout.offset(fldname) as i32;
         let mut tx = self.tx.lock().unwrap();
         tx.set_date(&self.blk, fldpos as i32, val, true)
     }
+    // my own extends
+    pub fn is_null(&mut self, slot: i32, fldname: &str) -> Result<bool> {
+        let (offset, bit) = self.layout.null_flag(fldname);
+        let mut tx = self.tx.lock().unwrap();
+        let flags = tx.get_i32(&self.blk, self.offset(slot) + offset as i32)?;
+
+        Ok(flags & bit != 0)
+    }
+    pub fn set_null(&mut self, slot: i32, fldname: &str) -> Result<()> {
+        self.set_null_flag(slot, fldname, true)
+    }
     pub fn delete(&mut self, slot: i32) -> Result<()> {
+        self.fresh.remove(&slot);
         self.set_flag(slot, SlotFlag::EMPTY)
     }
     pub fn format(&mut self) -> Result<()> {
@@ -85,6 +113,9 @@ impl RecordPage {
             let mut tx = self.tx.lock().unwrap();
 
             tx.set_i32(&self.blk, self.offset(slot), SlotFlag::EMPTY as i32, false)?;
+            for offset in self.layout.null_bitmap_offsets() {
+                tx.set_i32(&self.blk, self.offset(slot) + offset as i32, 0, false)?;
+            }
             let sch = self.layout.schema();
             for fldname in sch.fields() {
                 let fldpos = self.offset(slot) + self.layout.offset(fldname) as i32;
@@ -115,12 +146,21 @@ impl RecordPage {
         }
         Ok(())
     }
+    // my own extends
+    // empties the block by zeroing it. unlike format, the old image is logged,
+    // so the records are restored by a rollback even if the block is reused with another layout.
+    pub fn erase(&mut self) -> Result<()> {
+        let mut tx = self.tx.lock().unwrap();
+        let zeros = vec![0; tx.block_size() as usize];
+        tx.set_image(&self.blk, 0, &zeros, true)
+    }
     pub fn next_after(&mut self, slot: i32) -> Option<i32> {
         self.search_after(slot, SlotFlag::USED)
     }
     pub fn insert_after(&mut self, slot: i32) -> Option<i32> {
         if let Some(newslot) = self.search_after(slot, SlotFlag::EMPTY) {
-            self.set_flag(newslot, SlotFlag::USED).unwrap();
+            self.set_header(newslot).unwrap();
+            self.fresh.insert(newslot);
             return Some(newslot);
         }
 
@@ -135,6 +175,37 @@ impl RecordPage {
 
         tx.set_i32(&self.blk, offset, flag as i32, true)
     }
+    // marks the slot used, and the fields of the new record null until they are set.
+    // the flag and the null bitmap are written by one log record.
+    fn set_header(&mut self, slot: i32) -> Result<()> {
+        let nwords = self.layout.null_bitmap_offsets().len();
+        let mut header = Vec::with_capacity(mem::size_of::<i32>() * (1 + nwords));
+        header.extend_from_slice(&(SlotFlag::USED as i32).to_be_bytes());
+        for _ in 0..nwords {
+            header.extend_from_slice(&(-1_i32).to_be_bytes());
+        }
+        let mut tx = self.tx.lock().unwrap();
+
+        tx.set_image(&self.blk, self.offset(slot), &header, true)
+    }
+    // writes the null flag only when it changes, so that a non-null value costs no more log records.
+    // the flag of a fresh slot is not logged, since the undo of its header restores the bitmap.
+    fn set_null_flag(&mut self, slot: i32, fldname: &str, isnull: bool) -> Result<()> {
+        let (offset, bit) = self.layout.null_flag(fldname);
+        let pos = self.offset(slot) + offset as i32;
+        let mut tx = self.tx.lock().unwrap();
+        let flags = tx.get_i32(&self.blk, pos)?;
+        let newflags = match isnull {
+            true => flags | bit,
+            false => flags & !bit,
+        };
+        if newflags == flags {
+            return Ok(());
+        }
+
+        let ok_to_log = !self.fresh.contains(&slot);
+        tx.set_i32(&self.blk, pos, newflags, ok_to_log)
+    }
     fn search_after(&mut self, mut slot: i32, flag: SlotFlag) -> Option<i32> {
         slot += 1;
         while self.is_valid_slot(slot) {
@@ -231,4 +302,135 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn null_test() -> Result<()> {
+        if Path::new("_test/recordpagenull").exists() {
+            fs::remove_dir_all("_test/recordpagenull")?;
+        }
+
+        let simpledb = SimpleDB::new_with("_test/recordpagenull", 400, 8);
+
+        let tx = Arc::new(Mutex::new(simpledb.new_tx()?));
+        let mut sch = Schema::new();
+        sch.add_i32_field("A");
+        sch.add_string_field("B", 9);
+        let layout = Arc::new(Layout::new(Arc::new(sch)));
+
+        let blk = tx.lock().unwrap().append("testfile")?;
+        let mut rp = RecordPage::new(Arc::clone(&tx), blk.clone(), Arc::clone(&layout))?;
+        rp.format()?;
+
+        let lm = simpledb.log_mgr();
+        let nrecords = lm.lock().unwrap().iterator()?.count();
+        let slot = rp.insert_after(-1).unwrap();
+        assert!(rp.is_null(slot, "A")?);
+        assert!(rp.is_null(slot, "B")?);
+        rp.set_i32(slot, "A", 1)?;
+        assert!(!rp.is_null(slot, "A")?);
+        assert!(rp.is_null(slot, "B")?);
+        rp.set_string(slot, "B", "rec1".to_string())?;
+        // the header of the new record and the two values are logged, but not the null flags
+        assert_eq!(lm.lock().unwrap().iterator()?.count(), nrecords + 3);
+        rp.set_null(slot, "A")?;
+        assert!(rp.is_null(slot, "A")?);
+        assert!(!rp.is_null(slot, "B")?);
+        assert_eq!(rp.get_string(slot, "B")?, "rec1");
+
+        // a reused slot doesn't keep the flags of the deleted record
+        rp.delete(slot)?;
+        let slot = rp.insert_after(-1).unwrap();
+        assert!(rp.is_null(slot, "B")?);
+        rp.set_i32(slot, "A", 2)?;
+        tx.lock().unwrap().unpin(&blk)?;
+        tx.lock().unwrap().commit()?;
+
+        // the flags of a committed record are restored by a rollback
+        let tx = Arc::new(Mutex::new(simpledb.new_tx()?));
+        let mut rp = RecordPage::new(Arc::clone(&tx), blk.clone(), Arc::clone(&layout))?;
+        rp.set_null(slot, "A")?;
+        rp.set_string(slot, "B", "rec2".to_string())?;
+        // and a rolled back insert leaves the slot empty
+        let newslot = rp.insert_after(slot).unwrap();
+        rp.set_i32(newslot, "A", 3)?;
+        tx.lock().unwrap().unpin(&blk)?;
+        tx.lock().unwrap().rollback()?;
+
+        let tx = Arc::new(Mutex::new(simpledb.new_tx()?));
+        let mut rp = RecordPage::new(Arc::clone(&tx), blk.clone(), layout)?;
+        assert!(!rp.is_null(slot, "A")?);
+        assert_eq!(rp.get_i32(slot, "A")?, 2);
+        assert!(rp.is_null(slot, "B")?);
+        assert_eq!(rp.next_after(slot), None);
+        let newslot = rp.insert_after(slot).unwrap();
+        assert!(rp.is_null(newslot, "A")?);
+        tx.lock().unwrap().unpin(&blk)?;
+        tx.lock().unwrap().commit()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn erase_test() -> Result<()> {
+        if Path::new("_test/recordpageerase").exists() {
+            fs::remove_dir_all("_test/recordpageerase")?;
+        }
+
+        let simpledb = SimpleDB::new_with("_test/recordpageerase", 400, 8);
+
+        let mut sch = Schema::new();
+        sch.add_i32_field("A");
+        sch.add_string_field("B", 9);
+        let layout = Arc::new(Layout::new(Arc::new(sch)));
+
+        let tx = Arc::new(Mutex::new(simpledb.new_tx()?));
+        let blk = tx.lock().unwrap().append("testfile")?;
+        tx.lock().unwrap().pin(&blk)?;
+        let mut rp = RecordPage::new(Arc::clone(&tx), blk.clone(), Arc::clone(&layout))?;
+        rp.format()?;
+        let mut n = 0;
+        let mut next_slot = rp.insert_after(-1);
+        while let Some(slot) = next_slot {
+            rp.set_i32(slot, "A", n)?;
+            rp.set_string(slot, "B", format!("rec{}", n))?;
+            n += 1;
+            next_slot = rp.insert_after(slot);
+        }
+        tx.lock().unwrap().unpin(&blk)?;
+        tx.lock().unwrap().commit()?;
+
+        // the block is reused with another layout, and then rolled back
+        let mut sch2 = Schema::new();
+        sch2.add_string_field("C", 20);
+        let layout2 = Arc::new(Layout::new(Arc::new(sch2)));
+        let tx = Arc::new(Mutex::new(simpledb.new_tx()?));
+        tx.lock().unwrap().pin(&blk)?;
+        let mut rp = RecordPage::new(Arc::clone(&tx), blk.clone(), layout2)?;
+        rp.erase()?;
+        rp.format()?;
+        let mut next_slot = rp.insert_after(-1);
+        while let Some(slot) = next_slot {
+            rp.set_string(slot, "C", "overwritten".to_string())?;
+            next_slot = rp.insert_after(slot);
+        }
+        tx.lock().unwrap().unpin(&blk)?;
+        tx.lock().unwrap().rollback()?;
+
+        let tx = Arc::new(Mutex::new(simpledb.new_tx()?));
+        tx.lock().unwrap().pin(&blk)?;
+        let mut rp = RecordPage::new(Arc::clone(&tx), blk.clone(), layout)?;
+        let mut count = 0;
+        let mut next_slot = rp.next_after(-1);
+        while let Some(slot) = next_slot {
+            assert_eq!(rp.get_i32(slot, "A")?, count);
+            assert_eq!(rp.get_string(slot, "B")?, format!("rec{}", count));
+            count += 1;
+            next_slot = rp.next_after(slot);
+        }
+        assert_eq!(count, n);
+        tx.lock().unwrap().unpin(&blk)?;
+        tx.lock().unwrap().commit()?;
+
+        Ok(())
+    }
 }
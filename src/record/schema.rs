@@ -48,18 +48,79 @@ impl Schema {
         &self.fields
     }
     pub fn has_field(&self, fldname: &str) -> bool {
-        self.fields.contains(&fldname.to_string())
+        self.resolve(fldname).is_some()
     }
     pub fn field_type(&self, fldname: &str) -> FieldType {
-        self.info.get(fldname).unwrap().fld_type
+        self.info
+            .get(self.resolve(fldname).unwrap())
+            .unwrap()
+            .fld_type
     }
     pub fn length(&self, fldname: &str) -> usize {
-        self.info.get(fldname).unwrap().length
+        self.info
+            .get(self.resolve(fldname).unwrap())
+            .unwrap()
+            .length
     }
     // my own extends
     pub fn info(&self) -> &HashMap<String, FieldInfo> {
         &self.info
     }
+    // finds the field, which the name refers to.
+    // an unqualified name refers to a qualified field (e.g. "id" to "s.id"),
+    // only if it is not ambiguous.
+    pub fn resolve(&self, fldname: &str) -> Option<&String> {
+        resolve_field(&self.fields, fldname)
+    }
+    // the records of the two schemas can be combined by a set operation,
+    // if they have as many fields, and the fields at the same position have the same type.
+    pub fn is_union_compatible(&self, other: &Schema) -> bool {
+        self.fields.len() == other.fields.len()
+            && self.fields.iter().zip(other.fields.iter()).all(|(f1, f2)| {
+                self.info.get(f1).unwrap().fld_type == other.info.get(f2).unwrap().fld_type
+            })
+    }
+    // qualifies all unqualified fields, e.g. "id" to "s.id".
+    pub fn qualified(&self, qualifier: &str) -> Schema {
+        let mut sch = Schema::new();
+        for fldname in self.fields.iter() {
+            let info = self.info.get(fldname).unwrap();
+            sch.add_field(
+                &qualify_name(qualifier, fldname),
+                info.fld_type,
+                info.length,
+            );
+        }
+        sch
+    }
+}
+
+pub fn resolve_field<'a>(fields: &'a [String], fldname: &str) -> Option<&'a String> {
+    if let Some(f) = fields.iter().find(|f| *f == fldname) {
+        return Some(f);
+    }
+    if fldname.contains('.') {
+        return None;
+    }
+    let mut found = fields.iter().filter(|f| unqualified_name(f) == fldname);
+    match (found.next(), found.next()) {
+        (Some(f), None) => Some(f),
+        _ => None,
+    }
+}
+
+pub fn qualify_name(qualifier: &str, fldname: &str) -> String {
+    if fldname.contains('.') {
+        return fldname.to_string();
+    }
+    format!("{}.{}", qualifier, fldname)
+}
+
+pub fn unqualified_name(fldname: &str) -> &str {
+    match fldname.rsplit_once('.') {
+        Some((_, name)) => name,
+        None => fldname,
+    }
 }
 
 #[derive(FromPrimitive, Debug, Copy, Clone, Eq, PartialEq)]
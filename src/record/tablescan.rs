@@ -92,6 +92,9 @@ impl Scan for TableScan {
             .get_date(self.currentslot, fldname)
     }
     fn get_val(&mut self, fldname: &str) -> Result<Constant> {
+        if self.is_null(fldname)? {
+            return Ok(Constant::Null);
+        }
         return match self.layout.schema().field_type(fldname) {
             FieldType::SMALLINT => Ok(Constant::new_i16(self.get_i16(fldname).unwrap_or(0))),
             FieldType::INTEGER => Ok(Constant::new_i32(self.get_i32(fldname).unwrap_or(0))),
@@ -107,6 +110,9 @@ impl Scan for TableScan {
             )),
         };
     }
+    fn is_null(&mut self, fldname: &str) -> Result<bool> {
+        self.rp.as_mut().unwrap().is_null(self.currentslot, fldname)
+    }
     fn has_field(&self, fldname: &str) -> bool {
         self.layout.schema().has_field(fldname)
     }
@@ -164,21 +170,28 @@ impl UpdateScan for TableScan {
             .set_date(self.currentslot, fldname, val)
     }
     fn set_val(&mut self, fldname: &str, val: Constant) -> Result<()> {
+        if val.is_null() {
+            return self
+                .rp
+                .as_mut()
+                .unwrap()
+                .set_null(self.currentslot, fldname);
+        }
         match self.layout.schema().field_type(fldname) {
             FieldType::SMALLINT => {
-                self.set_i16(fldname, val.as_i16().unwrap())?;
+                self.set_i16(fldname, val.as_i16()?)?;
             }
             FieldType::INTEGER => {
-                self.set_i32(fldname, val.as_i32().unwrap())?;
+                self.set_i32(fldname, val.as_i32()?)?;
             }
             FieldType::VARCHAR => {
-                self.set_string(fldname, val.as_string().unwrap().to_string())?;
+                self.set_string(fldname, val.as_string()?.to_string())?;
             }
             FieldType::BOOL => {
-                self.set_bool(fldname, val.as_bool().unwrap())?;
+                self.set_bool(fldname, val.as_bool()?)?;
             }
             FieldType::DATE => {
-                self.set_date(fldname, val.as_date().unwrap())?;
+                self.set_date(fldname, val.as_date()?)?;
             }
         }
 
@@ -8,6 +8,7 @@ pub enum Operation {
         idxname: String,
         idxfldname: String,
         joinfld: String,
+        outer: bool,
     },
     IndexSelectScan {
         idxname: String,
@@ -16,12 +17,13 @@ pub enum Operation {
     },
     GroupByScan {
         fields: Vec<String>,
-        aggfns: Vec<(String, Constant)>,
+        aggfns: Vec<String>,
     },
     Materialize,
     MergeJoinScan {
         fldname1: String,
         fldname2: String,
+        outer: bool,
     },
     SortScan {
         compflds: Vec<String>,
@@ -35,6 +37,33 @@ pub enum Operation {
     TableScan {
         tblname: String,
     },
+    DistinctScan,
+    LimitScan {
+        limit: usize,
+        offset: usize,
+    },
+    OuterJoinScan {
+        pred: Predicate,
+    },
+    OuterRecordScan {
+        fields: Vec<String>,
+    },
+    UnionScan,
+    SetOpScan {
+        op: String,
+    },
+    IndexRangeScan {
+        idxname: String,
+        idxfldname: String,
+        loval: Constant,
+        hival: Constant,
+        inclusive: bool,
+    },
+    IndexInScan {
+        idxname: String,
+        idxfldname: String,
+        vals: Vec<Constant>,
+    },
 }
 
 pub trait PlanRepr {
@@ -10,8 +10,19 @@ use std::{
 
 use super::simpledb::SimpleDB;
 use crate::{
+    materialize::aggregationfn::Aggregate,
+    metadata::constraintmanager::ConstraintError,
+    parser::syntaxerror::SqlSyntaxError,
     plan::{plan::Plan, planner::Planner},
-    query::{constant::Constant, expression::Expression, scan::Scan},
+    query::{
+        constant::Constant,
+        expression::{ArithOp, Expression},
+        factor::Factor,
+        predicate::Predicate,
+        scan::Scan,
+        term::CompOp,
+    },
+    rdbc::statementadapter::StatementError,
     record::schema::{FieldType, Schema},
     remote_capnp::{
         self, affected, bool_box, date_box, int16_box, int32_box, remote_connection, remote_driver,
@@ -240,6 +251,9 @@ fn set_constant(cnst: &Constant, c: &mut remote_statement::constant::Builder) {
             dt.set_month(d.month() as u8);
             dt.set_day(d.day() as u8);
         }
+        Constant::Null => {
+            c.set_null(());
+        }
     }
 }
 fn set_expression(expr: &Expression, e: &mut remote_statement::expression::Builder) {
@@ -251,6 +265,133 @@ fn set_expression(expr: &Expression, e: &mut remote_statement::expression::Build
             let mut v = e.reborrow().init_val();
             set_constant(c, &mut v);
         }
+        Expression::Arith(lhs, op, rhs) => {
+            let mut a = e.reborrow().init_arith();
+            set_expression(lhs, &mut a.reborrow().init_lhs());
+            a.set_op(match op {
+                ArithOp::Add => remote_statement::ArithOp::Add,
+                ArithOp::Sub => remote_statement::ArithOp::Sub,
+                ArithOp::Mul => remote_statement::ArithOp::Mul,
+                ArithOp::Div => remote_statement::ArithOp::Div,
+            });
+            set_expression(rhs, &mut a.reborrow().init_rhs());
+        }
+        Expression::Neg(expr) => {
+            set_expression(expr, &mut e.reborrow().init_neg());
+        }
+        Expression::Aggregate(agg) => {
+            let mut a = e.reborrow().init_aggregate();
+            let (f, fldname) = match agg {
+                Aggregate::CountAll => (remote_statement::AggregateFn::CountAll, ""),
+                Aggregate::Count(fldname) => {
+                    (remote_statement::AggregateFn::Count, fldname.as_str())
+                }
+                Aggregate::Sum(fldname) => (remote_statement::AggregateFn::Sum, fldname.as_str()),
+                Aggregate::Avg(fldname) => (remote_statement::AggregateFn::Avg, fldname.as_str()),
+                Aggregate::Min(fldname) => (remote_statement::AggregateFn::Min, fldname.as_str()),
+                Aggregate::Max(fldname) => (remote_statement::AggregateFn::Max, fldname.as_str()),
+            };
+            a.set_func(f);
+            a.set_fldname(fldname);
+        }
+        Expression::Subquery(sq) => {
+            e.reborrow().set_subquery(sq.data().to_string().as_str());
+        }
+        Expression::Case(whens, otherwise) => {
+            let mut c = e.reborrow().init_searched_case();
+            let mut ws = c.reborrow().init_whens(whens.len() as u32);
+            for (i, (pred, result)) in whens.iter().enumerate() {
+                let mut w = ws.reborrow().get(i as u32);
+                set_predicate(pred, &mut w.reborrow().init_cond());
+                set_expression(result, &mut w.reborrow().init_result());
+            }
+            if let Some(expr) = otherwise {
+                set_expression(expr, &mut c.reborrow().init_otherwise());
+            }
+        }
+        Expression::SimpleCase(operand, whens, otherwise) => {
+            let mut c = e.reborrow().init_simple_case();
+            set_expression(operand, &mut c.reborrow().init_operand());
+            let mut ws = c.reborrow().init_whens(whens.len() as u32);
+            for (i, (val, result)) in whens.iter().enumerate() {
+                let mut w = ws.reborrow().get(i as u32);
+                set_expression(val, &mut w.reborrow().init_val());
+                set_expression(result, &mut w.reborrow().init_result());
+            }
+            if let Some(expr) = otherwise {
+                set_expression(expr, &mut c.reborrow().init_otherwise());
+            }
+        }
+        Expression::Coalesce(exprs) => {
+            let mut es = e.reborrow().init_coalesce(exprs.len() as u32);
+            for (i, expr) in exprs.iter().enumerate() {
+                set_expression(expr, &mut es.reborrow().get(i as u32));
+            }
+        }
+        Expression::Function(func, args) => {
+            let mut c = e.reborrow().init_function();
+            c.set_name(func.name());
+            let mut es = c.reborrow().init_args(args.len() as u32);
+            for (i, arg) in args.iter().enumerate() {
+                set_expression(arg, &mut es.reborrow().get(i as u32));
+            }
+        }
+    }
+}
+
+fn set_predicate(pred: &Predicate, p: &mut remote_statement::predicate::Builder) {
+    let mut fs = p.reborrow().init_factors(pred.factors().len() as u32);
+    for (i, factor) in pred.factors().into_iter().enumerate() {
+        let f = fs.reborrow().get(i as u32);
+        match factor {
+            Factor::Term(term) => {
+                let mut t = f.init_term();
+                let mut lhs = t.reborrow().init_lhs();
+                set_expression(term.lhs(), &mut lhs);
+                let mut rhs = t.reborrow().init_rhs();
+                set_expression(term.rhs(), &mut rhs);
+                t.set_op(match term.op() {
+                    CompOp::Eq => remote_statement::CompOp::Eq,
+                    CompOp::Ne => remote_statement::CompOp::Ne,
+                    CompOp::Lt => remote_statement::CompOp::Lt,
+                    CompOp::Le => remote_statement::CompOp::Le,
+                    CompOp::Gt => remote_statement::CompOp::Gt,
+                    CompOp::Ge => remote_statement::CompOp::Ge,
+                    CompOp::Like => remote_statement::CompOp::Like,
+                    CompOp::NotLike => remote_statement::CompOp::NotLike,
+                    CompOp::Is => remote_statement::CompOp::Is,
+                    CompOp::IsNot => remote_statement::CompOp::IsNot,
+                    CompOp::In => remote_statement::CompOp::In,
+                    CompOp::NotIn => remote_statement::CompOp::NotIn,
+                    CompOp::Between => remote_statement::CompOp::Between,
+                    CompOp::NotBetween => remote_statement::CompOp::NotBetween,
+                });
+                let mut rest = t.reborrow().init_rest(term.rest().len() as u32);
+                for (j, expr) in term.rest().iter().enumerate() {
+                    set_expression(expr, &mut rest.reborrow().get(j as u32));
+                }
+                if let Some(c) = term.escape() {
+                    t.set_escape(c.to_string().as_str());
+                }
+            }
+            Factor::Or(preds) => {
+                let mut ps = f.init_disjunction(preds.len() as u32);
+                for (j, pred) in preds.iter().enumerate() {
+                    set_predicate(pred, &mut ps.reborrow().get(j as u32));
+                }
+            }
+            Factor::Not(pred) => {
+                set_predicate(pred, &mut f.init_negation());
+            }
+            Factor::In(expr, sq) => {
+                let mut i = f.init_in_subquery();
+                set_expression(expr, &mut i.reborrow().init_lhs());
+                i.set_subquery(sq.data().to_string().as_str());
+            }
+            Factor::Exists(sq) => {
+                f.set_exists(sq.data().to_string().as_str());
+            }
+        }
     }
 }
 
@@ -265,11 +406,13 @@ fn set_operation(
             idxname,
             idxfldname,
             joinfld,
+            outer,
         } => {
             let mut op = op.init_index_join_scan();
             op.set_idxname(idxname.as_str());
             op.set_idxfldname(idxfldname.as_str());
             op.set_joinfld(joinfld.as_str());
+            op.set_outer(outer);
         }
         repr::planrepr::Operation::IndexSelectScan {
             idxname,
@@ -289,20 +432,22 @@ fn set_operation(
                 flds.set(i as u32, f.as_str());
             }
             let mut fns = op.reborrow().init_aggfns(aggfns.len() as u32);
-            for (i, (f, c)) in aggfns.into_iter().enumerate() {
-                let mut tpl = fns.reborrow().get(i as u32);
-                tpl.set_fst(f.as_str()).unwrap();
-                let mut v = tpl.init_snd();
-                set_constant(&c, &mut v);
+            for (i, f) in aggfns.into_iter().enumerate() {
+                fns.set(i as u32, f.as_str());
             }
         }
         repr::planrepr::Operation::Materialize => {
             op.init_materialize();
         }
-        repr::planrepr::Operation::MergeJoinScan { fldname1, fldname2 } => {
+        repr::planrepr::Operation::MergeJoinScan {
+            fldname1,
+            fldname2,
+            outer,
+        } => {
             let mut op = op.init_merge_join_scan();
             op.set_fldname1(fldname1.as_str());
             op.set_fldname2(fldname2.as_str());
+            op.set_outer(outer);
         }
         repr::planrepr::Operation::SortScan { compflds } => {
             let op = op.init_sort_scan();
@@ -322,19 +467,65 @@ fn set_operation(
         }
         repr::planrepr::Operation::SelectScan { pred } => {
             let op = op.init_select_scan();
-            let p = op.init_pred();
-            let mut ts = p.init_terms(pred.terms().len() as u32);
-            for (i, term) in pred.terms().into_iter().enumerate() {
-                let mut t = ts.reborrow().get(i as u32);
-                let mut lhs = t.reborrow().init_lhs();
-                set_expression(term.lhs(), &mut lhs);
-                let mut rhs = t.reborrow().init_rhs();
-                set_expression(term.rhs(), &mut rhs);
-            }
+            let mut p = op.init_pred();
+            set_predicate(&pred, &mut p);
         }
         repr::planrepr::Operation::TableScan { tblname } => {
             op.init_table_scan().set_tblname(tblname.as_str());
         }
+        repr::planrepr::Operation::DistinctScan => {
+            op.init_distinct_scan();
+        }
+        repr::planrepr::Operation::LimitScan { limit, offset } => {
+            let mut op = op.init_limit_scan();
+            op.set_limit(limit as u64);
+            op.set_offset(offset as u64);
+        }
+        repr::planrepr::Operation::OuterJoinScan { pred } => {
+            let op = op.init_outer_join_scan();
+            let mut p = op.init_pred();
+            set_predicate(&pred, &mut p);
+        }
+        repr::planrepr::Operation::OuterRecordScan { fields } => {
+            let op = op.init_outer_record_scan();
+            let mut flds = op.init_fields(fields.len() as u32);
+            for (i, f) in fields.into_iter().enumerate() {
+                flds.set(i as u32, f.as_str());
+            }
+        }
+        repr::planrepr::Operation::UnionScan => {
+            op.init_union_scan();
+        }
+        repr::planrepr::Operation::SetOpScan { op: setop } => {
+            op.init_set_op_scan().set_op(setop.as_str());
+        }
+        repr::planrepr::Operation::IndexRangeScan {
+            idxname,
+            idxfldname,
+            loval,
+            hival,
+            inclusive,
+        } => {
+            let mut op = op.init_index_range_scan();
+            op.set_idxname(idxname.as_str());
+            op.set_idxfldname(idxfldname.as_str());
+            set_constant(&loval, &mut op.reborrow().init_loval());
+            set_constant(&hival, &mut op.reborrow().init_hival());
+            op.set_inclusive(inclusive);
+        }
+        repr::planrepr::Operation::IndexInScan {
+            idxname,
+            idxfldname,
+            vals,
+        } => {
+            let mut op = op.init_index_in_scan();
+            op.set_idxname(idxname.as_str());
+            op.set_idxfldname(idxfldname.as_str());
+            let mut cs = op.init_vals(vals.len() as u32);
+            for (i, val) in vals.iter().enumerate() {
+                set_constant(val, &mut cs.reborrow().get(i as u32));
+            }
+        }
     }
 }
 
@@ -684,6 +875,7 @@ pub struct RemoteStatementImpl {
     sql: String,
     planner: Planner,
     conn: Rc<RefCell<ConnectionInternal>>,
+    syntax_error: Option<SqlSyntaxError>,
 }
 impl RemoteStatementImpl {
     pub fn new(sql: &str, planner: Planner, conn: Rc<RefCell<ConnectionInternal>>) -> Self {
@@ -691,8 +883,13 @@ impl RemoteStatementImpl {
             sql: sql.to_string(),
             planner,
             conn,
+            syntax_error: None,
         }
     }
+    // the syntax error is kept for the client, which asks it after the failure.
+    fn keep_syntax_error(&mut self, e: &anyhow::Error) {
+        self.syntax_error = e.downcast_ref::<SqlSyntaxError>().cloned();
+    }
 }
 
 impl remote_statement::Server for RemoteStatementImpl {
@@ -715,6 +912,7 @@ impl remote_statement::Server for RemoteStatementImpl {
                 return Promise::ok(());
             }
             Err(e) => {
+                self.keep_syntax_error(&e);
                 return Promise::err(capnp::Error::failed(format!(
                     "failed to create query plan: {}",
                     e
@@ -728,10 +926,25 @@ impl remote_statement::Server for RemoteStatementImpl {
         mut results: remote_statement::ExecuteUpdateResults,
     ) -> Promise<(), capnp::Error> {
         trace!("execute update: {}", self.sql);
-        let affected = self
+        let affected = match self
             .planner
             .execute_update(&self.sql, Arc::clone(&self.conn.borrow().current_tx))
-            .expect("execute update");
+        {
+            Ok(affected) => affected,
+            Err(e) => {
+                self.keep_syntax_error(&e);
+                self.conn.borrow_mut().rollback().expect("rollback");
+                self.conn.borrow_mut().renew_tx().expect("renew tx");
+                // a violation of the constraints is reported as it is
+                let msg = match e.downcast_ref::<ConstraintError>() {
+                    Some(violation) => {
+                        StatementError::ConstraintViolation(violation.to_string()).to_string()
+                    }
+                    None => format!("failed to execute update: {}", e),
+                };
+                return Promise::err(capnp::Error::failed(msg));
+            }
+        };
         let tx_num = self.conn.borrow().current_tx_num();
         self.conn.borrow_mut().close().expect("close");
         let affected: affected::Client = capnp_rpc::new_client(AffectedImpl::new(affected, tx_num));
@@ -758,15 +971,44 @@ impl remote_statement::Server for RemoteStatementImpl {
         mut results: remote_statement::ExplainPlanResults,
     ) -> Promise<(), capnp::Error> {
         trace!("explain plan");
-        let planrepr = self
+        let planrepr = match self
             .planner
             .create_query_plan(&self.sql, Arc::clone(&self.conn.borrow().current_tx))
-            .unwrap()
-            .repr();
+        {
+            Ok(plan) => plan.repr(),
+            Err(e) => {
+                self.keep_syntax_error(&e);
+                return Promise::err(capnp::Error::failed(format!(
+                    "failed to create query plan: {}",
+                    e
+                )));
+            }
+        };
 
         let mut pr = results.get().init_planrepr();
         set_plan_repr(planrepr, &mut pr);
 
+        Promise::ok(())
+    }
+    fn syntax_error(
+        &mut self,
+        _: remote_statement::SyntaxErrorParams,
+        mut results: remote_statement::SyntaxErrorResults,
+    ) -> Promise<(), capnp::Error> {
+        trace!("syntax error");
+        if let Some(err) = self.syntax_error.as_ref() {
+            let mut res = results.get();
+            res.set_found(true);
+            let mut e = res.init_error();
+            e.set_line(err.line());
+            e.set_column(err.column());
+            e.set_token(err.token());
+            let mut expected = e.init_expected(err.expected().len() as u32);
+            for (i, tok) in err.expected().iter().enumerate() {
+                expected.set(i as u32, tok.as_str());
+            }
+        }
+
         Promise::ok(())
     }
 }
@@ -782,6 +1024,9 @@ impl RemoteResultSetImpl {
         let sch = plan.schema();
         Self { scan, sch, conn }
     }
+    fn field_is_null(&self, fldname: &str) -> bool {
+        self.scan.lock().unwrap().is_null(fldname).expect("is null")
+    }
 }
 
 impl remote_result_set::Server for RemoteResultSetImpl {
@@ -838,6 +1083,10 @@ impl remote_result_set::Server for RemoteResultSetImpl {
                 .set_key(k.as_str())
                 .unwrap();
             let mut val = entries.reborrow().get(i as u32).init_value();
+            if self.field_is_null(k) {
+                val.reborrow().set_null(());
+                continue;
+            }
             match fi.fld_type {
                 FieldType::SMALLINT => {
                     if let Ok(v) = self.scan.lock().unwrap().get_i16(k) {
@@ -894,6 +1143,10 @@ impl remote_result_set::Server for RemoteResultSetImpl {
                         .set_key(k.as_str())
                         .unwrap();
                     let mut val = entries.reborrow().get(i as u32).init_value();
+                    if self.field_is_null(k) {
+                        val.reborrow().set_null(());
+                        continue;
+                    }
                     match fi.fld_type {
                         FieldType::SMALLINT => {
                             if let Ok(v) = self.scan.lock().unwrap().get_i16(k) {
@@ -945,12 +1198,16 @@ impl remote_result_set::Server for RemoteResultSetImpl {
     ) -> Promise<(), capnp::Error> {
         let fldname = pry!(pry!(params.get()).get_fldname()).to_str().unwrap();
         debug!("get int16 value: {}", fldname);
-        let val = self
-            .scan
-            .lock()
-            .unwrap()
-            .get_i16(fldname)
-            .expect("get int16");
+        // the value of a null is the default of the type
+        let val = match self.field_is_null(fldname) {
+            true => Default::default(),
+            false => self
+                .scan
+                .lock()
+                .unwrap()
+                .get_i16(fldname)
+                .expect("get int16"),
+        };
         let val: int16_box::Client = capnp_rpc::new_client(Int16BoxImpl::new(val));
         results.get().set_val(val);
 
@@ -963,12 +1220,16 @@ impl remote_result_set::Server for RemoteResultSetImpl {
     ) -> Promise<(), capnp::Error> {
         let fldname = pry!(pry!(params.get()).get_fldname()).to_str().unwrap();
         debug!("get int32 value: {}", fldname);
-        let val = self
-            .scan
-            .lock()
-            .unwrap()
-            .get_i32(fldname)
-            .expect("get int32");
+        // the value of a null is the default of the type
+        let val = match self.field_is_null(fldname) {
+            true => Default::default(),
+            false => self
+                .scan
+                .lock()
+                .unwrap()
+                .get_i32(fldname)
+                .expect("get int32"),
+        };
         let val: int32_box::Client = capnp_rpc::new_client(Int32BoxImpl::new(val));
         results.get().set_val(val);
 
@@ -981,12 +1242,16 @@ impl remote_result_set::Server for RemoteResultSetImpl {
     ) -> Promise<(), capnp::Error> {
         let fldname = pry!(pry!(params.get()).get_fldname()).to_str().unwrap();
         debug!("get string value: {}", fldname);
-        let val = self
-            .scan
-            .lock()
-            .unwrap()
-            .get_string(fldname)
-            .expect("get string");
+        // the value of a null is the default of the type
+        let val = match self.field_is_null(fldname) {
+            true => Default::default(),
+            false => self
+                .scan
+                .lock()
+                .unwrap()
+                .get_string(fldname)
+                .expect("get string"),
+        };
         let val: string_box::Client = capnp_rpc::new_client(StringBoxImpl::new(val));
         results.get().set_val(val);
 
@@ -999,12 +1264,16 @@ impl remote_result_set::Server for RemoteResultSetImpl {
     ) -> Promise<(), capnp::Error> {
         let fldname = pry!(pry!(params.get()).get_fldname()).to_str().unwrap();
         debug!("get bool value: {}", fldname);
-        let val = self
-            .scan
-            .lock()
-            .unwrap()
-            .get_bool(fldname)
-            .expect("get bool");
+        // the value of a null is the default of the type
+        let val = match self.field_is_null(fldname) {
+            true => Default::default(),
+            false => self
+                .scan
+                .lock()
+                .unwrap()
+                .get_bool(fldname)
+                .expect("get bool"),
+        };
         let val: bool_box::Client = capnp_rpc::new_client(BoolBoxImpl::new(val));
         results.get().set_val(val);
 
@@ -1017,15 +1286,32 @@ impl remote_result_set::Server for RemoteResultSetImpl {
     ) -> Promise<(), capnp::Error> {
         let fldname = pry!(pry!(params.get()).get_fldname()).to_str().unwrap();
         debug!("get date value: {}", fldname);
-        let val = self
-            .scan
-            .lock()
-            .unwrap()
-            .get_date(fldname)
-            .expect("get date");
+        // the value of a null is the default of the type
+        let val = match self.field_is_null(fldname) {
+            true => Default::default(),
+            false => self
+                .scan
+                .lock()
+                .unwrap()
+                .get_date(fldname)
+                .expect("get date"),
+        };
         let val: date_box::Client = capnp_rpc::new_client(DateBoxImpl::new(val));
         results.get().set_val(val);
 
+        Promise::ok(())
+    }
+    fn is_null(
+        &mut self,
+        params: remote_result_set::IsNullParams,
+        mut results: remote_result_set::IsNullResults,
+    ) -> Promise<(), capnp::Error> {
+        let fldname = pry!(pry!(params.get()).get_fldname()).to_str().unwrap();
+        debug!("is null: {}", fldname);
+        let val = self.field_is_null(fldname);
+        let val: bool_box::Client = capnp_rpc::new_client(BoolBoxImpl::new(val));
+        results.get().set_val(val);
+
         Promise::ok(())
     }
 }
@@ -89,8 +89,9 @@ impl SimpleDB {
             tx.lock().unwrap().recover()?;
         }
         let meta = MetadataMgr::new(isnew, Arc::clone(&tx))?;
+        // the temp tables of the queries and the metadata are numbered together
+        let next_table_num = meta.next_table_num();
         db.mdm = Some(Arc::new(Mutex::new(meta)));
-        let next_table_num = Arc::new(Mutex::new(0));
         let qp = HeuristicQueryPlanner::new(next_table_num, Arc::clone(&db.mdm.as_ref().unwrap()));
         db.qp = Some(Arc::new(Mutex::new(qp)));
         let up = IndexUpdatePlanner::new(Arc::clone(&db.mdm.as_ref().unwrap()));
@@ -227,11 +228,13 @@ impl SimpleDB {
                 tx.lock().unwrap().recover()?;
             }
             let meta = MetadataMgr::new(isnew, Arc::clone(&tx))?;
+            let next_table_num = meta.next_table_num();
             db.mdm = Some(Arc::new(Mutex::new(meta)));
-            let next_table_num = Arc::new(Mutex::new(0));
             let qp_mdm = Arc::clone(&db.mdm.as_ref().unwrap());
             let qp: Arc<Mutex<dyn QueryPlanner>> = match cfg.query_planner {
-                config::QueryPlanner::Basic => Arc::new(Mutex::new(BasicQueryPlanner::new(qp_mdm))),
+                config::QueryPlanner::Basic => {
+                    Arc::new(Mutex::new(BasicQueryPlanner::new(next_table_num, qp_mdm)))
+                }
                 config::QueryPlanner::Heuristic => Arc::new(Mutex::new(
                     HeuristicQueryPlanner::new(next_table_num, qp_mdm),
                 )),
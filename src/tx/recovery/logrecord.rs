@@ -13,6 +13,7 @@ pub mod set_bool_record;
 pub mod set_date_record;
 pub mod set_i16_record;
 pub mod set_i32_record;
+pub mod set_image_record;
 pub mod set_string_record;
 pub mod start_record;
 
@@ -43,6 +44,7 @@ pub enum TxType {
     SETSTRING = 6,
     SETBOOL = 7,
     SETDATE = 8,
+    SETIMAGE = 9,
 }
 
 pub trait LogRecord {
@@ -65,6 +67,7 @@ pub fn create_log_record(bytes: Vec<u8>) -> Result<Box<dyn LogRecord>> {
         Some(TxType::SETSTRING) => Ok(Box::new(set_string_record::SetStringRecord::new(p)?)),
         Some(TxType::SETBOOL) => Ok(Box::new(set_bool_record::SetBoolRecord::new(p)?)),
         Some(TxType::SETDATE) => Ok(Box::new(set_date_record::SetDateRecord::new(p)?)),
+        Some(TxType::SETIMAGE) => Ok(Box::new(set_image_record::SetImageRecord::new(p)?)),
         None => Err(From::from(LogRecordError::UnknownRecord)),
     }
 }
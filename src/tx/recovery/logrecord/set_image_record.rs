@@ -0,0 +1,109 @@
+use anyhow::Result;
+use core::fmt;
+use std::{
+    mem,
+    sync::{Arc, Mutex},
+};
+
+use super::{LogRecord, TxType};
+use crate::{
+    file::{block_id::BlockId, page::Page},
+    log::manager::LogMgr,
+    tx::transaction::Transaction,
+};
+
+// my own extends
+// the old bytes of a range of a block, e.g. a part of the block erased at once.
+pub struct SetImageRecord {
+    txnum: i32,
+    offset: i32,
+    val: Vec<u8>,
+    blk: BlockId,
+}
+
+impl fmt::Display for SetImageRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "<SETIMAGE {} {} {} {}>",
+            self.txnum,
+            self.blk,
+            self.offset,
+            self.val.len()
+        )
+    }
+}
+
+impl LogRecord for SetImageRecord {
+    fn op(&self) -> TxType {
+        TxType::SETIMAGE
+    }
+    fn tx_number(&self) -> i32 {
+        self.txnum
+    }
+    fn undo(&mut self, tx: Arc<Mutex<Transaction>>) -> Result<()> {
+        tx.lock().unwrap().pin(&self.blk)?;
+        tx.lock()
+            .unwrap()
+            .set_image(&self.blk, self.offset, &self.val, false)?; // don't log the undo!
+        tx.lock().unwrap().unpin(&self.blk)
+    }
+}
+impl SetImageRecord {
+    pub fn new(p: Page) -> Result<Self> {
+        let tpos = mem::size_of::<i32>();
+        let txnum = p.get_i32(tpos)?;
+        let fpos = tpos + mem::size_of::<i32>();
+        let filename = p.get_string(fpos)?;
+        let bpos = fpos + Page::max_length(filename.len());
+        let blknum = p.get_i32(bpos)?;
+        let blk = BlockId::new(&filename, blknum);
+        let opos = bpos + mem::size_of::<i32>();
+        let offset = p.get_i32(opos)?;
+        let vpos = opos + mem::size_of::<i32>();
+        let val = p.get_bytes_vec(vpos)?;
+
+        Ok(Self {
+            txnum,
+            offset,
+            val,
+            blk,
+        })
+    }
+    // the longest image whose record fits in an empty log block,
+    // which holds the boundary and the length of the record besides it.
+    pub fn max_image_length(blocksize: usize, blk: &BlockId) -> usize {
+        let reclen = Self::record_length(blk, 0);
+        blocksize.saturating_sub(reclen + 2 * mem::size_of::<i32>())
+    }
+    fn record_length(blk: &BlockId, vallen: usize) -> usize {
+        let fpos = 2 * mem::size_of::<i32>();
+        let bpos = fpos + Page::max_length(blk.file_name().len());
+        let vpos = bpos + 2 * mem::size_of::<i32>();
+        vpos + Page::max_length(vallen)
+    }
+    pub fn write_to_log(
+        lm: Arc<Mutex<LogMgr>>,
+        txnum: i32,
+        blk: &BlockId,
+        offset: i32,
+        val: &[u8],
+    ) -> Result<i32> {
+        let tpos = mem::size_of::<i32>();
+        let fpos = tpos + mem::size_of::<i32>();
+        let bpos = fpos + Page::max_length(blk.file_name().len());
+        let opos = bpos + mem::size_of::<i32>();
+        let vpos = opos + mem::size_of::<i32>();
+        let reclen = Self::record_length(blk, val.len());
+
+        let mut p = Page::new_from_size(reclen);
+        p.set_i32(0, TxType::SETIMAGE as i32)?;
+        p.set_i32(tpos, txnum)?;
+        p.set_string(fpos, blk.file_name())?;
+        p.set_i32(bpos, blk.number() as i32)?;
+        p.set_i32(opos, offset)?;
+        p.set_bytes(vpos, val)?;
+
+        lm.lock().unwrap().append(p.contents())
+    }
+}
@@ -7,7 +7,7 @@ use super::logrecord::{
     self, checkpoint_record::CheckpointRecord, commit_record::CommitRecord,
     rollback_record::RollbackRecord, set_bool_record::SetBoolRecord,
     set_date_record::SetDateRecord, set_i16_record::SetI16Record, set_i32_record::SetI32Record,
-    set_string_record::SetStringRecord, TxType,
+    set_image_record::SetImageRecord, set_string_record::SetStringRecord, TxType,
 };
 use crate::{
     buffer::{buffer::Buffer, manager::BufferMgr},
@@ -148,6 +148,33 @@ impl RecoveryMgr {
             "set_date".to_string(),
         )))
     }
+    // my own extends
+    // logs the old bytes in as few records as fit in the log blocks, returns the last lsn.
+    pub fn set_image(&mut self, buff: &mut Buffer, offset: i32, new_val: &[u8]) -> Result<i32> {
+        let start = offset as usize;
+        let page = buff.contents().contents();
+        // the log blocks are as large as the blocks of the buffers
+        let blocksize = page.len();
+        let old_val = page[start..start + new_val.len()].to_vec();
+        if let Some(blk) = buff.block() {
+            let chunk = SetImageRecord::max_image_length(blocksize, blk).max(1);
+            let mut lsn = -1;
+            for (i, val) in old_val.chunks(chunk).enumerate() {
+                lsn = SetImageRecord::write_to_log(
+                    Arc::clone(&self.lm),
+                    self.txnum,
+                    blk,
+                    offset + (i * chunk) as i32,
+                    val,
+                )?;
+            }
+            return Ok(lsn);
+        }
+
+        Err(From::from(RecoveryMgrError::BufferFailed(
+            "set_image".to_string(),
+        )))
+    }
     fn do_rollback(&mut self) -> Result<()> {
         let mut iter = self.lm.lock().unwrap().iterator()?;
         while let Some(bytes) = iter.next() {
@@ -1,5 +1,6 @@
 use anyhow::Result;
 use chrono::NaiveDate;
+use core::fmt;
 use std::{
     sync::{Arc, Mutex},
     usize,
@@ -18,6 +19,22 @@ use crate::{
 
 static END_OF_FILE: i32 = -1;
 
+#[derive(Debug)]
+pub enum TransactionError {
+    FileDeleted(String),
+}
+
+impl std::error::Error for TransactionError {}
+impl fmt::Display for TransactionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TransactionError::FileDeleted(filename) => {
+                write!(f, "file deleted in this transaction: {}", filename)
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Transaction {
     // static member (shared by all Transaction)
@@ -29,6 +46,9 @@ pub struct Transaction {
     fm: Arc<Mutex<FileMgr>>,
     txnum: i32,
     mybuffers: BufferList,
+    // my own extends
+    // the files removed when the transaction commits
+    pending_deletes: Vec<String>,
 }
 
 impl Transaction {
@@ -48,6 +68,7 @@ impl Transaction {
             fm,
             txnum: 0, // dummy
             mybuffers: BufferList::new(Arc::clone(&bm)),
+            pending_deletes: vec![],
         };
 
         // update txnum
@@ -66,6 +87,10 @@ impl Transaction {
             .lock()
             .unwrap()
             .commit()?;
+        // the files are removed while the locks on them are held
+        for filename in self.pending_deletes.drain(..) {
+            self.fm.lock().unwrap().delete(&filename)?;
+        }
         self.concur_mgr.release()?;
         self.mybuffers.unpin_all()?;
         println!("transaction {} committed", self.txnum);
@@ -79,6 +104,7 @@ impl Transaction {
             .lock()
             .unwrap()
             .rollback()?;
+        self.pending_deletes.clear();
         self.concur_mgr.release()?;
         self.mybuffers.unpin_all()?;
         println!("transaction {} rolled back", self.txnum);
@@ -213,6 +239,28 @@ impl Transaction {
 
         Ok(())
     }
+    // my own extends
+    // writes the raw bytes, without the length, e.g. the image of a part of the block.
+    pub fn set_image(
+        &mut self,
+        blk: &BlockId,
+        offset: i32,
+        val: &[u8],
+        ok_to_log: bool,
+    ) -> Result<()> {
+        self.concur_mgr.x_lock(blk)?;
+        let mut buff = self.mybuffers.get_bufer(blk).unwrap().lock().unwrap();
+        let mut lsn: i32 = -1;
+        if ok_to_log {
+            let mut rm = self.recovery_mgr.as_ref().unwrap().lock().unwrap();
+            lsn = rm.set_image(&mut buff, offset, val)?;
+        }
+        let start = offset as usize;
+        buff.contents().contents()[start..start + val.len()].copy_from_slice(val);
+        buff.set_modified(self.txnum, lsn);
+
+        Ok(())
+    }
     pub fn size(&mut self, filename: &str) -> Result<i32> {
         let dummyblk = BlockId::new(filename, END_OF_FILE);
         self.concur_mgr.s_lock(&dummyblk)?;
@@ -221,8 +269,24 @@ impl Transaction {
     pub fn append(&mut self, filename: &str) -> Result<BlockId> {
         let dummyblk = BlockId::new(filename, END_OF_FILE);
         self.concur_mgr.x_lock(&dummyblk)?;
+        if self.pending_deletes.iter().any(|f| f == filename) {
+            return Err(From::from(TransactionError::FileDeleted(
+                filename.to_string(),
+            )));
+        }
         self.fm.lock().unwrap().append(filename)
     }
+    // my own extends
+    // the file is removed when the transaction commits, and kept when it rolls back.
+    pub fn delete_file(&mut self, filename: &str) -> Result<()> {
+        let dummyblk = BlockId::new(filename, END_OF_FILE);
+        self.concur_mgr.x_lock(&dummyblk)?;
+        if !self.pending_deletes.iter().any(|f| f == filename) {
+            self.pending_deletes.push(filename.to_string());
+        }
+
+        Ok(())
+    }
     pub fn block_size(&self) -> i32 {
         self.fm.lock().unwrap().block_size()
     }